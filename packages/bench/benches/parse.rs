@@ -0,0 +1,46 @@
+//! Throughput of every step, serde_json and optionally simd-json on the
+//! usual corpus. Files missing from `test-files/` are skipped, `cargo xtask
+//! fetch` downloads them.
+
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use std::fs::read_to_string;
+
+const FILES: [&str; 3] = ["canada.json", "twitter.json", "citm_catalog.json"];
+
+fn parse(c: &mut Criterion) {
+    for file in FILES {
+        let path = format!("{}/../../test-files/{file}", env!("CARGO_MANIFEST_DIR"));
+        let Ok(json) = read_to_string(&path) else {
+            eprintln!("Skipping {file}, not found in test-files/");
+            continue;
+        };
+
+        let mut group = c.benchmark_group(file);
+        group.throughput(Throughput::Bytes(json.len() as u64));
+
+        group.bench_function("step-1", |b| b.iter(|| step_1::parse(&json).unwrap()));
+        group.bench_function("step-2", |b| b.iter(|| step_2::parse(&json).unwrap()));
+        group.bench_function("step-3", |b| b.iter(|| step_3::parse(&json).unwrap()));
+        group.bench_function("step-4", |b| b.iter(|| step_4::parse(&json).unwrap()));
+        group.bench_function("step-5", |b| {
+            b.iter(|| step_5::parse(json.as_bytes()).unwrap())
+        });
+        group.bench_function("serde_json", |b| {
+            b.iter(|| serde_json::from_str::<serde_json::Value>(&json).unwrap())
+        });
+        // simd-json parses in place, so every iteration needs a fresh copy
+        #[cfg(feature = "simd-json")]
+        group.bench_function("simd-json", |b| {
+            b.iter_batched(
+                || json.as_bytes().to_vec(),
+                |mut bytes| simd_json::to_owned_value(&mut bytes).unwrap(),
+                criterion::BatchSize::LargeInput,
+            )
+        });
+
+        group.finish();
+    }
+}
+
+criterion_group!(benches, parse);
+criterion_main!(benches);
@@ -0,0 +1 @@
+//! Nothing here: the benchmarks are in `benches/`.
@@ -0,0 +1,70 @@
+//! `json codegen`: Rust types with `serde` attributes for documents like the
+//! sample ones, or the code of a document itself.
+
+use std::process::ExitCode;
+
+use step_4::{infer::Schema, parse_with_path, render_error, rust_types, rust_value};
+
+use crate::read_input;
+
+const USAGE: &str = "usage: json codegen [--name NAME] [FILE...]
+       json codegen --value [FILE]
+
+    --name NAME   name of the root type, `Root` by default
+    --value       print a Rust expression building the document instead,
+                  to embed it with `include!`
+
+The types fit all the FILEs, fields missing from some of them being optional.";
+
+pub fn run(mut args: impl Iterator<Item = String>) -> Result<ExitCode, String> {
+    let mut name = "Root".to_owned();
+    let mut value = false;
+    let mut paths = Vec::new();
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--name" => {
+                name = args
+                    .next()
+                    .ok_or(format!("--name needs a name\n\n{USAGE}"))?
+            }
+            "--value" => value = true,
+            "-h" | "--help" => {
+                println!("{USAGE}");
+                return Ok(ExitCode::SUCCESS);
+            }
+            flag if flag.starts_with("--") => {
+                return Err(format!("unknown option `{flag}`\n\n{USAGE}"))
+            }
+            path => paths.push(path.to_owned()),
+        }
+    }
+
+    if paths.is_empty() {
+        paths.push("-".to_owned());
+    }
+    if value && paths.len() > 1 {
+        return Err(format!("--value takes a single FILE\n\n{USAGE}"));
+    }
+
+    let mut schema = Schema::default();
+    for path in &paths {
+        let input = read_input(path).map_err(|e| format!("{path}: {e}"))?;
+
+        match parse_with_path(&input) {
+            Ok(document) if value => {
+                println!("{}", rust_value(&document));
+                return Ok(ExitCode::SUCCESS);
+            }
+            Ok(document) => schema.add(&document),
+            Err(e) => {
+                eprint!("{path}: {}", render_error(&input, &e));
+                return Ok(ExitCode::FAILURE);
+            }
+        }
+    }
+
+    print!("{}", rust_types(&schema, &name));
+
+    Ok(ExitCode::SUCCESS)
+}
@@ -0,0 +1,105 @@
+//! `json convert`: converts a document between JSON, YAML and TOML.
+
+use std::process::ExitCode;
+
+use step_4::{
+    from_toml, from_yaml, parse_with_path, render_error, to_string_with, SerializeOptions,
+};
+
+use crate::read_input;
+
+const USAGE: &str = "usage: json convert [--from FORMAT] [--to FORMAT] [--sort-keys] [FILE]
+
+    --from FORMAT   json, yaml or toml, guessed from the extension of FILE by
+                    default
+    --to FORMAT     json, yaml or toml, yaml from json and json otherwise by
+                    default
+    --sort-keys     order the keys of JSON output lexicographically, as they
+                    always are in YAML and TOML output";
+
+#[derive(Clone, Copy, PartialEq)]
+enum Format {
+    Json,
+    Yaml,
+    Toml,
+}
+
+fn format(name: Option<String>, option: &str) -> Result<Format, String> {
+    match name.as_deref() {
+        Some("json") => Ok(Format::Json),
+        Some("yaml" | "yml") => Ok(Format::Yaml),
+        Some("toml") => Ok(Format::Toml),
+        _ => Err(format!("{option} needs json, yaml or toml\n\n{USAGE}")),
+    }
+}
+
+pub fn run(mut args: impl Iterator<Item = String>) -> Result<ExitCode, String> {
+    let mut from = None;
+    let mut to = None;
+    let mut sort_keys = false;
+    let mut path = "-".to_owned();
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--from" => from = Some(format(args.next(), "--from")?),
+            "--to" => to = Some(format(args.next(), "--to")?),
+            "--sort-keys" => sort_keys = true,
+            "-h" | "--help" => {
+                println!("{USAGE}");
+                return Ok(ExitCode::SUCCESS);
+            }
+            flag if flag.starts_with("--") => {
+                return Err(format!("unknown option `{flag}`\n\n{USAGE}"))
+            }
+            _ => path = arg,
+        }
+    }
+
+    let from = from.unwrap_or(if path.ends_with(".yaml") || path.ends_with(".yml") {
+        Format::Yaml
+    } else if path.ends_with(".toml") {
+        Format::Toml
+    } else {
+        Format::Json
+    });
+    let to = to.unwrap_or(match from {
+        Format::Json => Format::Yaml,
+        Format::Yaml | Format::Toml => Format::Json,
+    });
+
+    let input = read_input(&path).map_err(|e| format!("{path}: {e}"))?;
+
+    let parsed = match from {
+        Format::Json => parse_with_path(&input),
+        Format::Yaml => from_yaml(&input),
+        Format::Toml => from_toml(&input),
+    };
+    let document = match parsed {
+        Ok(document) => document,
+        Err(e) => {
+            eprint!("{}", render_error(&input, &e));
+            return Ok(ExitCode::FAILURE);
+        }
+    };
+
+    match to {
+        Format::Json => {
+            let opts = SerializeOptions {
+                indent: Some(2),
+                sort_keys,
+                ..Default::default()
+            };
+            println!("{}", to_string_with(&document, &opts));
+        }
+        Format::Yaml => print!("{}", document.to_yaml()),
+        Format::Toml => match document.to_toml() {
+            Ok(toml) => print!("{toml}"),
+            Err(e) => {
+                eprintln!("error: {e}");
+                return Ok(ExitCode::FAILURE);
+            }
+        },
+    }
+
+    Ok(ExitCode::SUCCESS)
+}
@@ -0,0 +1,87 @@
+//! `json fmt`: rewrites the whitespace of a document, keeping its key order
+//! and the text of its strings and numbers.
+
+use std::process::ExitCode;
+
+use step_4::{edit, render_error};
+
+use crate::read_input;
+
+const USAGE: &str = "usage: json fmt [--minify] [--indent N] [--sort-keys] [--check] [FILE]
+
+    --minify      remove all whitespace
+    --indent N    indent by N spaces, 2 by default
+    --sort-keys   order object keys lexicographically
+    --check       print nothing, exit with 1 if FILE isn't formatted";
+
+struct Options {
+    indent: Option<usize>,
+    sort_keys: bool,
+    check: bool,
+    help: bool,
+    path: String,
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Options, String> {
+    let mut options = Options {
+        indent: Some(2),
+        sort_keys: false,
+        check: false,
+        help: false,
+        path: "-".to_owned(),
+    };
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--minify" => options.indent = None,
+            "--check" => options.check = true,
+            "--sort-keys" => options.sort_keys = true,
+            "--indent" => {
+                let width = args.next().and_then(|width| width.parse().ok());
+                options.indent = Some(width.ok_or(format!("--indent needs a number\n\n{USAGE}"))?);
+            }
+            "-h" | "--help" => options.help = true,
+            flag if flag.starts_with("--") => {
+                return Err(format!("unknown option `{flag}`\n\n{USAGE}"))
+            }
+            path => options.path = path.to_owned(),
+        }
+    }
+
+    Ok(options)
+}
+
+pub fn run(args: impl Iterator<Item = String>) -> Result<ExitCode, String> {
+    let options = parse_args(args)?;
+    if options.help {
+        println!("{USAGE}");
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    let input = read_input(&options.path).map_err(|e| format!("{}: {e}", options.path))?;
+
+    let formatted = match edit(&input, |doc| {
+        if options.sort_keys {
+            doc.sort_keys();
+        }
+        doc.reformat(options.indent);
+    }) {
+        Ok(formatted) => formatted + "\n",
+        Err(e) => {
+            eprint!("{}", render_error(&input, &e));
+            return Ok(ExitCode::FAILURE);
+        }
+    };
+
+    if options.check {
+        if formatted == input {
+            return Ok(ExitCode::SUCCESS);
+        }
+
+        eprintln!("{} isn't formatted", options.path);
+        return Ok(ExitCode::FAILURE);
+    }
+
+    print!("{formatted}");
+
+    Ok(ExitCode::SUCCESS)
+}
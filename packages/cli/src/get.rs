@@ -0,0 +1,96 @@
+//! `json get`: prints the outputs of a jq-like filter over a document.
+//!
+//! The filters are those of step 5, see [`step_5_filters`]: `.statuses[0]`,
+//! `.statuses[] | select(.retweet_count > 10) | .user.name`, and so on. Like
+//! in jq, a missing key or index gives `null`.
+
+use std::{
+    io::{stdout, IsTerminal},
+    process::ExitCode,
+};
+
+use step_4::{parse_with_path, render_error, to_string_with, JsonValue, SerializeOptions};
+use step_5_filters::Filter;
+
+use crate::read_input;
+
+const USAGE: &str =
+    "usage: json get [--raw] [--pretty] [--sort-keys] [--ascii] [--color WHEN] FILTER [FILE]
+
+    --raw           print strings without quotes
+    --pretty        indent the values
+    --sort-keys     write object keys in lexicographic order
+    --ascii         escape every non-ASCII char
+    --color WHEN    highlight the values: auto (the default), always or never
+
+FILTER is like `.statuses[0].user.name`, or `.statuses[] | select(.lang == \"en\")`.";
+
+pub fn run(mut args: impl Iterator<Item = String>) -> Result<ExitCode, String> {
+    let mut raw = false;
+    let mut opts = SerializeOptions {
+        indent: None,
+        sort_keys: false,
+        ensure_ascii: false,
+        escape_html: false,
+        // Only for people, and those who opted out of colors
+        color: stdout().is_terminal() && std::env::var_os("NO_COLOR").is_none(),
+    };
+    let mut positional = Vec::new();
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--raw" | "-r" => raw = true,
+            "--pretty" | "-p" => opts.indent = Some(2),
+            "--sort-keys" | "-S" => opts.sort_keys = true,
+            "--ascii" | "-a" => opts.ensure_ascii = true,
+            "--color" => match args.next().as_deref() {
+                Some("always") => opts.color = true,
+                Some("never") => opts.color = false,
+                Some("auto") => {}
+                _ => return Err(format!("--color needs auto, always or never\n\n{USAGE}")),
+            },
+            "-h" | "--help" => {
+                println!("{USAGE}");
+                return Ok(ExitCode::SUCCESS);
+            }
+            flag if flag.starts_with("--") => {
+                return Err(format!("unknown option `{flag}`\n\n{USAGE}"))
+            }
+            _ => positional.push(arg),
+        }
+    }
+
+    let (filter, path) = match positional.as_slice() {
+        [filter] => (filter.as_str(), "-"),
+        [filter, path] => (filter.as_str(), path.as_str()),
+        _ => return Err(USAGE.to_owned()),
+    };
+
+    let filter = Filter::parse(filter).map_err(|e| format!("invalid filter `{filter}`: {e}"))?;
+    let input = read_input(path).map_err(|e| format!("{path}: {e}"))?;
+
+    let document = match parse_with_path(&input) {
+        Ok(document) => document,
+        Err(e) => {
+            eprint!("{}", render_error(&input, &e));
+            return Ok(ExitCode::FAILURE);
+        }
+    };
+
+    let values = match filter.apply(&document) {
+        Ok(values) => values,
+        Err(e) => {
+            eprintln!("error: {e}");
+            return Ok(ExitCode::FAILURE);
+        }
+    };
+
+    for value in &values {
+        match value {
+            JsonValue::Str(s) if raw => println!("{s}"),
+            value => println!("{}", to_string_with(value, &opts)),
+        }
+    }
+
+    Ok(ExitCode::SUCCESS)
+}
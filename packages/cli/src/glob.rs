@@ -0,0 +1,100 @@
+//! Expansion of glob patterns, for shells that don't do it (and quoted
+//! patterns in pre-commit configs): `*` and `?` within a path component,
+//! `**` for any number of directories.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+fn has_wildcards(pattern: &str) -> bool {
+    pattern.contains(['*', '?'])
+}
+
+/// Whether `name` matches `pattern`, where `*` is any run of chars and `?`
+/// any single char
+fn matches(pattern: &[char], name: &[char]) -> bool {
+    match (pattern.first(), name.first()) {
+        (None, None) => true,
+        (Some('*'), _) => {
+            matches(&pattern[1..], name) || (!name.is_empty() && matches(pattern, &name[1..]))
+        }
+        (Some('?'), Some(_)) => matches(&pattern[1..], &name[1..]),
+        (Some(p), Some(c)) if p == c => matches(&pattern[1..], &name[1..]),
+        _ => false,
+    }
+}
+
+fn entries(dir: &Path) -> Vec<PathBuf> {
+    let Ok(read_dir) = fs::read_dir(if dir.as_os_str().is_empty() {
+        Path::new(".")
+    } else {
+        dir
+    }) else {
+        return Vec::new();
+    };
+
+    let mut paths: Vec<_> = read_dir
+        .filter_map(|entry| entry.ok())
+        .map(|entry| dir.join(entry.file_name()))
+        .collect();
+    paths.sort();
+    paths
+}
+
+fn walk(base: PathBuf, components: &[&str], out: &mut Vec<PathBuf>) {
+    let Some((component, rest)) = components.split_first() else {
+        if base.is_file() {
+            out.push(base);
+        }
+        return;
+    };
+
+    match *component {
+        "**" => {
+            walk(base.clone(), rest, out);
+
+            for path in entries(&base) {
+                if path.is_dir() {
+                    walk(path, components, out);
+                }
+            }
+        }
+        component if has_wildcards(component) => {
+            let pattern: Vec<char> = component.chars().collect();
+
+            for path in entries(&base) {
+                let name = path.file_name().unwrap_or_default().to_string_lossy();
+
+                // Like shells, `*` doesn't match hidden files
+                if name.starts_with('.') && !component.starts_with('.') {
+                    continue;
+                }
+
+                if matches(&pattern, &name.chars().collect::<Vec<_>>()) {
+                    walk(path, rest, out);
+                }
+            }
+        }
+        component => walk(base.join(component), rest, out),
+    }
+}
+
+/// Files matching `pattern`, sorted. A pattern without wildcards is
+/// returned as is, even if the file doesn't exist, so that it gets reported
+pub fn expand(pattern: &str) -> Vec<PathBuf> {
+    if !has_wildcards(pattern) {
+        return vec![PathBuf::from(pattern)];
+    }
+
+    let (base, pattern) = match pattern.strip_prefix('/') {
+        Some(pattern) => (PathBuf::from("/"), pattern),
+        None => (PathBuf::new(), pattern),
+    };
+
+    let components: Vec<_> = pattern.split('/').filter(|c| !c.is_empty()).collect();
+    let mut out = Vec::new();
+    walk(base, &components, &mut out);
+    out.dedup();
+    out
+}
@@ -0,0 +1,87 @@
+//! `json`, a command line tool for JSON files built on the step-4 parser.
+//!
+//! ```text
+//! json fmt [--minify] [--indent N] [--sort-keys] [--check] [FILE]
+//! json validate [--quiet] [--watch] FILE|PATTERN|DIR...
+//! json get [--raw] [--pretty] [--sort-keys] [--ascii] [--color WHEN] FILTER [FILE]
+//! json codegen [--name NAME] [FILE...]
+//! json codegen --value [FILE]
+//! json convert [--from FORMAT] [--to FORMAT] [--sort-keys] [FILE]
+//! json stats [--top N] [FILE]
+//! ```
+//!
+//! Without a file, or with `-`, the input is read from stdin. Gzip-compressed
+//! inputs, like `dump.json.gz`, are decompressed on the fly.
+
+use std::{
+    io::{self, Read},
+    process::ExitCode,
+};
+
+use step_4::gzip;
+
+mod codegen;
+mod convert;
+mod fmt;
+mod get;
+mod glob;
+mod stats;
+mod validate;
+mod watch;
+
+const USAGE: &str = "usage: json <command> [options] [FILE...]
+
+commands:
+    fmt         pretty-print or minify a document
+    validate    check that files are valid JSON
+    get         print the outputs of a jq-like filter
+    codegen     generate Rust types for documents like the given ones, or
+                the code of a document
+    convert     convert a document between JSON, YAML and TOML
+    stats       profile a document: sizes, depth, values and keys";
+
+/// Reads `path`, or stdin when it is `-`, decompressing it if needed
+fn read_input(path: &str) -> io::Result<String> {
+    let mut bytes = Vec::new();
+
+    if path == "-" {
+        io::stdin().read_to_end(&mut bytes)?;
+    } else {
+        bytes = std::fs::read(path)?;
+    }
+
+    if gzip::is_gzip(&bytes) {
+        let mut input = String::new();
+        gzip::GzDecoder::new(bytes.as_slice()).read_to_string(&mut input)?;
+        return Ok(input);
+    }
+
+    String::from_utf8(bytes).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+fn main() -> ExitCode {
+    let mut args = std::env::args().skip(1);
+
+    let result = match args.next().as_deref() {
+        Some("fmt") => fmt::run(args),
+        Some("validate") => validate::run(args),
+        Some("get") => get::run(args),
+        Some("codegen") => codegen::run(args),
+        Some("convert") => convert::run(args),
+        Some("stats") => stats::run(args),
+        Some("-h" | "--help") => {
+            println!("{USAGE}");
+            return ExitCode::SUCCESS;
+        }
+        Some(command) => Err(format!("unknown command `{command}`\n\n{USAGE}")),
+        None => Err(USAGE.to_owned()),
+    };
+
+    match result {
+        Ok(code) => code,
+        Err(message) => {
+            eprintln!("{message}");
+            ExitCode::from(2)
+        }
+    }
+}
@@ -0,0 +1,157 @@
+//! `json stats`: what a document is made of and where its bytes are, to
+//! find out why a payload is so big.
+
+use std::{process::ExitCode, time::Instant};
+
+use step_4::{parse_with_path, render_error, to_string, JsonValue};
+
+use crate::read_input;
+
+const USAGE: &str = "usage: json stats [--top N] [FILE]
+
+    --top N   keys and subtrees to list, 10 by default
+
+Sizes are those of the document without whitespace.";
+
+/// `1536` is `1.5 KiB`
+fn human_size(bytes: usize) -> String {
+    let mut size = bytes as f64;
+
+    for unit in ["B", "KiB", "MiB"] {
+        if size < 1024.0 {
+            return match unit {
+                "B" => format!("{bytes} B"),
+                _ => format!("{size:.1} {unit}"),
+            };
+        }
+        size /= 1024.0;
+    }
+
+    format!("{size:.1} GiB")
+}
+
+/// Compact size of `value`, after adding those of its arrays and objects to
+/// `subtrees` by path
+fn subtree_sizes(
+    value: &JsonValue,
+    path: &mut String,
+    subtrees: &mut Vec<(String, usize)>,
+) -> usize {
+    let len = path.len();
+
+    let size = match value {
+        JsonValue::Array(values) => {
+            let mut size = 2 + values.len().saturating_sub(1);
+            for (index, value) in values.iter().enumerate() {
+                path.push_str(&format!("[{index}]"));
+                size += subtree_sizes(value, path, subtrees);
+                path.truncate(len);
+            }
+            size
+        }
+        JsonValue::Object(map) => {
+            let mut size = 2 + map.len().saturating_sub(1);
+            for (key, value) in map {
+                if !path.is_empty() {
+                    path.push('.');
+                }
+                path.push_str(key);
+                // The key, quoted and escaped, and the `:`
+                size += to_string(&JsonValue::from(key.as_str())).len() + 1;
+                size += subtree_sizes(value, path, subtrees);
+                path.truncate(len);
+            }
+            size
+        }
+        value => return to_string(value).len(),
+    };
+
+    if !path.is_empty() {
+        subtrees.push((path.clone(), size));
+    }
+
+    size
+}
+
+pub fn run(mut args: impl Iterator<Item = String>) -> Result<ExitCode, String> {
+    let mut top = 10;
+    let mut path = "-".to_owned();
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--top" => {
+                let n = args.next().and_then(|n| n.parse().ok());
+                top = n.ok_or(format!("--top needs a number\n\n{USAGE}"))?;
+            }
+            "-h" | "--help" => {
+                println!("{USAGE}");
+                return Ok(ExitCode::SUCCESS);
+            }
+            flag if flag.starts_with("--") => {
+                return Err(format!("unknown option `{flag}`\n\n{USAGE}"))
+            }
+            file => path = file.to_owned(),
+        }
+    }
+
+    let input = read_input(&path).map_err(|e| format!("{path}: {e}"))?;
+
+    let start = Instant::now();
+    let document = match parse_with_path(&input) {
+        Ok(document) => document,
+        Err(e) => {
+            eprint!("{path}: {}", render_error(&input, &e));
+            return Ok(ExitCode::FAILURE);
+        }
+    };
+    let elapsed = start.elapsed();
+
+    let stats = document.stats();
+    let counts = stats.counts;
+
+    println!("size          {}", human_size(input.len()));
+    println!("parse time    {elapsed:?}");
+    println!("max depth     {}", stats.max_depth);
+    println!("string bytes  {}", human_size(stats.string_bytes));
+
+    println!("\nvalues");
+    for (kind, count) in [
+        ("null", counts.null),
+        ("boolean", counts.boolean),
+        ("number", counts.number),
+        ("string", counts.string),
+        ("array", counts.array),
+        ("object", counts.object),
+    ] {
+        println!("  {kind:<10}  {count}");
+    }
+
+    let keys = stats.top_keys(top);
+    if !keys.is_empty() {
+        println!("\ntop keys");
+        let width = keys.iter().map(|(key, _)| key.len()).max().unwrap_or(0);
+        for (key, count) in keys {
+            println!("  {key:<width$}  {count}");
+        }
+    }
+
+    let mut subtrees = Vec::new();
+    subtree_sizes(&document, &mut String::new(), &mut subtrees);
+    // Ties in path order, for the same output every time
+    subtrees.sort_unstable_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+    subtrees.truncate(top);
+
+    if !subtrees.is_empty() {
+        println!("\nlargest subtrees");
+        let width = subtrees
+            .iter()
+            .map(|(path, _)| path.len())
+            .max()
+            .unwrap_or(0);
+        for (path, size) in subtrees {
+            println!("  {path:<width$}  {}", human_size(size));
+        }
+    }
+
+    Ok(ExitCode::SUCCESS)
+}
@@ -0,0 +1,118 @@
+//! `json validate`: checks many files at once and reports every error of
+//! each of them, exiting with 1 if any is invalid. Meant for pre-commit
+//! hooks and CI.
+
+use std::{
+    path::{Path, PathBuf},
+    process::ExitCode,
+};
+
+use step_4::{error_position, parse_recovering};
+
+use crate::{glob, read_input, watch};
+
+const USAGE: &str = "usage: json validate [--quiet] [--watch] FILE|PATTERN|DIR...
+
+    --quiet    only print the errors
+    --watch    keep running, validating the files again when they change
+
+Patterns like `config/**/*.json` are expanded, directories stand for the
+JSON files they contain, and `-` reads from stdin. Exits with 1 if any file
+is invalid.";
+
+/// Files `pattern` stands for
+pub fn expand(pattern: &str) -> Vec<PathBuf> {
+    if Path::new(pattern).is_dir() {
+        let dir = pattern.trim_end_matches('/');
+        let mut paths = glob::expand(&format!("{dir}/**/*.json"));
+        paths.extend(glob::expand(&format!("{dir}/**/*.json.gz")));
+        paths.sort();
+        return paths;
+    }
+
+    glob::expand(pattern)
+}
+
+/// Prints the errors of `path` as `path:line:column: message`, returns
+/// whether it is valid
+pub fn validate(path: &Path) -> bool {
+    let input = match read_input(&path.to_string_lossy()) {
+        Ok(input) => input,
+        Err(e) => {
+            println!("{}: {e}", path.display());
+            return false;
+        }
+    };
+
+    let (_, errors) = parse_recovering(&input);
+
+    for error in &errors {
+        match error_position(&input, error) {
+            Some(position) => println!(
+                "{}:{}:{}: {error}",
+                path.display(),
+                position.line,
+                position.column
+            ),
+            None => println!("{}: {error}", path.display()),
+        }
+    }
+
+    errors.is_empty()
+}
+
+pub fn run(args: impl Iterator<Item = String>) -> Result<ExitCode, String> {
+    let mut quiet = false;
+    let mut watch = false;
+    let mut patterns = Vec::new();
+    let mut paths = Vec::new();
+
+    for arg in args {
+        match arg.as_str() {
+            "--quiet" | "-q" => quiet = true,
+            "--watch" | "-w" => watch = true,
+            "-h" | "--help" => {
+                println!("{USAGE}");
+                return Ok(ExitCode::SUCCESS);
+            }
+            flag if flag.starts_with("--") => {
+                return Err(format!("unknown option `{flag}`\n\n{USAGE}"))
+            }
+            pattern => {
+                let matched = expand(pattern);
+
+                // Files may show up later when watching
+                if matched.is_empty() && !watch {
+                    return Err(format!("no file matches `{pattern}`"));
+                }
+
+                paths.extend(matched);
+                patterns.push(arg);
+            }
+        }
+    }
+
+    if patterns.is_empty() {
+        return Err(USAGE.to_owned());
+    }
+
+    if watch {
+        watch::run(&patterns);
+    }
+
+    let invalid = paths.iter().filter(|path| !validate(path)).count();
+
+    if !quiet {
+        let files = match paths.len() {
+            1 => "1 file".to_owned(),
+            n => format!("{n} files"),
+        };
+        eprintln!("{files} checked, {invalid} invalid");
+    }
+
+    Ok(if invalid == 0 {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    })
+}
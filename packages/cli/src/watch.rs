@@ -0,0 +1,44 @@
+//! `json validate --watch`: polls the files for changes and validates the
+//! ones that changed, for a quick feedback loop while editing fixtures.
+//!
+//! Polling the modification times keeps this free of platform-specific
+//! file system notification APIs, and is cheap for the few hundred files a
+//! config directory holds.
+
+use std::{
+    collections::HashMap,
+    path::PathBuf,
+    thread,
+    time::{Duration, SystemTime},
+};
+
+use crate::validate::{expand, validate};
+
+const INTERVAL: Duration = Duration::from_millis(500);
+
+/// Validates the files matching `patterns`, then every file that is
+/// modified or created, until interrupted
+pub fn run(patterns: &[String]) -> ! {
+    let mut seen: HashMap<PathBuf, Option<SystemTime>> = HashMap::new();
+
+    loop {
+        let mut current = HashMap::new();
+
+        for path in patterns.iter().flat_map(|pattern| expand(pattern)) {
+            let modified = path.metadata().and_then(|m| m.modified()).ok();
+
+            if seen.get(&path) != Some(&modified) && validate(&path) {
+                println!("{}: ok", path.display());
+            }
+
+            current.insert(path, modified);
+        }
+
+        for path in seen.keys().filter(|path| !current.contains_key(*path)) {
+            println!("{}: removed", path.display());
+        }
+
+        seen = current;
+        thread::sleep(INTERVAL);
+    }
+}
@@ -0,0 +1,251 @@
+//! Parses every file of `test-files/` with the parser of each step, checks
+//! that they all read the same document, and prints how long each one took.
+//!
+//! ```text
+//! cargo run --release -p compare
+//! ```
+//!
+//! Step 0 only parses "hello world". For the numbers of criterion, and a
+//! comparison with serde_json, see the `bench` package.
+
+use std::{
+    fs::{read_dir, read_to_string},
+    path::PathBuf,
+    process::ExitCode,
+    time::{Duration, Instant},
+};
+
+use step_4::JsonValue;
+use step_6::{Entries, Entry};
+
+const RUNS: usize = 10;
+
+/// Size of the chunks step 6 reads
+const CHUNK_SIZE: usize = 64 * 1024;
+
+struct Step {
+    name: &'static str,
+    /// Parses a document, for the timings: whether it's valid
+    parse: fn(&str) -> bool,
+    /// Document read from the input, see `normalize`
+    value: fn(&str) -> Option<JsonValue>,
+}
+
+/// The value of a step with a `JsonValue` of its own, as a step-4 one
+macro_rules! convert {
+    ($name:ident, $step:ident) => {
+        fn $name(value: $step::JsonValue) -> JsonValue {
+            match value {
+                $step::JsonValue::Null => JsonValue::Null,
+                $step::JsonValue::Str(s) => JsonValue::Str(s),
+                $step::JsonValue::Boolean(b) => JsonValue::Boolean(b),
+                $step::JsonValue::Num(n) => JsonValue::Num(n),
+                $step::JsonValue::Array(values) => {
+                    JsonValue::Array(values.into_iter().map($name).collect())
+                }
+                $step::JsonValue::Object(map) => {
+                    JsonValue::Object(map.into_iter().map(|(k, v)| (k, $name(v))).collect())
+                }
+            }
+        }
+    };
+}
+
+convert!(from_step_1, step_1);
+convert!(from_step_2, step_2);
+convert!(from_step_3, step_3);
+
+/// Every number as an `f64`, as steps 1 to 3 only have those
+fn normalize(value: JsonValue) -> JsonValue {
+    match value {
+        JsonValue::Int(n) => JsonValue::Num(n as f64),
+        JsonValue::UInt(n) => JsonValue::Num(n as f64),
+        JsonValue::Array(values) => JsonValue::Array(values.into_iter().map(normalize).collect()),
+        JsonValue::Object(map) => {
+            JsonValue::Object(map.into_iter().map(|(k, v)| (k, normalize(v))).collect())
+        }
+        value => value,
+    }
+}
+
+/// Step 6 only yields the entries of the top-level value, which is put back
+/// together from them
+fn step_6(json: &str) -> Option<JsonValue> {
+    let mut entries = Entries::new(json.as_bytes(), CHUNK_SIZE);
+
+    Some(match json.trim_start().chars().next() {
+        Some('{') => JsonValue::Object(
+            entries
+                .map(|entry| match entry {
+                    Ok(Entry::Member(key, value)) => Some((key, value)),
+                    _ => None,
+                })
+                .collect::<Option<_>>()?,
+        ),
+        Some('[') => JsonValue::Array(
+            entries
+                .map(|entry| match entry {
+                    Ok(Entry::Element(value)) => Some(value),
+                    _ => None,
+                })
+                .collect::<Option<_>>()?,
+        ),
+        _ => match entries.next() {
+            Some(Ok(Entry::Element(value))) => value,
+            _ => return None,
+        },
+    })
+}
+
+const STEPS: [Step; 6] = [
+    Step {
+        name: "step-1",
+        parse: |json| step_1::parse(json).is_ok(),
+        value: |json| {
+            step_1::parse(json)
+                .ok()
+                .map(|(_, value)| from_step_1(value))
+        },
+    },
+    Step {
+        name: "step-2",
+        parse: |json| step_2::parse(json).is_ok(),
+        value: |json| {
+            step_2::parse(json)
+                .ok()
+                .map(|(_, value)| from_step_2(value))
+        },
+    },
+    Step {
+        name: "step-3",
+        parse: |json| step_3::parse(json).is_ok(),
+        value: |json| {
+            step_3::parse(json)
+                .ok()
+                .map(|(_, value)| from_step_3(value))
+        },
+    },
+    Step {
+        name: "step-4",
+        parse: |json| step_4::parse(json).is_ok(),
+        value: |json| step_4::parse(json).ok().map(|(_, value)| value),
+    },
+    Step {
+        name: "step-5",
+        parse: |json| step_5::parse(json.as_bytes()).is_ok(),
+        value: |json| step_5::parse(json.as_bytes()).ok().map(|(_, value)| value),
+    },
+    Step {
+        name: "step-6",
+        parse: |json| Entries::new(json.as_bytes(), CHUNK_SIZE).all(|entry| entry.is_ok()),
+        value: step_6,
+    },
+];
+
+/// The step the others are checked against
+const REFERENCE: &str = "step-4";
+
+/// Fastest of `RUNS` runs of `f`, the least disturbed by everything else
+fn time(mut f: impl FnMut()) -> Duration {
+    (0..RUNS)
+        .map(|_| {
+            let start = Instant::now();
+            f();
+            start.elapsed()
+        })
+        .min()
+        .unwrap()
+}
+
+/// The JSON files of `test-files/`, by name
+fn test_files() -> Result<Vec<PathBuf>, String> {
+    let dir = concat!(env!("CARGO_MANIFEST_DIR"), "/../../test-files");
+    let entries =
+        read_dir(dir).map_err(|e| format!("test-files/: {e}, see `cargo xtask fetch`"))?;
+
+    let mut files: Vec<_> = entries
+        .filter_map(|entry| Some(entry.ok()?.path()))
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|extension| extension == "json")
+        })
+        .collect();
+    files.sort();
+
+    Ok(files)
+}
+
+fn main() -> ExitCode {
+    let files = match test_files() {
+        Ok(files) => files,
+        Err(message) => {
+            eprintln!("{message}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let mut agree = true;
+
+    println!(
+        "{:<20} {:>10} {:<8} {:>12} {:>12}",
+        "file", "size", "step", "time", "throughput"
+    );
+
+    for path in files {
+        let file = path.file_name().unwrap().to_string_lossy();
+        let json = match read_to_string(&path) {
+            Ok(json) => json,
+            Err(e) => {
+                println!("{file:<20} {e}");
+                continue;
+            }
+        };
+        let size = json.len() as f64 / 1e6;
+
+        // Same document from every step, or the timings mean nothing
+        let values: Vec<_> = STEPS
+            .iter()
+            .map(|step| (step.value)(&json).map(normalize))
+            .collect();
+        let reference = STEPS
+            .iter()
+            .position(|step| step.name == REFERENCE)
+            .unwrap();
+        let mut differ = Vec::new();
+
+        for (step, value) in STEPS.iter().zip(&values) {
+            let cells = match value {
+                None => format!("{:>12} {:>12}", "invalid", "-"),
+                Some(_) => {
+                    let elapsed = time(|| {
+                        (step.parse)(&json);
+                    });
+                    format!(
+                        "{:>9.2} ms {:>7.1} MB/s",
+                        elapsed.as_secs_f64() * 1e3,
+                        size / elapsed.as_secs_f64(),
+                    )
+                }
+            };
+            println!("{file:<20} {size:>7.1} MB {:<8} {cells}", step.name);
+
+            if value.is_some() && *value != values[reference] {
+                differ.push(step.name);
+            }
+        }
+
+        if !differ.is_empty() {
+            println!(
+                "{file:<20} {} read another document than {REFERENCE}",
+                differ.join(", ")
+            );
+            agree = false;
+        }
+    }
+
+    if agree {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
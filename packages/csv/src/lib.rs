@@ -0,0 +1,155 @@
+//! CSV as described by [RFC 4180](https://www.rfc-editor.org/rfc/rfc4180),
+//! with a parser written with nom, like the JSON one.
+//!
+//! Records are separated by line breaks, `\r\n` or `\n`, and fields by
+//! commas. A field in double quotes may contain commas and line breaks, a
+//! double quote being written twice: `"say ""hi"", then leave"`.
+//!
+//! The errors are those of the JSON parser, so [`step_4::render_error`]
+//! shows where the input went wrong:
+//!
+//! ```
+//! let input = "name,talk\nJules,\"nom\" parsers\n";
+//! let error = csv::parse(input).unwrap_err();
+//!
+//! assert_eq!(
+//!     step_4::render_error(input, &error),
+//!     "error: unexpected ' ', expected ','\n --> 2:12\n  |\n2 | Jules,\"nom\" parsers\n  |            ^ in quoted field\n",
+//! );
+//! ```
+
+use nom::{
+    branch::alt,
+    bytes::complete::{is_not, tag, take_while},
+    character::complete::char,
+    combinator::{cut, map, value},
+    error::{context, ContextError, ErrorKind, FromExternalError, ParseError},
+    multi::{fold_many0, separated_list1},
+    sequence::{delimited, terminated},
+    Finish, IResult,
+};
+
+use step_4::{JsonError, JsonValue, Map};
+
+type Result<'a, O, E> = IResult<&'a str, O, E>;
+
+/// `"..."`, where `""` is a `"`
+fn quoted<'a, E: ParseError<&'a str> + ContextError<&'a str>>(i: &'a str) -> Result<'a, String, E> {
+    context(
+        "quoted field",
+        delimited(
+            char('"'),
+            cut(fold_many0(
+                alt((is_not("\""), value("\"", tag("\"\"")))),
+                String::new,
+                |mut field, piece| {
+                    field.push_str(piece);
+                    field
+                },
+            )),
+            // Then the end of the field
+            cut(terminated(char('"'), field_end)),
+        ),
+    )(i)
+}
+
+fn unquoted<'a, E: ParseError<&'a str>>(i: &'a str) -> Result<'a, String, E> {
+    map(
+        terminated(
+            take_while(|c| !matches!(c, ',' | '"' | '\r' | '\n')),
+            field_end,
+        ),
+        String::from,
+    )(i)
+}
+
+/// Without consuming it: a comma, a line break or the end of the input
+fn field_end<'a, E: ParseError<&'a str>>(i: &'a str) -> Result<'a, (), E> {
+    match i.chars().next() {
+        None | Some(',' | '\n') => Ok((i, ())),
+        Some('\r') if i[1..].starts_with('\n') => Ok((i, ())),
+        Some(_) => Err(nom::Err::Failure(E::from_char(i, ','))),
+    }
+}
+
+fn record<'a, E: ParseError<&'a str> + ContextError<&'a str>>(
+    i: &'a str,
+) -> Result<'a, Vec<String>, E> {
+    separated_list1(char(','), alt((quoted, unquoted)))(i)
+}
+
+/// Records of the input, which is consumed whole, along with the input
+/// each starts at. The last one may be followed by a line break
+fn located_records<'a, E: ParseError<&'a str> + ContextError<&'a str>>(
+    mut i: &'a str,
+) -> Result<'a, Vec<(&'a str, Vec<String>)>, E> {
+    let mut records = Vec::new();
+
+    while !i.is_empty() {
+        let (rest, record) = record(i)?;
+        records.push((i, record));
+
+        i = rest
+            .strip_prefix("\r\n")
+            .or_else(|| rest.strip_prefix('\n'))
+            .unwrap_or(rest);
+    }
+
+    Ok((i, records))
+}
+
+/// Records of the input, which is consumed whole. The last one may be
+/// followed by a line break
+pub fn records<'a, E: ParseError<&'a str> + ContextError<&'a str>>(
+    i: &'a str,
+) -> Result<'a, Vec<Vec<String>>, E> {
+    map(located_records, |records| {
+        records.into_iter().map(|(_, record)| record).collect()
+    })(i)
+}
+
+/// Records of `input`, each a list of fields
+pub fn parse(input: &str) -> std::result::Result<Vec<Vec<String>>, JsonError> {
+    records::<JsonError>(input)
+        .finish()
+        .map(|(_, records)| records)
+}
+
+/// Records of `input` as an array of objects, from the names of the first
+/// record to the fields of each of the others.
+///
+/// A record that doesn't have as many fields as there are names is an error
+/// located at its start, like a syntax error.
+pub fn to_json(input: &str) -> std::result::Result<JsonValue, JsonError> {
+    let (_, records) = located_records::<JsonError>(input).finish()?;
+    let mut records = records.into_iter();
+    let Some((at, names)) = records.next() else {
+        return Ok(JsonValue::Array(Vec::new()));
+    };
+
+    let invalid =
+        |at, message: String| JsonError::from_external_error(at, ErrorKind::Verify, message);
+
+    for (index, name) in names.iter().enumerate() {
+        if names[..index].contains(name) {
+            return Err(invalid(at, format!("duplicate column `{name}`")));
+        }
+    }
+
+    let mut objects = Vec::new();
+    for (at, record) in records {
+        if record.len() != names.len() {
+            let message = format!("expected {} fields, found {}", names.len(), record.len());
+            return Err(invalid(at, message));
+        }
+
+        let object: Map<String, JsonValue> = names
+            .iter()
+            .cloned()
+            .zip(record.into_iter().map(JsonValue::Str))
+            .collect();
+        objects.push(JsonValue::Object(object));
+    }
+
+    Ok(JsonValue::Array(objects))
+}
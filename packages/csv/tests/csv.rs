@@ -0,0 +1,70 @@
+use step_4::{render_error, JsonValue};
+
+fn fields(records: &[&[&str]]) -> Vec<Vec<String>> {
+    records
+        .iter()
+        .map(|record| record.iter().map(|field| field.to_string()).collect())
+        .collect()
+}
+
+#[test]
+fn records() {
+    assert_eq!(csv::parse("").unwrap(), fields(&[]));
+    assert_eq!(
+        csv::parse("a,b\n1,2").unwrap(),
+        fields(&[&["a", "b"], &["1", "2"]])
+    );
+    assert_eq!(
+        csv::parse("a,b\r\n1,2\r\n").unwrap(),
+        fields(&[&["a", "b"], &["1", "2"]])
+    );
+    assert_eq!(csv::parse(",\n").unwrap(), fields(&[&["", ""]]));
+    assert_eq!(csv::parse("é,🦀 ").unwrap(), fields(&[&["é", "🦀 "]]));
+}
+
+#[test]
+fn quoted_fields() {
+    let input = "\"a, b\",\"say \"\"hi\"\"\"\n\"two\r\nlines\",\"\"\n";
+
+    assert_eq!(
+        csv::parse(input).unwrap(),
+        fields(&[&["a, b", "say \"hi\""], &["two\r\nlines", ""]])
+    );
+}
+
+#[test]
+fn to_json() {
+    let input = "name,talk\nJules,\"Parsing JSON\nwith nom\"\nFerris,\n";
+    let expected: JsonValue = r#"[
+        {"name": "Jules", "talk": "Parsing JSON\nwith nom"},
+        {"name": "Ferris", "talk": ""}
+    ]"#
+    .parse()
+    .unwrap();
+
+    assert_eq!(csv::to_json(input).unwrap(), expected);
+    assert_eq!(csv::to_json("").unwrap(), JsonValue::Array(Vec::new()));
+    assert_eq!(csv::to_json("a,b").unwrap(), JsonValue::Array(Vec::new()));
+}
+
+#[test]
+fn errors() {
+    let error = |input: &str| render_error(input, &csv::to_json(input).unwrap_err());
+
+    assert_eq!(
+        error("a,b\n\"x\n"),
+        "error: unexpected end of input, expected '\"'\n --> 3:1\n  |\n3 | \n  | ^ in quoted field\n"
+    );
+    assert_eq!(
+        error("a,b\nx\"y\",z\n"),
+        "error: unexpected '\"', expected ','\n --> 2:2\n  |\n2 | x\"y\",z\n  |  ^\n"
+    );
+    assert_eq!(
+        error("a,b\n\"1\nx\",2\n3\n"),
+        "error: expected 2 fields, found 1\n --> 4:1\n  |\n4 | 3\n  | ^\n"
+    );
+    assert_eq!(
+        error("a,b,a\n"),
+        "error: duplicate column `a`\n --> 1:1\n  |\n1 | a,b,a\n  | ^\n"
+    );
+}
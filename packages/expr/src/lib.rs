@@ -0,0 +1,156 @@
+//! Arithmetic expressions, the usual exercise after JSON: this time the
+//! grammar has precedence.
+//!
+//! ```text
+//! expr    = term (("+" | "-") term)*
+//! term    = unary (("*" | "/") unary)*
+//! unary   = "-" unary | power
+//! power   = atom ("^" unary)?
+//! atom    = number | variable | "(" expr ")"
+//! ```
+//!
+//! Each level parses the operands of the one above, so `*` binds tighter
+//! than `+`, and `^` than `-`: `-2^2` is `-(2^2)`. `^` is right-associative,
+//! its right operand being parsed again from `unary`.
+//!
+//! Nodes are [`Spanned`] with the [`Source`] of the JSON parser, and errors
+//! are [`JsonError`]s, so [`step_4::render_error`] shows them in the input,
+//! those of the evaluation too.
+//!
+//! ```
+//! use step_4::Map;
+//!
+//! let variables = Map::from_iter([("x".to_owned(), 3.0)]);
+//!
+//! assert_eq!(expr::evaluate("2 * (x + 1) ^ 2", &variables).unwrap(), 32.0);
+//!
+//! let input = "1 / (x - 3)";
+//! let error = expr::evaluate(input, &variables).unwrap_err();
+//! assert_eq!(
+//!     step_4::render_error(input, &error),
+//!     "error: division by zero\n --> 1:5\n  |\n1 | 1 / (x - 3)\n  |     ^\n",
+//! );
+//! ```
+
+use std::fmt::Display;
+
+use nom::error::{ErrorKind, FromExternalError};
+use step_4::{JsonError, Map, Position, Source, Spanned};
+
+mod parse;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Op {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Pow,
+}
+
+impl Display for Op {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let op = match self {
+            Op::Add => "+",
+            Op::Sub => "-",
+            Op::Mul => "*",
+            Op::Div => "/",
+            Op::Pow => "^",
+        };
+
+        write!(f, "{op}")
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    Number(f64),
+    Variable(String),
+    /// `-a`
+    Neg(Box<Spanned<Expr>>),
+    Binary(Box<Spanned<Expr>>, Op, Box<Spanned<Expr>>),
+}
+
+/// With every operation in parentheses, which shows the precedence:
+/// `1 + 2 * 3` is `(1 + (2 * 3))`
+impl Display for Expr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Expr::Number(n) => write!(f, "{n}"),
+            Expr::Variable(name) => write!(f, "{name}"),
+            Expr::Neg(operand) => write!(f, "(-{})", operand.value),
+            Expr::Binary(left, op, right) => write!(f, "({} {op} {})", left.value, right.value),
+        }
+    }
+}
+
+/// Error of the evaluation, on the expression between `start` and `end`
+#[derive(Debug, Clone, PartialEq)]
+pub struct EvalError {
+    pub message: String,
+    pub start: Position,
+    pub end: Position,
+}
+
+impl Display for EvalError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} at {}:{}",
+            self.message, self.start.line, self.start.column
+        )
+    }
+}
+
+impl std::error::Error for EvalError {}
+
+impl EvalError {
+    fn new<T>(message: String, expr: &Spanned<T>) -> Self {
+        Self {
+            message,
+            start: expr.start,
+            end: expr.end,
+        }
+    }
+}
+
+/// Parses a whole expression
+pub fn parse(input: &str) -> Result<Spanned<Expr>, JsonError> {
+    parse::expression(input, &Source::new(input))
+}
+
+/// Value of `expr`, its variables being looked up in `variables`
+pub fn eval(expr: &Spanned<Expr>, variables: &Map<String, f64>) -> Result<f64, EvalError> {
+    match &expr.value {
+        Expr::Number(n) => Ok(*n),
+        Expr::Variable(name) => variables
+            .get(name)
+            .copied()
+            .ok_or_else(|| EvalError::new(format!("unknown variable `{name}`"), expr)),
+        Expr::Neg(operand) => Ok(-eval(operand, variables)?),
+        Expr::Binary(left, op, right) => {
+            let (a, b) = (eval(left, variables)?, eval(right, variables)?);
+
+            Ok(match op {
+                Op::Add => a + b,
+                Op::Sub => a - b,
+                Op::Mul => a * b,
+                Op::Div if b == 0.0 => {
+                    return Err(EvalError::new("division by zero".to_owned(), right))
+                }
+                Op::Div => a / b,
+                Op::Pow => a.powf(b),
+            })
+        }
+    }
+}
+
+/// Parses and evaluates `input`. Errors of the evaluation are located in
+/// the input like those of the parser
+pub fn evaluate(input: &str, variables: &Map<String, f64>) -> Result<f64, JsonError> {
+    let expr = parse(input)?;
+
+    eval(&expr, variables).map_err(|e| {
+        JsonError::from_external_error(&input[e.start.offset..], ErrorKind::Verify, e.message)
+    })
+}
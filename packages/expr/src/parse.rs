@@ -0,0 +1,155 @@
+use nom::{
+    branch::alt,
+    bytes::complete::take_while,
+    character::complete::{char, digit1, multispace0, one_of, satisfy},
+    combinator::{all_consuming, cut, map, map_res, opt, recognize, value},
+    error::{context, ErrorKind, ParseError},
+    sequence::{pair, preceded, terminated, tuple},
+    Finish, IResult,
+};
+
+use step_4::{JsonError, Source, Spanned};
+
+use crate::{Expr, Op};
+
+type Result<'a, O> = IResult<&'a str, O, JsonError>;
+
+/// `12`, `1.5`, `6e-2`. The sign is left to `unary`
+fn number(i: &str) -> Result<'_, f64> {
+    map_res(
+        recognize(tuple((
+            digit1,
+            opt(pair(char('.'), digit1)),
+            opt(tuple((one_of("eE"), opt(one_of("+-")), digit1))),
+        ))),
+        str::parse,
+    )(i)
+}
+
+fn identifier(i: &str) -> Result<'_, &str> {
+    recognize(pair(
+        satisfy(|c| c.is_alphabetic() || c == '_'),
+        take_while(|c: char| c.is_alphanumeric() || c == '_'),
+    ))(i)
+}
+
+fn binary(left: Spanned<Expr>, op: Op, right: Spanned<Expr>) -> Spanned<Expr> {
+    Spanned {
+        start: left.start,
+        end: right.end,
+        value: Expr::Binary(Box::new(left), op, Box::new(right)),
+    }
+}
+
+fn atom<'a>(i: &'a str, source: &Source<'a>) -> Result<'a, Spanned<Expr>> {
+    let (i, _) = multispace0(i)?;
+
+    let result = alt((
+        source.spanned(map(number, Expr::Number)),
+        source.spanned(map(identifier, |name| Expr::Variable(name.to_owned()))),
+        // Spanning the parentheses too
+        map(
+            source.spanned(context(
+                "parentheses",
+                preceded(
+                    char('('),
+                    cut(terminated(
+                        |i| expr(i, source),
+                        preceded(multispace0, char(')')),
+                    )),
+                ),
+            )),
+            |outer| Spanned {
+                value: outer.value.value,
+                start: outer.start,
+                end: outer.end,
+            },
+        ),
+    ))(i);
+
+    match result {
+        // Rather than the error of the last alternative, `(` being expected
+        Err(nom::Err::Error(_)) => Err(nom::Err::Error(JsonError::from_error_kind(
+            i,
+            ErrorKind::Alt,
+        ))),
+        result => result,
+    }
+}
+
+/// `atom`, then `^` and an exponent that may have exponents too
+fn power<'a>(i: &'a str, source: &Source<'a>) -> Result<'a, Spanned<Expr>> {
+    let (i, base) = atom(i, source)?;
+    let (i, exponent) = opt(preceded(
+        preceded(multispace0, char('^')),
+        cut(|i| unary(i, source)),
+    ))(i)?;
+
+    match exponent {
+        Some(exponent) => Ok((i, binary(base, Op::Pow, exponent))),
+        None => Ok((i, base)),
+    }
+}
+
+fn unary<'a>(i: &'a str, source: &Source<'a>) -> Result<'a, Spanned<Expr>> {
+    let (i, _) = multispace0(i)?;
+    let start = source.position(i);
+
+    match char::<_, JsonError>('-')(i) {
+        Ok((i, _)) => {
+            let (i, operand) = cut(|i| unary(i, source))(i)?;
+            let spanned = Spanned {
+                start,
+                end: operand.end,
+                value: Expr::Neg(Box::new(operand)),
+            };
+
+            Ok((i, spanned))
+        }
+        Err(_) => power(i, source),
+    }
+}
+
+/// Operands separated by operators of the same precedence, grouped from the
+/// left: `1 - 2 - 3` is `(1 - 2) - 3`
+fn left_associative<'a>(
+    i: &'a str,
+    source: &Source<'a>,
+    mut operator: impl FnMut(&'a str) -> Result<'a, Op>,
+    operand: impl Fn(&'a str, &Source<'a>) -> Result<'a, Spanned<Expr>>,
+) -> Result<'a, Spanned<Expr>> {
+    let (mut i, mut left) = operand(i, source)?;
+
+    loop {
+        match preceded(multispace0, &mut operator)(i) {
+            Ok((rest, op)) => {
+                let (rest, right) = cut(|i| operand(i, source))(rest)?;
+                left = binary(left, op, right);
+                i = rest;
+            }
+            Err(nom::Err::Error(_)) => return Ok((i, left)),
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+fn term<'a>(i: &'a str, source: &Source<'a>) -> Result<'a, Spanned<Expr>> {
+    let operator = alt((value(Op::Mul, char('*')), value(Op::Div, char('/'))));
+
+    left_associative(i, source, operator, unary)
+}
+
+fn expr<'a>(i: &'a str, source: &Source<'a>) -> Result<'a, Spanned<Expr>> {
+    let operator = alt((value(Op::Add, char('+')), value(Op::Sub, char('-'))));
+
+    left_associative(i, source, operator, term)
+}
+
+pub(crate) fn expression<'a>(
+    input: &'a str,
+    source: &Source<'a>,
+) -> std::result::Result<Spanned<Expr>, JsonError> {
+    all_consuming(terminated(|i| expr(i, source), multispace0))(input)
+        .finish()
+        .map(|(_, expr)| expr)
+}
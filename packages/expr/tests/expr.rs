@@ -0,0 +1,94 @@
+use expr::{eval, parse, EvalError};
+use step_4::{render_error, Map, Position};
+
+fn parenthesized(input: &str) -> String {
+    parse(input).unwrap().value.to_string()
+}
+
+fn value(input: &str) -> f64 {
+    expr::evaluate(input, &Map::default()).unwrap()
+}
+
+#[test]
+fn precedence() {
+    assert_eq!(parenthesized("1 + 2 * 3"), "(1 + (2 * 3))");
+    assert_eq!(parenthesized("(1 + 2) * 3"), "((1 + 2) * 3)");
+    assert_eq!(parenthesized("1 - 2 - 3"), "((1 - 2) - 3)");
+    assert_eq!(parenthesized("8 / 4 / 2"), "((8 / 4) / 2)");
+    assert_eq!(parenthesized("2 ^ 3 ^ 2"), "(2 ^ (3 ^ 2))");
+    assert_eq!(parenthesized("-2^2"), "(-(2 ^ 2))");
+    assert_eq!(parenthesized("2^-1"), "(2 ^ (-1))");
+    assert_eq!(parenthesized("--x * y"), "((-(-x)) * y)");
+}
+
+#[test]
+fn evaluation() {
+    assert_eq!(value("1 + 2 * 3"), 7.0);
+    assert_eq!(value("(1 + 2) * 3"), 9.0);
+    assert_eq!(value("1 - 2 - 3"), -4.0);
+    assert_eq!(value("2 ^ 3 ^ 2"), 512.0);
+    assert_eq!(value("-2^2"), -4.0);
+    assert_eq!(value(" 1.5e1 / 2^-1 "), 30.0);
+
+    let variables = Map::from_iter([("width".to_owned(), 4.0), ("h_2".to_owned(), 2.5)]);
+    assert_eq!(
+        expr::evaluate("width * h_2 - width", &variables).unwrap(),
+        6.0
+    );
+}
+
+#[test]
+fn spans() {
+    let input = "1 +\n  (x * 2)";
+    let expr = parse(input).unwrap();
+    let error = eval(&expr, &Map::default()).unwrap_err();
+
+    assert_eq!(
+        error,
+        EvalError {
+            message: "unknown variable `x`".to_owned(),
+            start: Position {
+                offset: 7,
+                line: 2,
+                column: 4
+            },
+            end: Position {
+                offset: 8,
+                line: 2,
+                column: 5
+            },
+        }
+    );
+    assert_eq!(error.to_string(), "unknown variable `x` at 2:4");
+    assert_eq!(
+        expr.end,
+        Position {
+            offset: 13,
+            line: 2,
+            column: 10
+        }
+    );
+}
+
+#[test]
+fn errors() {
+    let error =
+        |input: &str| render_error(input, &expr::evaluate(input, &Map::default()).unwrap_err());
+
+    assert_eq!(
+        error("1 + * 2"),
+        "error: unexpected '*'\n --> 1:5\n  |\n1 | 1 + * 2\n  |     ^\n"
+    );
+    assert_eq!(
+        error("(1 + 2"),
+        "error: unexpected end of input, expected ')'\n --> 1:7\n  |\n1 | (1 + 2\n  |       ^ in parentheses\n"
+    );
+    assert_eq!(
+        error("1 2"),
+        "error: trailing characters\n --> 1:3\n  |\n1 | 1 2\n  |   ^\n"
+    );
+    assert_eq!(
+        error("3 / (1 - 1)"),
+        "error: division by zero\n --> 1:5\n  |\n1 | 3 / (1 - 1)\n  |     ^\n"
+    );
+}
@@ -0,0 +1,68 @@
+/*
+ * C API of the step-4 JSON parser.
+ *
+ * json_parse returns a document owning the whole tree, freed with json_free.
+ * Values are pointers into their document: they are never freed on their
+ * own, and are valid as long as the document is.
+ */
+
+#ifndef JSON_H
+#define JSON_H
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+typedef struct JsonDocument json_document_t;
+typedef struct JsonValue json_value_t;
+typedef struct JsonObjectIter json_object_iter_t;
+
+typedef enum {
+    JSON_NULL = 0,
+    JSON_BOOLEAN = 1,
+    JSON_NUMBER = 2,
+    JSON_STRING = 3,
+    JSON_ARRAY = 4,
+    JSON_OBJECT = 5,
+} json_type_t;
+
+/* Parses len bytes of input. On failure returns NULL and, if error isn't
+ * NULL, points it to a message to free with json_string_free. */
+json_document_t *json_parse(const char *input, size_t len, char **error);
+void json_free(json_document_t *document);
+void json_string_free(char *s);
+
+const json_value_t *json_root(const json_document_t *document);
+json_type_t json_type(const json_value_t *value);
+
+bool json_as_bool(const json_value_t *value);
+/* NaN if the value isn't a number */
+double json_as_double(const json_value_t *value);
+/* Whether the value is an integer fitting in an int64_t, written to out */
+bool json_as_int64(const json_value_t *value, int64_t *out);
+/* UTF-8 bytes, not NUL-terminated, NULL if the value isn't a string */
+const char *json_as_string(const json_value_t *value, size_t *len);
+
+/* Items of an array or entries of an object, 0 for other values */
+size_t json_len(const json_value_t *value);
+const json_value_t *json_array_get(const json_value_t *value, size_t index);
+const json_value_t *json_object_get(const json_value_t *value, const char *key);
+
+/* Entries of an object in no particular order, NULL for other values */
+json_object_iter_t *json_object_iter(const json_value_t *value);
+bool json_object_iter_next(json_object_iter_t *iter, const char **key, size_t *key_len,
+                           const json_value_t **value);
+void json_object_iter_free(json_object_iter_t *iter);
+
+/* Compact JSON text, to free with json_string_free */
+char *json_serialize(const json_value_t *value);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
@@ -0,0 +1,308 @@
+//! C API of the step-4 parser, declared in `include/json.h`.
+//!
+//! [`json_parse`] returns an opaque document owning the whole tree, freed
+//! with [`json_free`]. Values are pointers into the document: they are never
+//! freed on their own, and are valid as long as their document is.
+
+use std::{
+    collections::hash_map,
+    ffi::{c_char, CStr, CString},
+    ptr, slice,
+};
+
+use step_4::{parse_with_path, render_error, to_string, JsonValue};
+
+/// Opaque document returned by [`json_parse`]
+pub struct JsonDocument {
+    root: JsonValue,
+}
+
+/// Opaque iterator over the entries of an object
+pub struct JsonObjectIter<'d> {
+    entries: hash_map::Iter<'d, String, JsonValue>,
+}
+
+/// Type of a value, see `json_type_t`
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum JsonType {
+    Null = 0,
+    Boolean = 1,
+    Number = 2,
+    String = 3,
+    Array = 4,
+    Object = 5,
+}
+
+/// `s` as a C string to be freed with [`json_string_free`]. NUL chars, which
+/// C can't represent, become U+FFFD
+fn c_string(s: String) -> *mut c_char {
+    let s = if s.contains('\0') {
+        s.replace('\0', "\u{fffd}")
+    } else {
+        s
+    };
+
+    CString::new(s).unwrap_or_default().into_raw()
+}
+
+/// Parses the `len` bytes of `input`, which don't need to be NUL-terminated.
+///
+/// Returns `NULL` if the input is invalid, after pointing `error`, if it
+/// isn't `NULL`, to a description of the problem to be freed with
+/// [`json_string_free`].
+///
+/// # Safety
+///
+/// `input` must point to `len` readable bytes, and `error` be `NULL` or
+/// writable.
+#[no_mangle]
+pub unsafe extern "C" fn json_parse(
+    input: *const c_char,
+    len: usize,
+    error: *mut *mut c_char,
+) -> *mut JsonDocument {
+    let report = |message: String| {
+        if !error.is_null() {
+            *error = c_string(message);
+        }
+        ptr::null_mut()
+    };
+
+    if input.is_null() {
+        return report("input is NULL".to_owned());
+    }
+
+    let bytes = slice::from_raw_parts(input.cast::<u8>(), len);
+    let input = match std::str::from_utf8(bytes) {
+        Ok(input) => input,
+        Err(e) => return report(format!("invalid UTF-8: {e}")),
+    };
+
+    match parse_with_path(input) {
+        Ok(root) => Box::into_raw(Box::new(JsonDocument { root })),
+        Err(e) => report(render_error(input, &e)),
+    }
+}
+
+/// Frees a document returned by [`json_parse`], and with it all its values.
+///
+/// # Safety
+///
+/// `document` must come from [`json_parse`] and not be freed already, or be
+/// `NULL`.
+#[no_mangle]
+pub unsafe extern "C" fn json_free(document: *mut JsonDocument) {
+    if !document.is_null() {
+        drop(Box::from_raw(document));
+    }
+}
+
+/// Frees a string returned by this library.
+///
+/// # Safety
+///
+/// `s` must come from this library and not be freed already, or be `NULL`.
+#[no_mangle]
+pub unsafe extern "C" fn json_string_free(s: *mut c_char) {
+    if !s.is_null() {
+        drop(CString::from_raw(s));
+    }
+}
+
+/// Root value of `document`.
+///
+/// # Safety
+///
+/// `document` must be a live document.
+#[no_mangle]
+pub unsafe extern "C" fn json_root(document: *const JsonDocument) -> *const JsonValue {
+    &(*document).root
+}
+
+/// # Safety
+///
+/// `value` must be a value of a live document.
+#[no_mangle]
+pub unsafe extern "C" fn json_type(value: *const JsonValue) -> JsonType {
+    match &*value {
+        JsonValue::Null => JsonType::Null,
+        JsonValue::Boolean(_) => JsonType::Boolean,
+        JsonValue::Int(_) | JsonValue::UInt(_) | JsonValue::Num(_) | JsonValue::RawNum(..) => {
+            JsonType::Number
+        }
+        #[cfg(feature = "bignum")]
+        JsonValue::BigNum(_) => JsonType::Number,
+        JsonValue::Str(_) | JsonValue::Wtf8(_) => JsonType::String,
+        JsonValue::Array(_) => JsonType::Array,
+        JsonValue::Object(_) => JsonType::Object,
+    }
+}
+
+/// The boolean, `false` for other values.
+///
+/// # Safety
+///
+/// `value` must be a value of a live document.
+#[no_mangle]
+pub unsafe extern "C" fn json_as_bool(value: *const JsonValue) -> bool {
+    matches!(&*value, JsonValue::Boolean(true))
+}
+
+/// The number, rounded to the nearest `double` if needed. NaN for other
+/// values.
+///
+/// # Safety
+///
+/// `value` must be a value of a live document.
+#[no_mangle]
+pub unsafe extern "C" fn json_as_double(value: *const JsonValue) -> f64 {
+    match &*value {
+        JsonValue::Num(n) | JsonValue::RawNum(n, _) => *n,
+        JsonValue::Int(n) => *n as f64,
+        JsonValue::UInt(n) => *n as f64,
+        _ => f64::NAN,
+    }
+}
+
+/// Writes the number to `out` if it is an integer fitting in an `int64_t`,
+/// returns whether it did.
+///
+/// # Safety
+///
+/// `value` must be a value of a live document, and `out` writable.
+#[no_mangle]
+pub unsafe extern "C" fn json_as_int64(value: *const JsonValue, out: *mut i64) -> bool {
+    match &*value {
+        JsonValue::Int(n) => {
+            *out = *n;
+            true
+        }
+        _ => false,
+    }
+}
+
+/// The UTF-8 bytes of the string, with their length written to `len`. They
+/// aren't NUL-terminated, and can contain NUL chars. `NULL` for other values.
+///
+/// # Safety
+///
+/// `value` must be a value of a live document, and `len` writable.
+#[no_mangle]
+pub unsafe extern "C" fn json_as_string(value: *const JsonValue, len: *mut usize) -> *const c_char {
+    match &*value {
+        JsonValue::Str(s) => {
+            *len = s.len();
+            s.as_ptr().cast()
+        }
+        _ => ptr::null(),
+    }
+}
+
+/// Number of items of an array or entries of an object, 0 for other values.
+///
+/// # Safety
+///
+/// `value` must be a value of a live document.
+#[no_mangle]
+pub unsafe extern "C" fn json_len(value: *const JsonValue) -> usize {
+    match &*value {
+        JsonValue::Array(values) => values.len(),
+        JsonValue::Object(map) => map.len(),
+        _ => 0,
+    }
+}
+
+/// Item `index` of an array, `NULL` if out of bounds or for other values.
+///
+/// # Safety
+///
+/// `value` must be a value of a live document.
+#[no_mangle]
+pub unsafe extern "C" fn json_array_get(value: *const JsonValue, index: usize) -> *const JsonValue {
+    match &*value {
+        JsonValue::Array(values) => values.get(index).map_or(ptr::null(), ptr::from_ref),
+        _ => ptr::null(),
+    }
+}
+
+/// Value of the NUL-terminated `key` in an object, `NULL` if missing or for
+/// other values.
+///
+/// # Safety
+///
+/// `value` must be a value of a live document, and `key` a C string.
+#[no_mangle]
+pub unsafe extern "C" fn json_object_get(
+    value: *const JsonValue,
+    key: *const c_char,
+) -> *const JsonValue {
+    let (JsonValue::Object(map), Ok(key)) = (&*value, CStr::from_ptr(key).to_str()) else {
+        return ptr::null();
+    };
+
+    map.get(key).map_or(ptr::null(), ptr::from_ref)
+}
+
+/// Iterator over the entries of an object, in no particular order, to be
+/// freed with [`json_object_iter_free`]. `NULL` for other values.
+///
+/// # Safety
+///
+/// `value` must be a value of a live document, which must outlive the
+/// iterator.
+#[no_mangle]
+pub unsafe extern "C" fn json_object_iter(value: *const JsonValue) -> *mut JsonObjectIter<'static> {
+    match &*value {
+        JsonValue::Object(map) => Box::into_raw(Box::new(JsonObjectIter {
+            entries: map.iter(),
+        })),
+        _ => ptr::null_mut(),
+    }
+}
+
+/// Moves to the next entry, writing its key, as for [`json_as_string`], and
+/// its value. Returns `false` once all entries were seen.
+///
+/// # Safety
+///
+/// `iter` must be a live iterator, and `key`, `key_len` and `value`
+/// writable.
+#[no_mangle]
+pub unsafe extern "C" fn json_object_iter_next(
+    iter: *mut JsonObjectIter,
+    key: *mut *const c_char,
+    key_len: *mut usize,
+    value: *mut *const JsonValue,
+) -> bool {
+    match (*iter).entries.next() {
+        Some((k, v)) => {
+            *key = k.as_ptr().cast();
+            *key_len = k.len();
+            *value = v;
+            true
+        }
+        None => false,
+    }
+}
+
+/// # Safety
+///
+/// `iter` must come from [`json_object_iter`] and not be freed already, or
+/// be `NULL`.
+#[no_mangle]
+pub unsafe extern "C" fn json_object_iter_free(iter: *mut JsonObjectIter) {
+    if !iter.is_null() {
+        drop(Box::from_raw(iter));
+    }
+}
+
+/// Compact JSON text of the value, to be freed with [`json_string_free`].
+///
+/// # Safety
+///
+/// `value` must be a value of a live document.
+#[no_mangle]
+pub unsafe extern "C" fn json_serialize(value: *const JsonValue) -> *mut c_char {
+    c_string(to_string(&*value))
+}
@@ -0,0 +1,223 @@
+//! INI and dotenv files, into the objects of the JSON parser, with a parser
+//! written with nom.
+//!
+//! ```text
+//! ; a comment, `#` works too
+//! name = meetup             values are strings, without the spaces around
+//! url = http://x # tokyo    after a space, `#` and `;` start a comment
+//! export TOKEN="a\tb"       the `export` of dotenv is dropped
+//! QUOTE='C:\nom'            double quotes have escapes, single ones don't
+//!
+//! [server]                  the keys that follow go in its object
+//! port: 8080                `:` works like `=`
+//! ```
+//!
+//! A dotenv file is an INI file without sections: both are read by
+//! [`parse`]. Its errors are those of the JSON parser, so
+//! [`step_4::render_error`] shows where the input went wrong.
+//!
+//! ```
+//! let config = ini::parse("
+//! ; Written by hand
+//! export NAME=\"Tokyo Rust\"
+//!
+//! [server]
+//! port = 8080 ; the default
+//! ").unwrap();
+//!
+//! assert_eq!(
+//!     config,
+//!     step_4::parse_with_path(r#"{"NAME": "Tokyo Rust", "server": {"port": "8080"}}"#).unwrap()
+//! );
+//! ```
+
+use nom::{
+    branch::alt,
+    bytes::complete::{is_not, tag, take_while, take_while1},
+    character::complete::{char, line_ending, not_line_ending, one_of, space0, space1},
+    combinator::{cut, eof, map, opt, recognize, value},
+    error::{context, ErrorKind, FromExternalError},
+    multi::fold_many0,
+    sequence::{delimited, pair, preceded},
+    Finish, IResult,
+};
+
+use step_4::{JsonError, JsonValue, Map};
+
+type Result<'a, O> = IResult<&'a str, O, JsonError>;
+
+fn failure(i: &str, message: String) -> nom::Err<JsonError> {
+    nom::Err::Failure(JsonError::from_external_error(
+        i,
+        ErrorKind::Verify,
+        message,
+    ))
+}
+
+fn comment(i: &str) -> Result<'_, &str> {
+    recognize(pair(one_of("#;"), not_line_ending))(i)
+}
+
+/// Rest of a line: spaces, maybe a comment, then a line break or the end of
+/// the input
+fn line_end(i: &str) -> Result<'_, ()> {
+    let (i, _) = pair(space0, opt(comment))(i)?;
+
+    alt((value((), line_ending), value((), eof)))(i)
+        .map_err(|_: nom::Err<JsonError>| failure(i, "expected the end of the line".to_owned()))
+}
+
+/// `[name]`
+fn section(i: &str) -> Result<'_, &str> {
+    context(
+        "section",
+        delimited(
+            char('['),
+            cut(map(take_while1(|c| !"]\r\n".contains(c)), str::trim)),
+            cut(char(']')),
+        ),
+    )(i)
+}
+
+fn key(i: &str) -> Result<'_, &str> {
+    take_while1(|c: char| c.is_alphanumeric() || "_-.".contains(c))(i)
+}
+
+fn escape(i: &str) -> Result<'_, char> {
+    preceded(
+        char('\\'),
+        cut(alt((
+            value('\n', char('n')),
+            value('\t', char('t')),
+            value('\r', char('r')),
+            value('"', char('"')),
+            value('\\', char('\\')),
+            value('$', char('$')),
+        ))),
+    )(i)
+}
+
+/// `"..."`, across lines maybe
+fn double_quoted(i: &str) -> Result<'_, String> {
+    context(
+        "quoted value",
+        delimited(
+            char('"'),
+            fold_many0(
+                alt((
+                    map(is_not("\"\\"), |run: &str| run.to_owned()),
+                    map(escape, String::from),
+                )),
+                String::new,
+                |value, piece| value + &piece,
+            ),
+            cut(char('"')),
+        ),
+    )(i)
+}
+
+/// `'...'`, without escapes
+fn single_quoted(i: &str) -> Result<'_, String> {
+    context(
+        "quoted value",
+        map(
+            delimited(char('\''), take_while(|c| c != '\''), cut(char('\''))),
+            str::to_owned,
+        ),
+    )(i)
+}
+
+/// Rest of the line, up to a comment, without the spaces around
+fn unquoted(i: &str) -> Result<'_, String> {
+    let (rest, line) = not_line_ending(i)?;
+
+    let mut previous = ' ';
+    let end = line
+        .char_indices()
+        .find(|&(_, c)| {
+            let starts_comment = (c == '#' || c == ';') && previous.is_whitespace();
+            previous = c;
+            starts_comment
+        })
+        .map_or(line.len(), |(index, _)| index);
+
+    Ok((rest, line[..end].trim_end().to_owned()))
+}
+
+/// `key = value`, `key: value` or `export key=value`
+fn entry(i: &str) -> Result<'_, (&str, String)> {
+    let key_value = |i| {
+        let (i, key) = key(i)?;
+        let (i, _) =
+            delimited(space0, one_of("=:"), space0)(i).map_err(|_: nom::Err<JsonError>| {
+                failure(i, "expected `=` or `:` after the key".to_owned())
+            })?;
+        let (i, value) = alt((double_quoted, single_quoted, unquoted))(i)?;
+
+        Ok((i, (key, value)))
+    };
+
+    alt((preceded(pair(tag("export"), space1), key_value), key_value))(i)
+}
+
+enum Line<'a> {
+    Section(&'a str),
+    Entry(&'a str, String),
+}
+
+fn line(i: &str) -> Result<'_, Option<Line<'_>>> {
+    let (i, _) = space0(i)?;
+    let (i, line) = opt(alt((
+        map(section, Line::Section),
+        map(entry, |(key, value)| Line::Entry(key, value)),
+    )))(i)?;
+    let (i, _) = line_end(i)?;
+
+    Ok((i, line))
+}
+
+/// Object of the entries of `input`, with one more for each section
+pub fn parse(input: &str) -> std::result::Result<JsonValue, JsonError> {
+    let mut root = Map::default();
+    let mut section = None;
+    let mut i = input;
+
+    while !i.is_empty() {
+        let (rest, line) = line(i).finish()?;
+
+        match line {
+            None => {}
+            Some(Line::Section(name)) => {
+                let object = root
+                    .entry(name.to_owned())
+                    .or_insert_with(|| JsonValue::Object(Map::default()));
+
+                if !matches!(object, JsonValue::Object(_)) {
+                    let message = format!("`{name}` is already a value, not a section");
+                    return Err(JsonError::from_external_error(
+                        i.trim_start(),
+                        ErrorKind::Verify,
+                        message,
+                    ));
+                }
+                section = Some(name.to_owned());
+            }
+            Some(Line::Entry(key, value)) => {
+                let object = match &section {
+                    Some(name) => match root.get_mut(name) {
+                        Some(JsonValue::Object(object)) => object,
+                        _ => unreachable!("sections are objects"),
+                    },
+                    None => &mut root,
+                };
+
+                // Like dotenv, the last one wins
+                object.insert(key.to_owned(), JsonValue::Str(value));
+            }
+        }
+
+        i = rest;
+    }
+
+    Ok(JsonValue::Object(root))
+}
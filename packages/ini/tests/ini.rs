@@ -0,0 +1,90 @@
+use step_4::{render_error, JsonValue};
+
+fn json(text: &str) -> JsonValue {
+    text.parse().unwrap()
+}
+
+#[test]
+fn ini() {
+    let input = "\
+; Settings of the meetup
+title = Tokyo Rust  
+empty =
+
+[server]
+host: 127.0.0.1
+url = http://localhost:8080/#top ; the comment goes, not the anchor
+
+[ database ]
+user=jules
+[server]
+port = 8080
+";
+
+    assert_eq!(
+        ini::parse(input).unwrap(),
+        json(
+            r#"{
+                "title": "Tokyo Rust",
+                "empty": "",
+                "server": {"host": "127.0.0.1", "url": "http://localhost:8080/#top", "port": "8080"},
+                "database": {"user": "jules"}
+            }"#
+        )
+    );
+}
+
+#[test]
+fn dotenv() {
+    let input = "\
+# Secrets
+export API_KEY=abc123
+DATABASE_URL=\"postgres://localhost/db\"   # local only
+GREETING=\"hello\\tworld \\\"quoted\\\" \\$HOME\"
+PATH_ON_WINDOWS='C:\\new\\table'
+MULTILINE=\"line one
+line two\"
+API_KEY=overridden\r
+";
+
+    assert_eq!(
+        ini::parse(input).unwrap(),
+        json(
+            r#"{
+                "API_KEY": "overridden",
+                "DATABASE_URL": "postgres://localhost/db",
+                "GREETING": "hello\tworld \"quoted\" $HOME",
+                "PATH_ON_WINDOWS": "C:\\new\\table",
+                "MULTILINE": "line one\nline two"
+            }"#
+        )
+    );
+    assert_eq!(ini::parse("").unwrap(), json("{}"));
+    assert_eq!(ini::parse("export=1").unwrap(), json(r#"{"export": "1"}"#));
+}
+
+#[test]
+fn errors() {
+    let error = |input: &str| render_error(input, &ini::parse(input).unwrap_err());
+
+    assert_eq!(
+        error("[server\nport = 1\n"),
+        "error: unexpected '\\n', expected ']'\n --> 1:8\n  |\n1 | [server\n  |        ^ in section\n"
+    );
+    assert_eq!(
+        error("a = \"open\n"),
+        "error: unexpected end of input, expected '\"'\n --> 2:1\n  |\n2 | \n  | ^ in quoted value\n"
+    );
+    assert_eq!(
+        error("a = 'b' c\n"),
+        "error: expected the end of the line\n --> 1:9\n  |\n1 | a = 'b' c\n  |         ^\n"
+    );
+    assert_eq!(
+        error("name\n"),
+        "error: expected `=` or `:` after the key\n --> 1:5\n  |\n1 | name\n  |     ^\n"
+    );
+    assert_eq!(
+        error("server = 1\n  [server]\n"),
+        "error: `server` is already a value, not a section\n --> 2:3\n  |\n2 |   [server]\n  |   ^\n"
+    );
+}
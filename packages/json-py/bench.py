@@ -0,0 +1,28 @@
+"""Compares nom_json with the json module of the standard library.
+
+    maturin develop --release
+    python bench.py
+"""
+
+import json
+import pathlib
+import timeit
+
+import nom_json
+
+FILES = pathlib.Path(__file__).parent / "../../test-files"
+RUNS = 20
+
+for name in ["twitter.json", "canada.json"]:
+    text = (FILES / name).read_text()
+    value = json.loads(text)
+
+    assert nom_json.loads(text) == value
+
+    for label, loads, dumps in [
+        ("json", json.loads, json.dumps),
+        ("nom_json", nom_json.loads, nom_json.dumps),
+    ]:
+        parse = min(timeit.repeat(lambda: loads(text), number=1, repeat=RUNS))
+        serialize = min(timeit.repeat(lambda: dumps(value), number=1, repeat=RUNS))
+        print(f"{name:14} {label:9} loads {parse * 1000:7.2f} ms   dumps {serialize * 1000:7.2f} ms")
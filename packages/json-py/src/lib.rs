@@ -0,0 +1,137 @@
+//! `nom_json`, a Python module with `loads` and `dumps` built on the step-4
+//! parser.
+//!
+//! ```python
+//! import nom_json
+//!
+//! nom_json.loads('{"a": [1, 2.5]}')   # {'a': [1, 2.5]}
+//! nom_json.dumps({"a": [1, 2.5]})     # '{"a":[1,2.5]}'
+//! ```
+
+use pyo3::{
+    exceptions::{PyTypeError, PyValueError},
+    prelude::*,
+    types::{PyBool, PyBytes, PyDict, PyFloat, PyInt, PyList, PyString, PyTuple},
+};
+
+use step_4::{parse_with_path, render_error, to_string_with, JsonValue, Map, SerializeOptions};
+
+fn to_py(py: Python<'_>, value: &JsonValue) -> PyResult<PyObject> {
+    Ok(match value {
+        JsonValue::Null => py.None(),
+        JsonValue::Str(s) => s.into_py(py),
+        // Python strings can have lone surrogates, and WTF-8 is what
+        // `surrogatepass` decodes
+        JsonValue::Wtf8(bytes) => PyBytes::new_bound(py, bytes)
+            .call_method1("decode", ("utf-8", "surrogatepass"))?
+            .into_py(py),
+        JsonValue::Boolean(b) => b.into_py(py),
+        JsonValue::Num(n) | JsonValue::RawNum(n, _) => n.into_py(py),
+        // Integers stay `int`, like with the `json` module
+        JsonValue::Int(n) => n.into_py(py),
+        JsonValue::UInt(n) => n.into_py(py),
+        JsonValue::Array(values) => {
+            let items = values
+                .iter()
+                .map(|value| to_py(py, value))
+                .collect::<PyResult<Vec<_>>>()?;
+            PyList::new_bound(py, items).into_py(py)
+        }
+        JsonValue::Object(map) => {
+            let dict = PyDict::new_bound(py);
+            for (key, value) in map {
+                dict.set_item(key, to_py(py, value)?)?;
+            }
+            dict.into_py(py)
+        }
+    })
+}
+
+fn from_py(object: &Bound<'_, PyAny>) -> PyResult<JsonValue> {
+    if object.is_none() {
+        return Ok(JsonValue::Null);
+    }
+
+    // Before `int`, which `bool` is a subclass of
+    if let Ok(b) = object.downcast::<PyBool>() {
+        return Ok(JsonValue::Boolean(b.is_true()));
+    }
+
+    if object.is_instance_of::<PyInt>() {
+        if let Ok(n) = object.extract::<i64>() {
+            return Ok(JsonValue::Int(n));
+        }
+        return match object.extract::<u64>() {
+            Ok(n) => Ok(JsonValue::UInt(n)),
+            Err(_) => Err(PyValueError::new_err("int too large to be serialized")),
+        };
+    }
+
+    if let Ok(n) = object.downcast::<PyFloat>() {
+        return Ok(JsonValue::Num(n.value()));
+    }
+
+    if let Ok(s) = object.downcast::<PyString>() {
+        return Ok(JsonValue::Str(s.to_str()?.to_owned()));
+    }
+
+    if object.is_instance_of::<PyList>() || object.is_instance_of::<PyTuple>() {
+        return object
+            .iter()?
+            .map(|item| from_py(&item?))
+            .collect::<PyResult<_>>()
+            .map(JsonValue::Array);
+    }
+
+    if let Ok(dict) = object.downcast::<PyDict>() {
+        let mut map = Map::with_capacity_and_hasher(dict.len(), Default::default());
+
+        for (key, value) in dict {
+            let key = key
+                .downcast::<PyString>()
+                .map_err(|_| PyTypeError::new_err("keys must be str"))?;
+            map.insert(key.to_str()?.to_owned(), from_py(&value)?);
+        }
+
+        return Ok(JsonValue::Object(map));
+    }
+
+    Err(PyTypeError::new_err(format!(
+        "Object of type {} is not JSON serializable",
+        object.get_type().name()?
+    )))
+}
+
+/// Parses `s`, raising `ValueError` with the rendered error if it is invalid
+#[pyfunction]
+fn loads(py: Python<'_>, s: &str) -> PyResult<PyObject> {
+    let value = parse_with_path(s).map_err(|e| PyValueError::new_err(render_error(s, &e)))?;
+
+    to_py(py, &value)
+}
+
+/// Serializes `obj`, compact unless `indent` is given
+#[pyfunction]
+#[pyo3(signature = (obj, *, indent = None, sort_keys = false, ensure_ascii = true))]
+fn dumps(
+    obj: &Bound<'_, PyAny>,
+    indent: Option<usize>,
+    sort_keys: bool,
+    ensure_ascii: bool,
+) -> PyResult<String> {
+    let opts = SerializeOptions {
+        indent,
+        sort_keys,
+        ensure_ascii,
+        ..Default::default()
+    };
+
+    Ok(to_string_with(&from_py(obj)?, &opts))
+}
+
+#[pymodule]
+fn nom_json(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_function(wrap_pyfunction!(loads, m)?)?;
+    m.add_function(wrap_pyfunction!(dumps, m)?)?;
+    Ok(())
+}
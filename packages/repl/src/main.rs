@@ -0,0 +1,43 @@
+//! `repl`, to explore a document interactively: the file is parsed once,
+//! then looked into with paths and filters, which makes for live demos of
+//! the parser on the big fixtures.
+//!
+//! ```text
+//! $ cargo run --release -p repl -- test-files/twitter.json
+//! > get statuses[0].user.screen_name
+//! > .statuses[] | select(.retweet_count > 100) | .id
+//! > time 20
+//! ```
+
+use std::io::{self, BufRead, IsTerminal, Write};
+
+use session::Session;
+
+mod session;
+
+fn main() {
+    let mut session = Session::new(io::stdout().is_terminal());
+
+    if let Some(path) = std::env::args().nth(1) {
+        println!("{}", session.run(&format!("load {path}")));
+    }
+    println!("type `help` for the commands");
+
+    let mut lines = io::stdin().lock().lines();
+    loop {
+        print!("> ");
+        io::stdout().flush().unwrap();
+
+        let Some(Ok(line)) = lines.next() else {
+            // Ctrl-D
+            println!();
+            break;
+        };
+
+        match line.trim() {
+            "" => {}
+            "quit" | "exit" => break,
+            line => println!("{}", session.run(line)),
+        }
+    }
+}
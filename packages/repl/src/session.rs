@@ -0,0 +1,175 @@
+//! The commands of the REPL, on the loaded document.
+
+use std::{fmt::Write, io::Read, time::Instant};
+
+use step_4::{gzip, parse_with_path, render_error, to_string_with, JsonValue, SerializeOptions};
+use step_5_filters::Filter;
+
+const HELP: &str = "commands:
+    load FILE    parse FILE, which becomes the document
+    get PATH     value at a path, like `statuses[0].user` or `/statuses/0/user`
+    .FILTER      outputs of a jq-like filter, like `.statuses[] | .id`
+    stats        what the document is made of
+    time [N]     parse the file again N times, 10 by default
+    help         this message
+    quit         leave, Ctrl-D works too";
+
+pub struct Session {
+    /// Text of the document, kept to time parses and render errors
+    input: String,
+    document: Option<JsonValue>,
+    opts: SerializeOptions,
+}
+
+impl Session {
+    /// With colors if `color`
+    pub fn new(color: bool) -> Self {
+        Self {
+            input: String::new(),
+            document: None,
+            opts: SerializeOptions {
+                indent: Some(2),
+                color,
+                ..Default::default()
+            },
+        }
+    }
+
+    /// Output of `line`, errors included
+    pub fn run(&mut self, line: &str) -> String {
+        let (command, argument) = line.split_once(' ').unwrap_or((line, ""));
+        let argument = argument.trim();
+
+        let result = match command {
+            "load" => self.load(argument),
+            "help" => Ok(HELP.to_owned()),
+            _ if line.starts_with('.') => self.filter(line),
+            "get" => self.get(argument),
+            "stats" => self.stats(),
+            "time" => self.time(argument),
+            _ => Err(format!("unknown command `{command}`, see `help`")),
+        };
+
+        result.unwrap_or_else(|message| format!("error: {message}"))
+    }
+
+    fn document(&self) -> Result<&JsonValue, String> {
+        self.document
+            .as_ref()
+            .ok_or_else(|| "no document, see `load`".to_owned())
+    }
+
+    fn load(&mut self, path: &str) -> Result<String, String> {
+        let bytes = std::fs::read(path).map_err(|e| format!("{path}: {e}"))?;
+        let input = if gzip::is_gzip(&bytes) {
+            let mut input = String::new();
+            gzip::GzDecoder::new(bytes.as_slice())
+                .read_to_string(&mut input)
+                .map_err(|e| format!("{path}: {e}"))?;
+            input
+        } else {
+            String::from_utf8(bytes).map_err(|e| format!("{path}: {e}"))?
+        };
+
+        let start = Instant::now();
+        let parsed = parse_with_path(&input);
+        let elapsed = start.elapsed();
+
+        match parsed {
+            Ok(document) => {
+                self.document = Some(document);
+                self.input = input;
+                Ok(format!(
+                    "loaded {path}: {} bytes parsed in {elapsed:.2?}",
+                    self.input.len()
+                ))
+            }
+            Err(e) => Err(render_error(&input, &e)),
+        }
+    }
+
+    fn get(&self, path: &str) -> Result<String, String> {
+        let document = self.document()?;
+
+        let value = match path.strip_prefix('/') {
+            Some(pointer) => pointer_get(document, pointer),
+            None => document.get_path(path),
+        };
+
+        match value {
+            Some(value) => Ok(to_string_with(value, &self.opts)),
+            None => Err(format!("nothing at `{path}`")),
+        }
+    }
+
+    fn filter(&self, filter: &str) -> Result<String, String> {
+        let document = self.document()?;
+        let filter = Filter::parse(filter).map_err(|e| format!("invalid filter: {e}"))?;
+        let values = filter.apply(document).map_err(|e| e.to_string())?;
+
+        let outputs: Vec<String> = values
+            .iter()
+            .map(|value| to_string_with(value, &self.opts))
+            .collect();
+
+        Ok(outputs.join("\n"))
+    }
+
+    fn stats(&self) -> Result<String, String> {
+        let stats = self.document()?.stats();
+        let counts = stats.counts;
+        let mut out = String::new();
+
+        writeln!(out, "max depth    {}", stats.max_depth).unwrap();
+        writeln!(
+            out,
+            "values       {} null, {} boolean, {} number, {} string, {} array, {} object",
+            counts.null, counts.boolean, counts.number, counts.string, counts.array, counts.object
+        )
+        .unwrap();
+        writeln!(out, "string bytes {}", stats.string_bytes).unwrap();
+        write!(out, "top keys    ").unwrap();
+        for (key, count) in stats.top_keys(10) {
+            write!(out, " {key} ({count})").unwrap();
+        }
+
+        Ok(out)
+    }
+
+    fn time(&self, runs: &str) -> Result<String, String> {
+        self.document()?;
+        let runs = match runs {
+            "" => 10,
+            runs => runs
+                .parse::<u32>()
+                .ok()
+                .filter(|&runs| runs > 0)
+                .ok_or_else(|| format!("invalid number of runs `{runs}`"))?,
+        };
+
+        let start = Instant::now();
+        for _ in 0..runs {
+            parse_with_path(&self.input).map_err(|e| e.to_string())?;
+        }
+        let average = start.elapsed() / runs;
+        let throughput = self.input.len() as f64 / average.as_secs_f64() / 1e6;
+
+        Ok(format!(
+            "{runs} parses, {average:.2?} on average, {throughput:.0} MB/s"
+        ))
+    }
+}
+
+/// Value at a [JSON pointer](https://www.rfc-editor.org/rfc/rfc6901), after
+/// its first `/`
+fn pointer_get<'v>(value: &'v JsonValue, pointer: &str) -> Option<&'v JsonValue> {
+    pointer.split('/').try_fold(value, |value, token| {
+        let token = token.replace("~1", "/").replace("~0", "~");
+
+        match value {
+            JsonValue::Object(map) => map.get(&token),
+            JsonValue::Array(values) => values.get(token.parse::<usize>().ok()?),
+            _ => None,
+        }
+    })
+}
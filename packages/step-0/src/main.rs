@@ -18,27 +18,27 @@ struct HelloWorld {
     pub is_happy: bool,
 }
 
-fn hello<'a, E: ParseError<&'a str>>(i: &'a str) -> Result<String, E> {
+fn hello<'a, E: ParseError<&'a str>>(i: &'a str) -> Result<'a, String, E> {
     let (i, hello) = tag_no_case("hello")(i)?;
 
     Ok((i, hello.to_owned()))
 }
 
-fn world<'a, E: ParseError<&'a str>>(i: &'a str) -> Result<String, E> {
+fn world<'a, E: ParseError<&'a str>>(i: &'a str) -> Result<'a, String, E> {
     let (i, world) = tag_no_case("world")(i)?;
 
     Ok((i, world.to_owned()))
 }
 
-fn is_happy<'a, E: ParseError<&'a str>>(i: &'a str) -> Result<bool, E> {
+fn is_happy<'a, E: ParseError<&'a str>>(i: &'a str) -> Result<'a, bool, E> {
     map(opt(char('!')), |opt| opt.is_some())(i)
 }
 
-fn whitespaces<'a, E: ParseError<&'a str>>(i: &'a str) -> Result<&'a str, E> {
+fn whitespaces<'a, E: ParseError<&'a str>>(i: &'a str) -> Result<'a, &'a str, E> {
     take_while(|c| c == ' ' || c == '\n')(i)
 }
 
-fn hello_world<'a, E: ParseError<&'a str>>(i: &'a str) -> Result<(String, String, bool), E> {
+fn hello_world<'a, E: ParseError<&'a str>>(i: &'a str) -> Result<'a, (String, String, bool), E> {
     let (i, (hello, world)) = separated_pair(hello, whitespaces, world)(i)?;
 
     let (i, is_happy) = is_happy(i)?;
@@ -46,7 +46,7 @@ fn hello_world<'a, E: ParseError<&'a str>>(i: &'a str) -> Result<(String, String
     Ok((i, (hello, world, is_happy)))
 }
 
-fn parse<'a, E: ParseError<&'a str>>(i: &'a str) -> Result<HelloWorld, E> {
+fn parse<'a, E: ParseError<&'a str>>(i: &'a str) -> Result<'a, HelloWorld, E> {
     map(
         delimited(whitespaces, hello_world, whitespaces),
         |(hello, world, is_happy)| HelloWorld {
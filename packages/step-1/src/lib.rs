@@ -0,0 +1,195 @@
+use nom::{
+    branch::alt,
+    bytes::complete::{tag, take, take_while},
+    character::complete::{anychar, char, none_of},
+    combinator::{cut, map, map_opt, value, verify},
+    error::{context, ContextError, ErrorKind, ParseError, VerboseError},
+    multi::{fold_many0, separated_list0},
+    number::complete::double,
+    sequence::{delimited, preceded, separated_pair, terminated},
+    IResult, Parser,
+};
+use std::collections::HashMap;
+use std::str;
+
+#[derive(Debug, PartialEq)]
+pub enum JsonValue {
+    Null,
+    Str(String),
+    Boolean(bool),
+    Num(f64),
+    Array(Vec<JsonValue>),
+    Object(HashMap<String, JsonValue>),
+}
+
+type Result<'a, O, E> = IResult<&'a str, O, E>;
+
+fn sp<'a, E: ParseError<&'a str>>(i: &'a str) -> Result<'a, &'a str, E> {
+    let chars = " \t\r\n";
+
+    // nom combinators like `take_while` return a function. That function is the
+    // parser,to which we can pass the input
+    take_while(move |c| chars.contains(c))(i)
+}
+
+fn boolean<'a, E: ParseError<&'a str>>(input: &'a str) -> Result<'a, bool, E> {
+    let parse_true = value(true, tag("true"));
+
+    let parse_false = value(false, tag("false"));
+
+    alt((parse_true, parse_false)).parse(input)
+}
+
+fn null<'a, E: ParseError<&'a str>>(input: &'a str) -> Result<'a, (), E> {
+    value((), tag("null")).parse(input)
+}
+
+fn u16_hex<'a, E: ParseError<&'a str>>(i: &'a str) -> Result<'a, u16, E> {
+    map_opt(take(4usize), |s: &'a str| u16::from_str_radix(s, 16).ok())(i)
+}
+
+fn unicode_escape<'a, E: ParseError<&'a str>>(i: &'a str) -> Result<'a, char, E> {
+    map_opt(
+        alt((
+            // Not a surrogate
+            map(verify(u16_hex, |cp| !(0xD800..0xE000).contains(cp)), |cp| {
+                cp as u32
+            }),
+            // See https://en.wikipedia.org/wiki/UTF-16#Code_points_from_U+010000_to_U+10FFFF for details
+            map(
+                verify(
+                    separated_pair(u16_hex, tag("\\u"), u16_hex),
+                    |(high, low)| (0xD800..0xDC00).contains(high) && (0xDC00..0xE000).contains(low),
+                ),
+                |(high, low)| {
+                    let high_ten = (high as u32) - 0xD800;
+                    let low_ten = (low as u32) - 0xDC00;
+                    (high_ten << 10) + low_ten + 0x10000
+                },
+            ),
+        )),
+        std::char::from_u32,
+    )(i)
+}
+
+/// Any char but `"`, a `\` starting an escape
+fn parse_char<'a, E: ParseError<&'a str>>(i: &'a str) -> Result<'a, char, E> {
+    let (i, c) = none_of("\"")(i)?;
+
+    if c == '\\' {
+        let (rest, escaped_char) = anychar(i)?;
+        let final_char = match escaped_char {
+            '"' | '\\' | '/' => escaped_char,
+            'b' => '\x08',
+            'f' => '\x0C',
+            'n' => '\n',
+            'r' => '\r',
+            't' => '\t',
+            'u' => return unicode_escape(rest),
+            _ => return Err(nom::Err::Failure(E::from_error_kind(i, ErrorKind::Char))),
+        };
+
+        Ok((rest, final_char))
+    } else {
+        Ok((i, c))
+    }
+}
+
+fn string<'a, E: ParseError<&'a str> + ContextError<&'a str>>(i: &'a str) -> Result<'a, String, E> {
+    context(
+        "string",
+        preceded(
+            char('"'),
+            cut(terminated(
+                fold_many0(parse_char, String::new, |mut string, c| {
+                    string.push(c);
+                    string
+                }),
+                char('"'),
+            )),
+        ),
+    )
+    .parse(i)
+}
+
+fn array<'a, E: ParseError<&'a str> + ContextError<&'a str>>(
+    i: &'a str,
+) -> Result<'a, Vec<JsonValue>, E> {
+    context(
+        "array",
+        preceded(
+            char('['),
+            cut(terminated(
+                separated_list0(preceded(sp, char(',')), json_value),
+                preceded(sp, char(']')),
+            )),
+        ),
+    )
+    .parse(i)
+}
+
+fn key_value<'a, E: ParseError<&'a str> + ContextError<&'a str>>(
+    i: &'a str,
+) -> Result<'a, (String, JsonValue), E> {
+    separated_pair(
+        preceded(sp, string),
+        cut(preceded(sp, char(':'))),
+        json_value,
+    )
+    .parse(i)
+}
+
+fn hash<'a, E: ParseError<&'a str> + ContextError<&'a str>>(
+    i: &'a str,
+) -> Result<'a, HashMap<String, JsonValue>, E> {
+    context(
+        "map",
+        preceded(
+            char('{'),
+            cut(terminated(
+                map(
+                    separated_list0(preceded(sp, char(',')), key_value),
+                    |tuple_vec| tuple_vec.into_iter().collect(),
+                ),
+                preceded(sp, char('}')),
+            )),
+        ),
+    )
+    .parse(i)
+}
+
+fn json_value<'a, E: ParseError<&'a str> + ContextError<&'a str>>(
+    i: &'a str,
+) -> Result<'a, JsonValue, E> {
+    preceded(
+        sp,
+        alt((
+            map(hash, JsonValue::Object),
+            map(array, JsonValue::Array),
+            map(string, JsonValue::Str),
+            map(boolean, JsonValue::Boolean),
+            map(null, |_| JsonValue::Null),
+            map(double, JsonValue::Num),
+        )),
+    )
+    .parse(i)
+}
+
+/// Parses a JSON value. An invalid escape is an error, not a panic:
+///
+/// ```
+/// assert!(step_1::parse(r#"{"a": "\u00e9"}"#).is_ok());
+/// assert!(step_1::parse(r#"{"a": "\uZZZZ"}"#).is_err());
+/// ```
+pub fn parse(i: &str) -> Result<'_, JsonValue, VerboseError<&str>> {
+    delimited(
+        sp,
+        alt((
+            map(hash, JsonValue::Object),
+            map(array, JsonValue::Array),
+            map(null, |_| JsonValue::Null),
+        )),
+        sp,
+    )
+    .parse(i)
+}
@@ -0,0 +1,26 @@
+//! Owned tree vs arena document on the number-heavy canada.json.
+//!
+//! Run with `cargo bench -p step_4 --features arena --bench arena`.
+
+use std::{fs::read_to_string, time::Instant};
+
+use step_4::{parse, parse_arena};
+
+const ITERATIONS: u32 = 20;
+
+fn main() {
+    let path = concat!(env!("CARGO_MANIFEST_DIR"), "/../../test-files/canada.json");
+    let json = read_to_string(path).unwrap();
+
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        parse(&json).unwrap();
+    }
+    println!("Owned: {:?}", start.elapsed() / ITERATIONS);
+
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        parse_arena(&json).unwrap();
+    }
+    println!("Arena: {:?}", start.elapsed() / ITERATIONS);
+}
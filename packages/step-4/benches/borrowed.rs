@@ -0,0 +1,32 @@
+//! Owned vs borrowed vs deferred strings on the string-heavy twitter.json.
+//!
+//! Run with `cargo bench -p step_4 --bench borrowed`.
+
+use std::{fs::read_to_string, time::Instant};
+
+use step_4::{parse, parse_borrowed, parse_deferred};
+
+const ITERATIONS: u32 = 50;
+
+fn main() {
+    let path = concat!(env!("CARGO_MANIFEST_DIR"), "/../../test-files/twitter.json");
+    let json = read_to_string(path).unwrap();
+
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        parse(&json).unwrap();
+    }
+    println!("Owned:    {:?}", start.elapsed() / ITERATIONS);
+
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        parse_borrowed(&json).unwrap();
+    }
+    println!("Borrowed: {:?}", start.elapsed() / ITERATIONS);
+
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        parse_deferred(&json).unwrap();
+    }
+    println!("Deferred: {:?}", start.elapsed() / ITERATIONS);
+}
@@ -0,0 +1,72 @@
+//! Owned tree vs compact tree: time, and allocations counted by the global
+//! allocator.
+//!
+//! Run with `cargo bench -p step_4 --features compact --bench compact`.
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    fs::read_to_string,
+    sync::atomic::{AtomicUsize, Ordering},
+    time::Instant,
+};
+
+use step_4::{parse, parse_compact};
+
+const ITERATIONS: u32 = 20;
+
+struct Counting;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: Counting = Counting;
+
+/// Allocations made by `f`
+fn allocations<T>(f: impl FnOnce() -> T) -> usize {
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    f();
+    ALLOCATIONS.load(Ordering::Relaxed) - before
+}
+
+fn main() {
+    for file in ["twitter", "canada"] {
+        let path = format!(
+            "{}/../../test-files/{file}.json",
+            env!("CARGO_MANIFEST_DIR")
+        );
+        let json = read_to_string(path).unwrap();
+
+        println!("{file}.json");
+
+        let start = Instant::now();
+        for _ in 0..ITERATIONS {
+            parse(&json).unwrap();
+        }
+        let owned = allocations(|| parse(&json).unwrap());
+        println!(
+            "  Owned:   {:?}, {owned} allocations",
+            start.elapsed() / ITERATIONS
+        );
+
+        let start = Instant::now();
+        for _ in 0..ITERATIONS {
+            parse_compact(&json).unwrap();
+        }
+        let compact = allocations(|| parse_compact(&json).unwrap());
+        println!(
+            "  Compact: {:?}, {compact} allocations",
+            start.elapsed() / ITERATIONS
+        );
+    }
+}
@@ -0,0 +1,40 @@
+//! Cost of the error type: the parser is the same, only the error it builds
+//! on every failed alternative changes.
+//!
+//! Run with `cargo bench -p step_4 --bench errors`.
+
+use std::{fs::read_to_string, time::Instant};
+
+use step_4::{parse, parse_fast, parse_verbose};
+
+const ITERATIONS: u32 = 20;
+
+fn main() {
+    for file in ["twitter", "canada"] {
+        let path = format!(
+            "{}/../../test-files/{file}.json",
+            env!("CARGO_MANIFEST_DIR")
+        );
+        let json = read_to_string(path).unwrap();
+
+        println!("{file}.json");
+
+        let start = Instant::now();
+        for _ in 0..ITERATIONS {
+            parse_fast(&json).unwrap();
+        }
+        println!("  ():           {:?}", start.elapsed() / ITERATIONS);
+
+        let start = Instant::now();
+        for _ in 0..ITERATIONS {
+            parse(&json).unwrap();
+        }
+        println!("  JsonError:    {:?}", start.elapsed() / ITERATIONS);
+
+        let start = Instant::now();
+        for _ in 0..ITERATIONS {
+            parse_verbose(&json).unwrap();
+        }
+        println!("  VerboseError: {:?}", start.elapsed() / ITERATIONS);
+    }
+}
@@ -0,0 +1,25 @@
+//! Reading the file in a `String` vs mapping it in memory, on canada.json.
+//!
+//! Run with `cargo bench -p step_4 --bench file --features mmap`.
+
+use std::{fs::read_to_string, time::Instant};
+
+use step_4::{parse_file, parse_with_path};
+
+const ITERATIONS: u32 = 20;
+
+fn main() {
+    let path = concat!(env!("CARGO_MANIFEST_DIR"), "/../../test-files/canada.json");
+
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        parse_with_path(&read_to_string(path).unwrap()).unwrap();
+    }
+    println!("read_to_string: {:?}", start.elapsed() / ITERATIONS);
+
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        parse_file(path).unwrap();
+    }
+    println!("mmap:           {:?}", start.elapsed() / ITERATIONS);
+}
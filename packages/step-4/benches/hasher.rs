@@ -0,0 +1,70 @@
+//! SipHash vs FxHash for the objects of the key-heavy twitter.json.
+//!
+//! Run with `cargo bench -p step_4 --bench hasher`, and with `--features
+//! fxhash` to parse with `FxHasher`.
+
+use std::{
+    collections::{hash_map::RandomState, HashMap},
+    fs::read_to_string,
+    hash::BuildHasher,
+    hint::black_box,
+    time::Instant,
+};
+
+use step_4::{hasher::FxBuildHasher, parse, JsonValue};
+
+const ITERATIONS: u32 = 50;
+
+/// Keys of every object of `value`
+fn objects<'a>(value: &'a JsonValue, keys: &mut Vec<Vec<&'a str>>) {
+    match value {
+        JsonValue::Array(values) => values.iter().for_each(|value| objects(value, keys)),
+        JsonValue::Object(map) => {
+            keys.push(map.keys().map(String::as_str).collect());
+            map.values().for_each(|value| objects(value, keys));
+        }
+        _ => {}
+    }
+}
+
+fn build<S: BuildHasher + Default>(objects: &[Vec<&str>]) -> usize {
+    objects
+        .iter()
+        .map(|keys| {
+            let map: HashMap<&str, (), S> = keys.iter().map(|key| (*key, ())).collect();
+            black_box(map).len()
+        })
+        .sum()
+}
+
+fn main() {
+    let path = concat!(env!("CARGO_MANIFEST_DIR"), "/../../test-files/twitter.json");
+    let json = read_to_string(path).unwrap();
+
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        parse(&json).unwrap();
+    }
+    let hasher = if cfg!(feature = "fxhash") {
+        "FxHash"
+    } else {
+        "SipHash"
+    };
+    println!("Parse ({hasher}): {:?}", start.elapsed() / ITERATIONS);
+
+    let (_, value) = parse(&json).unwrap();
+    let mut keys = Vec::new();
+    objects(&value, &mut keys);
+
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        build::<RandomState>(&keys);
+    }
+    println!("Maps (SipHash):  {:?}", start.elapsed() / ITERATIONS);
+
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        build::<FxBuildHasher>(&keys);
+    }
+    println!("Maps (FxHash):   {:?}", start.elapsed() / ITERATIONS);
+}
@@ -0,0 +1,47 @@
+//! The number backends on the number-heavy canada.json and on twitter.json,
+//! whose numbers are mostly IDs.
+//!
+//! Run with `cargo bench -p step_4 --features lexical --bench numbers`.
+
+use std::{fs::read_to_string, time::Instant};
+
+use step_4::{parse_with_options, NumberBackend, ParseOptions};
+
+const ITERATIONS: u32 = 20;
+
+fn main() {
+    for file in ["canada", "twitter"] {
+        let path = format!(
+            "{}/../../test-files/{file}.json",
+            env!("CARGO_MANIFEST_DIR")
+        );
+        let json = read_to_string(path).unwrap();
+
+        println!("{file}.json");
+
+        let mut values = Vec::new();
+        for (name, backend) in [
+            ("nom", NumberBackend::Nom),
+            ("Strict", NumberBackend::Strict),
+            ("Lexical", NumberBackend::Lexical),
+        ] {
+            let opts = ParseOptions {
+                number_backend: backend,
+                ..Default::default()
+            };
+
+            let start = Instant::now();
+            for _ in 0..ITERATIONS {
+                parse_with_options(&json, &opts).unwrap();
+            }
+            println!("  {name:<8} {:?}", start.elapsed() / ITERATIONS);
+
+            values.push(parse_with_options(&json, &opts).unwrap().1);
+        }
+
+        assert!(
+            values.windows(2).all(|pair| pair[0] == pair[1]),
+            "the backends read different numbers"
+        );
+    }
+}
@@ -0,0 +1,330 @@
+//! Arena-allocated document: every node lives in one `Vec`, every string in
+//! one `String`, and the children of arrays and objects in contiguous blocks.
+//!
+//! Parsing canada.json into a [`JsonValue`] allocates a `Vec` per coordinate
+//! pair; here the whole document is a handful of amortized allocations. Nodes
+//! are read through the borrowed [`ValueRef`] view.
+
+use nom::{
+    character::complete::{anychar, char, multispace0},
+    combinator::{cut, peek},
+    error::{ErrorKind, ParseError},
+    sequence::preceded,
+};
+use std::ops::Range;
+
+use crate::{
+    lexer::{borrowed_string, null, number, parse_false, parse_true, Number},
+    JsonError, JsonValue, Result,
+};
+
+/// Index of a node in its [`Document`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NodeId(usize);
+
+#[derive(Debug, Clone, PartialEq)]
+enum Node {
+    Null,
+    Str(Range<usize>),
+    Boolean(bool),
+    Num(f64),
+    Int(i64),
+    UInt(u64),
+    /// Range in `Document::items`
+    Array(Range<usize>),
+    /// Range in `Document::entries`
+    Object(Range<usize>),
+}
+
+#[derive(Debug, Default)]
+pub struct Document {
+    nodes: Vec<Node>,
+    strings: String,
+    items: Vec<NodeId>,
+    entries: Vec<(Range<usize>, NodeId)>,
+    root: Option<NodeId>,
+}
+
+/// Borrowed view of a node of a [`Document`]
+#[derive(Debug, Clone, Copy)]
+pub enum ValueRef<'d> {
+    Null,
+    Str(&'d str),
+    Boolean(bool),
+    Num(f64),
+    Int(i64),
+    UInt(u64),
+    Array(ArrayRef<'d>),
+    Object(ObjectRef<'d>),
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct ArrayRef<'d> {
+    document: &'d Document,
+    items: &'d [NodeId],
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct ObjectRef<'d> {
+    document: &'d Document,
+    entries: &'d [(Range<usize>, NodeId)],
+}
+
+impl Document {
+    pub fn root(&self) -> ValueRef<'_> {
+        self.get(self.root.unwrap_or(NodeId(0)))
+    }
+
+    pub fn get(&self, id: NodeId) -> ValueRef<'_> {
+        match &self.nodes[id.0] {
+            Node::Null => ValueRef::Null,
+            Node::Str(range) => ValueRef::Str(&self.strings[range.clone()]),
+            Node::Boolean(b) => ValueRef::Boolean(*b),
+            Node::Num(n) => ValueRef::Num(*n),
+            Node::Int(n) => ValueRef::Int(*n),
+            Node::UInt(n) => ValueRef::UInt(*n),
+            Node::Array(range) => ValueRef::Array(ArrayRef {
+                document: self,
+                items: &self.items[range.clone()],
+            }),
+            Node::Object(range) => ValueRef::Object(ObjectRef {
+                document: self,
+                entries: &self.entries[range.clone()],
+            }),
+        }
+    }
+
+    /// Number of nodes in the document
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+}
+
+impl<'d> ValueRef<'d> {
+    /// Copies the node and its children into an owned value
+    pub fn to_value(&self) -> JsonValue {
+        match self {
+            ValueRef::Null => JsonValue::Null,
+            ValueRef::Str(s) => JsonValue::Str(s.to_string()),
+            ValueRef::Boolean(b) => JsonValue::Boolean(*b),
+            ValueRef::Num(n) => JsonValue::Num(*n),
+            ValueRef::Int(n) => JsonValue::Int(*n),
+            ValueRef::UInt(n) => JsonValue::UInt(*n),
+            ValueRef::Array(array) => {
+                JsonValue::Array(array.iter().map(|v| v.to_value()).collect())
+            }
+            ValueRef::Object(object) => JsonValue::Object(
+                object
+                    .iter()
+                    .map(|(k, v)| (k.to_string(), v.to_value()))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+impl<'d> ArrayRef<'d> {
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    pub fn get(&self, index: usize) -> Option<ValueRef<'d>> {
+        self.items.get(index).map(|id| self.document.get(*id))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = ValueRef<'d>> + 'd {
+        let document = self.document;
+
+        self.items.iter().map(move |id| document.get(*id))
+    }
+}
+
+impl<'d> ObjectRef<'d> {
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Linear lookup, the entries are kept in document order. When a key is
+    /// repeated, the last one wins, like when parsing into a `HashMap`
+    pub fn get(&self, key: &str) -> Option<ValueRef<'d>> {
+        self.iter()
+            .filter(|(k, _)| *k == key)
+            .last()
+            .map(|(_, v)| v)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&'d str, ValueRef<'d>)> + 'd {
+        let document = self.document;
+
+        self.entries
+            .iter()
+            .map(move |(key, id)| (&document.strings[key.clone()], document.get(*id)))
+    }
+}
+
+/// Builds the document. Children are parsed before their parent can be
+/// written, so they are collected on scratch stacks shared by every level,
+/// then moved to the document as one contiguous block.
+#[derive(Default)]
+struct Builder {
+    document: Document,
+    items: Vec<NodeId>,
+    entries: Vec<(Range<usize>, NodeId)>,
+}
+
+impl Builder {
+    fn push(&mut self, node: Node) -> NodeId {
+        self.document.nodes.push(node);
+        NodeId(self.document.nodes.len() - 1)
+    }
+
+    fn push_str(&mut self, s: &str) -> Range<usize> {
+        let start = self.document.strings.len();
+        self.document.strings.push_str(s);
+        start..self.document.strings.len()
+    }
+
+    /// After a value: `,` to continue, or `close` to end the container.
+    /// Returns `None` on `close`
+    fn separator<'a>(i: &'a str, close: char) -> Result<'a, Option<()>, JsonError> {
+        let (i, _) = multispace0(i)?;
+
+        match i.chars().next() {
+            Some(',') => Ok((&i[1..], Some(()))),
+            Some(c) if c == close => Ok((&i[1..], None)),
+            _ => Err(nom::Err::Failure(JsonError::from_error_kind(
+                i,
+                ErrorKind::Char,
+            ))),
+        }
+    }
+
+    fn array<'a>(&mut self, i: &'a str) -> Result<'a, NodeId, JsonError> {
+        let (i, _) = char('[')(i)?;
+        let mark = self.items.len();
+
+        let (mut i, _) = multispace0(i)?;
+        if let Some(rest) = i.strip_prefix(']') {
+            i = rest;
+        } else {
+            loop {
+                let (rest, id) = self.json_value(i)?;
+                self.items.push(id);
+
+                let (rest, more) = Self::separator(rest, ']')?;
+                i = rest;
+
+                if more.is_none() {
+                    break;
+                }
+            }
+        }
+
+        let start = self.document.items.len();
+        self.document.items.extend(self.items.drain(mark..));
+        let end = self.document.items.len();
+
+        Ok((i, self.push(Node::Array(start..end))))
+    }
+
+    fn hash<'a>(&mut self, i: &'a str) -> Result<'a, NodeId, JsonError> {
+        let (i, _) = char('{')(i)?;
+        let mark = self.entries.len();
+
+        let (mut i, _) = multispace0(i)?;
+        if let Some(rest) = i.strip_prefix('}') {
+            i = rest;
+        } else {
+            loop {
+                let (rest, _) = multispace0(i)?;
+                let (rest, key) = borrowed_string(rest)?;
+                let key = self.push_str(&key);
+
+                let (rest, _) = cut(preceded(multispace0, char(':')))(rest)?;
+
+                let (rest, id) = self.json_value(rest)?;
+                self.entries.push((key, id));
+
+                let (rest, more) = Self::separator(rest, '}')?;
+                i = rest;
+
+                if more.is_none() {
+                    break;
+                }
+            }
+        }
+
+        let start = self.document.entries.len();
+        self.document.entries.extend(self.entries.drain(mark..));
+        let end = self.document.entries.len();
+
+        Ok((i, self.push(Node::Object(start..end))))
+    }
+
+    fn json_value<'a>(&mut self, i: &'a str) -> Result<'a, NodeId, JsonError> {
+        let (i, _) = multispace0(i)?;
+
+        let (i, first_char) = peek(anychar)(i)?;
+
+        let (i, node) = match first_char {
+            '{' => return self.hash(i),
+            '[' => return self.array(i),
+            '"' => {
+                let (i, s) = borrowed_string(i)?;
+                (i, Node::Str(self.push_str(&s)))
+            }
+            '-' | '0'..='9' => {
+                let (i, n) = number(i)?;
+                let node = match n {
+                    Number::Int(n) => Node::Int(n),
+                    Number::UInt(n) => Node::UInt(n),
+                    Number::Float(n) => Node::Num(n),
+                };
+                (i, node)
+            }
+            'f' => {
+                let (i, b) = parse_false(i)?;
+                (i, Node::Boolean(b))
+            }
+            't' => {
+                let (i, b) = parse_true(i)?;
+                (i, Node::Boolean(b))
+            }
+            'n' => {
+                let (i, _) = null(i)?;
+                (i, Node::Null)
+            }
+            c => {
+                return Err(nom::Err::Failure(JsonError::from_error_kind(
+                    i,
+                    ErrorKind::Char,
+                )))
+            }
+        };
+
+        Ok((i, self.push(node)))
+    }
+}
+
+/// Parses `i` into an arena-allocated [`Document`]
+pub fn parse_arena(i: &str) -> Result<'_, Document, JsonError> {
+    let mut builder = Builder::default();
+
+    let (i, root) = builder.json_value(i)?;
+    let (i, _) = multispace0(i)?;
+    builder.document.root = Some(root);
+
+    Ok((i, builder.document))
+}
@@ -0,0 +1,167 @@
+//! Exact decimal numbers, for data like amounts of money where the rounding
+//! of `f64` is unacceptable.
+
+use alloc::{
+    borrow::ToOwned,
+    format,
+    string::{String, ToString},
+};
+use core::{cmp::Ordering, fmt::Display};
+
+/// Decimal number stored as `digits × 10^exponent`.
+///
+/// It is normalized, without leading or trailing zeros in `digits`, so that
+/// equal numbers compare equal whatever their spelling: `1.50` is `1.5e0`.
+/// Zero keeps its sign, like an `f64` does.
+///
+/// ```
+/// use step_4::BigNumber;
+///
+/// let n = |text| BigNumber::parse(text).unwrap();
+///
+/// assert_eq!(n("1.50"), n("15e-1"));
+/// assert_eq!(n("0012.5000"), n("1.25E1"));
+/// assert_eq!(n("0.000"), n("0e10"));
+/// assert_ne!(n("-0.0"), n("0"));
+/// assert_eq!(BigNumber::parse("1.x"), None);
+/// assert_eq!(BigNumber::parse("1.x"), None);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct BigNumber {
+    negative: bool,
+    /// ASCII digits, empty for zero
+    digits: String,
+    exponent: i64,
+}
+
+impl BigNumber {
+    /// Parses a JSON number lexeme, `None` if `text` isn't one
+    pub fn parse(text: &str) -> Option<Self> {
+        let (negative, text) = match text.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, text),
+        };
+
+        let (mantissa, exponent) = match text.find(['e', 'E']) {
+            Some(index) => (&text[..index], text[index + 1..].parse::<i64>().ok()?),
+            None => (text, 0),
+        };
+
+        let (integer, fraction) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+
+        let is_digits = |s: &str| s.bytes().all(|b| b.is_ascii_digit());
+        if integer.is_empty() || !is_digits(integer) || !is_digits(fraction) {
+            return None;
+        }
+
+        let mut digits = format!("{integer}{fraction}");
+        let mut exponent = exponent.checked_sub(fraction.len() as i64)?;
+
+        let trailing = digits.len() - digits.trim_end_matches('0').len();
+        digits.truncate(digits.len() - trailing);
+        exponent = exponent.checked_add(trailing as i64)?;
+
+        let digits = digits.trim_start_matches('0').to_owned();
+
+        if digits.is_empty() {
+            exponent = 0;
+        }
+
+        Some(Self {
+            negative,
+            digits,
+            exponent,
+        })
+    }
+
+    /// Order of magnitude, then digits: without trailing zeros, the digits
+    /// compare like the fractions they make once scaled to the same magnitude
+    fn magnitude(&self) -> (i64, &str) {
+        (self.digits.len() as i64 + self.exponent, &self.digits)
+    }
+
+    /// Nearest `f64`
+    pub fn to_f64(&self) -> f64 {
+        self.to_string().parse().unwrap()
+    }
+}
+
+/// Numeric order, `-0` coming right before `0` like with [`f64::total_cmp`]
+///
+/// ```
+/// use step_4::BigNumber;
+///
+/// let n = |text| BigNumber::parse(text).unwrap();
+/// let mut numbers = ["10", "-0", "9.99", "-1e3", "0", "-2.5", "1e-30", "-0.01"].map(n);
+/// numbers.sort();
+///
+/// assert_eq!(
+///     numbers,
+///     ["-1e3", "-2.5", "-0.01", "-0", "0", "1e-30", "9.99", "10"].map(n)
+/// );
+/// ```
+impl Ord for BigNumber {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let sign = |n: &Self| match (n.negative, n.digits.is_empty()) {
+            (true, false) => 0,
+            (true, true) => 1,
+            (false, true) => 2,
+            (false, false) => 3,
+        };
+
+        match sign(self).cmp(&sign(other)) {
+            Ordering::Equal if self.negative => other.magnitude().cmp(&self.magnitude()),
+            Ordering::Equal => self.magnitude().cmp(&other.magnitude()),
+            ordering => ordering,
+        }
+    }
+}
+
+impl PartialOrd for BigNumber {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// The shortest JSON number for the value: integers have no fraction
+///
+/// ```
+/// use step_4::BigNumber;
+///
+/// let display = |text| BigNumber::parse(text).unwrap().to_string();
+///
+/// assert_eq!(display("123456789012345678901234567890"), "123456789012345678901234567890");
+/// assert_eq!(display("1.50"), "1.5");
+/// assert_eq!(display("-0.0"), "-0");
+/// assert_eq!(display("0.00"), "0");
+/// assert_eq!(display("0.00012"), "0.00012");
+/// assert_eq!(display("1e-30"), "1e-30");
+/// assert_eq!(display("1.5e40"), "15e39");
+/// ```
+impl Display for BigNumber {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        if self.negative {
+            write!(f, "-")?;
+        }
+
+        if self.digits.is_empty() {
+            return write!(f, "0");
+        }
+
+        let len = self.digits.len() as i64;
+
+        // Plain notation as long as it doesn't take many more chars than the
+        // scientific one
+        match self.exponent {
+            0..=20 => write!(f, "{}{}", self.digits, "0".repeat(self.exponent as usize)),
+            e if e < 0 && -e < len => {
+                let (integer, fraction) = self.digits.split_at((len + e) as usize);
+                write!(f, "{integer}.{fraction}")
+            }
+            e if e < 0 && -e - len < 6 => {
+                write!(f, "0.{}{}", "0".repeat((-e - len) as usize), self.digits)
+            }
+            e => write!(f, "{}e{e}", self.digits),
+        }
+    }
+}
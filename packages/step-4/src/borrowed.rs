@@ -0,0 +1,235 @@
+//! A value tree borrowing from the input.
+//!
+//! Strings without escapes, which are the vast majority in practice, are
+//! slices of the input rather than fresh `String`s. It also makes it possible
+//! to leave parts of the document unparsed until they are needed: with
+//! [`parse_lazy`], the values of the selected keys are only checked for
+//! balanced brackets and kept as [`BorrowedValue::Raw`] slices: skipping the
+//! giant `coordinates` array of canada.json costs a byte scan instead of
+//! thousands of allocations.
+
+use nom::{
+    character::complete::{anychar, char, multispace0},
+    combinator::{cut, map, peek},
+    error::{context, ContextError, ErrorKind, ParseError},
+    multi::separated_list0,
+    sequence::{delimited, preceded, terminated},
+    Parser,
+};
+use std::borrow::Cow;
+
+use crate::{
+    json_value as owned_json_value,
+    lexer::{borrowed_string, null, number, parse_false, parse_true, Number},
+    path::JsonPath,
+    JsonError, JsonValue, Map, ParseOptions, Result,
+};
+
+#[derive(Debug, PartialEq)]
+pub enum BorrowedValue<'a> {
+    Null,
+    Str(Cow<'a, str>),
+    Boolean(bool),
+    Num(f64),
+    Int(i64),
+    UInt(u64),
+    Array(Vec<BorrowedValue<'a>>),
+    Object(Map<Cow<'a, str>, BorrowedValue<'a>>),
+    /// Unparsed slice of the input, only validated for balanced brackets
+    Raw(&'a str),
+}
+
+impl<'a> BorrowedValue<'a> {
+    /// Converts to an owned value, parsing the raw subtrees on the way
+    pub fn into_value(self) -> std::result::Result<JsonValue, JsonError> {
+        Ok(match self {
+            BorrowedValue::Null => JsonValue::Null,
+            BorrowedValue::Str(s) => JsonValue::Str(s.into_owned()),
+            BorrowedValue::Boolean(b) => JsonValue::Boolean(b),
+            BorrowedValue::Num(n) => JsonValue::Num(n),
+            BorrowedValue::Int(n) => JsonValue::Int(n),
+            BorrowedValue::UInt(n) => JsonValue::UInt(n),
+            BorrowedValue::Array(values) => JsonValue::Array(
+                values
+                    .into_iter()
+                    .map(BorrowedValue::into_value)
+                    .collect::<std::result::Result<_, _>>()?,
+            ),
+            BorrowedValue::Object(map) => JsonValue::Object(
+                map.into_iter()
+                    .map(|(k, v)| Ok((k.into_owned(), v.into_value()?)))
+                    .collect::<std::result::Result<_, JsonError>>()?,
+            ),
+            BorrowedValue::Raw(raw) => {
+                owned_json_value::<JsonError>(raw, &ParseOptions::default(), &JsonPath::default())?
+                    .1
+            }
+        })
+    }
+}
+
+/// Recognizes a value by only matching its brackets, skipping over strings
+fn raw_value<'a, E: ParseError<&'a str>>(i: &'a str) -> Result<'a, &'a str, E> {
+    let (i, _) = multispace0(i)?;
+
+    let split = |index: usize| match index {
+        0 => Err(nom::Err::Error(E::from_error_kind(i, ErrorKind::TakeUntil))),
+        _ => Ok((&i[index..], &i[..index])),
+    };
+
+    let mut closing = Vec::new();
+    let mut in_string = false;
+    let mut escaped = false;
+
+    for (index, byte) in i.bytes().enumerate() {
+        if in_string {
+            match byte {
+                _ if escaped => escaped = false,
+                b'\\' => escaped = true,
+                b'"' => {
+                    in_string = false;
+
+                    if closing.is_empty() {
+                        return split(index + 1);
+                    }
+                }
+                _ => {}
+            }
+
+            continue;
+        }
+
+        match byte {
+            b'"' => in_string = true,
+            b'{' => closing.push(b'}'),
+            b'[' => closing.push(b']'),
+            // End of a scalar value
+            b'}' | b']' | b',' | b' ' | b'\t' | b'\r' | b'\n' if closing.is_empty() => {
+                return split(index)
+            }
+            b'}' | b']' => {
+                if closing.pop() != Some(byte) {
+                    return Err(nom::Err::Failure(E::from_error_kind(
+                        &i[index..],
+                        ErrorKind::Char,
+                    )));
+                }
+
+                if closing.is_empty() {
+                    return split(index + 1);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    if !closing.is_empty() || in_string {
+        return Err(nom::Err::Failure(E::from_error_kind(
+            &i[i.len()..],
+            ErrorKind::Eof,
+        )));
+    }
+
+    split(i.len())
+}
+
+fn array<'a, E: ParseError<&'a str> + ContextError<&'a str>>(
+    i: &'a str,
+    raw_keys: &[&str],
+) -> Result<'a, Vec<BorrowedValue<'a>>, E> {
+    context(
+        "array",
+        delimited(
+            cut(char('[')),
+            cut(separated_list0(preceded(multispace0, char(',')), |i| {
+                let (i, next_char) = peek(anychar)(i)?;
+
+                if next_char == ']' {
+                    return Err(nom::Err::Error(E::from_error_kind(i, ErrorKind::Char)));
+                }
+
+                json_value(i, raw_keys)
+            })),
+            preceded(multispace0, char(']')),
+        ),
+    )(i)
+}
+
+fn key_value<'a, E: ParseError<&'a str> + ContextError<&'a str>>(
+    i: &'a str,
+    raw_keys: &[&str],
+) -> Result<'a, (Cow<'a, str>, BorrowedValue<'a>), E> {
+    let (i, _) = multispace0(i)?;
+
+    let (i, next_char) = peek(anychar)(i)?;
+
+    if next_char == '}' {
+        return Err(nom::Err::Error(E::from_error_kind(i, ErrorKind::Char)));
+    }
+
+    let (i, key) = borrowed_string(i)?;
+    let (i, _) = cut(preceded(multispace0, char(':')))(i)?;
+
+    let (i, value) = if raw_keys.contains(&key.as_ref()) {
+        map(cut(raw_value), BorrowedValue::Raw)(i)?
+    } else {
+        json_value(i, raw_keys)?
+    };
+
+    Ok((i, (key, value)))
+}
+
+fn hash<'a, E: ParseError<&'a str> + ContextError<&'a str>>(
+    i: &'a str,
+    raw_keys: &[&str],
+) -> Result<'a, Map<Cow<'a, str>, BorrowedValue<'a>>, E> {
+    context(
+        "map",
+        preceded(
+            cut(char('{')),
+            cut(terminated(
+                map(
+                    separated_list0(preceded(multispace0, char(',')), |i| key_value(i, raw_keys)),
+                    |tuple_vec| tuple_vec.into_iter().collect(),
+                ),
+                preceded(multispace0, char('}')),
+            )),
+        ),
+    )
+    .parse(i)
+}
+
+fn json_value<'a, E: ParseError<&'a str> + ContextError<&'a str>>(
+    i: &'a str,
+    raw_keys: &[&str],
+) -> Result<'a, BorrowedValue<'a>, E> {
+    let (i, _) = multispace0(i)?;
+
+    let (i, first_char) = peek(anychar)(i)?;
+
+    match first_char {
+        '{' => map(|i| hash(i, raw_keys), BorrowedValue::Object)(i),
+        '[' => map(|i| array(i, raw_keys), BorrowedValue::Array)(i),
+        '"' => map(borrowed_string, BorrowedValue::Str)(i),
+        '-' | '0'..='9' => map(number, |n| match n {
+            Number::Int(n) => BorrowedValue::Int(n),
+            Number::UInt(n) => BorrowedValue::UInt(n),
+            Number::Float(n) => BorrowedValue::Num(n),
+        })(i),
+        'f' => map(parse_false, BorrowedValue::Boolean)(i),
+        't' => map(parse_true, BorrowedValue::Boolean)(i),
+        'n' => map(null, |_| BorrowedValue::Null)(i),
+        c => Err(nom::Err::Failure(E::from_error_kind(i, ErrorKind::Char))),
+    }
+}
+
+/// Parses `i` without copying the strings that have no escapes
+pub fn parse_borrowed(i: &str) -> Result<'_, BorrowedValue<'_>, JsonError> {
+    parse_lazy(i, &[])
+}
+
+/// Like [`parse_borrowed`], but keeps the values of the entries named in `raw_keys` as
+/// [`BorrowedValue::Raw`], at any depth
+pub fn parse_lazy<'a>(i: &'a str, raw_keys: &[&str]) -> Result<'a, BorrowedValue<'a>, JsonError> {
+    terminated(|i| json_value(i, raw_keys), multispace0).parse(i)
+}
@@ -0,0 +1,752 @@
+//! Conversion to and from [BSON](https://bsonspec.org/), the documents of
+//! MongoDB.
+//!
+//! The types of BSON that JSON lacks are written in relaxed
+//! [Extended JSON](https://www.mongodb.com/docs/manual/reference/mongodb-extended-json/):
+//! `{"$oid": "…"}` for an ObjectId, `{"$date": "…"}` for a date, and so on.
+//! Objects of that shape become those types again in BSON, so that documents
+//! exported by MongoDB tools keep their types through this crate.
+//!
+//! Decimal128 values and the deprecated DBPointer and code with scope types
+//! aren't supported.
+
+use alloc::{
+    borrow::ToOwned,
+    boxed::Box,
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+
+use crate::{path::JsonPath, wtf8, JsonError, JsonValue, Map};
+
+const DOUBLE: u8 = 0x01;
+const STRING: u8 = 0x02;
+const DOCUMENT: u8 = 0x03;
+const ARRAY: u8 = 0x04;
+const BINARY: u8 = 0x05;
+const UNDEFINED: u8 = 0x06;
+const OBJECT_ID: u8 = 0x07;
+const BOOLEAN: u8 = 0x08;
+const DATETIME: u8 = 0x09;
+const NULL: u8 = 0x0a;
+const REGEX: u8 = 0x0b;
+const CODE: u8 = 0x0d;
+const SYMBOL: u8 = 0x0e;
+const INT32: u8 = 0x10;
+const TIMESTAMP: u8 = 0x11;
+const INT64: u8 = 0x12;
+const DECIMAL128: u8 = 0x13;
+const MIN_KEY: u8 = 0xff;
+const MAX_KEY: u8 = 0x7f;
+
+/// Milliseconds of 9999-12-31T23:59:59.999Z, the last date that relaxed
+/// Extended JSON writes as an ISO 8601 string
+const MAX_ISO_DATE: i64 = 253_402_300_799_999;
+
+impl JsonValue {
+    /// BSON document with the same content, which must be an object.
+    ///
+    /// Integers are 32-bit when they fit, 64-bit otherwise, and other
+    /// numbers are doubles. Extended JSON objects become the type they
+    /// stand for.
+    ///
+    /// ```
+    /// let document = step_4::parse_with_path(r#"{
+    ///     "_id": {"$oid": "5f43a1b2c3d4e5f60718293a"},
+    ///     "at": {"$date": "2023-06-01T12:00:00Z"}
+    /// }"#).unwrap();
+    /// let bytes = document.to_bson().unwrap();
+    ///
+    /// assert_eq!(bytes.len(), 34);
+    /// assert_eq!(step_4::from_bson(&bytes).unwrap(), document);
+    ///
+    /// // BSON has no unsigned 64-bit integers
+    /// let error = step_4::parse_with_path(r#"{"n": [18446744073709551615]}"#).unwrap().to_bson();
+    /// assert_eq!(
+    ///     error.unwrap_err().to_string(),
+    ///     "integer too large for BSON at $.n[0]",
+    /// );
+    /// ```
+    pub fn to_bson(&self) -> Result<Vec<u8>, JsonError> {
+        let JsonValue::Object(map) = self else {
+            return Err(JsonError::Custom(format!(
+                "BSON documents are objects, found {}",
+                self.type_name()
+            )));
+        };
+
+        let mut writer = BsonWriter {
+            out: Vec::new(),
+            path: JsonPath::default(),
+        };
+        writer.document(map)?;
+
+        Ok(writer.out)
+    }
+}
+
+struct BsonWriter {
+    out: Vec<u8>,
+    /// Value being written, for errors
+    path: JsonPath,
+}
+
+impl BsonWriter {
+    fn error(&self, message: &str) -> JsonError {
+        JsonError::Path(
+            self.path.to_string(),
+            Box::new(JsonError::Custom(message.to_owned())),
+        )
+    }
+
+    fn cstring(&mut self, s: &str) -> Result<(), JsonError> {
+        if s.contains('\0') {
+            return Err(self.error("BSON keys and regexes can't contain NUL"));
+        }
+        self.out.extend_from_slice(s.as_bytes());
+        self.out.push(0);
+
+        Ok(())
+    }
+
+    fn string(&mut self, s: &str) -> Result<(), JsonError> {
+        let len = self.len(s.len() + 1)?;
+        self.out.extend_from_slice(&len.to_le_bytes());
+        self.out.extend_from_slice(s.as_bytes());
+        self.out.push(0);
+
+        Ok(())
+    }
+
+    fn len(&self, len: usize) -> Result<i32, JsonError> {
+        i32::try_from(len).map_err(|_| self.error("too large for BSON"))
+    }
+
+    /// Type, key and value of an element of a document
+    fn element(&mut self, key: &str, value: &JsonValue) -> Result<(), JsonError> {
+        let kind_at = self.out.len();
+        self.out.push(0);
+        self.cstring(key)?;
+        self.out[kind_at] = self.value(value)?;
+
+        Ok(())
+    }
+
+    /// Writes the length of the document starting at `start`, and its end
+    fn end_document(&mut self, start: usize) -> Result<(), JsonError> {
+        self.out.push(0);
+        let len = self.len(self.out.len() - start)?;
+        self.out[start..start + 4].copy_from_slice(&len.to_le_bytes());
+
+        Ok(())
+    }
+
+    fn document(&mut self, map: &Map<String, JsonValue>) -> Result<(), JsonError> {
+        let start = self.out.len();
+        self.out.extend_from_slice(&[0; 4]);
+
+        for (key, value) in map {
+            self.path.push_key(key.clone());
+            self.element(key, value)?;
+            self.path.pop();
+        }
+
+        self.end_document(start)
+    }
+
+    /// Arrays are documents with the indexes as keys
+    fn array(&mut self, values: &[JsonValue]) -> Result<(), JsonError> {
+        let start = self.out.len();
+        self.out.extend_from_slice(&[0; 4]);
+
+        for (index, value) in values.iter().enumerate() {
+            self.path.push_index(index);
+            self.element(&index.to_string(), value)?;
+            self.path.pop();
+        }
+
+        self.end_document(start)
+    }
+
+    /// Writes `value`, and returns its type
+    fn value(&mut self, value: &JsonValue) -> Result<u8, JsonError> {
+        match value {
+            JsonValue::Null => Ok(NULL),
+            JsonValue::Boolean(b) => {
+                self.out.push(*b as u8);
+                Ok(BOOLEAN)
+            }
+            JsonValue::Int(n) => Ok(self.int(*n)),
+            JsonValue::UInt(n) => match i64::try_from(*n) {
+                Ok(n) => Ok(self.int(n)),
+                Err(_) => Err(self.error("integer too large for BSON")),
+            },
+            JsonValue::Num(n) | JsonValue::RawNum(n, _) => Ok(self.double(*n)),
+            #[cfg(feature = "bignum")]
+            JsonValue::BigNum(n) => Ok(self.double(n.to_f64())),
+            JsonValue::Str(s) => {
+                self.string(s)?;
+                Ok(STRING)
+            }
+            // Strings are UTF-8
+            JsonValue::Wtf8(bytes) => {
+                self.string(&wtf8::to_string_lossy(bytes))?;
+                Ok(STRING)
+            }
+            JsonValue::Array(values) => {
+                self.array(values)?;
+                Ok(ARRAY)
+            }
+            JsonValue::Object(map) => match self.extended(map)? {
+                Some(kind) => Ok(kind),
+                None => {
+                    self.document(map)?;
+                    Ok(DOCUMENT)
+                }
+            },
+        }
+    }
+
+    fn int(&mut self, n: i64) -> u8 {
+        match i32::try_from(n) {
+            Ok(n) => {
+                self.out.extend_from_slice(&n.to_le_bytes());
+                INT32
+            }
+            Err(_) => {
+                self.out.extend_from_slice(&n.to_le_bytes());
+                INT64
+            }
+        }
+    }
+
+    fn double(&mut self, n: f64) -> u8 {
+        self.out.extend_from_slice(&n.to_le_bytes());
+        DOUBLE
+    }
+
+    /// Writes the value an Extended JSON object stands for, and returns its
+    /// type. `None` for other objects, which are documents.
+    fn extended(&mut self, map: &Map<String, JsonValue>) -> Result<Option<u8>, JsonError> {
+        let mut members = map.iter();
+        let (Some((key, value)), None) = (members.next(), members.next()) else {
+            return Ok(None);
+        };
+
+        let invalid = |writer: &Self| writer.error(&format!("invalid `{key}`"));
+
+        let kind = match (key.as_str(), value) {
+            ("$oid", JsonValue::Str(hex)) => {
+                let bytes = from_hex(hex)
+                    .filter(|b| b.len() == 12)
+                    .ok_or_else(|| invalid(self))?;
+                self.out.extend_from_slice(&bytes);
+                OBJECT_ID
+            }
+            ("$date", date) => {
+                let millis = match date {
+                    JsonValue::Str(iso) => parse_iso_date(iso),
+                    JsonValue::Object(map) if map.len() == 1 => match map.get("$numberLong") {
+                        Some(JsonValue::Str(n)) => n.parse().ok(),
+                        _ => None,
+                    },
+                    // Legacy form of the canonical one
+                    JsonValue::Int(n) => Some(*n),
+                    _ => None,
+                };
+                self.out
+                    .extend_from_slice(&millis.ok_or_else(|| invalid(self))?.to_le_bytes());
+                DATETIME
+            }
+            ("$numberInt", JsonValue::Str(n)) => {
+                let n: i32 = n.parse().map_err(|_| invalid(self))?;
+                self.out.extend_from_slice(&n.to_le_bytes());
+                INT32
+            }
+            ("$numberLong", JsonValue::Str(n)) => {
+                let n: i64 = n.parse().map_err(|_| invalid(self))?;
+                self.out.extend_from_slice(&n.to_le_bytes());
+                INT64
+            }
+            ("$numberDouble", JsonValue::Str(n)) => {
+                let n = match n.as_str() {
+                    "Infinity" => f64::INFINITY,
+                    "-Infinity" => f64::NEG_INFINITY,
+                    "NaN" => f64::NAN,
+                    n => n.parse().map_err(|_| invalid(self))?,
+                };
+                self.double(n)
+            }
+            ("$numberDecimal", _) => return Err(self.error("Decimal128 isn't supported")),
+            ("$binary", JsonValue::Object(binary)) => {
+                let (Some(JsonValue::Str(base64)), Some(JsonValue::Str(sub_type)), 2) =
+                    (binary.get("base64"), binary.get("subType"), binary.len())
+                else {
+                    return Err(invalid(self));
+                };
+                let bytes = from_base64(base64).ok_or_else(|| invalid(self))?;
+                let sub_type = match from_hex(&format!("{sub_type:0>2}")).as_deref() {
+                    Some(&[sub_type]) => sub_type,
+                    _ => return Err(invalid(self)),
+                };
+
+                let len = self.len(bytes.len())?;
+                self.out.extend_from_slice(&len.to_le_bytes());
+                self.out.push(sub_type);
+                self.out.extend_from_slice(&bytes);
+                BINARY
+            }
+            ("$timestamp", JsonValue::Object(timestamp)) => {
+                let field = |name| match timestamp.get(name) {
+                    Some(JsonValue::Int(n)) => u32::try_from(*n).ok(),
+                    _ => None,
+                };
+                let (Some(t), Some(i), 2) = (field("t"), field("i"), timestamp.len()) else {
+                    return Err(invalid(self));
+                };
+
+                self.out.extend_from_slice(&i.to_le_bytes());
+                self.out.extend_from_slice(&t.to_le_bytes());
+                TIMESTAMP
+            }
+            ("$regularExpression", JsonValue::Object(regex)) => {
+                let (Some(JsonValue::Str(pattern)), Some(JsonValue::Str(options)), 2) =
+                    (regex.get("pattern"), regex.get("options"), regex.len())
+                else {
+                    return Err(invalid(self));
+                };
+
+                self.cstring(pattern)?;
+                self.cstring(options)?;
+                REGEX
+            }
+            ("$code", JsonValue::Str(code)) => {
+                self.string(code)?;
+                CODE
+            }
+            ("$symbol", JsonValue::Str(symbol)) => {
+                self.string(symbol)?;
+                SYMBOL
+            }
+            ("$minKey", JsonValue::Int(1)) => MIN_KEY,
+            ("$maxKey", JsonValue::Int(1)) => MAX_KEY,
+            ("$undefined", JsonValue::Boolean(true)) => UNDEFINED,
+            (
+                "$oid" | "$numberInt" | "$numberLong" | "$numberDouble" | "$binary" | "$timestamp"
+                | "$regularExpression" | "$code" | "$symbol" | "$minKey" | "$maxKey" | "$undefined",
+                _,
+            ) => return Err(invalid(self)),
+            _ => return Ok(None),
+        };
+
+        Ok(Some(kind))
+    }
+}
+
+/// Decodes one document, which must span all of `bytes`.
+///
+/// A truncated input is a [`JsonError::NeedMoreData`].
+pub fn from_bson(bytes: &[u8]) -> Result<JsonValue, JsonError> {
+    let mut decoder = Decoder { bytes, pos: 0 };
+    let map = decoder.document()?.into_iter().collect();
+
+    if decoder.pos < bytes.len() {
+        return Err(decoder.error("trailing bytes after the document"));
+    }
+
+    Ok(JsonValue::Object(map))
+}
+
+/// `{key: value}`
+fn extended(key: &str, value: JsonValue) -> JsonValue {
+    JsonValue::Object([(key.to_owned(), value)].into_iter().collect())
+}
+
+struct Decoder<'b> {
+    bytes: &'b [u8],
+    pos: usize,
+}
+
+impl<'b> Decoder<'b> {
+    fn error(&self, message: &str) -> JsonError {
+        JsonError::Custom(format!("{message} at byte {}", self.pos))
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'b [u8], JsonError> {
+        let end = self
+            .pos
+            .checked_add(len)
+            .filter(|&end| end <= self.bytes.len())
+            .ok_or(JsonError::NeedMoreData)?;
+        let taken = &self.bytes[self.pos..end];
+        self.pos = end;
+
+        Ok(taken)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], JsonError> {
+        Ok(self.take(N)?.try_into().unwrap())
+    }
+
+    fn byte(&mut self) -> Result<u8, JsonError> {
+        Ok(self.array::<1>()?[0])
+    }
+
+    fn len(&mut self, min: usize) -> Result<usize, JsonError> {
+        let len = i32::from_le_bytes(self.array()?);
+
+        match usize::try_from(len) {
+            Ok(len) if len >= min => Ok(len),
+            _ => {
+                self.pos -= 4;
+                Err(self.error("invalid length"))
+            }
+        }
+    }
+
+    fn utf8(&mut self, bytes: &[u8], start: usize) -> Result<String, JsonError> {
+        match core::str::from_utf8(bytes) {
+            Ok(s) => Ok(s.into()),
+            Err(e) => {
+                self.pos = start + e.valid_up_to();
+                Err(self.error("invalid UTF-8"))
+            }
+        }
+    }
+
+    fn cstring(&mut self) -> Result<String, JsonError> {
+        let start = self.pos;
+        let len = self.bytes[start..]
+            .iter()
+            .position(|&b| b == 0)
+            .ok_or(JsonError::NeedMoreData)?;
+        let bytes = self.take(len + 1)?;
+
+        self.utf8(&bytes[..len], start)
+    }
+
+    fn string(&mut self) -> Result<String, JsonError> {
+        let len = self.len(1)?;
+        let start = self.pos;
+        let bytes = self.take(len)?;
+
+        if bytes[len - 1] != 0 {
+            self.pos -= 1;
+            return Err(self.error("expected the NUL ending a string"));
+        }
+
+        self.utf8(&bytes[..len - 1], start)
+    }
+
+    fn document(&mut self) -> Result<Vec<(String, JsonValue)>, JsonError> {
+        let start = self.pos;
+        let end = start + self.len(5)?;
+        if end > self.bytes.len() {
+            return Err(JsonError::NeedMoreData);
+        }
+
+        let mut elements = Vec::new();
+
+        loop {
+            if self.pos >= end {
+                break;
+            }
+
+            let kind = self.byte()?;
+            if kind == 0 {
+                break;
+            }
+
+            let key = self.cstring()?;
+            let value = self.value(kind)?;
+            elements.push((key, value));
+        }
+
+        if self.pos != end {
+            self.pos = start;
+            return Err(self.error("document length doesn't match its content"));
+        }
+
+        Ok(elements)
+    }
+
+    fn value(&mut self, kind: u8) -> Result<JsonValue, JsonError> {
+        Ok(match kind {
+            DOUBLE => match f64::from_le_bytes(self.array()?) {
+                n if n.is_finite() => JsonValue::Num(n),
+                n => {
+                    let n = match n {
+                        f64::INFINITY => "Infinity",
+                        f64::NEG_INFINITY => "-Infinity",
+                        _ => "NaN",
+                    };
+                    extended("$numberDouble", JsonValue::Str(n.into()))
+                }
+            },
+            STRING => JsonValue::Str(self.string()?),
+            DOCUMENT => JsonValue::Object(self.document()?.into_iter().collect()),
+            ARRAY => JsonValue::Array(
+                self.document()?
+                    .into_iter()
+                    .map(|(_, value)| value)
+                    .collect(),
+            ),
+            BINARY => {
+                let len = self.len(0)?;
+                let sub_type = self.byte()?;
+                let bytes = self.take(len)?;
+
+                let binary = [
+                    ("base64", JsonValue::Str(to_base64(bytes))),
+                    ("subType", JsonValue::Str(format!("{sub_type:02x}"))),
+                ];
+                extended(
+                    "$binary",
+                    JsonValue::Object(binary.into_iter().map(|(k, v)| (k.into(), v)).collect()),
+                )
+            }
+            UNDEFINED => extended("$undefined", JsonValue::Boolean(true)),
+            OBJECT_ID => extended("$oid", JsonValue::Str(to_hex(&self.array::<12>()?))),
+            BOOLEAN => match self.byte()? {
+                0 => JsonValue::Boolean(false),
+                1 => JsonValue::Boolean(true),
+                _ => {
+                    self.pos -= 1;
+                    return Err(self.error("invalid boolean"));
+                }
+            },
+            DATETIME => {
+                let millis = i64::from_le_bytes(self.array()?);
+                let date = if (0..=MAX_ISO_DATE).contains(&millis) {
+                    JsonValue::Str(format_iso_date(millis))
+                } else {
+                    extended("$numberLong", JsonValue::Str(millis.to_string()))
+                };
+                extended("$date", date)
+            }
+            NULL => JsonValue::Null,
+            REGEX => {
+                let regex = [("pattern", self.cstring()?), ("options", self.cstring()?)];
+                extended(
+                    "$regularExpression",
+                    JsonValue::Object(
+                        regex
+                            .into_iter()
+                            .map(|(k, v)| (k.into(), JsonValue::Str(v)))
+                            .collect(),
+                    ),
+                )
+            }
+            CODE => extended("$code", JsonValue::Str(self.string()?)),
+            SYMBOL => extended("$symbol", JsonValue::Str(self.string()?)),
+            INT32 => JsonValue::Int(i32::from_le_bytes(self.array()?) as i64),
+            TIMESTAMP => {
+                let i = u32::from_le_bytes(self.array()?);
+                let t = u32::from_le_bytes(self.array()?);
+
+                let timestamp = [("t", t), ("i", i)];
+                extended(
+                    "$timestamp",
+                    JsonValue::Object(
+                        timestamp
+                            .into_iter()
+                            .map(|(k, v)| (k.into(), JsonValue::Int(v as i64)))
+                            .collect(),
+                    ),
+                )
+            }
+            INT64 => JsonValue::Int(i64::from_le_bytes(self.array()?)),
+            MIN_KEY => extended("$minKey", JsonValue::Int(1)),
+            MAX_KEY => extended("$maxKey", JsonValue::Int(1)),
+            DECIMAL128 => return Err(self.error("Decimal128 isn't supported")),
+            kind => return Err(self.error(&format!("unsupported type 0x{kind:02x}"))),
+        })
+    }
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+fn from_hex(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) || !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return None;
+    }
+
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).ok())
+        .collect()
+}
+
+const BASE64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+fn to_base64(bytes: &[u8]) -> String {
+    let mut out = String::with_capacity(bytes.len().div_ceil(3) * 4);
+
+    for chunk in bytes.chunks(3) {
+        let mut group = [0; 3];
+        group[..chunk.len()].copy_from_slice(chunk);
+        let bits = u32::from_be_bytes([0, group[0], group[1], group[2]]);
+
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(BASE64[(bits >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+
+    out
+}
+
+fn from_base64(base64: &str) -> Option<Vec<u8>> {
+    let digits = base64.trim_end_matches('=');
+    if !base64.len().is_multiple_of(4) || base64.len() - digits.len() > 2 {
+        return None;
+    }
+
+    let mut out = Vec::with_capacity(digits.len() * 3 / 4);
+    let mut bits = 0u32;
+    let mut count = 0;
+
+    for c in digits.bytes() {
+        let digit = BASE64.iter().position(|&d| d == c)? as u32;
+        bits = bits << 6 | digit;
+        count += 1;
+
+        if count == 4 {
+            out.extend_from_slice(&bits.to_be_bytes()[1..]);
+            (bits, count) = (0, 0);
+        }
+    }
+
+    match count {
+        0 => {}
+        2 => out.push((bits >> 4) as u8),
+        3 => out.extend_from_slice(&((bits >> 2) as u16).to_be_bytes()),
+        _ => return None,
+    }
+
+    Some(out)
+}
+
+/// Days since 1970-01-01 of a date of the proleptic Gregorian calendar
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// Inverse of [`days_from_civil`]
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+
+    (year_of_era + era * 400 + (month <= 2) as i64, month, day)
+}
+
+/// `2023-06-01T12:00:00Z`, with milliseconds when there are some
+fn format_iso_date(millis: i64) -> String {
+    let (days, millis) = (millis.div_euclid(86_400_000), millis.rem_euclid(86_400_000));
+    let (year, month, day) = civil_from_days(days);
+    let seconds = millis / 1000;
+
+    let mut date = format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}",
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60
+    );
+    if millis % 1000 != 0 {
+        date.push_str(&format!(".{:03}", millis % 1000));
+    }
+    date.push('Z');
+
+    date
+}
+
+/// Milliseconds since the epoch of `YYYY-MM-DDTHH:MM:SS[.fff](Z|±HH:MM)`.
+/// Fractions past milliseconds are truncated.
+fn parse_iso_date(date: &str) -> Option<i64> {
+    let bytes = date.as_bytes();
+    let number = |range: core::ops::Range<usize>| -> Option<i64> {
+        let digits = bytes.get(range)?;
+        if !digits.iter().all(u8::is_ascii_digit) {
+            return None;
+        }
+        digits
+            .iter()
+            .try_fold(0, |n: i64, d| Some(n * 10 + (d - b'0') as i64))
+    };
+    let separators = [(4, b'-'), (7, b'-'), (10, b'T'), (13, b':'), (16, b':')];
+    if !separators
+        .iter()
+        .all(|&(i, c)| bytes.get(i).map(u8::to_ascii_uppercase) == Some(c))
+    {
+        return None;
+    }
+
+    let (year, month, day) = (number(0..4)?, number(5..7)?, number(8..10)?);
+    let (hour, minute, second) = (number(11..13)?, number(14..16)?, number(17..19)?);
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) || hour > 23 || minute > 59 {
+        return None;
+    }
+    // Leap seconds fold into the next second
+    if second > 60 {
+        return None;
+    }
+
+    let mut rest = &date[19..];
+    let mut millis = 0;
+    if let Some(fraction) = rest.strip_prefix('.') {
+        let len = fraction.bytes().take_while(u8::is_ascii_digit).count();
+        if len == 0 {
+            return None;
+        }
+        let digits = &fraction[..len.min(3)];
+        millis = digits.bytes().fold(0, |n, d| n * 10 + (d - b'0') as i64)
+            * 10i64.pow(3 - digits.len() as u32);
+        rest = &fraction[len..];
+    }
+
+    let offset = match rest.as_bytes() {
+        [b'Z' | b'z'] => 0,
+        [sign @ (b'+' | b'-'), h1, h2, b':', m1, m2] | [sign @ (b'+' | b'-'), h1, h2, m1, m2] => {
+            let digits = [h1, h2, m1, m2];
+            if !digits.iter().all(|d| d.is_ascii_digit()) {
+                return None;
+            }
+            let [h1, h2, m1, m2] = digits.map(|d| (d - b'0') as i64);
+            let offset = (h1 * 10 + h2) * 60 + m1 * 10 + m2;
+            if *sign == b'-' {
+                -offset
+            } else {
+                offset
+            }
+        }
+        _ => return None,
+    };
+
+    let days = days_from_civil(year, month, day);
+    let seconds = ((days * 24 + hour) * 60 + minute - offset) * 60 + second;
+
+    Some(seconds * 1000 + millis)
+}
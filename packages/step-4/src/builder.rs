@@ -0,0 +1,100 @@
+//! Arrays and objects built by chained calls, for when the keys or the
+//! number of elements are only known at run time.
+//!
+//! ```
+//! use step_4::{ArrayBuilder, ObjectBuilder};
+//!
+//! let mut tags = ArrayBuilder::new().push("rust");
+//! tags.extend(["nom", "serde"]);
+//!
+//! let lang = "ja";
+//! let value = ObjectBuilder::new()
+//!     .key("id", 1)
+//!     .key(format!("title_{lang}"), "パーサー")
+//!     .key("tags", tags)
+//!     .key("author", ObjectBuilder::new().key("name", "jules"))
+//!     .build();
+//!
+//! assert_eq!(
+//!     value,
+//!     step_4::parse_with_path(r#"{
+//!         "id": 1,
+//!         "title_ja": "パーサー",
+//!         "tags": ["rust", "nom", "serde"],
+//!         "author": {"name": "jules"}
+//!     }"#).unwrap()
+//! );
+//! ```
+
+use alloc::{string::String, vec::Vec};
+
+use crate::{JsonValue, Map};
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ObjectBuilder {
+    map: Map<String, JsonValue>,
+}
+
+impl ObjectBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the member `key`, replacing the one already there if any
+    pub fn key(mut self, key: impl Into<String>, value: impl Into<JsonValue>) -> Self {
+        self.map.insert(key.into(), value.into());
+        self
+    }
+
+    pub fn build(self) -> JsonValue {
+        JsonValue::Object(self.map)
+    }
+}
+
+impl<K: Into<String>, V: Into<JsonValue>> Extend<(K, V)> for ObjectBuilder {
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, members: I) {
+        self.map.extend(
+            members
+                .into_iter()
+                .map(|(key, value)| (key.into(), value.into())),
+        );
+    }
+}
+
+impl From<ObjectBuilder> for JsonValue {
+    fn from(builder: ObjectBuilder) -> Self {
+        builder.build()
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ArrayBuilder {
+    values: Vec<JsonValue>,
+}
+
+impl ArrayBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(mut self, value: impl Into<JsonValue>) -> Self {
+        self.values.push(value.into());
+        self
+    }
+
+    pub fn build(self) -> JsonValue {
+        JsonValue::Array(self.values)
+    }
+}
+
+impl<V: Into<JsonValue>> Extend<V> for ArrayBuilder {
+    fn extend<I: IntoIterator<Item = V>>(&mut self, values: I) {
+        self.values.extend(values.into_iter().map(Into::into));
+    }
+}
+
+impl From<ArrayBuilder> for JsonValue {
+    fn from(builder: ArrayBuilder) -> Self {
+        builder.build()
+    }
+}
@@ -0,0 +1,249 @@
+//! The parser over `&[u8]`, so files can be parsed straight from a `Vec<u8>`
+//! or a memory map without converting the whole input to a `str` first.
+//!
+//! Everything outside of strings is ASCII, so UTF-8 is only validated inside
+//! strings, one run of unescaped bytes at a time.
+
+use crate::{lexer::Number, JsonError, JsonValue, Map};
+use memchr::memchr2;
+use nom::{
+    branch::alt,
+    bytes::complete::{tag, take},
+    character::complete::{char, multispace0},
+    combinator::{cut, map, map_opt, peek, verify},
+    error::{context, ContextError, ErrorKind, ParseError},
+    multi::separated_list0,
+    number::complete::{double, u8 as byte},
+    sequence::{delimited, preceded, separated_pair, terminated},
+    IResult, Parser,
+};
+
+type Result<'a, O, E> = IResult<&'a [u8], O, E>;
+
+/// Same as over a `str`, `remaining` counting bytes in both cases
+impl<'a> ParseError<&'a [u8]> for JsonError {
+    fn from_error_kind(input: &'a [u8], kind: ErrorKind) -> Self {
+        // The char that starts there, if the bytes are valid UTF-8
+        let head = &input[..input.len().min(4)];
+        let valid = match std::str::from_utf8(head) {
+            Ok(head) => head,
+            Err(e) => std::str::from_utf8(&head[..e.valid_up_to()]).unwrap(),
+        };
+        let found = match valid.chars().next() {
+            None if !input.is_empty() => Some(char::REPLACEMENT_CHARACTER),
+            c => c,
+        };
+
+        JsonError::Syntax {
+            kind,
+            found,
+            remaining: input.len(),
+            expected: None,
+            message: None,
+            context: Vec::new(),
+        }
+    }
+
+    fn append(input: &'a [u8], kind: ErrorKind, other: Self) -> Self {
+        other
+    }
+
+    fn from_char(input: &'a [u8], c: char) -> Self {
+        let mut error = Self::from_error_kind(input, ErrorKind::Char);
+
+        if let JsonError::Syntax { expected, .. } = &mut error {
+            *expected = Some(c);
+        }
+
+        error
+    }
+}
+
+impl ContextError<&[u8]> for JsonError {
+    fn add_context(input: &[u8], ctx: &'static str, mut other: Self) -> Self {
+        if let JsonError::Syntax { context, .. } = &mut other {
+            context.push((ctx, input.len()));
+        }
+
+        other
+    }
+}
+
+fn u16_hex<'a, E: ParseError<&'a [u8]>>(i: &'a [u8]) -> Result<'a, u16, E> {
+    map_opt(take(4usize), |hex: &'a [u8]| {
+        u16::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok()
+    })(i)
+}
+
+fn unicode_escape<'a, E: ParseError<&'a [u8]>>(i: &'a [u8]) -> Result<'a, char, E> {
+    map_opt(
+        alt((
+            // Not a surrogate
+            map(verify(u16_hex, |cp| !(0xD800..0xE000).contains(cp)), |cp| {
+                cp as u32
+            }),
+            // See https://en.wikipedia.org/wiki/UTF-16#Code_points_from_U+010000_to_U+10FFFF for details
+            map(
+                verify(
+                    separated_pair(u16_hex, tag("\\u"), u16_hex),
+                    |(high, low)| (0xD800..0xDC00).contains(high) && (0xDC00..0xE000).contains(low),
+                ),
+                |(high, low)| {
+                    let high_ten = (high as u32) - 0xD800;
+                    let low_ten = (low as u32) - 0xDC00;
+                    (high_ten << 10) + low_ten + 0x10000
+                },
+            ),
+        )),
+        std::char::from_u32,
+    )(i)
+}
+
+/// Parses what follows a `\` inside a string
+fn escape_char<'a, E: ParseError<&'a [u8]>>(input: &'a [u8]) -> Result<'a, char, E> {
+    let (i, escaped_byte) = byte(input)?;
+    let final_char = match escaped_byte {
+        b'"' | b'\\' | b'/' => escaped_byte as char,
+        b'b' => '\x08',
+        b'f' => '\x0C',
+        b'n' => '\n',
+        b'r' => '\r',
+        b't' => '\t',
+        b'u' => return unicode_escape(i),
+        _ => {
+            return Err(nom::Err::Failure(E::from_error_kind(
+                input,
+                ErrorKind::Char,
+            )))
+        }
+    };
+
+    Ok((i, final_char))
+}
+
+fn string<'a, E: ParseError<&'a [u8]> + ContextError<&'a [u8]>>(
+    i: &'a [u8],
+) -> Result<'a, String, E> {
+    let (mut i, _) = char('"')(i)?;
+    let mut string = String::new();
+
+    loop {
+        let Some(end) = memchr2(b'"', b'\\', i) else {
+            return Err(nom::Err::Failure(E::add_context(
+                i,
+                "string",
+                E::from_error_kind(i, ErrorKind::Eof),
+            )));
+        };
+
+        // Unescaped run, the only place where UTF-8 has to be checked
+        match std::str::from_utf8(&i[..end]) {
+            Ok(run) => string.push_str(run),
+            Err(e) => {
+                let invalid = &i[e.valid_up_to()..];
+                return Err(nom::Err::Failure(E::from_error_kind(
+                    invalid,
+                    ErrorKind::Char,
+                )));
+            }
+        }
+
+        if i[end] == b'"' {
+            return Ok((&i[end + 1..], string));
+        }
+
+        let (rest, c) = escape_char(&i[end + 1..])?;
+        string.push(c);
+        i = rest;
+    }
+}
+
+fn number<'a, E: ParseError<&'a [u8]>>(i: &'a [u8]) -> Result<'a, Number, E> {
+    let (rest, float) = double(i)?;
+    // `double` only matches ASCII
+    let text = std::str::from_utf8(&i[..i.len() - rest.len()]).unwrap();
+
+    Ok((rest, Number::new(text, float)))
+}
+
+fn array<'a, E: ParseError<&'a [u8]> + ContextError<&'a [u8]>>(
+    i: &'a [u8],
+) -> Result<'a, Vec<JsonValue>, E> {
+    context(
+        "array",
+        delimited(
+            cut(char('[')),
+            cut(separated_list0(preceded(multispace0, char(',')), |i| {
+                let (i, next_byte) = peek(byte)(i)?;
+
+                if next_byte == b']' {
+                    return Err(nom::Err::Error(E::from_error_kind(i, ErrorKind::Char)));
+                }
+
+                cut(json_value)(i)
+            })),
+            preceded(multispace0, char(']')),
+        ),
+    )(i)
+}
+
+fn key_value<'a, E: ParseError<&'a [u8]> + ContextError<&'a [u8]>>(
+    i: &'a [u8],
+) -> Result<'a, (String, JsonValue), E> {
+    let (i, _) = multispace0(i)?;
+
+    let (i, next_byte) = peek(byte)(i)?;
+
+    if next_byte == b'}' {
+        return Err(nom::Err::Error(E::from_error_kind(i, ErrorKind::Char)));
+    }
+
+    separated_pair(
+        string,
+        cut(preceded(multispace0, char(':'))),
+        cut(json_value),
+    )
+    .parse(i)
+}
+
+fn hash<'a, E: ParseError<&'a [u8]> + ContextError<&'a [u8]>>(
+    i: &'a [u8],
+) -> Result<'a, Map<String, JsonValue>, E> {
+    context(
+        "map",
+        preceded(
+            cut(char('{')),
+            cut(terminated(
+                map(
+                    separated_list0(preceded(multispace0, char(',')), key_value),
+                    |tuple_vec| tuple_vec.into_iter().collect(),
+                ),
+                preceded(multispace0, char('}')),
+            )),
+        ),
+    )
+    .parse(i)
+}
+
+fn json_value<'a, E: ParseError<&'a [u8]> + ContextError<&'a [u8]>>(
+    i: &'a [u8],
+) -> Result<'a, JsonValue, E> {
+    let (i, _) = multispace0(i)?;
+
+    let (i, first_byte) = peek(byte)(i)?;
+
+    match first_byte {
+        b'{' => map(hash, JsonValue::Object)(i),
+        b'[' => map(array, JsonValue::Array)(i),
+        b'"' => map(string, JsonValue::Str)(i),
+        b'-' | b'0'..=b'9' => map(number, JsonValue::from)(i),
+        b'f' => map(tag("false"), |_| JsonValue::Boolean(false))(i),
+        b't' => map(tag("true"), |_| JsonValue::Boolean(true))(i),
+        b'n' => map(tag("null"), |_| JsonValue::Null)(i),
+        b => Err(nom::Err::Failure(E::from_error_kind(i, ErrorKind::Char))),
+    }
+}
+
+pub fn parse_bytes(i: &[u8]) -> Result<'_, JsonValue, JsonError> {
+    terminated(json_value, multispace0).parse(i)
+}
@@ -0,0 +1,331 @@
+//! [CBOR](https://www.rfc-editor.org/rfc/rfc8949) encoding of values, the
+//! binary sibling of [`msgpack`](crate::msgpack) for where CBOR is the norm.
+//!
+//! Encoding follows the deterministic rules of the RFC for integers and
+//! lengths, and writes other numbers as 64-bit floats: `RawNum` loses its
+//! text on the way, and `BigNum` its digits past the precision of an `f64`.
+//!
+//! Decoding takes the JSON-compatible subset: byte strings, `undefined`,
+//! other simple values and non-string keys are refused, and tags are skipped
+//! to the value they wrap.
+
+use alloc::{format, string::String, vec::Vec};
+
+use crate::{wtf8, JsonError, JsonValue, Map};
+
+const UINT: u8 = 0;
+const NEGATIVE: u8 = 1;
+const BYTES: u8 = 2;
+const TEXT: u8 = 3;
+const ARRAY: u8 = 4;
+const MAP: u8 = 5;
+const TAG: u8 = 6;
+const SIMPLE: u8 = 7;
+
+/// Additional information of an indefinite length
+const INDEFINITE: u8 = 31;
+const BREAK: u8 = 0xff;
+
+impl JsonValue {
+    /// ```
+    /// let value = step_4::parse_with_path("[1, [2, 3], [4, 5]]").unwrap();
+    /// let bytes = value.to_cbor();
+    ///
+    /// assert_eq!(bytes, [0x83, 0x01, 0x82, 0x02, 0x03, 0x82, 0x04, 0x05]);
+    /// assert_eq!(step_4::from_cbor(&bytes).unwrap(), value);
+    /// ```
+    pub fn to_cbor(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        encode(&mut out, self);
+        out
+    }
+}
+
+/// Initial byte of `major`, followed by `argument` in as few bytes as it fits
+fn encode_head(out: &mut Vec<u8>, major: u8, argument: u64) {
+    let major = major << 5;
+
+    if argument < 24 {
+        out.push(major | argument as u8);
+    } else if let Ok(argument) = u8::try_from(argument) {
+        out.extend_from_slice(&[major | 24, argument]);
+    } else if let Ok(argument) = u16::try_from(argument) {
+        out.push(major | 25);
+        out.extend_from_slice(&argument.to_be_bytes());
+    } else if let Ok(argument) = u32::try_from(argument) {
+        out.push(major | 26);
+        out.extend_from_slice(&argument.to_be_bytes());
+    } else {
+        out.push(major | 27);
+        out.extend_from_slice(&argument.to_be_bytes());
+    }
+}
+
+fn encode_float(out: &mut Vec<u8>, n: f64) {
+    out.push(SIMPLE << 5 | 27);
+    out.extend_from_slice(&n.to_be_bytes());
+}
+
+fn encode_str(out: &mut Vec<u8>, s: &str) {
+    encode_head(out, TEXT, s.len() as u64);
+    out.extend_from_slice(s.as_bytes());
+}
+
+fn encode(out: &mut Vec<u8>, value: &JsonValue) {
+    match value {
+        JsonValue::Null => out.push(0xf6),
+        JsonValue::Boolean(false) => out.push(0xf4),
+        JsonValue::Boolean(true) => out.push(0xf5),
+        JsonValue::Int(n) if *n < 0 => encode_head(out, NEGATIVE, !*n as u64),
+        JsonValue::Int(n) => encode_head(out, UINT, *n as u64),
+        JsonValue::UInt(n) => encode_head(out, UINT, *n),
+        JsonValue::Num(n) | JsonValue::RawNum(n, _) => encode_float(out, *n),
+        #[cfg(feature = "bignum")]
+        JsonValue::BigNum(n) => encode_float(out, n.to_f64()),
+        JsonValue::Str(s) => encode_str(out, s),
+        // Text is UTF-8
+        JsonValue::Wtf8(bytes) => encode_str(out, &wtf8::to_string_lossy(bytes)),
+        JsonValue::Array(values) => {
+            encode_head(out, ARRAY, values.len() as u64);
+            for value in values {
+                encode(out, value);
+            }
+        }
+        JsonValue::Object(map) => {
+            encode_head(out, MAP, map.len() as u64);
+            for (key, value) in map {
+                encode_str(out, key);
+                encode(out, value);
+            }
+        }
+    }
+}
+
+/// Value of the bits of an IEEE 754 half-precision float
+fn f16_to_f64(bits: u16) -> f64 {
+    let sign = if bits & 0x8000 == 0 { 1.0 } else { -1.0 };
+    let exponent = (bits >> 10) & 0x1f;
+    let mantissa = (bits & 0x3ff) as u64;
+
+    match exponent {
+        // Subnormal: mantissa × 2⁻²⁴
+        0 => sign * mantissa as f64 / (1 << 24) as f64,
+        0x1f if mantissa == 0 => sign * f64::INFINITY,
+        0x1f => f64::NAN,
+        _ => sign * f64::from_bits((exponent as u64 + 1023 - 15) << 52 | mantissa << 42),
+    }
+}
+
+/// Decodes one data item, which must span all of `bytes`.
+///
+/// A truncated input is a [`JsonError::NeedMoreData`].
+pub fn from_cbor(bytes: &[u8]) -> Result<JsonValue, JsonError> {
+    let mut decoder = Decoder { bytes, pos: 0 };
+    let value = decoder.value()?;
+
+    if decoder.pos < bytes.len() {
+        return Err(decoder.error("trailing bytes after the value"));
+    }
+
+    Ok(value)
+}
+
+struct Decoder<'b> {
+    bytes: &'b [u8],
+    pos: usize,
+}
+
+impl<'b> Decoder<'b> {
+    fn error(&self, message: &str) -> JsonError {
+        JsonError::Custom(format!("{message} at byte {}", self.pos))
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'b [u8], JsonError> {
+        let end = self
+            .pos
+            .checked_add(len)
+            .filter(|&end| end <= self.bytes.len())
+            .ok_or(JsonError::NeedMoreData)?;
+        let taken = &self.bytes[self.pos..end];
+        self.pos = end;
+
+        Ok(taken)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], JsonError> {
+        Ok(self.take(N)?.try_into().unwrap())
+    }
+
+    fn peek(&self) -> Result<u8, JsonError> {
+        self.bytes
+            .get(self.pos)
+            .copied()
+            .ok_or(JsonError::NeedMoreData)
+    }
+
+    /// Major type and argument of the next item. The argument is `None` for
+    /// an indefinite length.
+    fn head(&mut self) -> Result<(u8, Option<u64>), JsonError> {
+        let initial = self.array::<1>()?[0];
+        let (major, info) = (initial >> 5, initial & 0x1f);
+
+        let argument = match info {
+            0..=23 => info as u64,
+            24 => self.array::<1>()?[0] as u64,
+            25 => u16::from_be_bytes(self.array()?) as u64,
+            26 => u32::from_be_bytes(self.array()?) as u64,
+            27 => u64::from_be_bytes(self.array()?),
+            INDEFINITE if matches!(major, BYTES | TEXT | ARRAY | MAP) => return Ok((major, None)),
+            _ => {
+                self.pos -= 1;
+                return Err(self.error("invalid additional information"));
+            }
+        };
+
+        Ok((major, Some(argument)))
+    }
+
+    fn len(&self, argument: u64) -> Result<usize, JsonError> {
+        usize::try_from(argument).map_err(|_| self.error("length too large"))
+    }
+
+    /// Whether the next byte is the end of an indefinite length item, which
+    /// it then consumes
+    fn at_break(&mut self) -> Result<bool, JsonError> {
+        let at_break = self.peek()? == BREAK;
+        if at_break {
+            self.pos += 1;
+        }
+
+        Ok(at_break)
+    }
+
+    fn text(&mut self, len: usize) -> Result<&'b str, JsonError> {
+        let start = self.pos;
+        let bytes = self.take(len)?;
+
+        core::str::from_utf8(bytes).map_err(|e| {
+            self.pos = start + e.valid_up_to();
+            self.error("invalid UTF-8")
+        })
+    }
+
+    fn string(&mut self, argument: Option<u64>) -> Result<String, JsonError> {
+        let Some(argument) = argument else {
+            // Indefinite length: definite length chunks, up to a break
+            let mut s = String::new();
+
+            while !self.at_break()? {
+                let start = self.pos;
+                match self.head()? {
+                    (TEXT, Some(argument)) => {
+                        let len = self.len(argument)?;
+                        s.push_str(self.text(len)?);
+                    }
+                    _ => {
+                        self.pos = start;
+                        return Err(self.error("expected a chunk of text"));
+                    }
+                }
+            }
+
+            return Ok(s);
+        };
+
+        let len = self.len(argument)?;
+        Ok(self.text(len)?.into())
+    }
+
+    /// Whether there is another of `len` items, or of the items before a
+    /// break when `len` is `None`
+    fn has_next(&mut self, len: &mut Option<u64>) -> Result<bool, JsonError> {
+        match len {
+            Some(0) => Ok(false),
+            Some(n) => {
+                *n -= 1;
+                Ok(true)
+            }
+            None => Ok(!self.at_break()?),
+        }
+    }
+
+    fn values(&mut self, mut len: Option<u64>) -> Result<Vec<JsonValue>, JsonError> {
+        // Every value takes a byte at least: a corrupted length can't make
+        // us allocate more than the input
+        let capacity = len.unwrap_or(0).min((self.bytes.len() - self.pos) as u64);
+        let mut values = Vec::with_capacity(capacity as usize);
+
+        while self.has_next(&mut len)? {
+            values.push(self.value()?);
+        }
+
+        Ok(values)
+    }
+
+    fn map(&mut self, mut len: Option<u64>) -> Result<Map<String, JsonValue>, JsonError> {
+        let mut map = Map::default();
+
+        while self.has_next(&mut len)? {
+            let start = self.pos;
+            let key = match self.head()? {
+                (TEXT, argument) => self.string(argument)?,
+                _ => {
+                    self.pos = start;
+                    return Err(self.error("keys must be strings"));
+                }
+            };
+
+            let value = self.value()?;
+            map.insert(key, value);
+        }
+
+        Ok(map)
+    }
+
+    fn value(&mut self) -> Result<JsonValue, JsonError> {
+        let start = self.pos;
+
+        // Floats and simple values are read from their initial byte, their
+        // argument isn't an integer
+        if self.peek()? >> 5 == SIMPLE {
+            self.pos += 1;
+            return Ok(match self.bytes[start] {
+                0xf4 => JsonValue::Boolean(false),
+                0xf5 => JsonValue::Boolean(true),
+                0xf6 => JsonValue::Null,
+                0xf9 => JsonValue::Num(f16_to_f64(u16::from_be_bytes(self.array()?))),
+                0xfa => JsonValue::Num(f32::from_be_bytes(self.array()?) as f64),
+                0xfb => JsonValue::Num(f64::from_be_bytes(self.array()?)),
+                0xf7 => {
+                    self.pos = start;
+                    return Err(self.error("undefined has no JSON counterpart"));
+                }
+                BREAK => {
+                    self.pos = start;
+                    return Err(self.error("unexpected break"));
+                }
+                _ => {
+                    self.pos = start;
+                    return Err(self.error("simple values have no JSON counterpart"));
+                }
+            });
+        }
+
+        Ok(match self.head()? {
+            (UINT, Some(n)) => i64::try_from(n).map_or(JsonValue::UInt(n), JsonValue::Int),
+            // -1 - n, past `i64::MIN` for the largest arguments
+            (NEGATIVE, Some(n)) => match i64::try_from(n) {
+                Ok(n) => JsonValue::Int(!n),
+                Err(_) => JsonValue::Num(-1.0 - n as f64),
+            },
+            (TEXT, argument) => JsonValue::Str(self.string(argument)?),
+            (ARRAY, len) => JsonValue::Array(self.values(len)?),
+            (MAP, len) => JsonValue::Object(self.map(len)?),
+            (TAG, _) => self.value()?,
+            _ => {
+                self.pos = start;
+                return Err(self.error("byte strings have no JSON counterpart"));
+            }
+        })
+    }
+}
@@ -0,0 +1,131 @@
+//! Total order and hashing of [`JsonValue`], so that values can be used as
+//! `HashMap` keys or deduplicated in a `BTreeSet`.
+//!
+//! Values of different variants are ordered by variant, in declaration order:
+//! `Int(1)` and `Num(1.0)` are different values, `Int(1)` being the smaller.
+//! Within a variant:
+//! - floats follow [`f64::total_cmp`]: `-0.0 < 0.0`, and `NaN` equals itself
+//! - arrays compare element by element
+//! - objects compare their entries sorted by key, so the order of the keys
+//!   in the source doesn't matter
+
+use alloc::{string::String, vec::Vec};
+use core::{
+    cmp::Ordering,
+    hash::{Hash, Hasher},
+};
+#[cfg(feature = "std")]
+use std::collections::hash_map::DefaultHasher;
+
+use crate::{JsonValue, Map};
+
+impl JsonValue {
+    /// Position of the variant in the declaration
+    fn rank(&self) -> u8 {
+        match self {
+            JsonValue::Null => 0,
+            JsonValue::Str(_) => 1,
+            JsonValue::Boolean(_) => 2,
+            JsonValue::Num(_) => 3,
+            JsonValue::Int(_) => 4,
+            JsonValue::UInt(_) => 5,
+            #[cfg(feature = "bignum")]
+            JsonValue::BigNum(_) => 6,
+            JsonValue::RawNum(..) => 7,
+            JsonValue::Wtf8(_) => 8,
+            JsonValue::Array(_) => 9,
+            JsonValue::Object(_) => 10,
+        }
+    }
+}
+
+fn sorted_entries(map: &Map<String, JsonValue>) -> Vec<(&String, &JsonValue)> {
+    let mut entries: Vec<_> = map.iter().collect();
+    entries.sort_unstable_by_key(|(key, _)| *key);
+    entries
+}
+
+impl Ord for JsonValue {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (JsonValue::Str(a), JsonValue::Str(b)) => a.cmp(b),
+            (JsonValue::Boolean(a), JsonValue::Boolean(b)) => a.cmp(b),
+            (JsonValue::Num(a), JsonValue::Num(b)) => a.total_cmp(b),
+            (JsonValue::Int(a), JsonValue::Int(b)) => a.cmp(b),
+            (JsonValue::UInt(a), JsonValue::UInt(b)) => a.cmp(b),
+            #[cfg(feature = "bignum")]
+            (JsonValue::BigNum(a), JsonValue::BigNum(b)) => a.cmp(b),
+            (JsonValue::RawNum(a, a_text), JsonValue::RawNum(b, b_text)) => {
+                a.total_cmp(b).then_with(|| a_text.cmp(b_text))
+            }
+            (JsonValue::Wtf8(a), JsonValue::Wtf8(b)) => a.cmp(b),
+            (JsonValue::Array(a), JsonValue::Array(b)) => a.cmp(b),
+            (JsonValue::Object(a), JsonValue::Object(b)) => {
+                // Equal maps, the common case when deduplicating, need no sorting
+                if a.len() == b.len() && a == b {
+                    return Ordering::Equal;
+                }
+
+                sorted_entries(a).cmp(&sorted_entries(b))
+            }
+            _ => self.rank().cmp(&other.rank()),
+        }
+    }
+}
+
+impl PartialOrd for JsonValue {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for JsonValue {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other).is_eq()
+    }
+}
+
+impl Eq for JsonValue {}
+
+impl Hash for JsonValue {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.rank().hash(state);
+
+        match self {
+            JsonValue::Null => {}
+            JsonValue::Str(s) => s.hash(state),
+            JsonValue::Boolean(b) => b.hash(state),
+            JsonValue::Num(n) => n.to_bits().hash(state),
+            JsonValue::Int(n) => n.hash(state),
+            JsonValue::UInt(n) => n.hash(state),
+            #[cfg(feature = "bignum")]
+            JsonValue::BigNum(n) => n.hash(state),
+            JsonValue::RawNum(n, text) => {
+                n.to_bits().hash(state);
+                text.hash(state);
+            }
+            JsonValue::Wtf8(bytes) => bytes.hash(state),
+            JsonValue::Array(values) => values.hash(state),
+            #[cfg(feature = "std")]
+            JsonValue::Object(map) => {
+                // Iteration order differs between equal maps: entries are
+                // hashed on their own and combined with a commutative sum
+                let sum = map.iter().fold(0u64, |sum, entry| {
+                    let mut hasher = DefaultHasher::new();
+                    entry.hash(&mut hasher);
+                    sum.wrapping_add(hasher.finish())
+                });
+
+                map.len().hash(state);
+                sum.hash(state);
+            }
+            // A `BTreeMap` iterates in key order, which is the same for
+            // equal maps
+            #[cfg(not(feature = "std"))]
+            JsonValue::Object(map) => {
+                map.len().hash(state);
+                map.iter().for_each(|entry| entry.hash(state));
+            }
+        }
+    }
+}
@@ -0,0 +1,343 @@
+//! Rust types for documents of a [`Schema`], to deserialize them with
+//! `serde` and `serde_json`: a little [quicktype](https://quicktype.io/).
+//!
+//! ```
+//! let sample = step_4::parse_with_path(r#"{"id": 1, "user": {"screen_name": "jules"}}"#)
+//!     .unwrap();
+//!
+//! let code = step_4::rust_types(&step_4::infer([&sample]), "Tweet");
+//! assert!(code.contains("pub struct Tweet {\n    pub id: i64,\n    pub user: User,\n}"));
+//! assert!(code.contains("pub struct User {\n    pub screen_name: String,\n}"));
+//! ```
+//!
+//! [`rust_value`] writes the code of a value instead, to compile fixtures
+//! into tests rather than read them from `test-files/`.
+
+use alloc::{
+    collections::{BTreeMap, BTreeSet, VecDeque},
+    format,
+    string::String,
+    vec::Vec,
+};
+use core::fmt::Write;
+
+use crate::{
+    infer::{Field, Schema},
+    JsonValue,
+};
+
+/// Keywords that can't be made raw identifiers
+const NOT_RAW: [&str; 4] = ["crate", "self", "super", "Self"];
+
+const KEYWORDS: [&str; 38] = [
+    "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum", "extern",
+    "false", "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub",
+    "ref", "return", "self", "Self", "static", "struct", "super", "trait", "true", "type",
+    "unsafe", "use", "where", "while",
+];
+
+/// Definitions of the types of the documents of `schema`, the root one being
+/// called `name`.
+///
+/// Objects become structs named after their key, and arrays `Vec`s. Fields
+/// that can be `null` or missing are `Option`s, and values that can be of
+/// several types a `serde_json::Value`.
+pub fn rust_types(schema: &Schema, name: &str) -> String {
+    let mut generator = Generator::default();
+    let mut out = String::from("use serde::{Deserialize, Serialize};\n");
+
+    let root = pascal_case(name);
+    match &schema.object {
+        Some(fields) if is_only_object(schema) => {
+            generator.queue(&root, fields);
+        }
+        _ => {
+            generator.names.insert(root.clone());
+            let ty = generator.ty(schema, &root);
+            let _ = write!(out, "\npub type {root} = {ty};\n");
+        }
+    }
+
+    while let Some((name, fields)) = generator.structs.pop_front() {
+        out.push('\n');
+        generator.write_struct(&mut out, &name, fields);
+    }
+
+    out
+}
+
+#[derive(Default)]
+struct Generator<'s> {
+    /// Names given to a struct so far
+    names: BTreeSet<String>,
+    /// Structs left to write
+    structs: VecDeque<(String, &'s BTreeMap<String, Field>)>,
+}
+
+impl<'s> Generator<'s> {
+    /// Name for a new struct of `fields`, `User2` if there is a `User`
+    /// already
+    fn queue(&mut self, name: &str, fields: &'s BTreeMap<String, Field>) -> String {
+        let mut unique = String::from(name);
+        let mut n = 1;
+
+        while self.names.contains(&unique) {
+            n += 1;
+            unique = format!("{name}{n}");
+        }
+
+        self.names.insert(unique.clone());
+        self.structs.push_back((unique.clone(), fields));
+        unique
+    }
+
+    /// Type of the values of `schema`, `name` being the one of their struct
+    /// if they are objects
+    fn ty(&mut self, schema: &'s Schema, name: &str) -> String {
+        let nullable = schema.null;
+        let kinds = [
+            schema.boolean,
+            schema.integer || schema.number,
+            schema.string,
+            schema.array.is_some(),
+            schema.object.is_some(),
+        ];
+
+        let ty = match kinds.iter().filter(|&&seen| seen).count() {
+            // Always `null`
+            0 if nullable => return String::from("Option<serde_json::Value>"),
+            // Never seen
+            0 => return String::from("serde_json::Value"),
+            1 => {
+                if schema.boolean {
+                    String::from("bool")
+                } else if schema.number {
+                    String::from("f64")
+                } else if schema.integer {
+                    String::from("i64")
+                } else if schema.string {
+                    String::from("String")
+                } else if let Some(elements) = &schema.array {
+                    format!("Vec<{}>", self.ty(elements, &singular(name)))
+                } else if let Some(fields) = &schema.object {
+                    self.queue(name, fields)
+                } else {
+                    unreachable!()
+                }
+            }
+            _ => return String::from("serde_json::Value"),
+        };
+
+        if nullable {
+            format!("Option<{ty}>")
+        } else {
+            ty
+        }
+    }
+
+    fn write_struct(&mut self, out: &mut String, name: &str, fields: &'s BTreeMap<String, Field>) {
+        out.push_str("#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]\n");
+
+        if fields.is_empty() {
+            let _ = writeln!(out, "pub struct {name} {{}}");
+            return;
+        }
+
+        let _ = writeln!(out, "pub struct {name} {{");
+
+        for (key, field) in fields {
+            let ident = field_ident(key);
+            let mut ty = self.ty(&field.schema, &pascal_case(key));
+
+            if ident.trim_start_matches("r#") != key {
+                let _ = writeln!(out, "    #[serde(rename = {key:?})]");
+            }
+            if field.optional {
+                if !ty.starts_with("Option<") {
+                    ty = format!("Option<{ty}>");
+                }
+                out.push_str("    #[serde(default, skip_serializing_if = \"Option::is_none\")]\n");
+            }
+
+            let _ = writeln!(out, "    pub {ident}: {ty},");
+        }
+
+        out.push_str("}\n");
+    }
+}
+
+/// Rust expression that builds `value`, with `JsonValue` and `Map` in
+/// scope, and `BigNumber` for exact numbers. Keys are sorted, for the code to
+/// be the same from one run to the next.
+///
+/// The whole value is built by a single expression: in a debug build, the
+/// frame of the function it is in grows with it, enough to overflow the
+/// stack of a test thread for a document the size of twitter.json.
+///
+/// ```
+/// let value = step_4::parse_with_path(r#"{"id": 1, "tags": ["a\"b"]}"#).unwrap();
+///
+/// assert_eq!(
+///     step_4::rust_value(&value),
+///     r#"JsonValue::Object(Map::from_iter([
+///     ("id".to_owned(), JsonValue::Int(1)),
+///     ("tags".to_owned(), JsonValue::Array(vec![
+///         JsonValue::Str("a\"b".to_owned()),
+///     ])),
+/// ]))"#
+/// );
+/// ```
+pub fn rust_value(value: &JsonValue) -> String {
+    let mut out = String::new();
+    write_value(&mut out, value, 0);
+    out
+}
+
+fn write_value(out: &mut String, value: &JsonValue, depth: usize) {
+    let indent = "    ".repeat(depth + 1);
+
+    let _ = match value {
+        JsonValue::Null => write!(out, "JsonValue::Null"),
+        JsonValue::Str(s) => write!(out, "JsonValue::Str({s:?}.to_owned())"),
+        JsonValue::Boolean(b) => write!(out, "JsonValue::Boolean({b})"),
+        JsonValue::Num(n) => write!(out, "JsonValue::Num({})", float_literal(*n)),
+        JsonValue::Int(n) => write!(out, "JsonValue::Int({n})"),
+        JsonValue::UInt(n) => write!(out, "JsonValue::UInt({n})"),
+        #[cfg(feature = "bignum")]
+        JsonValue::BigNum(n) => {
+            write!(out, "JsonValue::BigNum(BigNumber::parse(\"{n}\").unwrap())")
+        }
+        JsonValue::RawNum(n, text) => write!(
+            out,
+            "JsonValue::RawNum({}, {text:?}.to_owned())",
+            float_literal(*n)
+        ),
+        JsonValue::Wtf8(bytes) => write!(out, "JsonValue::Wtf8(vec!{bytes:?})"),
+        JsonValue::Array(values) if values.is_empty() => {
+            write!(out, "JsonValue::Array(Vec::new())")
+        }
+        JsonValue::Array(values) => {
+            out.push_str("JsonValue::Array(vec![\n");
+            for value in values {
+                out.push_str(&indent);
+                write_value(out, value, depth + 1);
+                out.push_str(",\n");
+            }
+            write!(out, "{}])", &indent[4..])
+        }
+        JsonValue::Object(map) if map.is_empty() => {
+            write!(out, "JsonValue::Object(Map::default())")
+        }
+        JsonValue::Object(map) => {
+            let mut entries: Vec<_> = map.iter().collect();
+            entries.sort_unstable_by_key(|(key, _)| *key);
+
+            out.push_str("JsonValue::Object(Map::from_iter([\n");
+            for (key, value) in entries {
+                let _ = write!(out, "{indent}({key:?}.to_owned(), ");
+                write_value(out, value, depth + 1);
+                out.push_str("),\n");
+            }
+            write!(out, "{}]))", &indent[4..])
+        }
+    };
+}
+
+/// `n` as a literal that reads back to it
+fn float_literal(n: f64) -> String {
+    match n {
+        _ if n.is_nan() => "f64::NAN".into(),
+        f64::INFINITY => "f64::INFINITY".into(),
+        f64::NEG_INFINITY => "f64::NEG_INFINITY".into(),
+        // `{:?}` always has a `.` or an exponent, so it isn't an integer
+        _ => format!("{n:?}"),
+    }
+}
+
+/// Whether the values of `schema` are always objects
+fn is_only_object(schema: &Schema) -> bool {
+    Schema {
+        object: None,
+        ..schema.clone()
+    }
+    .is_empty()
+}
+
+/// Words of `key`, as split by `_`, `-`, spaces, and lower to upper case
+/// changes
+fn words(key: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut word = String::new();
+    let mut previous_lower = false;
+
+    for c in key.chars() {
+        if !c.is_alphanumeric() {
+            words.push(core::mem::take(&mut word));
+            previous_lower = false;
+            continue;
+        }
+        if c.is_uppercase() && previous_lower {
+            words.push(core::mem::take(&mut word));
+        }
+
+        previous_lower = c.is_lowercase() || c.is_numeric();
+        word.extend(c.to_lowercase());
+    }
+    words.push(word);
+
+    words.retain(|word| !word.is_empty());
+    words
+}
+
+fn pascal_case(key: &str) -> String {
+    let mut name: String = words(key)
+        .iter()
+        .flat_map(|word| {
+            let mut chars = word.chars();
+            chars
+                .next()
+                .into_iter()
+                .flat_map(char::to_uppercase)
+                .chain(chars)
+        })
+        .collect();
+
+    if !name.starts_with(|c: char| c.is_alphabetic()) {
+        name.insert_str(0, "Type");
+    }
+    name
+}
+
+/// `snake_case` identifier of the field of `key`
+fn field_ident(key: &str) -> String {
+    let mut ident = words(key).join("_");
+
+    if !ident.starts_with(|c: char| c.is_alphabetic()) {
+        ident.insert(0, '_');
+    }
+    if ident == "_" {
+        ident.push_str("field");
+    }
+
+    if NOT_RAW.contains(&ident.as_str()) {
+        ident.push('_');
+    } else if KEYWORDS.contains(&ident.as_str()) {
+        ident.insert_str(0, "r#");
+    }
+
+    ident
+}
+
+/// Name of the elements of an array named `name`: `Status` for `Statuses`,
+/// `Category` for `Categories`, `Url` for `Urls`
+fn singular(name: &str) -> String {
+    if let Some(stem) = name.strip_suffix("ies") {
+        format!("{stem}y")
+    } else if let Some(stem) = name.strip_suffix("uses").or(name.strip_suffix("sses")) {
+        format!("{stem}{}", &name[stem.len()..stem.len() + 2])
+    } else if let Some(stem) = name.strip_suffix('s').filter(|stem| !stem.ends_with('s')) {
+        String::from(stem)
+    } else {
+        format!("{name}Element")
+    }
+}
@@ -0,0 +1,275 @@
+//! A value tree made of fewer allocations.
+//!
+//! Most strings of a document are short: ids, dates, names, keys. A
+//! [`SmallString`] keeps up to 22 bytes inline, in the 24 bytes a `String`
+//! takes anyway, so only the longer ones are allocated. An array can't keep
+//! its items inline, those being values themselves: it is a
+//! `Box<[CompactValue]>`, allocated at its exact size, an empty one
+//! allocating nothing.
+//!
+//! ```
+//! use step_4::{parse_compact, CompactValue};
+//!
+//! let (_, value) = parse_compact(r#"{"id": "a1", "tags": []}"#).unwrap();
+//!
+//! let CompactValue::Object(map) = &value else { panic!() };
+//! let CompactValue::Str(id) = &map["id"] else { panic!() };
+//! assert!(id.is_inline());
+//! assert_eq!(&**id, "a1");
+//!
+//! assert_eq!(
+//!     value.into_value(),
+//!     step_4::parse_with_path(r#"{"id": "a1", "tags": []}"#).unwrap()
+//! );
+//! ```
+
+use nom::{
+    character::complete::{anychar, char, multispace0},
+    combinator::{cut, map, peek},
+    error::{context, ContextError, ErrorKind, ParseError},
+    multi::separated_list0,
+    sequence::{delimited, preceded, terminated},
+    Parser,
+};
+use std::{
+    borrow::{Borrow, Cow},
+    fmt::{self, Debug, Display},
+    hash::{Hash, Hasher},
+    ops::Deref,
+    str,
+};
+
+use crate::{
+    lexer::{borrowed_string, null, number, parse_false, parse_true, Number},
+    JsonError, JsonValue, Map, Result,
+};
+
+/// Longest string kept inline
+const INLINE: usize = 22;
+
+#[derive(Clone)]
+enum Repr {
+    Inline { len: u8, bytes: [u8; INLINE] },
+    Heap(Box<str>),
+}
+
+/// String stored inline when it is at most 22 bytes long, and on the heap
+/// otherwise
+#[derive(Clone)]
+pub struct SmallString(Repr);
+
+impl SmallString {
+    /// Whether the string is stored without an allocation
+    pub fn is_inline(&self) -> bool {
+        matches!(self.0, Repr::Inline { .. })
+    }
+}
+
+impl Deref for SmallString {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        match &self.0 {
+            Repr::Inline { len, bytes } => {
+                // SAFETY: the bytes were copied from a `str`, see `From<&str>`
+                unsafe { str::from_utf8_unchecked(&bytes[..*len as usize]) }
+            }
+            Repr::Heap(s) => s,
+        }
+    }
+}
+
+impl From<&str> for SmallString {
+    fn from(s: &str) -> Self {
+        if s.len() > INLINE {
+            return SmallString(Repr::Heap(s.into()));
+        }
+
+        let mut bytes = [0; INLINE];
+        bytes[..s.len()].copy_from_slice(s.as_bytes());
+        SmallString(Repr::Inline {
+            len: s.len() as u8,
+            bytes,
+        })
+    }
+}
+
+/// Keeps the allocation of a long `s`
+impl From<String> for SmallString {
+    fn from(s: String) -> Self {
+        if s.len() > INLINE {
+            SmallString(Repr::Heap(s.into_boxed_str()))
+        } else {
+            SmallString::from(s.as_str())
+        }
+    }
+}
+
+impl From<Cow<'_, str>> for SmallString {
+    fn from(s: Cow<'_, str>) -> Self {
+        match s {
+            Cow::Borrowed(s) => SmallString::from(s),
+            Cow::Owned(s) => SmallString::from(s),
+        }
+    }
+}
+
+impl Borrow<str> for SmallString {
+    fn borrow(&self) -> &str {
+        self
+    }
+}
+
+/// Like a `str`, so that a map can be looked up by `&str`
+impl Hash for SmallString {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        (**self).hash(state)
+    }
+}
+
+impl PartialEq for SmallString {
+    fn eq(&self, other: &Self) -> bool {
+        **self == **other
+    }
+}
+
+impl Eq for SmallString {}
+
+impl Debug for SmallString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Debug::fmt(&**self, f)
+    }
+}
+
+impl Display for SmallString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CompactValue {
+    Null,
+    Str(SmallString),
+    Boolean(bool),
+    Num(f64),
+    Int(i64),
+    UInt(u64),
+    Array(Box<[CompactValue]>),
+    Object(Map<SmallString, CompactValue>),
+}
+
+impl CompactValue {
+    pub fn into_value(self) -> JsonValue {
+        match self {
+            CompactValue::Null => JsonValue::Null,
+            CompactValue::Str(s) => JsonValue::Str(s.to_string()),
+            CompactValue::Boolean(b) => JsonValue::Boolean(b),
+            CompactValue::Num(n) => JsonValue::Num(n),
+            CompactValue::Int(n) => JsonValue::Int(n),
+            CompactValue::UInt(n) => JsonValue::UInt(n),
+            CompactValue::Array(values) => JsonValue::Array(
+                values
+                    .into_vec()
+                    .into_iter()
+                    .map(CompactValue::into_value)
+                    .collect(),
+            ),
+            CompactValue::Object(map) => JsonValue::Object(
+                map.into_iter()
+                    .map(|(k, v)| (k.to_string(), v.into_value()))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+fn array<'a, E: ParseError<&'a str> + ContextError<&'a str>>(
+    i: &'a str,
+) -> Result<'a, Box<[CompactValue]>, E> {
+    context(
+        "array",
+        delimited(
+            cut(char('[')),
+            cut(map(
+                separated_list0(preceded(multispace0, char(',')), |i| {
+                    let (i, next_char) = peek(anychar)(i)?;
+
+                    if next_char == ']' {
+                        return Err(nom::Err::Error(E::from_error_kind(i, ErrorKind::Char)));
+                    }
+
+                    json_value(i)
+                }),
+                Vec::into_boxed_slice,
+            )),
+            preceded(multispace0, char(']')),
+        ),
+    )(i)
+}
+
+fn key_value<'a, E: ParseError<&'a str> + ContextError<&'a str>>(
+    i: &'a str,
+) -> Result<'a, (SmallString, CompactValue), E> {
+    let (i, _) = multispace0(i)?;
+
+    let (i, next_char) = peek(anychar)(i)?;
+
+    if next_char == '}' {
+        return Err(nom::Err::Error(E::from_error_kind(i, ErrorKind::Char)));
+    }
+
+    let (i, key) = borrowed_string(i)?;
+    let (i, _) = cut(preceded(multispace0, char(':')))(i)?;
+    let (i, value) = json_value(i)?;
+
+    Ok((i, (key.into(), value)))
+}
+
+fn hash<'a, E: ParseError<&'a str> + ContextError<&'a str>>(
+    i: &'a str,
+) -> Result<'a, Map<SmallString, CompactValue>, E> {
+    context(
+        "map",
+        preceded(
+            cut(char('{')),
+            cut(terminated(
+                map(
+                    separated_list0(preceded(multispace0, char(',')), key_value),
+                    |tuple_vec| tuple_vec.into_iter().collect(),
+                ),
+                preceded(multispace0, char('}')),
+            )),
+        ),
+    )
+    .parse(i)
+}
+
+fn json_value<'a, E: ParseError<&'a str> + ContextError<&'a str>>(
+    i: &'a str,
+) -> Result<'a, CompactValue, E> {
+    let (i, _) = multispace0(i)?;
+
+    let (i, first_char) = peek(anychar)(i)?;
+
+    match first_char {
+        '{' => map(hash, CompactValue::Object)(i),
+        '[' => map(array, CompactValue::Array)(i),
+        // Borrowed so that a short string is never allocated, even briefly
+        '"' => map(borrowed_string, |s| CompactValue::Str(s.into()))(i),
+        '-' | '0'..='9' => map(number, |n| match n {
+            Number::Int(n) => CompactValue::Int(n),
+            Number::UInt(n) => CompactValue::UInt(n),
+            Number::Float(n) => CompactValue::Num(n),
+        })(i),
+        'f' => map(parse_false, CompactValue::Boolean)(i),
+        't' => map(parse_true, CompactValue::Boolean)(i),
+        'n' => map(null, |_| CompactValue::Null)(i),
+        c => Err(nom::Err::Failure(E::from_error_kind(i, ErrorKind::Char))),
+    }
+}
+
+/// Parses `i` into a [`CompactValue`]
+pub fn parse_compact(i: &str) -> Result<'_, CompactValue, JsonError> {
+    terminated(json_value, multispace0).parse(i)
+}
@@ -0,0 +1,247 @@
+//! Conversions between [`JsonValue`] and Rust values.
+//!
+//! Converting out of a value of the wrong type fails with a
+//! [`JsonError::Custom`] telling what was expected and what was found.
+
+use alloc::{
+    borrow::ToOwned,
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+
+use crate::{JsonError, JsonValue, Map};
+
+impl From<&str> for JsonValue {
+    fn from(s: &str) -> Self {
+        JsonValue::Str(s.to_owned())
+    }
+}
+
+impl From<String> for JsonValue {
+    fn from(s: String) -> Self {
+        JsonValue::Str(s)
+    }
+}
+
+impl From<f64> for JsonValue {
+    fn from(n: f64) -> Self {
+        JsonValue::Num(n)
+    }
+}
+
+impl From<i64> for JsonValue {
+    fn from(n: i64) -> Self {
+        JsonValue::Int(n)
+    }
+}
+
+impl From<bool> for JsonValue {
+    fn from(b: bool) -> Self {
+        JsonValue::Boolean(b)
+    }
+}
+
+impl From<Vec<JsonValue>> for JsonValue {
+    fn from(values: Vec<JsonValue>) -> Self {
+        JsonValue::Array(values)
+    }
+}
+
+/// Any `HashMap` with the default hasher, whatever the one of [`Map`]
+///
+/// ```
+/// use std::collections::HashMap;
+/// use step_4::JsonValue;
+///
+/// let map = HashMap::from([("a".to_owned(), JsonValue::Int(1))]);
+/// assert_eq!(JsonValue::from(map), step_4::parse_with_path(r#"{"a": 1}"#).unwrap());
+/// ```
+#[cfg(feature = "std")]
+impl From<std::collections::HashMap<String, JsonValue>> for JsonValue {
+    fn from(map: std::collections::HashMap<String, JsonValue>) -> Self {
+        // Rehashed only when `Map` has another hasher
+        #[cfg(feature = "fxhash")]
+        let map = map.into_iter().collect();
+
+        JsonValue::Object(map)
+    }
+}
+
+/// Without the `fxhash` feature, [`Map`] is the `HashMap` above, or a
+/// `BTreeMap` without `std`
+#[cfg(any(feature = "fxhash", not(feature = "std")))]
+impl From<Map<String, JsonValue>> for JsonValue {
+    fn from(map: Map<String, JsonValue>) -> Self {
+        JsonValue::Object(map)
+    }
+}
+
+impl JsonValue {
+    pub(crate) fn type_name(&self) -> &'static str {
+        match self {
+            JsonValue::Null => "null",
+            JsonValue::Str(_) | JsonValue::Wtf8(_) => "a string",
+            JsonValue::Boolean(_) => "a boolean",
+            JsonValue::Num(_) | JsonValue::RawNum(..) => "a number",
+            #[cfg(feature = "bignum")]
+            JsonValue::BigNum(_) => "a number",
+            JsonValue::Int(_) | JsonValue::UInt(_) => "an integer",
+            JsonValue::Array(_) => "an array",
+            JsonValue::Object(_) => "an object",
+        }
+    }
+
+    pub(crate) fn mismatch(&self, expected: &str) -> JsonError {
+        JsonError::Custom(format!("expected {expected}, found {}", self.type_name()))
+    }
+
+    /// Elements of an array, moved out of it
+    pub fn into_array(self) -> Result<Vec<JsonValue>, JsonError> {
+        match self {
+            JsonValue::Array(values) => Ok(values),
+            value => Err(value.mismatch("an array")),
+        }
+    }
+
+    /// Members of an object, moved out of it
+    ///
+    /// ```
+    /// let value = step_4::parse_with_path(r#"{"name": "nom", "tags": ["parser"]}"#).unwrap();
+    /// let mut object = value.into_object().unwrap();
+    ///
+    /// let tags: Vec<String> = object.remove("tags").unwrap().try_into().unwrap();
+    /// assert_eq!(tags, ["parser"]);
+    ///
+    /// let error = object.remove("name").unwrap().into_object().unwrap_err();
+    /// assert_eq!(error.to_string(), "expected an object, found a string");
+    /// ```
+    pub fn into_object(self) -> Result<Map<String, JsonValue>, JsonError> {
+        match self {
+            JsonValue::Object(map) => Ok(map),
+            value => Err(value.mismatch("an object")),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+type IntoValues = std::collections::hash_map::IntoValues<String, JsonValue>;
+#[cfg(not(feature = "std"))]
+type IntoValues = alloc::collections::btree_map::IntoValues<String, JsonValue>;
+
+/// Iterator of [`JsonValue::into_iter`]
+#[derive(Debug)]
+pub enum IntoIter {
+    Array(alloc::vec::IntoIter<JsonValue>),
+    Object(IntoValues),
+    Empty,
+}
+
+impl Iterator for IntoIter {
+    type Item = JsonValue;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            IntoIter::Array(values) => values.next(),
+            IntoIter::Object(values) => values.next(),
+            IntoIter::Empty => None,
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match self {
+            IntoIter::Array(values) => values.size_hint(),
+            IntoIter::Object(values) => values.size_hint(),
+            IntoIter::Empty => (0, Some(0)),
+        }
+    }
+}
+
+/// Elements of an array, or values of an object, without copying them.
+/// Other values have none. [`JsonValue::into_object`] keeps the keys.
+///
+/// ```
+/// let value = step_4::parse_with_path("[1, 2, 3]").unwrap();
+///
+/// let numbers: Vec<f64> = value.into_iter().map(|n| n.try_into().unwrap()).collect();
+/// assert_eq!(numbers, [1.0, 2.0, 3.0]);
+/// ```
+impl IntoIterator for JsonValue {
+    type Item = JsonValue;
+    type IntoIter = IntoIter;
+
+    fn into_iter(self) -> Self::IntoIter {
+        match self {
+            JsonValue::Array(values) => IntoIter::Array(values.into_iter()),
+            JsonValue::Object(map) => IntoIter::Object(map.into_values()),
+            _ => IntoIter::Empty,
+        }
+    }
+}
+
+impl TryFrom<JsonValue> for String {
+    type Error = JsonError;
+
+    fn try_from(value: JsonValue) -> Result<Self, Self::Error> {
+        match value {
+            JsonValue::Str(s) => Ok(s),
+            value => Err(value.mismatch("a string")),
+        }
+    }
+}
+
+/// Any number, rounded to the nearest `f64` if needed
+impl TryFrom<JsonValue> for f64 {
+    type Error = JsonError;
+
+    fn try_from(value: JsonValue) -> Result<Self, Self::Error> {
+        match value {
+            JsonValue::Num(n) | JsonValue::RawNum(n, _) => Ok(n),
+            JsonValue::Int(n) => Ok(n as f64),
+            JsonValue::UInt(n) => Ok(n as f64),
+            #[cfg(feature = "bignum")]
+            JsonValue::BigNum(n) => Ok(n.to_f64()),
+            value => Err(value.mismatch("a number")),
+        }
+    }
+}
+
+/// Integers only: `1.5` and `1e3` are refused rather than truncated
+impl TryFrom<JsonValue> for i64 {
+    type Error = JsonError;
+
+    fn try_from(value: JsonValue) -> Result<Self, Self::Error> {
+        let exact = match &value {
+            JsonValue::Int(n) => Some(*n),
+            JsonValue::RawNum(_, text) => text.parse().ok(),
+            #[cfg(feature = "bignum")]
+            JsonValue::BigNum(n) => n.to_string().parse().ok(),
+            _ => None,
+        };
+
+        exact.ok_or_else(|| value.mismatch("an integer fitting in an i64"))
+    }
+}
+
+impl TryFrom<JsonValue> for bool {
+    type Error = JsonError;
+
+    fn try_from(value: JsonValue) -> Result<Self, Self::Error> {
+        match value {
+            JsonValue::Boolean(b) => Ok(b),
+            value => Err(value.mismatch("a boolean")),
+        }
+    }
+}
+
+/// Fails on the first element that can't be converted
+impl<T: TryFrom<JsonValue, Error = JsonError>> TryFrom<JsonValue> for Vec<T> {
+    type Error = JsonError;
+
+    fn try_from(value: JsonValue) -> Result<Self, Self::Error> {
+        match value {
+            JsonValue::Array(values) => values.into_iter().map(T::try_from).collect(),
+            value => Err(value.mismatch("an array")),
+        }
+    }
+}
@@ -0,0 +1,317 @@
+//! Lossless syntax tree: on top of the values, every node keeps the
+//! whitespace around it and the exact text of its strings and numbers.
+//!
+//! Printing a [`CstNode`] gives back its input byte for byte, so a tool can
+//! change one value and write the file back with everything else untouched.
+
+use alloc::{borrow::ToOwned, format, string::String, vec::Vec};
+use core::fmt::Display;
+use nom::{
+    character::complete::{anychar, char, multispace0},
+    combinator::{cut, peek, recognize},
+    error::{context, ContextError, ErrorKind, ParseError},
+    Parser,
+};
+
+use crate::{
+    lexer::{null, number, parse_false, parse_true, string, unescape},
+    JsonError, JsonValue, Result,
+};
+
+/// A value along with the whitespace before and after it
+#[derive(Debug, Clone, PartialEq)]
+pub struct CstNode {
+    /// Whitespace between the previous token and the value
+    pub leading: String,
+    pub kind: CstKind,
+    /// Whitespace between the value and the next token
+    pub trailing: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CstKind {
+    Null,
+    Boolean(bool),
+    /// Text in the source, like `1.50` or `1E3`
+    Number(String),
+    /// Text in the source, quotes and escapes included
+    Str(String),
+    Array {
+        items: Vec<CstNode>,
+        /// Whitespace between the brackets, when there are no items
+        inner: String,
+    },
+    Object {
+        members: Vec<CstMember>,
+        /// Whitespace between the braces, when there are no members
+        inner: String,
+    },
+}
+
+/// Entry of an object, in document order. The trailing whitespace of `key`
+/// is the one before the `:`
+#[derive(Debug, Clone, PartialEq)]
+pub struct CstMember {
+    /// A [`CstKind::Str`]
+    pub key: CstNode,
+    pub value: CstNode,
+}
+
+impl CstNode {
+    /// Node without any whitespace around it
+    pub fn new(kind: CstKind) -> Self {
+        Self {
+            leading: String::new(),
+            kind,
+            trailing: String::new(),
+        }
+    }
+
+    /// Drops the layout. Duplicate keys are resolved like [`crate::parse`]
+    /// does, the last one wins
+    pub fn to_value(&self) -> JsonValue {
+        match &self.kind {
+            CstKind::Null => JsonValue::Null,
+            CstKind::Boolean(b) => JsonValue::Boolean(*b),
+            CstKind::Number(text) => number::<()>(text).map_or(JsonValue::Null, |(_, n)| n.into()),
+            CstKind::Str(text) => JsonValue::Str(unescape_text(text)),
+            CstKind::Array { items, .. } => {
+                JsonValue::Array(items.iter().map(CstNode::to_value).collect())
+            }
+            CstKind::Object { members, .. } => JsonValue::Object(
+                members
+                    .iter()
+                    .map(|member| (member.key(), member.value.to_value()))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+impl CstNode {
+    /// Rewrites all the whitespace: one item per line indented by `indent`
+    /// spaces per level, or no whitespace at all when `indent` is `None`.
+    /// Key order and the text of strings and numbers are kept
+    pub fn reformat(&mut self, indent: Option<usize>) {
+        self.leading.clear();
+        self.trailing.clear();
+        self.reformat_children(indent, 0);
+    }
+
+    /// Orders the members of every object by key, each one keeping its
+    /// whitespace
+    pub fn sort_keys(&mut self) {
+        match &mut self.kind {
+            CstKind::Array { items, .. } => items.iter_mut().for_each(CstNode::sort_keys),
+            CstKind::Object { members, .. } => {
+                // The layout belongs to the positions, not to the members
+                let layout: Vec<_> = members
+                    .iter()
+                    .map(|member| (member.key.leading.clone(), member.value.trailing.clone()))
+                    .collect();
+
+                members.sort_by_cached_key(CstMember::key);
+
+                for (member, (leading, trailing)) in members.iter_mut().zip(layout) {
+                    member.key.leading = leading;
+                    member.value.trailing = trailing;
+                    member.value.sort_keys();
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn reformat_children(&mut self, indent: Option<usize>, depth: usize) {
+        let line = |depth: usize| match indent {
+            Some(width) => format!("\n{}", " ".repeat(width * depth)),
+            None => String::new(),
+        };
+        // Before the `]` or `}`
+        let closing = |index: usize, count: usize| match index + 1 == count {
+            true => line(depth),
+            false => String::new(),
+        };
+
+        match &mut self.kind {
+            CstKind::Array { items, inner } => {
+                inner.clear();
+                let count = items.len();
+
+                for (index, item) in items.iter_mut().enumerate() {
+                    item.leading = line(depth + 1);
+                    item.trailing = closing(index, count);
+                    item.reformat_children(indent, depth + 1);
+                }
+            }
+            CstKind::Object { members, inner } => {
+                inner.clear();
+                let count = members.len();
+
+                for (index, member) in members.iter_mut().enumerate() {
+                    member.key.leading = line(depth + 1);
+                    member.key.trailing.clear();
+                    member.value.leading = if indent.is_some() { " " } else { "" }.to_owned();
+                    member.value.trailing = closing(index, count);
+                    member.value.reformat_children(indent, depth + 1);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+impl CstMember {
+    /// Decoded key
+    pub fn key(&self) -> String {
+        match &self.key.kind {
+            CstKind::Str(text) => unescape_text(text),
+            _ => String::new(),
+        }
+    }
+}
+
+fn unescape_text(text: &str) -> String {
+    unescape(text).map(|s| s.into_owned()).unwrap_or_default()
+}
+
+impl Display for CstNode {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(&self.leading)?;
+
+        match &self.kind {
+            CstKind::Null => f.write_str("null")?,
+            CstKind::Boolean(b) => write!(f, "{b}")?,
+            CstKind::Number(text) | CstKind::Str(text) => f.write_str(text)?,
+            CstKind::Array { items, inner } => {
+                f.write_str("[")?;
+                if items.is_empty() {
+                    f.write_str(inner)?;
+                }
+                for (index, item) in items.iter().enumerate() {
+                    if index > 0 {
+                        f.write_str(",")?;
+                    }
+                    write!(f, "{item}")?;
+                }
+                f.write_str("]")?;
+            }
+            CstKind::Object { members, inner } => {
+                f.write_str("{")?;
+                if members.is_empty() {
+                    f.write_str(inner)?;
+                }
+                for (index, member) in members.iter().enumerate() {
+                    if index > 0 {
+                        f.write_str(",")?;
+                    }
+                    write!(f, "{}:{}", member.key, member.value)?;
+                }
+                f.write_str("}")?;
+            }
+        }
+
+        f.write_str(&self.trailing)
+    }
+}
+
+fn ws<'a, E: ParseError<&'a str>>(i: &'a str) -> Result<'a, String, E> {
+    let (i, trivia) = multispace0(i)?;
+
+    Ok((i, trivia.to_owned()))
+}
+
+/// Items separated by `,` up to `close`, every one of them surrounded by
+/// its whitespace. Returns the inner whitespace when there are no items
+fn items<'a, T, E: ParseError<&'a str>>(
+    i: &'a str,
+    close: char,
+    mut item: impl FnMut(&'a str) -> Result<'a, T, E>,
+) -> Result<'a, (Vec<T>, String), E> {
+    let (rest, inner) = ws(i)?;
+
+    if let Some(rest) = rest.strip_prefix(close) {
+        return Ok((rest, (Vec::new(), inner)));
+    }
+
+    let mut items = Vec::new();
+    let mut i = i;
+
+    loop {
+        let (rest, value) = item(i)?;
+        items.push(value);
+
+        match rest.chars().next() {
+            Some(',') => i = &rest[1..],
+            Some(c) if c == close => return Ok((&rest[1..], (items, String::new()))),
+            _ => return Err(nom::Err::Failure(E::from_error_kind(rest, ErrorKind::Char))),
+        }
+    }
+}
+
+fn member<'a, E: ParseError<&'a str> + ContextError<&'a str>>(
+    i: &'a str,
+) -> Result<'a, CstMember, E> {
+    let (i, leading) = ws(i)?;
+    let (i, key) = cut(recognize(string))(i)?;
+    let (i, trailing) = ws(i)?;
+    let (i, _) = cut(char(':'))(i)?;
+    let (i, value) = json_value(i)?;
+
+    let key = CstNode {
+        leading,
+        kind: CstKind::Str(key.to_owned()),
+        trailing,
+    };
+
+    Ok((i, CstMember { key, value }))
+}
+
+pub(crate) fn json_value<'a, E: ParseError<&'a str> + ContextError<&'a str>>(
+    i: &'a str,
+) -> Result<'a, CstNode, E> {
+    let (i, leading) = ws(i)?;
+
+    let (i, first_char) = peek(anychar)(i)?;
+
+    let (i, kind) = match first_char {
+        '{' => {
+            let (i, (members, inner)) =
+                context("map", |i: &'a str| items(&i[1..], '}', member))(i)?;
+            (i, CstKind::Object { members, inner })
+        }
+        '[' => {
+            let (i, (items, inner)) =
+                context("array", |i: &'a str| items(&i[1..], ']', json_value))(i)?;
+            (i, CstKind::Array { items, inner })
+        }
+        '"' => {
+            let (i, text) = recognize(string)(i)?;
+            (i, CstKind::Str(text.to_owned()))
+        }
+        '-' | '0'..='9' => {
+            let (i, text) = recognize(number).parse(i)?;
+            (i, CstKind::Number(text.to_owned()))
+        }
+        'f' => (parse_false(i)?.0, CstKind::Boolean(false)),
+        't' => (parse_true(i)?.0, CstKind::Boolean(true)),
+        'n' => (null(i)?.0, CstKind::Null),
+        _ => return Err(nom::Err::Failure(E::from_error_kind(i, ErrorKind::Char))),
+    };
+
+    let (i, trailing) = ws(i)?;
+
+    Ok((
+        i,
+        CstNode {
+            leading,
+            kind,
+            trailing,
+        },
+    ))
+}
+
+/// Parses `i` into a [`CstNode`] whose `Display` gives `i` back
+pub fn parse_cst(i: &str) -> Result<'_, CstNode, JsonError> {
+    json_value(i)
+}
@@ -0,0 +1,352 @@
+//! Typed extraction of values, with decoders combined like the parsers of
+//! nom, or the decoders of Elm's `Json.Decode`.
+//!
+//! A [`Decoder`] is anything that turns a [`JsonValue`] into a Rust value,
+//! such as the closures returned by the functions of this module:
+//!
+//! ```
+//! use step_4::decode::{field, list, map2, string, Decoder};
+//!
+//! #[derive(Debug, PartialEq)]
+//! struct Tweet {
+//!     text: String,
+//!     hashtags: Vec<String>,
+//! }
+//!
+//! let tweet = map2(
+//!     field("text", string()),
+//!     field("hashtags", list(field("text", string()))),
+//!     |text, hashtags| Tweet { text, hashtags },
+//! );
+//!
+//! let json = r#"{"text": "Hello", "hashtags": [{"text": "rust"}, {"text": "nom"}]}"#;
+//! assert_eq!(
+//!     tweet.decode_str(json).unwrap(),
+//!     Tweet { text: "Hello".into(), hashtags: vec!["rust".into(), "nom".into()] },
+//! );
+//!
+//! // Errors tell where the unexpected value is
+//! let json = r#"{"text": "Hello", "hashtags": [{"text": 1}]}"#;
+//! assert_eq!(
+//!     tweet.decode_str(json).unwrap_err().to_string(),
+//!     "expected a string, found an integer at $.hashtags[0].text",
+//! );
+//! ```
+
+use alloc::{
+    borrow::ToOwned,
+    boxed::Box,
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::fmt::Display;
+
+use crate::{
+    json_value, parse_with_path, path::JsonPath, JsonError, JsonValue, Map, ParseOptions, Result,
+};
+
+pub trait Decoder {
+    type Output;
+
+    fn decode(&self, value: &JsonValue) -> core::result::Result<Self::Output, JsonError>;
+
+    /// Parses `input` with [`parse_with_path`], and decodes the document
+    fn decode_str(&self, input: &str) -> core::result::Result<Self::Output, JsonError> {
+        self.decode(&parse_with_path(input)?)
+    }
+}
+
+impl<T, F> Decoder for F
+where
+    F: Fn(&JsonValue) -> core::result::Result<T, JsonError>,
+{
+    type Output = T;
+
+    fn decode(&self, value: &JsonValue) -> core::result::Result<T, JsonError> {
+        self(value)
+    }
+}
+
+impl<T> Decoder for Box<dyn Decoder<Output = T> + '_> {
+    type Output = T;
+
+    fn decode(&self, value: &JsonValue) -> core::result::Result<T, JsonError> {
+        (**self).decode(value)
+    }
+}
+
+/// Locates `error` in the child of the value at `path`
+fn within(path: JsonPath, error: JsonError) -> JsonError {
+    match error {
+        // Already located in the child: `$` is replaced by the path to it
+        JsonError::Path(inner, error) => JsonError::Path(format!("{path}{}", &inner[1..]), error),
+        error => JsonError::Path(path.to_string(), Box::new(error)),
+    }
+}
+
+fn in_key(key: &str, error: JsonError) -> JsonError {
+    let path = JsonPath::default();
+    path.push_key(key.to_owned());
+    within(path, error)
+}
+
+fn in_index(index: usize, error: JsonError) -> JsonError {
+    let path = JsonPath::default();
+    path.push_index(index);
+    within(path, error)
+}
+
+/// Nom parser of a JSON value, decoded as soon as it is parsed. A value that
+/// can't be decoded is a [`nom::Err::Failure`].
+///
+/// ```
+/// use nom::{bytes::complete::tag, sequence::preceded};
+/// use step_4::decode::{list, parser, string};
+///
+/// let (rest, tags) = preceded(tag("tags="), parser(list(string())))(r#"tags=["a", "b"];"#)
+///     .unwrap();
+/// assert_eq!((rest, tags), (";", vec!["a".to_owned(), "b".to_owned()]));
+/// ```
+pub fn parser<'a, D: Decoder>(
+    decoder: D,
+) -> impl FnMut(&'a str) -> Result<'a, D::Output, JsonError> {
+    move |i| {
+        let (rest, value) = json_value(i, &ParseOptions::default(), &JsonPath::default())?;
+        let output = decoder.decode(&value).map_err(nom::Err::Failure)?;
+
+        Ok((rest, output))
+    }
+}
+
+/// The value itself, cloned
+pub fn value() -> impl Decoder<Output = JsonValue> {
+    |value: &JsonValue| Ok(value.clone())
+}
+
+pub fn string() -> impl Decoder<Output = String> {
+    |value: &JsonValue| match value {
+        JsonValue::Str(s) => Ok(s.clone()),
+        value => Err(value.mismatch("a string")),
+    }
+}
+
+pub fn bool() -> impl Decoder<Output = bool> {
+    |value: &JsonValue| match value {
+        JsonValue::Boolean(b) => Ok(*b),
+        value => Err(value.mismatch("a boolean")),
+    }
+}
+
+/// Any number, like `f64::try_from`
+pub fn f64() -> impl Decoder<Output = f64> {
+    |value: &JsonValue| match value {
+        JsonValue::Array(_) | JsonValue::Object(_) => Err(value.mismatch("a number")),
+        value => f64::try_from(value.clone()),
+    }
+}
+
+/// Integers only, like `i64::try_from`
+pub fn i64() -> impl Decoder<Output = i64> {
+    |value: &JsonValue| match value {
+        JsonValue::Int(n) => Ok(*n),
+        JsonValue::Array(_) | JsonValue::Object(_) => {
+            Err(value.mismatch("an integer fitting in an i64"))
+        }
+        value => i64::try_from(value.clone()),
+    }
+}
+
+/// `default` for `null`
+pub fn null<T: Clone>(default: T) -> impl Decoder<Output = T> {
+    move |value: &JsonValue| match value {
+        JsonValue::Null => Ok(default.clone()),
+        value => Err(value.mismatch("null")),
+    }
+}
+
+/// `None` for `null`, the value decoded by `decoder` otherwise
+pub fn nullable<D: Decoder>(decoder: D) -> impl Decoder<Output = Option<D::Output>> {
+    move |value: &JsonValue| match value {
+        JsonValue::Null => Ok(None),
+        value => decoder.decode(value).map(Some),
+    }
+}
+
+/// Array of values that are all decoded by `decoder`
+pub fn list<D: Decoder>(decoder: D) -> impl Decoder<Output = Vec<D::Output>> {
+    move |value: &JsonValue| match value {
+        JsonValue::Array(values) => values
+            .iter()
+            .enumerate()
+            .map(|(index, value)| decoder.decode(value).map_err(|e| in_index(index, e)))
+            .collect(),
+        value => Err(value.mismatch("an array")),
+    }
+}
+
+/// Object of values that are all decoded by `decoder`
+pub fn dict<D: Decoder>(decoder: D) -> impl Decoder<Output = Map<String, D::Output>> {
+    move |value: &JsonValue| match value {
+        JsonValue::Object(map) => map
+            .iter()
+            .map(|(key, value)| match decoder.decode(value) {
+                Ok(output) => Ok((key.clone(), output)),
+                Err(e) => Err(in_key(key, e)),
+            })
+            .collect(),
+        value => Err(value.mismatch("an object")),
+    }
+}
+
+/// Element `index` of an array
+pub fn index<D: Decoder>(index: usize, decoder: D) -> impl Decoder<Output = D::Output> {
+    move |value: &JsonValue| match value {
+        JsonValue::Array(values) => match values.get(index) {
+            Some(element) => decoder.decode(element).map_err(|e| in_index(index, e)),
+            None => Err(JsonError::Custom(format!(
+                "expected an element at index {index}, found an array of {}",
+                values.len()
+            ))),
+        },
+        value => Err(value.mismatch("an array")),
+    }
+}
+
+/// Value of the key `name` of an object
+pub fn field<'n, D: Decoder + 'n>(
+    name: &'n str,
+    decoder: D,
+) -> impl Decoder<Output = D::Output> + 'n {
+    move |value: &JsonValue| match value {
+        JsonValue::Object(map) => match map.get(name) {
+            Some(member) => decoder.decode(member).map_err(|e| in_key(name, e)),
+            None => Err(JsonError::Custom(format!("missing field `{name}`"))),
+        },
+        value => Err(value.mismatch("an object")),
+    }
+}
+
+/// Like [`field`], but `None` when the key is missing or `null`
+pub fn optional_field<'n, D: Decoder + 'n>(
+    name: &'n str,
+    decoder: D,
+) -> impl Decoder<Output = Option<D::Output>> + 'n {
+    move |value: &JsonValue| match value {
+        JsonValue::Object(map) => match map.get(name) {
+            None | Some(JsonValue::Null) => Ok(None),
+            Some(member) => decoder
+                .decode(member)
+                .map(Some)
+                .map_err(|e| in_key(name, e)),
+        },
+        value => Err(value.mismatch("an object")),
+    }
+}
+
+/// Value nested under the keys of `path`: `at(&["user", "name"], string())`
+/// is `field("user", field("name", string()))`
+pub fn at<'p, D: Decoder + 'p>(
+    path: &'p [&'p str],
+    decoder: D,
+) -> impl Decoder<Output = D::Output> + 'p {
+    move |value: &JsonValue| {
+        let mut current = value;
+
+        for (depth, name) in path.iter().enumerate() {
+            let locate = |error| {
+                path[..depth]
+                    .iter()
+                    .rev()
+                    .fold(error, |e, key| in_key(key, e))
+            };
+
+            current = match current {
+                JsonValue::Object(map) => match map.get(*name) {
+                    Some(member) => member,
+                    None => {
+                        return Err(locate(JsonError::Custom(format!("missing field `{name}`"))))
+                    }
+                },
+                value => return Err(locate(value.mismatch("an object"))),
+            };
+        }
+
+        decoder
+            .decode(current)
+            .map_err(|e| path.iter().rev().fold(e, |e, key| in_key(key, e)))
+    }
+}
+
+/// Always `output`, whatever the value
+pub fn succeed<T: Clone>(output: T) -> impl Decoder<Output = T> {
+    move |_: &JsonValue| Ok(output.clone())
+}
+
+/// Always fails with `message`
+pub fn fail<T>(message: impl Display) -> impl Decoder<Output = T> {
+    let message = format!("{message}");
+    move |_: &JsonValue| Err(JsonError::Custom(message.clone()))
+}
+
+/// The output of the first of `decoders` that succeeds
+///
+/// ```
+/// use step_4::decode::{f64, one_of, string, map, Decoder};
+///
+/// // Numbers that are sometimes sent as strings
+/// let lenient = one_of(vec![
+///     Box::new(f64()),
+///     Box::new(map(string(), |s| s.parse().unwrap_or(f64::NAN))),
+/// ]);
+/// assert_eq!(lenient.decode_str(r#""1.5""#).unwrap(), 1.5);
+/// ```
+pub fn one_of<'d, T: 'd>(
+    decoders: Vec<Box<dyn Decoder<Output = T> + 'd>>,
+) -> impl Decoder<Output = T> + 'd {
+    move |value: &JsonValue| {
+        let mut errors = Vec::new();
+
+        for decoder in &decoders {
+            match decoder.decode(value) {
+                Ok(output) => return Ok(output),
+                Err(e) => errors.push(format!("{e}")),
+            }
+        }
+
+        Err(JsonError::Custom(format!(
+            "no decoder succeeded: {}",
+            errors.join("; ")
+        )))
+    }
+}
+
+pub fn map<D: Decoder, T>(decoder: D, f: impl Fn(D::Output) -> T) -> impl Decoder<Output = T> {
+    move |value: &JsonValue| decoder.decode(value).map(&f)
+}
+
+pub fn map2<A: Decoder, B: Decoder, T>(
+    a: A,
+    b: B,
+    f: impl Fn(A::Output, B::Output) -> T,
+) -> impl Decoder<Output = T> {
+    move |value: &JsonValue| Ok(f(a.decode(value)?, b.decode(value)?))
+}
+
+pub fn map3<A: Decoder, B: Decoder, C: Decoder, T>(
+    a: A,
+    b: B,
+    c: C,
+    f: impl Fn(A::Output, B::Output, C::Output) -> T,
+) -> impl Decoder<Output = T> {
+    move |value: &JsonValue| Ok(f(a.decode(value)?, b.decode(value)?, c.decode(value)?))
+}
+
+/// Decodes the value a second time, with the decoder `f` picks from the
+/// output of `decoder`: to pick a variant from a `"type"` field, say
+pub fn and_then<D: Decoder, E: Decoder>(
+    decoder: D,
+    f: impl Fn(D::Output) -> E,
+) -> impl Decoder<Output = E::Output> {
+    move |value: &JsonValue| f(decoder.decode(value)?).decode(value)
+}
@@ -0,0 +1,190 @@
+//! A value tree whose strings are decoded when read.
+//!
+//! [`parse_borrowed`](crate::parse_borrowed) still allocates every string
+//! with escapes, while most consumers only ever read a few of the strings of
+//! a document. Here the escapes are only checked while parsing, and each
+//! string is a [`LazyStr`]: a slice of the input and whether it has escapes,
+//! decoded by [`LazyStr::as_str`]. Keys are decoded right away, as objects
+//! are looked up by them.
+//!
+//! ```
+//! use step_4::{parse_deferred, DeferredValue};
+//!
+//! let (_, value) = parse_deferred(r#"["plain", "tab\tbed"]"#).unwrap();
+//!
+//! let DeferredValue::Array(values) = value else { panic!() };
+//! let [DeferredValue::Str(plain), DeferredValue::Str(escaped)] = &values[..] else { panic!() };
+//!
+//! assert!(!plain.is_escaped());
+//! assert_eq!(plain.raw(), r#""plain""#);
+//! assert_eq!(plain.as_str(), "plain");
+//!
+//! assert!(escaped.is_escaped());
+//! assert_eq!(escaped.raw(), r#""tab\tbed""#);
+//! assert_eq!(escaped.as_str(), "tab\tbed");
+//! ```
+
+use nom::{
+    character::complete::{anychar, char, multispace0},
+    combinator::{cut, map, peek},
+    error::{context, ContextError, ErrorKind, ParseError},
+    multi::separated_list0,
+    sequence::{delimited, preceded, terminated},
+    Parser,
+};
+use std::borrow::Cow;
+
+use crate::{
+    lexer::{borrowed_string, null, number, parse_false, parse_true, raw_string, unescape, Number},
+    JsonError, JsonValue, Map, Result,
+};
+
+/// String of the input, decoded on demand
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LazyStr<'a> {
+    raw: &'a str,
+    escaped: bool,
+}
+
+impl<'a> LazyStr<'a> {
+    /// Text of the string in the input, quotes and escapes included
+    pub fn raw(&self) -> &'a str {
+        self.raw
+    }
+
+    pub fn is_escaped(&self) -> bool {
+        self.escaped
+    }
+
+    /// Content of the string: a slice of the input without escapes, decoded
+    /// again on every call otherwise
+    pub fn as_str(&self) -> Cow<'a, str> {
+        if !self.escaped {
+            return Cow::Borrowed(&self.raw[1..self.raw.len() - 1]);
+        }
+
+        unescape(self.raw).expect("checked while parsing")
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum DeferredValue<'a> {
+    Null,
+    Str(LazyStr<'a>),
+    Boolean(bool),
+    Num(f64),
+    Int(i64),
+    UInt(u64),
+    Array(Vec<DeferredValue<'a>>),
+    Object(Map<Cow<'a, str>, DeferredValue<'a>>),
+}
+
+impl DeferredValue<'_> {
+    /// Converts to an owned value, decoding every string
+    pub fn into_value(self) -> JsonValue {
+        match self {
+            DeferredValue::Null => JsonValue::Null,
+            DeferredValue::Str(s) => JsonValue::Str(s.as_str().into_owned()),
+            DeferredValue::Boolean(b) => JsonValue::Boolean(b),
+            DeferredValue::Num(n) => JsonValue::Num(n),
+            DeferredValue::Int(n) => JsonValue::Int(n),
+            DeferredValue::UInt(n) => JsonValue::UInt(n),
+            DeferredValue::Array(values) => {
+                JsonValue::Array(values.into_iter().map(DeferredValue::into_value).collect())
+            }
+            DeferredValue::Object(map) => JsonValue::Object(
+                map.into_iter()
+                    .map(|(k, v)| (k.into_owned(), v.into_value()))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+fn array<'a, E: ParseError<&'a str> + ContextError<&'a str>>(
+    i: &'a str,
+) -> Result<'a, Vec<DeferredValue<'a>>, E> {
+    context(
+        "array",
+        delimited(
+            cut(char('[')),
+            cut(separated_list0(preceded(multispace0, char(',')), |i| {
+                let (i, next_char) = peek(anychar)(i)?;
+
+                if next_char == ']' {
+                    return Err(nom::Err::Error(E::from_error_kind(i, ErrorKind::Char)));
+                }
+
+                json_value(i)
+            })),
+            preceded(multispace0, char(']')),
+        ),
+    )(i)
+}
+
+fn key_value<'a, E: ParseError<&'a str> + ContextError<&'a str>>(
+    i: &'a str,
+) -> Result<'a, (Cow<'a, str>, DeferredValue<'a>), E> {
+    let (i, _) = multispace0(i)?;
+
+    let (i, next_char) = peek(anychar)(i)?;
+
+    if next_char == '}' {
+        return Err(nom::Err::Error(E::from_error_kind(i, ErrorKind::Char)));
+    }
+
+    let (i, key) = borrowed_string(i)?;
+    let (i, _) = cut(preceded(multispace0, char(':')))(i)?;
+    let (i, value) = json_value(i)?;
+
+    Ok((i, (key, value)))
+}
+
+fn hash<'a, E: ParseError<&'a str> + ContextError<&'a str>>(
+    i: &'a str,
+) -> Result<'a, Map<Cow<'a, str>, DeferredValue<'a>>, E> {
+    context(
+        "map",
+        preceded(
+            cut(char('{')),
+            cut(terminated(
+                map(
+                    separated_list0(preceded(multispace0, char(',')), key_value),
+                    |tuple_vec| tuple_vec.into_iter().collect(),
+                ),
+                preceded(multispace0, char('}')),
+            )),
+        ),
+    )
+    .parse(i)
+}
+
+fn json_value<'a, E: ParseError<&'a str> + ContextError<&'a str>>(
+    i: &'a str,
+) -> Result<'a, DeferredValue<'a>, E> {
+    let (i, _) = multispace0(i)?;
+
+    let (i, first_char) = peek(anychar)(i)?;
+
+    match first_char {
+        '{' => map(hash, DeferredValue::Object)(i),
+        '[' => map(array, DeferredValue::Array)(i),
+        '"' => map(raw_string, |(raw, escaped)| {
+            DeferredValue::Str(LazyStr { raw, escaped })
+        })(i),
+        '-' | '0'..='9' => map(number, |n| match n {
+            Number::Int(n) => DeferredValue::Int(n),
+            Number::UInt(n) => DeferredValue::UInt(n),
+            Number::Float(n) => DeferredValue::Num(n),
+        })(i),
+        'f' => map(parse_false, DeferredValue::Boolean)(i),
+        't' => map(parse_true, DeferredValue::Boolean)(i),
+        'n' => map(null, |_| DeferredValue::Null)(i),
+        c => Err(nom::Err::Failure(E::from_error_kind(i, ErrorKind::Char))),
+    }
+}
+
+/// Parses `i` without decoding the strings of its values, see [`LazyStr`]
+pub fn parse_deferred(i: &str) -> Result<'_, DeferredValue<'_>, JsonError> {
+    terminated(json_value, multispace0).parse(i)
+}
@@ -0,0 +1,153 @@
+//! Format-preserving editing of documents, for tools that update config
+//! files: only the edited values change, the rest is written back as is.
+
+use nom::{combinator::all_consuming, Finish};
+
+use crate::{
+    cst::{json_value, CstKind, CstMember, CstNode},
+    ser::{to_string, write_str},
+    JsonError, JsonValue,
+};
+use alloc::{
+    borrow::ToOwned,
+    string::{String, ToString},
+    vec::Vec,
+};
+
+impl CstNode {
+    /// Node of `value`, without any whitespace
+    pub fn from_value(value: &JsonValue) -> Self {
+        // Serialized JSON is always valid
+        let (_, node) = json_value::<()>(&to_string(value)).unwrap();
+        node
+    }
+
+    /// Value of `key` in an object, the last one if it is repeated
+    pub fn get_mut(&mut self, key: &str) -> Option<&mut CstNode> {
+        match &mut self.kind {
+            CstKind::Object { members, .. } => members
+                .iter_mut()
+                .rev()
+                .find(|member| member.key() == key)
+                .map(|member| &mut member.value),
+            _ => None,
+        }
+    }
+
+    pub fn get_index_mut(&mut self, index: usize) -> Option<&mut CstNode> {
+        match &mut self.kind {
+            CstKind::Array { items, .. } => items.get_mut(index),
+            _ => None,
+        }
+    }
+
+    /// Replaces the value, keeping the whitespace around it
+    pub fn replace(&mut self, value: JsonValue) {
+        self.kind = CstNode::from_value(&value).kind;
+    }
+
+    /// Sets `key` in an object, replacing its value if it is already there,
+    /// or adding it at the end laid out like the other members otherwise.
+    /// Does nothing on other values
+    pub fn set(&mut self, key: &str, value: JsonValue) {
+        if let Some(node) = self.get_mut(key) {
+            node.replace(value);
+            return;
+        }
+
+        if let CstKind::Object { members, inner } = &mut self.kind {
+            let mut name = String::new();
+            write_str(&mut name, key);
+
+            let mut member = CstMember {
+                key: CstNode::new(CstKind::Str(name)),
+                value: CstNode::from_value(&value),
+            };
+
+            let keys: Vec<_> = members.iter().map(|member| &member.key).collect();
+            member.key.leading = next_leading(&keys);
+
+            match members.last_mut() {
+                Some(last) => {
+                    member.key.trailing = last.key.trailing.clone();
+                    member.value.leading = last.value.leading.clone();
+                    // The whitespace before the `}` moves after the new last
+                    member.value.trailing = core::mem::take(&mut last.value.trailing);
+                }
+                None => {
+                    member.value.leading = " ".to_owned();
+                    member.value.trailing = core::mem::take(inner);
+                }
+            }
+
+            members.push(member);
+        }
+    }
+
+    /// Removes `key` from an object, along with the whitespace before it,
+    /// and returns its value. The whitespace after the `{` stays there
+    pub fn remove(&mut self, key: &str) -> Option<JsonValue> {
+        let CstKind::Object { members, .. } = &mut self.kind else {
+            return None;
+        };
+
+        let index = members.iter().rposition(|member| member.key() == key)?;
+        let removed = members.remove(index);
+
+        // The next member takes the place of the first one
+        if index == 0 {
+            if let Some(first) = members.first_mut() {
+                first.key.leading = removed.key.leading.clone();
+            }
+        }
+
+        // Keep the whitespace before the `}`
+        if index == members.len() {
+            if let Some(last) = members.last_mut() {
+                last.value.trailing = removed.value.trailing.clone();
+            }
+        }
+
+        Some(removed.value.to_value())
+    }
+
+    /// Appends `value` to an array, laid out like the other items. Does
+    /// nothing on other values
+    pub fn push(&mut self, value: JsonValue) {
+        if let CstKind::Array { items, inner } = &mut self.kind {
+            let mut item = CstNode::from_value(&value);
+
+            item.leading = next_leading(&items.iter().collect::<Vec<_>>());
+
+            // The whitespace before the `]` moves after the new last item
+            item.trailing = match items.last_mut() {
+                Some(last) => core::mem::take(&mut last.trailing),
+                None => core::mem::take(inner),
+            };
+
+            items.push(item);
+        }
+    }
+}
+
+/// Whitespace before a node appended after `nodes`
+fn next_leading(nodes: &[&CstNode]) -> String {
+    match nodes {
+        [] => String::new(),
+        // The first node follows the `[` or `{` rather than a `,`: only reuse
+        // its whitespace when nodes go on their own lines
+        [first] if !first.leading.contains('\n') => " ".to_owned(),
+        [.., last] => last.leading.clone(),
+    }
+}
+
+/// Parses `input`, lets `f` change it, and writes it back. Whitespace,
+/// number spelling, escapes and key order are kept everywhere `f` didn't
+/// touch
+pub fn edit(input: &str, f: impl FnOnce(&mut CstNode)) -> Result<String, JsonError> {
+    let (_, mut document) = all_consuming(json_value::<JsonError>)(input).finish()?;
+
+    f(&mut document);
+
+    Ok(document.to_string())
+}
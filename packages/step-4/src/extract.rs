@@ -0,0 +1,240 @@
+//! The values at a few paths of a document, read with a [`JsonReader`]:
+//! the rest is skimmed through without building anything, so reading three
+//! fields of a huge file takes no more memory than those fields.
+//!
+//! [`parse_at`] does the same for a single [JSON
+//! pointer](https://www.rfc-editor.org/rfc/rfc6901), like `/meta/version`.
+
+use alloc::{borrow::Cow, format, string::String, vec, vec::Vec};
+
+use crate::{
+    flatten::{path_segments, Segment},
+    parse, Event, JsonError, JsonReader, JsonValue,
+};
+
+/// Where the reader is in a container
+enum Frame<'a> {
+    /// Index of the current element
+    Array(Option<usize>),
+    /// Key of the current member
+    Object(Option<Cow<'a, str>>),
+}
+
+/// Whether `path` starts with the location of `stack`
+fn starts_with(path: &[Segment], stack: &[Frame]) -> bool {
+    path.len() >= stack.len()
+        && path
+            .iter()
+            .zip(stack)
+            .all(|(segment, frame)| match (segment, frame) {
+                (Segment::Key(key), Frame::Object(Some(current))) => key == current,
+                (Segment::Index(index), Frame::Array(Some(current))) => index == current,
+                _ => false,
+            })
+}
+
+/// Skips the rest of the array or object that was just started
+fn skip(reader: &mut JsonReader) -> Result<(), JsonError> {
+    let mut depth = 1;
+
+    while depth > 0 {
+        let Some(event) = reader.next() else {
+            break;
+        };
+
+        match event?.1 {
+            Event::StartObject | Event::StartArray => depth += 1,
+            Event::EndObject | Event::EndArray => depth -= 1,
+            _ => {}
+        }
+    }
+
+    Ok(())
+}
+
+/// Values at `paths`, written like for [`JsonValue::get_path`], in the same
+/// order. `None` for the paths that aren't in the document.
+///
+/// Reading stops once every path is found: the rest of the document isn't
+/// checked.
+///
+/// ```
+/// let input = r#"{"statuses": [{"id": 1, "user": {"name": "jules"}}], "search_metadata": {"count": 1}}"#;
+///
+/// let values = step_4::extract(input, &["statuses[0].user.name", "search_metadata", "missing"]).unwrap();
+///
+/// assert_eq!(values[0], Some("jules".into()));
+/// assert_eq!(values[1], Some(step_4::parse_with_path(r#"{"count": 1}"#).unwrap()));
+/// assert_eq!(values[2], None);
+/// ```
+pub fn extract(input: &str, paths: &[&str]) -> Result<Vec<Option<JsonValue>>, JsonError> {
+    let paths: Vec<Vec<Segment>> = paths.iter().map(|path| path_segments(path)).collect();
+    let mut values = vec![None; paths.len()];
+    let mut missing = paths.len();
+
+    let mut reader = JsonReader::new(input);
+    let mut stack: Vec<Frame> = Vec::new();
+
+    while missing > 0 {
+        let Some(event) = reader.next() else {
+            break;
+        };
+        let (offset, event) = event?;
+
+        match event {
+            Event::Key(key) => {
+                if let Some(Frame::Object(current)) = stack.last_mut() {
+                    *current = Some(key);
+                }
+                continue;
+            }
+            Event::EndObject | Event::EndArray => {
+                stack.pop();
+                continue;
+            }
+            _ => {}
+        }
+
+        // A value starts
+        if let Some(Frame::Array(index)) = stack.last_mut() {
+            *index = Some(index.map_or(0, |index| index + 1));
+        }
+        let is_container = matches!(event, Event::StartObject | Event::StartArray);
+
+        let mut parsed = None;
+        for (path, value) in paths.iter().zip(&mut values) {
+            if value.is_none() && path.len() == stack.len() && starts_with(path, &stack) {
+                // Parsed again from its start, to get exactly what `parse`
+                // would, integers included
+                if parsed.is_none() {
+                    parsed = Some(parse(&input[offset..])?.1);
+                }
+                *value = parsed.clone();
+                missing -= 1;
+            }
+        }
+
+        if !is_container {
+            continue;
+        }
+
+        let deeper = paths
+            .iter()
+            .any(|path| path.len() > stack.len() && starts_with(path, &stack));
+        match (deeper, event) {
+            (true, Event::StartObject) => stack.push(Frame::Object(None)),
+            (true, _) => stack.push(Frame::Array(None)),
+            (false, _) => skip(&mut reader)?,
+        }
+    }
+
+    Ok(values)
+}
+
+fn next_event<'a>(reader: &mut JsonReader<'a>) -> Result<(usize, Event<'a>), JsonError> {
+    reader.next().unwrap_or(Err(JsonError::NeedMoreData))
+}
+
+/// Skips the value that starts with `event`
+fn skip_value(reader: &mut JsonReader, event: &Event) -> Result<(), JsonError> {
+    match event {
+        Event::StartObject | Event::StartArray => skip(reader),
+        _ => Ok(()),
+    }
+}
+
+/// Reference tokens of `pointer`, `~1` and `~0` standing for `/` and `~`
+fn pointer_tokens(pointer: &str) -> Result<Vec<String>, JsonError> {
+    if pointer.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let Some(tokens) = pointer.strip_prefix('/') else {
+        return Err(JsonError::Custom(format!(
+            "JSON pointer `{pointer}` doesn't start with `/`"
+        )));
+    };
+
+    Ok(tokens
+        .split('/')
+        .map(|token| token.replace("~1", "/").replace("~0", "~"))
+        .collect())
+}
+
+/// Index of an array a pointer token stands for: digits, without leading
+/// zeros
+fn array_index(token: &str) -> Option<usize> {
+    let digits = !token.is_empty() && token.bytes().all(|b| b.is_ascii_digit());
+
+    if !digits || (token.len() > 1 && token.starts_with('0')) {
+        return None;
+    }
+
+    token.parse().ok()
+}
+
+/// Value at the JSON pointer `pointer`, `None` if it isn't in the document.
+///
+/// Only the value is parsed: the containers on the way to it are scanned
+/// for its start, and checked for errors up to it. Whatever comes after it
+/// isn't read at all, like with [`extract`].
+///
+/// ```
+/// let input = r#"{"data": [0, 1, 2], "meta": {"a/b": true, "version": "1.2"}}"#;
+///
+/// assert_eq!(step_4::parse_at(input, "/meta/version").unwrap(), Some("1.2".into()));
+/// assert_eq!(step_4::parse_at(input, "/meta/a~1b").unwrap(), Some(true.into()));
+/// assert_eq!(step_4::parse_at(input, "/data/1").unwrap(), Some(1.into()));
+/// assert_eq!(step_4::parse_at(input, "/data/3").unwrap(), None);
+///
+/// // Broken before the value
+/// assert!(step_4::parse_at(r#"{"data": [0 1], "meta": {}}"#, "/meta").is_err());
+/// ```
+pub fn parse_at(input: &str, pointer: &str) -> Result<Option<JsonValue>, JsonError> {
+    let tokens = pointer_tokens(pointer)?;
+
+    let mut reader = JsonReader::new(input);
+    let (mut offset, mut event) = next_event(&mut reader)?;
+
+    for token in &tokens {
+        let found = match event {
+            Event::StartObject => loop {
+                match next_event(&mut reader)?.1 {
+                    Event::Key(key) if key == token.as_str() => {
+                        break Some(next_event(&mut reader)?);
+                    }
+                    Event::Key(_) => {
+                        let (_, value) = next_event(&mut reader)?;
+                        skip_value(&mut reader, &value)?;
+                    }
+                    _ => break None,
+                }
+            },
+            Event::StartArray => {
+                let target = array_index(token);
+                let mut index = 0;
+
+                loop {
+                    let (offset, value) = next_event(&mut reader)?;
+                    if value == Event::EndArray {
+                        break None;
+                    }
+                    if Some(index) == target {
+                        break Some((offset, value));
+                    }
+                    skip_value(&mut reader, &value)?;
+                    index += 1;
+                }
+            }
+            // A scalar has nothing inside
+            _ => None,
+        };
+
+        match found {
+            Some(found) => (offset, event) = found,
+            None => return Ok(None),
+        }
+    }
+
+    Ok(Some(parse(&input[offset..])?.1))
+}
@@ -0,0 +1,328 @@
+//! Conversion between nested values and flat objects of paths, such as
+//! `{"a.b[0].c": 1}`, for key/value stores and spreadsheets.
+//!
+//! Keys that contain the separator, or end with something like `[0]`, read
+//! as paths: they come back nested from a round trip.
+//!
+//! The same paths look a value up with [`JsonValue::get_path`].
+
+use alloc::{
+    borrow::ToOwned,
+    collections::BTreeMap,
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+
+use crate::{
+    options::{FlattenOptions, IndexStyle},
+    JsonError, JsonValue, Map,
+};
+
+impl JsonValue {
+    /// [`flatten_with`](Self::flatten_with) the default options
+    pub fn flatten(&self) -> JsonValue {
+        self.flatten_with(&FlattenOptions::default())
+    }
+
+    /// Object of the scalars, empty arrays and empty objects of the value,
+    /// by their path. A scalar is at the empty path.
+    ///
+    /// ```
+    /// use step_4::{FlattenOptions, IndexStyle};
+    ///
+    /// let value = step_4::parse_with_path(r#"{"a": {"b": [{"c": 1}, []]}}"#).unwrap();
+    ///
+    /// let flat = value.flatten();
+    /// assert_eq!(flat, step_4::parse_with_path(r#"{"a.b[0].c": 1, "a.b[1]": []}"#).unwrap());
+    /// assert_eq!(flat.unflatten().unwrap(), value);
+    ///
+    /// let opts = FlattenOptions {
+    ///     separator: "/".into(),
+    ///     index_style: IndexStyle::Separator,
+    /// };
+    /// let flat = value.flatten_with(&opts);
+    /// assert_eq!(flat, step_4::parse_with_path(r#"{"a/b/0/c": 1, "a/b/1": []}"#).unwrap());
+    /// assert_eq!(flat.unflatten_with(&opts).unwrap(), value);
+    /// ```
+    pub fn flatten_with(&self, opts: &FlattenOptions) -> JsonValue {
+        let mut flat = Map::default();
+        flatten(self, &mut String::new(), true, opts, &mut flat);
+
+        JsonValue::Object(flat)
+    }
+
+    /// [`unflatten_with`](Self::unflatten_with) the default options
+    pub fn unflatten(&self) -> Result<JsonValue, JsonError> {
+        self.unflatten_with(&FlattenOptions::default())
+    }
+
+    /// Inverse of [`flatten_with`](Self::flatten_with), for an object.
+    ///
+    /// Paths must not conflict, like `a` and `a.b` do, and the indexes of an
+    /// array must leave no gap.
+    ///
+    /// ```
+    /// let flat = step_4::parse_with_path(r#"{"a": 1, "a.b": 2}"#).unwrap();
+    /// let error = flat.unflatten().unwrap_err().to_string();
+    /// assert!(error.ends_with("conflicts with another path"), "{error}");
+    ///
+    /// let flat = step_4::parse_with_path(r#"{"a[0]": 1, "a[2]": 3}"#).unwrap();
+    /// let error = flat.unflatten().unwrap_err().to_string();
+    /// assert_eq!(error, "missing the element 1 of an array");
+    /// ```
+    pub fn unflatten_with(&self, opts: &FlattenOptions) -> Result<JsonValue, JsonError> {
+        let JsonValue::Object(flat) = self else {
+            return Err(self.mismatch("an object"));
+        };
+
+        // An array when the first path starts with an index
+        let mut tree = match flat.keys().next() {
+            Some(path) => Tree::new(&segments(path, opts)),
+            None => Tree::Object(Map::default()),
+        };
+
+        for (path, value) in flat {
+            let conflict = || JsonError::Custom(format!("`{path}` conflicts with another path"));
+
+            if path.is_empty() {
+                return match flat.len() {
+                    1 => Ok(value.clone()),
+                    _ => Err(conflict()),
+                };
+            }
+
+            tree.insert(&segments(path, opts), value.clone())
+                .ok_or_else(conflict)?;
+        }
+
+        tree.into_value(opts.index_style)
+    }
+
+    /// Value at `path`, written like the keys of [`flatten`](Self::flatten):
+    /// `a.b[2].c` is `c` in the third element of `b` in `a`. The empty path
+    /// is the value itself.
+    ///
+    /// ```
+    /// let mut value = step_4::parse_with_path(r#"{"a": {"b": [0, 1, {"c": true}]}}"#).unwrap();
+    ///
+    /// assert_eq!(value.get_path("a.b[2].c"), Some(&true.into()));
+    /// assert_eq!(value.get_path("a.b[3]"), None);
+    /// assert_eq!(value.get_path("a[0]"), None);
+    ///
+    /// *value.get_path_mut("a.b[0]").unwrap() = "zero".into();
+    /// assert_eq!(value.get_path("a.b[0]"), Some(&"zero".into()));
+    /// ```
+    pub fn get_path(&self, path: &str) -> Option<&JsonValue> {
+        let mut value = self;
+
+        for segment in path_segments(path) {
+            value = match (segment, value) {
+                (Segment::Key(key), JsonValue::Object(map)) => map.get(key)?,
+                (Segment::Index(index), JsonValue::Array(values)) => values.get(index)?,
+                _ => return None,
+            };
+        }
+
+        Some(value)
+    }
+
+    /// Like [`get_path`](Self::get_path), to change the value
+    pub fn get_path_mut(&mut self, path: &str) -> Option<&mut JsonValue> {
+        let mut value = self;
+
+        for segment in path_segments(path) {
+            value = match (segment, value) {
+                (Segment::Key(key), JsonValue::Object(map)) => map.get_mut(key)?,
+                (Segment::Index(index), JsonValue::Array(values)) => values.get_mut(index)?,
+                _ => return None,
+            };
+        }
+
+        Some(value)
+    }
+}
+
+/// Segments of a path with the default options, none for the empty path
+pub(crate) fn path_segments(path: &str) -> Vec<Segment<'_>> {
+    match path {
+        "" => Vec::new(),
+        path => segments(path, &FlattenOptions::default()),
+    }
+}
+
+fn flatten(
+    value: &JsonValue,
+    path: &mut String,
+    root: bool,
+    opts: &FlattenOptions,
+    flat: &mut Map<String, JsonValue>,
+) {
+    let len = path.len();
+
+    match value {
+        JsonValue::Array(values) if !values.is_empty() => {
+            for (index, value) in values.iter().enumerate() {
+                match opts.index_style {
+                    IndexStyle::Brackets => path.push_str(&format!("[{index}]")),
+                    IndexStyle::Separator => {
+                        if !root {
+                            path.push_str(&opts.separator);
+                        }
+                        path.push_str(&index.to_string());
+                    }
+                }
+                flatten(value, path, false, opts, flat);
+                path.truncate(len);
+            }
+        }
+        JsonValue::Object(map) if !map.is_empty() => {
+            for (key, value) in map {
+                if !root {
+                    path.push_str(&opts.separator);
+                }
+                path.push_str(key);
+                flatten(value, path, false, opts, flat);
+                path.truncate(len);
+            }
+        }
+        value => {
+            flat.insert(path.clone(), value.clone());
+        }
+    }
+}
+
+pub(crate) enum Segment<'p> {
+    Key(&'p str),
+    Index(usize),
+}
+
+/// `a.b[0][1]` is `a`, `b`, `0` and `1`
+fn segments<'p>(path: &'p str, opts: &FlattenOptions) -> Vec<Segment<'p>> {
+    let parts: Vec<&str> = if opts.separator.is_empty() {
+        Vec::from([path])
+    } else {
+        path.split(opts.separator.as_str()).collect()
+    };
+
+    let mut segments = Vec::new();
+    for (position, part) in parts.into_iter().enumerate() {
+        match opts.index_style {
+            IndexStyle::Brackets => {
+                let (key, indexes) = split_indexes(part);
+                // `[0].a`: the root is an array
+                if position > 0 || !key.is_empty() || indexes.is_empty() {
+                    segments.push(Segment::Key(key));
+                }
+                segments.extend(indexes.into_iter().map(Segment::Index));
+            }
+            IndexStyle::Separator => segments.push(Segment::Key(part)),
+        }
+    }
+
+    segments
+}
+
+/// `b[0][1]` is `b` with the indexes `0` and `1`
+fn split_indexes(mut part: &str) -> (&str, Vec<usize>) {
+    let mut indexes = Vec::new();
+
+    while let Some(rest) = part.strip_suffix(']') {
+        let Some((rest, index)) = rest.rsplit_once('[') else {
+            break;
+        };
+        if index.is_empty() || !index.bytes().all(|b| b.is_ascii_digit()) {
+            break;
+        }
+        let Ok(index) = index.parse() else {
+            break;
+        };
+
+        indexes.push(index);
+        part = rest;
+    }
+    indexes.reverse();
+
+    (part, indexes)
+}
+
+/// Value being unflattened
+enum Tree {
+    Leaf(JsonValue),
+    Object(Map<String, Tree>),
+    Array(BTreeMap<usize, Tree>),
+}
+
+impl Tree {
+    /// `None` when `segments` goes through a leaf, or ends where there's
+    /// already something
+    fn insert(&mut self, segments: &[Segment], value: JsonValue) -> Option<()> {
+        let (segment, rest) = segments.split_first()?;
+
+        let child = match (self, segment) {
+            (Tree::Object(map), Segment::Key(key)) => map
+                .entry((*key).to_owned())
+                .or_insert_with(|| Tree::new(rest)),
+            (Tree::Array(map), Segment::Index(index)) => {
+                map.entry(*index).or_insert_with(|| Tree::new(rest))
+            }
+            _ => return None,
+        };
+
+        match rest {
+            [] if matches!(child, Tree::Object(map) if map.is_empty()) => {
+                *child = Tree::Leaf(value);
+                Some(())
+            }
+            [] => None,
+            rest => child.insert(rest, value),
+        }
+    }
+
+    /// Container for the first of `segments`, empty object for a leaf
+    fn new(segments: &[Segment]) -> Tree {
+        match segments.first() {
+            Some(Segment::Index(_)) => Tree::Array(BTreeMap::new()),
+            _ => Tree::Object(Map::default()),
+        }
+    }
+
+    fn into_value(self, index_style: IndexStyle) -> Result<JsonValue, JsonError> {
+        match self {
+            Tree::Leaf(value) => Ok(value),
+            Tree::Object(map) => {
+                let mut map = map
+                    .into_iter()
+                    .map(|(key, tree)| Ok((key, tree.into_value(index_style)?)))
+                    .collect::<Result<Map<_, _>, JsonError>>()?;
+
+                if index_style == IndexStyle::Separator && !map.is_empty() {
+                    let len = map.len();
+                    if (0..len).all(|index| map.contains_key(index.to_string().as_str())) {
+                        let values = (0..len)
+                            .map(|index| map.remove(index.to_string().as_str()).unwrap())
+                            .collect();
+                        return Ok(JsonValue::Array(values));
+                    }
+                }
+
+                Ok(JsonValue::Object(map))
+            }
+            Tree::Array(map) => {
+                let mut values = Vec::with_capacity(map.len());
+
+                for (index, tree) in map {
+                    if index != values.len() {
+                        return Err(JsonError::Custom(format!(
+                            "missing the element {} of an array",
+                            values.len()
+                        )));
+                    }
+                    values.push(tree.into_value(index_style)?);
+                }
+
+                Ok(JsonValue::Array(values))
+            }
+        }
+    }
+}
@@ -0,0 +1,235 @@
+//! Conversion to and from [gron](https://github.com/tomnomnom/gron): one
+//! assignment per line, with the whole path to the value, so that `grep`
+//! finds things in a huge document and tells where they are.
+//!
+//! ```text
+//! json = {};
+//! json.statuses = [];
+//! json.statuses[0] = {};
+//! json.statuses[0].user = {};
+//! json.statuses[0].user["screen name"] = "jules";
+//! ```
+//!
+//! The lines that are left after a `grep`, or edited, make a document again
+//! with [`from_gron`].
+
+use alloc::{
+    borrow::ToOwned,
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::fmt::Write;
+use nom::{
+    branch::alt,
+    bytes::complete::{tag, take_while},
+    character::complete::{char, digit1, multispace0, satisfy, space0},
+    combinator::{cut, map, map_res, recognize},
+    error::{ErrorKind, FromExternalError},
+    multi::many0,
+    sequence::{delimited, pair, preceded},
+    Finish,
+};
+
+use crate::{
+    json_value, lexer::string, path::JsonPath, ser::write_str, to_string, JsonError, JsonValue,
+    Map, ParseOptions, Result,
+};
+
+impl JsonValue {
+    /// gron lines of the value, keys being in lexicographic order. Arrays and
+    /// objects have a line of their own, as `[]` and `{}`, so that empty ones
+    /// aren't lost.
+    ///
+    /// ```
+    /// let value = step_4::parse_with_path(r#"{"b": [true], "a b": {}}"#).unwrap();
+    ///
+    /// assert_eq!(value.to_gron(), r#"json = {};
+    /// json["a b"] = {};
+    /// json.b = [];
+    /// json.b[0] = true;
+    /// "#);
+    /// ```
+    pub fn to_gron(&self) -> String {
+        let mut out = String::new();
+        gron(self, &mut String::from("json"), &mut out);
+        out
+    }
+}
+
+fn gron(value: &JsonValue, path: &mut String, out: &mut String) {
+    let len = path.len();
+
+    match value {
+        JsonValue::Array(values) => {
+            let _ = writeln!(out, "{path} = [];");
+            for (index, value) in values.iter().enumerate() {
+                let _ = write!(path, "[{index}]");
+                gron(value, path, out);
+                path.truncate(len);
+            }
+        }
+        JsonValue::Object(map) => {
+            let _ = writeln!(out, "{path} = {{}};");
+
+            let mut entries: Vec<_> = map.iter().collect();
+            entries.sort_unstable_by_key(|(key, _)| *key);
+
+            for (key, value) in entries {
+                if is_identifier(key) {
+                    path.push('.');
+                    path.push_str(key);
+                } else {
+                    path.push('[');
+                    write_str(path, key);
+                    path.push(']');
+                }
+                gron(value, path, out);
+                path.truncate(len);
+            }
+        }
+        value => {
+            let _ = writeln!(out, "{path} = {};", to_string(value));
+        }
+    }
+}
+
+/// Whether `key` can follow a `.`, as in JavaScript
+fn is_identifier(key: &str) -> bool {
+    let mut chars = key.chars();
+
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_' || c == '$')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$')
+}
+
+enum Step {
+    Key(String),
+    Index(usize),
+}
+
+fn step(i: &str) -> Result<'_, Step, JsonError> {
+    alt((
+        map(
+            preceded(
+                char('.'),
+                cut(recognize(pair(
+                    satisfy(|c| c.is_ascii_alphabetic() || c == '_' || c == '$'),
+                    take_while(|c: char| c.is_ascii_alphanumeric() || c == '_' || c == '$'),
+                ))),
+            ),
+            |key: &str| Step::Key(key.to_owned()),
+        ),
+        delimited(
+            char('['),
+            cut(alt((
+                map(map_res(digit1, str::parse), Step::Index),
+                // Last, as it fails for good without a `"`
+                map(string, Step::Key),
+            ))),
+            cut(char(']')),
+        ),
+    ))(i)
+}
+
+fn failure(i: &str, message: String) -> nom::Err<JsonError> {
+    nom::Err::Failure(JsonError::from_external_error(
+        i,
+        ErrorKind::Verify,
+        message,
+    ))
+}
+
+/// Sets the value at `steps` of `root`, making the arrays and objects on
+/// the way. Assigning `[]` or `{}` to an array or an object leaves it as it
+/// is, for the lines of its elements not to depend on the order.
+fn assign(
+    root: &mut JsonValue,
+    steps: &[Step],
+    value: JsonValue,
+) -> core::result::Result<(), String> {
+    let mut target = root;
+
+    for step in steps {
+        if let JsonValue::Null = target {
+            *target = match step {
+                Step::Key(_) => JsonValue::Object(Map::default()),
+                Step::Index(_) => JsonValue::Array(Vec::new()),
+            };
+        }
+
+        target = match (step, target) {
+            (Step::Key(key), JsonValue::Object(map)) => {
+                map.entry(key.clone()).or_insert(JsonValue::Null)
+            }
+            (Step::Index(index), JsonValue::Array(values)) => {
+                if *index == values.len() {
+                    values.push(JsonValue::Null);
+                }
+                let len = values.len();
+                values
+                    .get_mut(*index)
+                    .ok_or_else(|| format!("missing the element {len} of an array"))?
+            }
+            (_, target) => return Err(format!("this goes through {}", target.type_name())),
+        };
+    }
+
+    match (&*target, &value) {
+        (JsonValue::Array(_), JsonValue::Array(values)) if values.is_empty() => {}
+        (JsonValue::Object(_), JsonValue::Object(map)) if map.is_empty() => {}
+        _ => *target = value,
+    }
+
+    Ok(())
+}
+
+/// Inverse of [`JsonValue::to_gron`]: the document the assignments of
+/// `input` make, in any order but with the elements of an array from the
+/// first one. Blank lines are skipped.
+///
+/// ```
+/// let value = step_4::from_gron(r#"
+/// json.talks[0].title = "nom";
+/// json.talks[1] = {"title": "serde"};
+/// json.meetup = "Tokyo Rust";
+/// "#).unwrap();
+///
+/// assert_eq!(
+///     value,
+///     step_4::parse_with_path(r#"{
+///         "meetup": "Tokyo Rust",
+///         "talks": [{"title": "nom"}, {"title": "serde"}]
+///     }"#).unwrap()
+/// );
+///
+/// let error = step_4::from_gron("json.a = 1;\njson.a.b = 2;").unwrap_err();
+/// assert_eq!(error.to_string(), "this goes through an integer");
+/// ```
+pub fn from_gron(input: &str) -> core::result::Result<JsonValue, JsonError> {
+    statements(input).finish().map(|(_, root)| root)
+}
+
+fn statements(input: &str) -> Result<'_, JsonValue, JsonError> {
+    let mut root = JsonValue::Null;
+    let (mut i, _) = multispace0(input)?;
+
+    while !i.is_empty() {
+        let start = i;
+
+        let (rest, _) = tag("json")(i)?;
+        let (rest, steps) = many0(step)(rest)?;
+        let (rest, _) = cut(delimited(space0, char('='), space0))(rest)?;
+        let (rest, value) =
+            json_value::<JsonError>(rest, &ParseOptions::default(), &JsonPath::default())?;
+        let (rest, _) = preceded(space0, char(';'))(rest)
+            .map_err(|_: nom::Err<JsonError>| failure(rest, "expected `;`".to_owned()))?;
+
+        assign(&mut root, &steps, value).map_err(|message| failure(start, message))?;
+
+        (i, _) = multispace0(rest)?;
+    }
+
+    Ok((i, root))
+}
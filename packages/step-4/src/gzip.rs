@@ -0,0 +1,473 @@
+//! Decompression of gzip streams, so that `.json.gz` dumps can be parsed
+//! without unpacking them first.
+//!
+//! This is a plain DEFLATE decoder (RFC 1951) behind the gzip framing
+//! (RFC 1952), decoding one block at a time: memory usage is bounded by the
+//! 32 KiB window plus the size of a block, not by the size of the data.
+
+use std::io::{self, Read};
+
+/// Start of every gzip stream
+const MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+/// How far back a match can reach
+const WINDOW: usize = 32 * 1024;
+
+const LENGTH_BASE: [u16; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131,
+    163, 195, 227, 258,
+];
+const LENGTH_EXTRA: [u8; 29] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0,
+];
+const DISTANCE_BASE: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537,
+    2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577,
+];
+const DISTANCE_EXTRA: [u8; 30] = [
+    0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13,
+    13,
+];
+
+/// Order in which the code lengths of the code length alphabet are sent
+const CODE_LENGTH_ORDER: [usize; 19] = [
+    16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15,
+];
+
+const CRC_TABLE: [u32; 256] = {
+    let mut table = [0; 256];
+    let mut n = 0;
+
+    while n < 256 {
+        let mut crc = n as u32;
+        let mut k = 0;
+        while k < 8 {
+            crc = if crc & 1 == 1 {
+                0xedb8_8320 ^ (crc >> 1)
+            } else {
+                crc >> 1
+            };
+            k += 1;
+        }
+        table[n] = crc;
+        n += 1;
+    }
+
+    table
+};
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, format!("gzip: {message}"))
+}
+
+/// Whether `start`, the first bytes of an input, is the start of a gzip
+/// stream. JSON text never starts with these bytes
+pub fn is_gzip(start: &[u8]) -> bool {
+    start.starts_with(&MAGIC)
+}
+
+/// Reads the bits of the input, least significant first
+struct Bits<R> {
+    inner: R,
+    buffer: Box<[u8]>,
+    pos: usize,
+    len: usize,
+    bits: u64,
+    count: u32,
+}
+
+impl<R: Read> Bits<R> {
+    fn new(inner: R) -> Self {
+        Self {
+            inner,
+            buffer: vec![0; 8 * 1024].into_boxed_slice(),
+            pos: 0,
+            len: 0,
+            bits: 0,
+            count: 0,
+        }
+    }
+
+    /// Next whole byte of the input, `None` at its end
+    fn next_byte(&mut self) -> io::Result<Option<u8>> {
+        if self.pos == self.len {
+            self.len = loop {
+                match self.inner.read(&mut self.buffer) {
+                    Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                    read => break read?,
+                }
+            };
+            self.pos = 0;
+
+            if self.len == 0 {
+                return Ok(None);
+            }
+        }
+
+        self.pos += 1;
+        Ok(Some(self.buffer[self.pos - 1]))
+    }
+
+    fn bits(&mut self, n: u32) -> io::Result<u32> {
+        while self.count < n {
+            let byte = self
+                .next_byte()?
+                .ok_or_else(|| io::Error::from(io::ErrorKind::UnexpectedEof))?;
+            self.bits |= u64::from(byte) << self.count;
+            self.count += 8;
+        }
+
+        let value = (self.bits & ((1 << n) - 1)) as u32;
+        self.bits >>= n;
+        self.count -= n;
+
+        Ok(value)
+    }
+
+    /// Drops the bits left in the current byte
+    fn align(&mut self) {
+        let extra = self.count % 8;
+        self.bits >>= extra;
+        self.count -= extra;
+    }
+
+    fn u16_le(&mut self) -> io::Result<u16> {
+        Ok(self.bits(16)? as u16)
+    }
+
+    fn u32_le(&mut self) -> io::Result<u32> {
+        Ok(self.bits(16)? | (self.bits(16)? << 16))
+    }
+
+    /// Whether the input has more data, once aligned on a byte
+    fn at_end(&mut self) -> io::Result<bool> {
+        if self.count > 0 {
+            return Ok(false);
+        }
+
+        match self.next_byte()? {
+            Some(byte) => {
+                self.bits = u64::from(byte);
+                self.count = 8;
+                Ok(false)
+            }
+            None => Ok(true),
+        }
+    }
+}
+
+/// Canonical Huffman code, decoded one bit at a time
+struct Huffman {
+    /// Number of codes of each length
+    counts: [u16; 16],
+    /// Symbols ordered by code
+    symbols: Vec<u16>,
+}
+
+impl Huffman {
+    fn new(lengths: &[u8]) -> io::Result<Self> {
+        let mut counts = [0u16; 16];
+        for &length in lengths {
+            counts[length as usize] += 1;
+        }
+        counts[0] = 0;
+
+        // Over-subscribed codes can't be decoded
+        let mut left = 1i32;
+        for &count in &counts[1..] {
+            left = (left << 1) - i32::from(count);
+            if left < 0 {
+                return Err(invalid("invalid Huffman code"));
+            }
+        }
+
+        let mut offsets = [0u16; 16];
+        for length in 1..15 {
+            offsets[length + 1] = offsets[length] + counts[length];
+        }
+
+        let mut symbols = vec![0; lengths.len()];
+        for (symbol, &length) in lengths.iter().enumerate() {
+            if length != 0 {
+                symbols[offsets[length as usize] as usize] = symbol as u16;
+                offsets[length as usize] += 1;
+            }
+        }
+
+        Ok(Self { counts, symbols })
+    }
+
+    fn fixed() -> (Self, Self) {
+        let mut lengths = [0u8; 288];
+        lengths[..144].fill(8);
+        lengths[144..256].fill(9);
+        lengths[256..280].fill(7);
+        lengths[280..].fill(8);
+
+        (Self::new(&lengths).unwrap(), Self::new(&[5; 30]).unwrap())
+    }
+
+    fn decode<R: Read>(&self, bits: &mut Bits<R>) -> io::Result<u16> {
+        // First code of the current length, and index of its symbol
+        let (mut code, mut first, mut index) = (0i32, 0i32, 0i32);
+
+        for &count in &self.counts[1..] {
+            code |= bits.bits(1)? as i32;
+            let count = i32::from(count);
+
+            if code - first < count {
+                return Ok(self.symbols[(index + code - first) as usize]);
+            }
+
+            index += count;
+            first = (first + count) << 1;
+            code <<= 1;
+        }
+
+        Err(invalid("invalid code"))
+    }
+}
+
+/// [`Read`] adapter decompressing the gzip stream read from `inner`.
+/// Concatenated streams, as written by `cat a.gz b.gz`, are read as one
+pub struct GzDecoder<R> {
+    bits: Bits<R>,
+    /// The last `WINDOW` bytes already read, followed by the block being read
+    history: Vec<u8>,
+    /// Start of the bytes of `history` not read yet
+    pos: usize,
+    crc: u32,
+    size: u32,
+    in_member: bool,
+    last_block: bool,
+    done: bool,
+}
+
+impl<R: Read> GzDecoder<R> {
+    pub fn new(inner: R) -> Self {
+        Self {
+            bits: Bits::new(inner),
+            history: Vec::new(),
+            pos: 0,
+            crc: !0,
+            size: 0,
+            in_member: false,
+            last_block: false,
+            done: false,
+        }
+    }
+
+    fn skip_zero_terminated(&mut self) -> io::Result<()> {
+        while self.bits.bits(8)? != 0 {}
+        Ok(())
+    }
+
+    fn header(&mut self) -> io::Result<()> {
+        let magic = [self.bits.bits(8)? as u8, self.bits.bits(8)? as u8];
+        if magic != MAGIC {
+            return Err(invalid("not a gzip stream"));
+        }
+
+        if self.bits.bits(8)? != 8 {
+            return Err(invalid("unknown compression method"));
+        }
+
+        let flags = self.bits.bits(8)?;
+        // Modification time, extra flags and OS
+        for _ in 0..6 {
+            self.bits.bits(8)?;
+        }
+
+        if flags & 0x04 != 0 {
+            for _ in 0..self.bits.u16_le()? {
+                self.bits.bits(8)?;
+            }
+        }
+        if flags & 0x08 != 0 {
+            self.skip_zero_terminated()?;
+        }
+        if flags & 0x10 != 0 {
+            self.skip_zero_terminated()?;
+        }
+        if flags & 0x02 != 0 {
+            self.bits.u16_le()?;
+        }
+
+        self.in_member = true;
+        self.last_block = false;
+        self.crc = !0;
+        self.size = 0;
+
+        Ok(())
+    }
+
+    fn trailer(&mut self) -> io::Result<()> {
+        self.bits.align();
+
+        if self.bits.u32_le()? != !self.crc {
+            return Err(invalid("CRC mismatch"));
+        }
+        if self.bits.u32_le()? != self.size {
+            return Err(invalid("size mismatch"));
+        }
+
+        self.in_member = false;
+        self.done = self.bits.at_end()?;
+
+        Ok(())
+    }
+
+    fn stored(&mut self) -> io::Result<()> {
+        self.bits.align();
+
+        let len = self.bits.u16_le()?;
+        if self.bits.u16_le()? != !len {
+            return Err(invalid("invalid stored block length"));
+        }
+
+        for _ in 0..len {
+            let byte = self.bits.bits(8)? as u8;
+            self.history.push(byte);
+        }
+
+        Ok(())
+    }
+
+    fn dynamic_codes(&mut self) -> io::Result<(Huffman, Huffman)> {
+        let literals = self.bits.bits(5)? as usize + 257;
+        let distances = self.bits.bits(5)? as usize + 1;
+        let code_lengths = self.bits.bits(4)? as usize + 4;
+
+        let mut lengths = [0u8; 19];
+        for &index in &CODE_LENGTH_ORDER[..code_lengths] {
+            lengths[index] = self.bits.bits(3)? as u8;
+        }
+        let code_length_code = Huffman::new(&lengths)?;
+
+        let mut lengths = Vec::with_capacity(literals + distances);
+        while lengths.len() < literals + distances {
+            let (length, repeat) = match code_length_code.decode(&mut self.bits)? {
+                symbol @ 0..=15 => (symbol as u8, 1),
+                16 => {
+                    let previous = *lengths
+                        .last()
+                        .ok_or_else(|| invalid("repeat without a previous length"))?;
+                    (previous, 3 + self.bits.bits(2)?)
+                }
+                17 => (0, 3 + self.bits.bits(3)?),
+                _ => (0, 11 + self.bits.bits(7)?),
+            };
+
+            if lengths.len() + repeat as usize > literals + distances {
+                return Err(invalid("too many code lengths"));
+            }
+            lengths.resize(lengths.len() + repeat as usize, length);
+        }
+
+        if lengths[256] == 0 {
+            return Err(invalid("missing end of block code"));
+        }
+
+        Ok((
+            Huffman::new(&lengths[..literals])?,
+            Huffman::new(&lengths[literals..])?,
+        ))
+    }
+
+    fn compressed(&mut self, literals: &Huffman, distances: &Huffman) -> io::Result<()> {
+        loop {
+            let symbol = literals.decode(&mut self.bits)? as usize;
+
+            match symbol {
+                0..=255 => self.history.push(symbol as u8),
+                256 => return Ok(()),
+                _ => {
+                    let index = symbol - 257;
+                    if index >= LENGTH_BASE.len() {
+                        return Err(invalid("invalid length code"));
+                    }
+                    let length = LENGTH_BASE[index] as usize
+                        + self.bits.bits(LENGTH_EXTRA[index].into())? as usize;
+
+                    let index = distances.decode(&mut self.bits)? as usize;
+                    if index >= DISTANCE_BASE.len() {
+                        return Err(invalid("invalid distance code"));
+                    }
+                    let distance = DISTANCE_BASE[index] as usize
+                        + self.bits.bits(DISTANCE_EXTRA[index].into())? as usize;
+
+                    if distance > self.history.len() {
+                        return Err(invalid("distance too far back"));
+                    }
+
+                    // The match can overlap the bytes it produces
+                    let start = self.history.len() - distance;
+                    for offset in 0..length {
+                        let byte = self.history[start + offset];
+                        self.history.push(byte);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Decodes the next block into `history`, going through the gzip
+    /// headers and trailers on the way
+    fn next_block(&mut self) -> io::Result<()> {
+        if !self.in_member {
+            return self.header();
+        }
+
+        if self.last_block {
+            return self.trailer();
+        }
+
+        let start = self.history.len();
+
+        self.last_block = self.bits.bits(1)? == 1;
+        match self.bits.bits(2)? {
+            0 => self.stored()?,
+            1 => {
+                let (literals, distances) = Huffman::fixed();
+                self.compressed(&literals, &distances)?;
+            }
+            2 => {
+                let (literals, distances) = self.dynamic_codes()?;
+                self.compressed(&literals, &distances)?;
+            }
+            _ => return Err(invalid("invalid block type")),
+        }
+
+        for &byte in &self.history[start..] {
+            self.crc = CRC_TABLE[((self.crc ^ u32::from(byte)) & 0xff) as usize] ^ (self.crc >> 8);
+        }
+        self.size = self.size.wrapping_add((self.history.len() - start) as u32);
+
+        Ok(())
+    }
+}
+
+impl<R: Read> Read for GzDecoder<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.pos == self.history.len() {
+            if self.done {
+                return Ok(0);
+            }
+
+            // Everything was read, only the window is needed from now on
+            if self.history.len() > WINDOW {
+                let excess = self.history.len() - WINDOW;
+                self.history.drain(..excess);
+                self.pos -= excess;
+            }
+
+            self.next_block()?;
+        }
+
+        let len = buf.len().min(self.history.len() - self.pos);
+        buf[..len].copy_from_slice(&self.history[self.pos..self.pos + len]);
+        self.pos += len;
+
+        Ok(len)
+    }
+}
@@ -0,0 +1,62 @@
+//! Hasher of the keys of objects.
+//!
+//! By default a [`Map`](crate::Map) hashes its keys with SipHash, seeded
+//! at random: slower, but a document crafted so that its keys collide can't
+//! turn building its objects quadratic. With the `fxhash` feature it uses
+//! [`FxHasher`] instead, the hasher of `rustc`, for input that is trusted.
+
+use core::hash::{BuildHasherDefault, Hasher};
+
+const SEED: u64 = 0x51_7c_c1_b7_27_22_0a_95;
+
+/// Hashes 8 bytes at a time with a rotation, a xor and a multiplication.
+/// Fast on short keys, but trivial to make collide
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FxHasher {
+    hash: u64,
+}
+
+impl FxHasher {
+    fn add(&mut self, word: u64) {
+        self.hash = (self.hash.rotate_left(5) ^ word).wrapping_mul(SEED);
+    }
+}
+
+impl Hasher for FxHasher {
+    fn write(&mut self, bytes: &[u8]) {
+        let mut words = bytes.chunks_exact(8);
+        for word in &mut words {
+            self.add(u64::from_le_bytes(word.try_into().unwrap()));
+        }
+
+        let rest = words.remainder();
+        if !rest.is_empty() {
+            let mut word = [0; 8];
+            word[..rest.len()].copy_from_slice(rest);
+            self.add(u64::from_le_bytes(word));
+        }
+    }
+
+    fn write_u8(&mut self, i: u8) {
+        self.add(i as u64);
+    }
+
+    fn write_u32(&mut self, i: u32) {
+        self.add(i as u64);
+    }
+
+    fn write_u64(&mut self, i: u64) {
+        self.add(i);
+    }
+
+    fn write_usize(&mut self, i: usize) {
+        self.add(i as u64);
+    }
+
+    fn finish(&self) -> u64 {
+        self.hash
+    }
+}
+
+/// Builds the hashers of a map, see [`MapHasher`](crate::MapHasher)
+pub type FxBuildHasher = BuildHasherDefault<FxHasher>;
@@ -0,0 +1,191 @@
+//! Shape of documents: which fields their objects have, of which types, and
+//! which ones are sometimes missing. Handy to find one's way around a dump
+//! of an unknown API.
+//!
+//! ```
+//! let tweets = step_4::parse_with_path(r#"[
+//!     {"id": 1, "text": "hello", "reply_to": null},
+//!     {"id": 2, "text": "world", "reply_to": 1, "tags": ["a"]}
+//! ]"#).unwrap();
+//!
+//! assert_eq!(
+//!     step_4::infer(&[tweets]).to_string(),
+//!     "[{
+//!   id: integer,
+//!   reply_to: null | integer,
+//!   tags?: [string],
+//!   text: string,
+//! }]"
+//! );
+//! ```
+
+use alloc::{boxed::Box, collections::BTreeMap, string::String};
+use core::fmt::{self, Display};
+
+use crate::{to_string, JsonValue};
+
+/// Every type a value was seen with. A value that can be of several types
+/// has several of them set, and one that was never seen, like the elements
+/// of arrays that were always empty, has none.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Schema {
+    pub null: bool,
+    pub boolean: bool,
+    /// Numbers without a fraction nor an exponent
+    pub integer: bool,
+    /// Other numbers
+    pub number: bool,
+    pub string: bool,
+    /// Shape of the elements of all the arrays
+    pub array: Option<Box<Schema>>,
+    /// Fields of all the objects, sorted
+    pub object: Option<BTreeMap<String, Field>>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Field {
+    pub schema: Schema,
+    /// Missing from some of the objects
+    pub optional: bool,
+}
+
+/// Schema of all of `values` together
+pub fn infer<'a>(values: impl IntoIterator<Item = &'a JsonValue>) -> Schema {
+    let mut schema = Schema::default();
+
+    for value in values {
+        schema.add(value);
+    }
+
+    schema
+}
+
+impl Schema {
+    /// Widens the schema so that it describes `value` too
+    pub fn add(&mut self, value: &JsonValue) {
+        match value {
+            JsonValue::Null => self.null = true,
+            JsonValue::Boolean(_) => self.boolean = true,
+            JsonValue::Int(_) | JsonValue::UInt(_) => self.integer = true,
+            JsonValue::Num(_) | JsonValue::RawNum(..) => self.number = true,
+            #[cfg(feature = "bignum")]
+            JsonValue::BigNum(_) => self.number = true,
+            JsonValue::Str(_) | JsonValue::Wtf8(_) => self.string = true,
+            JsonValue::Array(values) => {
+                let elements = self.array.get_or_insert_with(Box::default);
+
+                for value in values {
+                    elements.add(value);
+                }
+            }
+            JsonValue::Object(map) => match &mut self.object {
+                Some(fields) => {
+                    for (key, field) in fields.iter_mut() {
+                        field.optional |= !map.contains_key(key);
+                    }
+
+                    for (key, value) in map {
+                        fields
+                            .entry(key.clone())
+                            // Missing from the objects seen so far
+                            .or_insert_with(|| Field {
+                                schema: Schema::default(),
+                                optional: true,
+                            })
+                            .schema
+                            .add(value);
+                    }
+                }
+                None => {
+                    let fields = map
+                        .iter()
+                        .map(|(key, value)| {
+                            let mut field = Field::default();
+                            field.schema.add(value);
+                            (key.clone(), field)
+                        })
+                        .collect();
+
+                    self.object = Some(fields);
+                }
+            },
+        }
+    }
+
+    /// Whether no value was seen
+    pub fn is_empty(&self) -> bool {
+        *self == Schema::default()
+    }
+
+    fn fmt_indented(&self, f: &mut fmt::Formatter<'_>, indent: usize) -> fmt::Result {
+        if self.is_empty() {
+            return f.write_str("unknown");
+        }
+
+        let scalars = [
+            (self.null, "null"),
+            (self.boolean, "boolean"),
+            (self.integer, "integer"),
+            (self.number, "number"),
+            (self.string, "string"),
+        ];
+        let mut first = true;
+        let mut separate = |f: &mut fmt::Formatter<'_>| {
+            if !core::mem::take(&mut first) {
+                f.write_str(" | ")?;
+            }
+            Ok(())
+        };
+
+        for (_, name) in scalars.iter().filter(|(seen, _)| *seen) {
+            separate(f)?;
+            f.write_str(name)?;
+        }
+
+        if let Some(elements) = &self.array {
+            separate(f)?;
+            f.write_str("[")?;
+            elements.fmt_indented(f, indent)?;
+            f.write_str("]")?;
+        }
+
+        if let Some(fields) = &self.object {
+            separate(f)?;
+
+            if fields.is_empty() {
+                return f.write_str("{}");
+            }
+
+            f.write_str("{\n")?;
+            for (key, field) in fields {
+                write!(f, "{:width$}", "", width = (indent + 1) * 2)?;
+                fmt_key(f, key)?;
+                f.write_str(if field.optional { "?: " } else { ": " })?;
+                field.schema.fmt_indented(f, indent + 1)?;
+                f.write_str(",\n")?;
+            }
+            write!(f, "{:width$}}}", "", width = indent * 2)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Bare if it is an identifier, quoted like in JSON otherwise
+fn fmt_key(f: &mut fmt::Formatter<'_>, key: &str) -> fmt::Result {
+    let bare = key.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+        && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+
+    if bare {
+        f.write_str(key)
+    } else {
+        f.write_str(&to_string(&JsonValue::Str(key.into())))
+    }
+}
+
+/// TypeScript-like notation, fields being sorted
+impl Display for Schema {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.fmt_indented(f, 0)
+    }
+}
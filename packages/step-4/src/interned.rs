@@ -0,0 +1,179 @@
+//! A value tree whose object keys come from an [`Interner`].
+//!
+//! twitter.json repeats the same ~50 keys for every tweet and user; with
+//! [`parse_interned`] each distinct key is allocated once and every object
+//! holds a reference-counted pointer to it. Reusing the interner across
+//! documents of the same shape extends the sharing to all of them.
+
+use nom::{
+    character::complete::{anychar, char, multispace0},
+    combinator::{cut, map, peek},
+    error::{context, ContextError, ErrorKind, ParseError},
+    multi::separated_list0,
+    sequence::{delimited, preceded, terminated},
+    Parser,
+};
+use std::{cell::RefCell, collections::HashSet, sync::Arc};
+
+use crate::{
+    lexer::{borrowed_string, null, number, parse_false, parse_true, string, Number},
+    JsonError, JsonValue, Map, Result,
+};
+
+#[derive(Debug, PartialEq)]
+pub enum InternedValue {
+    Null,
+    Str(String),
+    Boolean(bool),
+    Num(f64),
+    Int(i64),
+    UInt(u64),
+    Array(Vec<InternedValue>),
+    Object(Map<Arc<str>, InternedValue>),
+}
+
+impl InternedValue {
+    pub fn into_value(self) -> JsonValue {
+        match self {
+            InternedValue::Null => JsonValue::Null,
+            InternedValue::Str(s) => JsonValue::Str(s),
+            InternedValue::Boolean(b) => JsonValue::Boolean(b),
+            InternedValue::Num(n) => JsonValue::Num(n),
+            InternedValue::Int(n) => JsonValue::Int(n),
+            InternedValue::UInt(n) => JsonValue::UInt(n),
+            InternedValue::Array(values) => {
+                JsonValue::Array(values.into_iter().map(InternedValue::into_value).collect())
+            }
+            InternedValue::Object(map) => JsonValue::Object(
+                map.into_iter()
+                    .map(|(k, v)| (k.to_string(), v.into_value()))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+/// Pool of the keys seen so far
+#[derive(Debug, Default)]
+pub struct Interner {
+    keys: RefCell<HashSet<Arc<str>>>,
+}
+
+impl Interner {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the pooled copy of `key`, adding it to the pool if needed
+    pub fn intern(&self, key: &str) -> Arc<str> {
+        let mut keys = self.keys.borrow_mut();
+
+        if let Some(interned) = keys.get(key) {
+            return interned.clone();
+        }
+
+        let interned: Arc<str> = Arc::from(key);
+        keys.insert(interned.clone());
+        interned
+    }
+
+    /// Number of distinct keys in the pool
+    pub fn len(&self) -> usize {
+        self.keys.borrow().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keys.borrow().is_empty()
+    }
+}
+
+fn array<'a, E: ParseError<&'a str> + ContextError<&'a str>>(
+    i: &'a str,
+    interner: &Interner,
+) -> Result<'a, Vec<InternedValue>, E> {
+    context(
+        "array",
+        delimited(
+            cut(char('[')),
+            cut(separated_list0(preceded(multispace0, char(',')), |i| {
+                let (i, next_char) = peek(anychar)(i)?;
+
+                if next_char == ']' {
+                    return Err(nom::Err::Error(E::from_error_kind(i, ErrorKind::Char)));
+                }
+
+                json_value(i, interner)
+            })),
+            preceded(multispace0, char(']')),
+        ),
+    )(i)
+}
+
+fn key_value<'a, E: ParseError<&'a str> + ContextError<&'a str>>(
+    i: &'a str,
+    interner: &Interner,
+) -> Result<'a, (Arc<str>, InternedValue), E> {
+    let (i, _) = multispace0(i)?;
+
+    let (i, next_char) = peek(anychar)(i)?;
+
+    if next_char == '}' {
+        return Err(nom::Err::Error(E::from_error_kind(i, ErrorKind::Char)));
+    }
+
+    // Borrowed so that a key already in the pool costs no allocation at all
+    let (i, key) = borrowed_string(i)?;
+    let (i, _) = cut(preceded(multispace0, char(':')))(i)?;
+    let (i, value) = json_value(i, interner)?;
+
+    Ok((i, (interner.intern(&key), value)))
+}
+
+fn hash<'a, E: ParseError<&'a str> + ContextError<&'a str>>(
+    i: &'a str,
+    interner: &Interner,
+) -> Result<'a, Map<Arc<str>, InternedValue>, E> {
+    context(
+        "map",
+        preceded(
+            cut(char('{')),
+            cut(terminated(
+                map(
+                    separated_list0(preceded(multispace0, char(',')), |i| key_value(i, interner)),
+                    |tuple_vec| tuple_vec.into_iter().collect(),
+                ),
+                preceded(multispace0, char('}')),
+            )),
+        ),
+    )
+    .parse(i)
+}
+
+fn json_value<'a, E: ParseError<&'a str> + ContextError<&'a str>>(
+    i: &'a str,
+    interner: &Interner,
+) -> Result<'a, InternedValue, E> {
+    let (i, _) = multispace0(i)?;
+
+    let (i, first_char) = peek(anychar)(i)?;
+
+    match first_char {
+        '{' => map(|i| hash(i, interner), InternedValue::Object)(i),
+        '[' => map(|i| array(i, interner), InternedValue::Array)(i),
+        '"' => map(string, InternedValue::Str)(i),
+        '-' | '0'..='9' => map(number, |n| match n {
+            Number::Int(n) => InternedValue::Int(n),
+            Number::UInt(n) => InternedValue::UInt(n),
+            Number::Float(n) => InternedValue::Num(n),
+        })(i),
+        'f' => map(parse_false, InternedValue::Boolean)(i),
+        't' => map(parse_true, InternedValue::Boolean)(i),
+        'n' => map(null, |_| InternedValue::Null)(i),
+        c => Err(nom::Err::Failure(E::from_error_kind(i, ErrorKind::Char))),
+    }
+}
+
+/// Parses `i`, taking the object keys from `interner`
+pub fn parse_interned<'a>(i: &'a str, interner: &Interner) -> Result<'a, InternedValue, JsonError> {
+    terminated(|i| json_value(i, interner), multispace0).parse(i)
+}
@@ -0,0 +1,276 @@
+//! [JSON5](https://spec.json5.org/) dialect, built on top of the strict combinators.
+//!
+//! On top of plain JSON it accepts comments, trailing commas, unquoted keys,
+//! single-quoted and multi-line strings, hex numbers, leading/trailing decimal
+//! points, explicit `+` signs, `Infinity` and `NaN`.
+
+use crate::{
+    lexer::{escape_char, null, parse_false, parse_true, Number},
+    JsonError, JsonValue, Map, Result,
+};
+use nom::{
+    branch::alt,
+    bytes::complete::{tag, tag_no_case, take_till, take_until, take_while, take_while1},
+    character::complete::{anychar, char, digit0, digit1, hex_digit1, one_of, satisfy},
+    combinator::{cut, map, map_opt, not, opt, peek, recognize, value},
+    error::{context, ContextError, ErrorKind, ParseError},
+    multi::{fold_many0, many0_count, separated_list0},
+    sequence::{delimited, pair, preceded, separated_pair, terminated, tuple},
+    Parser,
+};
+
+fn is_line_terminator(c: char) -> bool {
+    matches!(c, '\n' | '\r' | '\u{2028}' | '\u{2029}')
+}
+
+fn is_space(c: char) -> bool {
+    c.is_whitespace() || c == '\u{FEFF}'
+}
+
+fn comment<'a, E: ParseError<&'a str>>(i: &'a str) -> Result<'a, &'a str, E> {
+    alt((
+        preceded(tag("//"), take_till(is_line_terminator)),
+        delimited(tag("/*"), take_until("*/"), tag("*/")),
+    ))(i)
+}
+
+/// Whitespace and comments
+fn sp<'a, E: ParseError<&'a str>>(i: &'a str) -> Result<'a, (), E> {
+    value((), many0_count(alt((take_while1(is_space), comment))))(i)
+}
+
+fn hex<'a, E: ParseError<&'a str>>(i: &'a str) -> Result<'a, Number, E> {
+    map(preceded(tag_no_case("0x"), hex_digit1), |digits: &str| {
+        match u64::from_str_radix(digits, 16) {
+            Ok(n) => i64::try_from(n).map_or(Number::UInt(n), Number::Int),
+            // Folding by hand so that literals wider than u64 degrade like decimals do
+            Err(_) => Number::Float(
+                digits
+                    .chars()
+                    .fold(0.0, |acc, c| acc * 16.0 + c.to_digit(16).unwrap() as f64),
+            ),
+        }
+    })(i)
+}
+
+fn decimal<'a, E: ParseError<&'a str>>(i: &'a str) -> Result<'a, Number, E> {
+    map_opt(
+        recognize(tuple((
+            alt((
+                recognize(pair(digit1, opt(pair(char('.'), digit0)))),
+                recognize(pair(char('.'), digit1)),
+            )),
+            opt(tuple((one_of("eE"), opt(one_of("+-")), cut(digit1)))),
+        ))),
+        |number: &str| Some(Number::new(number, number.parse().ok()?)),
+    )(i)
+}
+
+fn number<'a, E: ParseError<&'a str>>(i: &'a str) -> Result<'a, Number, E> {
+    let (i, sign) = opt(one_of("+-"))(i)?;
+
+    let (i, number) = alt((
+        value(Number::Float(f64::INFINITY), tag("Infinity")),
+        value(Number::Float(f64::NAN), tag("NaN")),
+        hex,
+        decimal,
+    ))(i)?;
+
+    if sign != Some('-') {
+        return Ok((i, number));
+    }
+
+    let negated = match number {
+        // `-0`, which an integer can't hold
+        Number::Int(0) => Number::Float(-0.0),
+        Number::Int(n) => Number::Int(-n),
+        Number::UInt(n) => Number::Float(-(n as f64)),
+        Number::Float(n) => Number::Float(-n),
+    };
+
+    Ok((i, negated))
+}
+
+/// Like `parse_char`, but closed by `quote` and with the extra JSON5 escapes.
+/// Returns `None` for line continuations, which don't produce any char
+fn parse_char<'a, E: ParseError<&'a str>>(
+    input: &'a str,
+    quote: char,
+) -> Result<'a, Option<char>, E> {
+    let (i, c) = anychar(input)?;
+
+    if c == quote {
+        return Err(nom::Err::Error(E::from_error_kind(input, ErrorKind::Char)));
+    }
+
+    if c == '\n' || c == '\r' {
+        return Err(nom::Err::Failure(E::from_error_kind(
+            input,
+            ErrorKind::Char,
+        )));
+    }
+
+    if c != '\\' {
+        return Ok((i, Some(c)));
+    }
+
+    let (rest, escaped_char) = anychar(i)?;
+    let final_char = match escaped_char {
+        '"' | '\\' | '/' | 'b' | 'f' | 'n' | 'r' | 't' | 'u' => return map(escape_char, Some)(i),
+        '\r' => return value(None, opt(char('\n')))(rest),
+        c if is_line_terminator(c) => return Ok((rest, None)),
+        '\'' => '\'',
+        'v' => '\x0B',
+        '0' => {
+            let (rest, _) = not(satisfy(|c| c.is_ascii_digit()))(rest)?;
+            return Ok((rest, Some('\0')));
+        }
+        'x' => {
+            return map_opt(
+                preceded(char('x'), recognize(pair(satisfy(is_hex), satisfy(is_hex)))),
+                |hex: &str| {
+                    u8::from_str_radix(hex, 16)
+                        .ok()
+                        .map(|byte| Some(byte as char))
+                },
+            )(i)
+        }
+        c @ '1'..='9' => return Err(nom::Err::Failure(E::from_error_kind(rest, ErrorKind::Char))),
+        c => c,
+    };
+
+    Ok((rest, Some(final_char)))
+}
+
+fn is_hex(c: char) -> bool {
+    c.is_ascii_hexdigit()
+}
+
+fn string<'a, E: ParseError<&'a str> + ContextError<&'a str>>(i: &'a str) -> Result<'a, String, E> {
+    let (i, quote) = one_of("\"'")(i)?;
+
+    context(
+        "string",
+        terminated(
+            fold_many0(
+                move |i| parse_char(i, quote),
+                String::new,
+                |mut string, c| {
+                    string.extend(c);
+                    string
+                },
+            ),
+            cut(char(quote)),
+        ),
+    )(i)
+}
+
+fn identifier<'a, E: ParseError<&'a str>>(i: &'a str) -> Result<'a, String, E> {
+    map(
+        recognize(pair(
+            satisfy(|c| c.is_alphabetic() || c == '$' || c == '_'),
+            take_while(|c: char| {
+                c.is_alphanumeric() || matches!(c, '$' | '_' | '\u{200C}' | '\u{200D}')
+            }),
+        )),
+        String::from,
+    )(i)
+}
+
+/// The `,` allowed after the last element or member, when there is one:
+/// `[,]` and `{,}` are invalid
+fn trailing_comma<'a, E: ParseError<&'a str>>(i: &'a str, empty: bool) -> Result<'a, (), E> {
+    if empty {
+        return Ok((i, ()));
+    }
+
+    value((), opt(preceded(sp, char(','))))(i)
+}
+
+fn array<'a, E: ParseError<&'a str> + ContextError<&'a str>>(
+    i: &'a str,
+) -> Result<'a, Vec<JsonValue>, E> {
+    context(
+        "array",
+        delimited(
+            char('['),
+            cut(|i| {
+                let (i, values) = separated_list0(preceded(sp, char(',')), |i| {
+                    let (i, _) = sp(i)?;
+                    let (i, next_char) = peek(anychar)(i)?;
+
+                    if next_char == ']' {
+                        return Err(nom::Err::Error(E::from_error_kind(i, ErrorKind::Char)));
+                    }
+
+                    json_value(i)
+                })(i)?;
+                let (i, _) = trailing_comma(i, values.is_empty())?;
+
+                Ok((i, values))
+            }),
+            cut(preceded(sp, char(']'))),
+        ),
+    )(i)
+}
+
+fn key_value<'a, E: ParseError<&'a str> + ContextError<&'a str>>(
+    i: &'a str,
+) -> Result<'a, (String, JsonValue), E> {
+    let (i, _) = sp(i)?;
+
+    let (i, next_char) = peek(anychar)(i)?;
+
+    if next_char == '}' {
+        return Err(nom::Err::Error(E::from_error_kind(i, ErrorKind::Char)));
+    }
+
+    separated_pair(
+        alt((string, identifier)),
+        cut(preceded(sp, char(':'))),
+        json_value,
+    )
+    .parse(i)
+}
+
+fn hash<'a, E: ParseError<&'a str> + ContextError<&'a str>>(
+    i: &'a str,
+) -> Result<'a, Map<String, JsonValue>, E> {
+    context(
+        "map",
+        delimited(
+            char('{'),
+            cut(|i| {
+                let (i, members) = separated_list0(preceded(sp, char(',')), key_value)(i)?;
+                let (i, _) = trailing_comma(i, members.is_empty())?;
+
+                Ok((i, members.into_iter().collect()))
+            }),
+            cut(preceded(sp, char('}'))),
+        ),
+    )
+    .parse(i)
+}
+
+fn json_value<'a, E: ParseError<&'a str> + ContextError<&'a str>>(
+    i: &'a str,
+) -> Result<'a, JsonValue, E> {
+    let (i, _) = sp(i)?;
+
+    let (i, first_char) = peek(anychar)(i)?;
+
+    match first_char {
+        '{' => map(hash, JsonValue::Object)(i),
+        '[' => map(array, JsonValue::Array)(i),
+        '"' | '\'' => map(string, JsonValue::Str)(i),
+        '-' | '+' | '.' | '0'..='9' | 'I' | 'N' => map(number, JsonValue::from)(i),
+        'f' => map(parse_false, JsonValue::Boolean)(i),
+        't' => map(parse_true, JsonValue::Boolean)(i),
+        'n' => map(null, |_| JsonValue::Null)(i),
+        c => Err(nom::Err::Failure(E::from_error_kind(i, ErrorKind::Char))),
+    }
+}
+
+pub fn parse(i: &str) -> Result<'_, JsonValue, JsonError> {
+    terminated(json_value, sp).parse(i)
+}
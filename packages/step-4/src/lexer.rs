@@ -0,0 +1,400 @@
+//! Low-level pieces of the grammar: strings, numbers, literals, punctuation
+//! and whitespace.
+//!
+//! Besides the combinators the parsers are built from, [`Lexer`] splits a
+//! document into spanned [`Token`]s, which is what editor tooling (syntax
+//! highlighting, folding, ...) needs rather than a value tree.
+
+use alloc::{borrow::Cow, string::String, vec::Vec};
+use core::ops::Range;
+use memchr::memchr2;
+use nom::{
+    branch::alt,
+    bytes::complete::{tag, take},
+    character::complete::{anychar, char, multispace1},
+    combinator::{consumed, cut, map, map_opt, recognize, value, verify},
+    error::{context, ContextError, ErrorKind, ParseError},
+    multi::fold_many0,
+    number::complete::double,
+    sequence::{preceded, separated_pair, terminated},
+    Parser,
+};
+
+use crate::{wtf8, JsonError, NumberParser, Result, SurrogateMode};
+
+pub(crate) fn parse_true<'a, E: ParseError<&'a str>>(i: &'a str) -> Result<'a, bool, E> {
+    value(true, tag("true"))(i)
+}
+
+pub(crate) fn parse_false<'a, E: ParseError<&'a str>>(i: &'a str) -> Result<'a, bool, E> {
+    value(false, tag("false"))(i)
+}
+
+pub(crate) fn null<'a, E: ParseError<&'a str>>(input: &'a str) -> Result<'a, (), E> {
+    value((), tag("null")).parse(input)
+}
+
+fn u16_hex<'a, E: ParseError<&'a str>>(i: &'a str) -> Result<'a, u16, E> {
+    map_opt(take(4usize), |s: &'a str| u16::from_str_radix(s, 16).ok())(i)
+}
+
+fn unicode_escape<'a, E: ParseError<&'a str>>(i: &'a str) -> Result<'a, char, E> {
+    map_opt(
+        alt((
+            // Not a surrogate
+            map(verify(u16_hex, |cp| !(0xD800..0xE000).contains(cp)), |cp| {
+                cp as u32
+            }),
+            // See https://en.wikipedia.org/wiki/UTF-16#Code_points_from_U+010000_to_U+10FFFF for details
+            map(
+                verify(
+                    separated_pair(u16_hex, tag("\\u"), u16_hex),
+                    |(high, low)| (0xD800..0xDC00).contains(high) && (0xDC00..0xE000).contains(low),
+                ),
+                |(high, low)| {
+                    let high_ten = (high as u32) - 0xD800;
+                    let low_ten = (low as u32) - 0xDC00;
+                    (high_ten << 10) + low_ten + 0x10000
+                },
+            ),
+        )),
+        // Could probably be replaced with .unwrap() or _unchecked due to the verify checks
+        core::char::from_u32,
+    )(i)
+}
+
+/// Parses what follows a `\` inside a string
+pub(crate) fn escape_char<'a, E: ParseError<&'a str>>(input: &'a str) -> Result<'a, char, E> {
+    let (i, escaped_char) = anychar(input)?;
+    let final_char = match escaped_char {
+        '"' | '\\' | '/' => escaped_char,
+        'b' => '\x08',
+        'f' => '\x0C',
+        'n' => '\n',
+        'r' => '\r',
+        't' => '\t',
+        'u' => return unicode_escape(i),
+        _ => {
+            return Err(nom::Err::Failure(E::from_error_kind(
+                input,
+                ErrorKind::Char,
+            )))
+        }
+    };
+
+    Ok((i, final_char))
+}
+
+/// `\uXXXX` of a surrogate without its other half, after the `\u`
+fn lone_surrogate<'a, E: ParseError<&'a str>>(i: &'a str) -> Result<'a, u16, E> {
+    verify(u16_hex, |unit| (0xD800..0xE000).contains(unit))(i)
+}
+
+/// Run of chars up to the next `"` or `\`, found in bulk with `memchr`
+/// rather than char by char
+fn unescaped_run<'a, E: ParseError<&'a str>>(i: &'a str) -> Result<'a, &'a str, E> {
+    match memchr2(b'"', b'\\', i.as_bytes()).unwrap_or(i.len()) {
+        0 => Err(nom::Err::Error(E::from_error_kind(i, ErrorKind::TakeTill1))),
+        // Both delimiters are ASCII, so `end` is a char boundary
+        end => Ok((&i[end..], &i[..end])),
+    }
+}
+
+enum Fragment<'a> {
+    Run(&'a str),
+    Escaped(char),
+    /// Only parsed by `wtf8_string`
+    Surrogate(u16),
+}
+
+pub(crate) fn string<'a, E: ParseError<&'a str> + ContextError<&'a str>>(
+    i: &'a str,
+) -> Result<'a, String, E> {
+    context(
+        "string",
+        preceded(
+            cut(tag("\"")),
+            terminated(
+                fold_many0(
+                    alt((
+                        map(unescaped_run, Fragment::Run),
+                        map(preceded(char('\\'), escape_char), Fragment::Escaped),
+                    )),
+                    String::new,
+                    |mut string, fragment| {
+                        match fragment {
+                            Fragment::Run(run) => string.push_str(run),
+                            Fragment::Escaped(c) => string.push(c),
+                            Fragment::Surrogate(_) => unreachable!("not parsed"),
+                        }
+                        string
+                    },
+                ),
+                cut(char('"')),
+            ),
+        ),
+    )(i)
+}
+
+/// Like `string`, with the lone surrogates `mode` allows: the content is
+/// WTF-8 with `SurrogateMode::Wtf8`, UTF-8 otherwise
+pub(crate) fn wtf8_string<'a, E: ParseError<&'a str> + ContextError<&'a str>>(
+    i: &'a str,
+    mode: SurrogateMode,
+) -> Result<'a, Vec<u8>, E> {
+    context(
+        "string",
+        preceded(
+            cut(tag("\"")),
+            terminated(
+                fold_many0(
+                    alt((
+                        map(unescaped_run, Fragment::Run),
+                        map(preceded(char('\\'), escape_char), Fragment::Escaped),
+                        map(preceded(tag("\\u"), lone_surrogate), Fragment::Surrogate),
+                    )),
+                    Vec::new,
+                    move |mut bytes, fragment| {
+                        match fragment {
+                            Fragment::Run(run) => bytes.extend_from_slice(run.as_bytes()),
+                            Fragment::Escaped(c) => {
+                                bytes.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes())
+                            }
+                            Fragment::Surrogate(unit) => match mode {
+                                SurrogateMode::Wtf8 => wtf8::push_surrogate(&mut bytes, unit),
+                                _ => bytes.extend_from_slice("\u{FFFD}".as_bytes()),
+                            },
+                        }
+                        bytes
+                    },
+                ),
+                cut(char('"')),
+            ),
+        ),
+    )(i)
+}
+
+/// Like `string`, but borrows the content from the input when it has no
+/// escapes
+pub(crate) fn borrowed_string<'a, E: ParseError<&'a str> + ContextError<&'a str>>(
+    i: &'a str,
+) -> Result<'a, Cow<'a, str>, E> {
+    if let Some(content) = i.strip_prefix('"') {
+        if let Some(end) = memchr2(b'"', b'\\', content.as_bytes()) {
+            if content.as_bytes()[end] == b'"' {
+                return Ok((&content[end + 1..], Cow::Borrowed(&content[..end])));
+            }
+        }
+    }
+
+    map(string, Cow::Owned)(i)
+}
+
+/// Like `string`, but only checks the escapes rather than decoding them:
+/// the text of the string, quotes included, and whether it has escapes
+pub(crate) fn raw_string<'a, E: ParseError<&'a str> + ContextError<&'a str>>(
+    i: &'a str,
+) -> Result<'a, (&'a str, bool), E> {
+    consumed(context(
+        "string",
+        preceded(
+            cut(tag("\"")),
+            terminated(
+                fold_many0(
+                    alt((
+                        value(false, unescaped_run),
+                        value(true, preceded(char('\\'), escape_char)),
+                    )),
+                    || false,
+                    |escaped, fragment| escaped || fragment,
+                ),
+                cut(char('"')),
+            ),
+        ),
+    ))(i)
+}
+
+/// Number with integers kept exact: `f64` only has 53 bits of mantissa,
+/// which isn't enough for 64-bit IDs
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Number {
+    Int(i64),
+    UInt(u64),
+    Float(f64),
+}
+
+impl Number {
+    /// `text` is the lexeme `float` was parsed from. Integers that fit in an
+    /// `i64` are `Int`, larger positive ones `UInt`, and the rest stay floats,
+    /// `-0` included since an integer would lose its sign
+    pub(crate) fn new(text: &str, float: f64) -> Self {
+        let negative_zero = float == 0.0 && text.starts_with('-');
+
+        if !negative_zero && text.bytes().all(|b| b == b'-' || b.is_ascii_digit()) {
+            if let Ok(n) = text.parse() {
+                return Number::Int(n);
+            }
+
+            if let Ok(n) = text.parse() {
+                return Number::UInt(n);
+            }
+        }
+
+        Number::Float(float)
+    }
+}
+
+pub(crate) fn number<'a, E: ParseError<&'a str>>(i: &'a str) -> Result<'a, Number, E> {
+    let (rest, float) = double(i)?;
+
+    Ok((rest, Number::new(&i[..i.len() - rest.len()], float)))
+}
+
+/// [`number`] read by `parser` rather than nom's `double`
+pub(crate) fn number_with<'a, E: ParseError<&'a str>>(
+    i: &'a str,
+    parser: &impl NumberParser,
+) -> Result<'a, Number, E> {
+    match parser.parse_number(i) {
+        Some((len, float)) => Ok((&i[len..], Number::new(&i[..len], float))),
+        None => Err(nom::Err::Error(E::from_error_kind(i, ErrorKind::Float))),
+    }
+}
+
+/// Decodes the text of a [`TokenKind::Str`] token, quotes included,
+/// borrowing it when it has no escapes
+///
+/// ```
+/// use step_4::lexer::unescape;
+///
+/// assert_eq!(unescape(r#""a\nb""#).unwrap(), "a\nb");
+/// assert_eq!(unescape(r#""""#).unwrap(), "");
+///
+/// for text in ["", "\"", "abc", r#""a"b""#, r#""a\""#] {
+///     assert!(unescape(text).is_err(), "{text}");
+/// }
+/// ```
+pub fn unescape(text: &str) -> core::result::Result<Cow<'_, str>, JsonError> {
+    let borrowed = text
+        .strip_prefix('"')
+        .and_then(|text| text.strip_suffix('"'))
+        .filter(|content| !content.contains(['\\', '"']));
+
+    if let Some(content) = borrowed {
+        return Ok(Cow::Borrowed(content));
+    }
+
+    let (rest, string) = string::<JsonError>(text)?;
+
+    if !rest.is_empty() {
+        return Err(JsonError::from_error_kind(rest, ErrorKind::Eof));
+    }
+
+    Ok(Cow::Owned(string))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TokenKind {
+    BeginObject,
+    EndObject,
+    BeginArray,
+    EndArray,
+    Colon,
+    Comma,
+    Str,
+    Number,
+    True,
+    False,
+    Null,
+    Whitespace,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Token<'a> {
+    pub kind: TokenKind,
+    /// Byte range of the token in the input
+    pub span: Range<usize>,
+    /// Raw text of the token, escapes included
+    pub text: &'a str,
+}
+
+/// Iterator over the [`Token`]s of a document, whitespace included so that
+/// the spans cover the whole input.
+///
+/// Only the tokens are validated, not the way they are laid out: `]]` is a
+/// perfectly fine token stream. The iterator stops after the first error.
+pub struct Lexer<'a> {
+    input: &'a str,
+    rest: &'a str,
+}
+
+impl<'a> Lexer<'a> {
+    pub fn new(input: &'a str) -> Self {
+        Self { input, rest: input }
+    }
+
+    /// Byte offset of the next unread char
+    pub fn offset(&self) -> usize {
+        self.input.len() - self.rest.len()
+    }
+
+    fn token(i: &'a str) -> Result<'a, (TokenKind, &'a str), JsonError> {
+        let (_, first_char) = anychar(i)?;
+
+        let kind = match first_char {
+            ' ' | '\t' | '\r' | '\n' => TokenKind::Whitespace,
+            '{' => TokenKind::BeginObject,
+            '}' => TokenKind::EndObject,
+            '[' => TokenKind::BeginArray,
+            ']' => TokenKind::EndArray,
+            ':' => TokenKind::Colon,
+            ',' => TokenKind::Comma,
+            '"' => return map(recognize(string), |text| (TokenKind::Str, text))(i),
+            '-' | '0'..='9' => return map(recognize(double), |text| (TokenKind::Number, text))(i),
+            't' => return map(recognize(parse_true), |text| (TokenKind::True, text))(i),
+            'f' => return map(recognize(parse_false), |text| (TokenKind::False, text))(i),
+            'n' => return map(recognize(null), |text| (TokenKind::Null, text))(i),
+            c => {
+                return Err(nom::Err::Failure(JsonError::from_error_kind(
+                    i,
+                    ErrorKind::Char,
+                )))
+            }
+        };
+
+        if kind == TokenKind::Whitespace {
+            return map(multispace1, |text| (kind, text))(i);
+        }
+
+        Ok((&i[1..], (kind, &i[..1])))
+    }
+}
+
+impl<'a> Iterator for Lexer<'a> {
+    type Item = core::result::Result<Token<'a>, JsonError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.rest.is_empty() {
+            return None;
+        }
+
+        let start = self.offset();
+
+        match Self::token(self.rest) {
+            Ok((rest, (kind, text))) => {
+                self.rest = rest;
+
+                Some(Ok(Token {
+                    kind,
+                    span: start..self.offset(),
+                    text,
+                }))
+            }
+            Err(e) => {
+                self.rest = "";
+
+                Some(Err(e.into()))
+            }
+        }
+    }
+}
@@ -0,0 +1,681 @@
+#![allow(unused)]
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+use alloc::{
+    borrow::ToOwned,
+    boxed::Box,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::{fmt::Display, panic, str};
+use nom::{
+    branch::alt,
+    bytes::complete::{escaped, tag, take},
+    character::complete::{
+        alphanumeric1 as alphanumeric, anychar, char, multispace0, multispace1, none_of, one_of,
+    },
+    combinator::{all_consuming, cut, map, map_opt, peek, value, verify},
+    error::{context, ContextError, Error, ErrorKind, FromExternalError, ParseError, VerboseError},
+    multi::{fold_many0, many0, separated_list0},
+    number::complete::double,
+    sequence::{delimited, preceded, separated_pair, terminated},
+    Finish, IResult, Parser,
+};
+
+#[cfg(feature = "arena")]
+pub mod arena;
+#[cfg(feature = "bignum")]
+pub mod bignum;
+#[cfg(feature = "std")]
+pub mod borrowed;
+#[cfg(feature = "bson")]
+pub mod bson;
+pub mod builder;
+#[cfg(feature = "std")]
+pub mod bytes;
+#[cfg(feature = "cbor")]
+pub mod cbor;
+mod cmp;
+pub mod codegen;
+#[cfg(feature = "compact")]
+pub mod compact;
+mod convert;
+pub mod cst;
+pub mod decode;
+#[cfg(feature = "std")]
+pub mod deferred;
+pub mod edit;
+pub mod extract;
+pub mod flatten;
+pub mod gron;
+#[cfg(feature = "std")]
+pub mod gzip;
+pub mod hasher;
+pub mod infer;
+#[cfg(feature = "std")]
+pub mod interned;
+#[cfg(feature = "json5")]
+pub mod json5;
+pub mod lexer;
+pub mod lines;
+#[cfg(feature = "std")]
+pub mod metrics;
+#[cfg(feature = "mmap")]
+pub mod mmap;
+#[cfg(feature = "msgpack")]
+pub mod msgpack;
+pub mod number_parser;
+pub mod options;
+#[cfg(feature = "std")]
+pub mod parallel;
+mod path;
+pub mod reader;
+#[cfg(feature = "std")]
+pub mod recover;
+pub mod render;
+pub mod ser;
+#[cfg(feature = "std")]
+pub mod shared;
+pub mod spanned;
+#[cfg(feature = "std")]
+pub mod stream;
+#[cfg(feature = "testing")]
+pub mod testing;
+pub mod toml;
+mod transform;
+pub mod visitor;
+mod wtf8;
+pub mod yaml;
+
+#[cfg(feature = "arena")]
+pub use arena::parse_arena;
+#[cfg(feature = "bignum")]
+pub use bignum::BigNumber;
+#[cfg(feature = "std")]
+pub use borrowed::{parse_borrowed, parse_lazy, BorrowedValue};
+#[cfg(feature = "bson")]
+pub use bson::from_bson;
+pub use builder::{ArrayBuilder, ObjectBuilder};
+#[cfg(feature = "std")]
+pub use bytes::parse_bytes;
+#[cfg(feature = "cbor")]
+pub use cbor::from_cbor;
+pub use codegen::{rust_types, rust_value};
+#[cfg(feature = "compact")]
+pub use compact::{parse_compact, CompactValue, SmallString};
+pub use convert::IntoIter;
+pub use cst::{parse_cst, CstKind, CstMember, CstNode};
+#[cfg(feature = "std")]
+pub use deferred::{parse_deferred, DeferredValue, LazyStr};
+pub use edit::edit;
+pub use extract::{extract, parse_at};
+pub use gron::from_gron;
+pub use infer::{infer, Field, Schema};
+#[cfg(feature = "std")]
+pub use interned::{parse_interned, InternedValue, Interner};
+pub use lines::parse_lines;
+#[cfg(feature = "std")]
+pub use lines::{par_parse_lines, ParLines};
+#[cfg(feature = "std")]
+pub use metrics::{parse_with_metrics, Metrics, PerKind, Stats};
+#[cfg(feature = "mmap")]
+pub use mmap::parse_file;
+#[cfg(feature = "msgpack")]
+pub use msgpack::from_msgpack;
+pub use number_parser::{NumberBackend, NumberParser};
+pub use options::{
+    FlattenOptions, IndexStyle, NumberMode, ParseOptions, SerializeOptions, SurrogateMode,
+};
+#[cfg(feature = "std")]
+pub use parallel::{parse_parallel, parse_parallel_with, ParallelOptions};
+pub use reader::{Event, JsonReader};
+#[cfg(feature = "std")]
+pub use recover::{parse_lossy, parse_recovering, Lossy};
+pub use render::{error_position, render_error};
+pub use ser::{render_colored, to_string, to_string_with};
+#[cfg(feature = "std")]
+pub use shared::SharedValue;
+pub use spanned::{parse_spanned, Position, Source, Spanned, SpannedValue};
+#[cfg(feature = "std")]
+pub use stream::{parse_reader, parse_reader_with_progress, JsonFeeder};
+#[cfg(feature = "toml")]
+pub use toml::from_toml;
+pub use visitor::{parse_with_visitor, JsonVisitor};
+#[cfg(feature = "yaml")]
+pub use yaml::from_yaml;
+
+use lexer::{null, number_with, parse_false, parse_true, string, wtf8_string, Number};
+use path::JsonPath;
+
+/// Hasher of a [`Map`]: SipHash, or [`FxHasher`](hasher::FxHasher) with the
+/// `fxhash` feature, see [`hasher`]
+#[cfg(all(feature = "std", not(feature = "fxhash")))]
+pub type MapHasher = std::collections::hash_map::RandomState;
+#[cfg(feature = "fxhash")]
+pub type MapHasher = hasher::FxBuildHasher;
+
+/// Members of an object. `alloc` has no hash map, so without the `std`
+/// feature it is a `BTreeMap`, with the keys in order.
+///
+/// The hasher depends on a feature, so maps are made with
+/// `Map::default()` or `collect()` rather than `HashMap::new()`
+#[cfg(feature = "std")]
+pub type Map<K, V> = std::collections::HashMap<K, V, MapHasher>;
+#[cfg(not(feature = "std"))]
+pub type Map<K, V> = alloc::collections::BTreeMap<K, V>;
+
+/// Totally ordered and hashable: values of different variants are ordered
+/// by variant, floats by [`f64::total_cmp`], and objects by their entries
+/// sorted by key, whatever their order in the source
+#[derive(Debug, Clone)]
+pub enum JsonValue {
+    Null,
+    Str(String),
+    Boolean(bool),
+    Num(f64),
+    /// Integer that fits in an `i64`
+    Int(i64),
+    /// Integer above `i64::MAX`
+    UInt(u64),
+    /// Exact number, see [`NumberMode::BigNumber`]
+    #[cfg(feature = "bignum")]
+    BigNum(BigNumber),
+    /// Number along with its text in the source, see [`NumberMode::Preserve`]
+    RawNum(f64, String),
+    /// String with lone surrogates, as WTF-8, see [`SurrogateMode::Wtf8`]
+    Wtf8(Vec<u8>),
+    Array(Vec<JsonValue>),
+    Object(Map<String, JsonValue>),
+}
+
+impl From<Number> for JsonValue {
+    fn from(n: Number) -> Self {
+        match n {
+            Number::Int(n) => JsonValue::Int(n),
+            Number::UInt(n) => JsonValue::UInt(n),
+            Number::Float(n) => JsonValue::Num(n),
+        }
+    }
+}
+
+/// Whole document, surrounding whitespace allowed, like [`parse_with_path`]
+impl str::FromStr for JsonValue {
+    type Err = JsonError;
+
+    fn from_str(s: &str) -> core::result::Result<Self, Self::Err> {
+        parse_with_path(s)
+    }
+}
+
+type Result<'a, O, E> = IResult<&'a str, O, E>;
+
+#[derive(Debug)]
+pub enum JsonError {
+    NomError(ErrorKind),
+    Custom(String),
+    /// Error raised by the parser
+    Syntax {
+        kind: ErrorKind,
+        /// Char the parser failed on, `None` at the end of the input
+        found: Option<char>,
+        /// Length of the input left after the failure, which locates it in
+        /// the input without borrowing it
+        remaining: usize,
+        /// Char that would have been valid instead of `found`
+        expected: Option<char>,
+        /// Message of the error a `map_res` conversion failed with
+        message: Option<String>,
+        /// Labels of the `context(...)` the failure happened in, innermost
+        /// first, with the length of the input left where each one started
+        context: Vec<(&'static str, usize)>,
+    },
+    /// Error on a given line (1-based) of a multi-document input
+    Line(usize, Box<JsonError>),
+    /// Error on the value at a given JSONPath
+    Path(String, Box<JsonError>),
+    /// Error at a given byte offset of an input that was never held whole,
+    /// like the one of `parse_reader`
+    Offset(usize, Box<JsonError>),
+    #[cfg(feature = "std")]
+    Io(std::io::Error),
+    /// The input ended before the document did: more data is needed
+    NeedMoreData,
+}
+
+impl Display for JsonError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            JsonError::NomError(message) => write!(f, "{message:?}"),
+            JsonError::Custom(message) => write!(f, "{message}"),
+            JsonError::Syntax {
+                message: Some(message),
+                ..
+            } => write!(f, "{message}"),
+            JsonError::Syntax {
+                kind,
+                found,
+                expected,
+                ..
+            } => {
+                match (kind, found) {
+                    (_, None) => write!(f, "unexpected end of input")?,
+                    (ErrorKind::Eof, Some(_)) => write!(f, "trailing characters")?,
+                    (ErrorKind::Float, _) => write!(f, "invalid number")?,
+                    (ErrorKind::Tag, _) => write!(f, "invalid literal")?,
+                    (_, Some(c)) => write!(f, "unexpected {c:?}")?,
+                }
+
+                match expected {
+                    Some(c) => write!(f, ", expected {c:?}"),
+                    None => Ok(()),
+                }
+            }
+            JsonError::Line(line, error) => write!(f, "line {line}: {error}"),
+            JsonError::Path(path, error) => write!(f, "{error} at {path}"),
+            JsonError::Offset(offset, error) => write!(f, "{error} at byte {offset}"),
+            #[cfg(feature = "std")]
+            JsonError::Io(error) => write!(f, "{error}"),
+            JsonError::NeedMoreData => write!(f, "unexpected end of input"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for JsonError {}
+
+impl From<nom::Err<JsonError>> for JsonError {
+    fn from(e: nom::Err<JsonError>) -> Self {
+        match e {
+            nom::Err::Error(e) | nom::Err::Failure(e) => e,
+            nom::Err::Incomplete(_) => JsonError::NeedMoreData,
+        }
+    }
+}
+
+impl<'a> ParseError<&'a str> for JsonError {
+    fn from_error_kind(input: &'a str, kind: nom::error::ErrorKind) -> Self {
+        JsonError::Syntax {
+            kind,
+            found: input.chars().next(),
+            remaining: input.len(),
+            expected: None,
+            message: None,
+            context: Vec::new(),
+        }
+    }
+
+    /// Keeps the innermost error, which is where the input went wrong
+    fn append(input: &'a str, kind: nom::error::ErrorKind, other: Self) -> Self {
+        other
+    }
+
+    /// Called by `char(c)` when the input doesn't start with `c`
+    fn from_char(input: &'a str, c: char) -> Self {
+        let mut error = Self::from_error_kind(input, ErrorKind::Char);
+
+        if let JsonError::Syntax { expected, .. } = &mut error {
+            *expected = Some(c);
+        }
+
+        error
+    }
+}
+
+/// `E` only needs to be displayed, so that this works without
+/// `std::error::Error` too
+impl<'a, E: Display> FromExternalError<&'a str, E> for JsonError {
+    fn from_external_error(input: &'a str, kind: ErrorKind, e: E) -> Self {
+        let mut error = Self::from_error_kind(input, kind);
+
+        if let JsonError::Syntax { message, .. } = &mut error {
+            *message = Some(e.to_string());
+        }
+
+        error
+    }
+}
+
+impl ContextError<&str> for JsonError {
+    fn add_context(input: &str, ctx: &'static str, mut other: Self) -> Self {
+        if let JsonError::Syntax { context, .. } = &mut other {
+            context.push((ctx, input.len()));
+        }
+
+        other
+    }
+}
+
+fn parse_str<'a, E: ParseError<&'a str>>(i: &'a str) -> Result<'a, &'a str, E> {
+    escaped(alphanumeric, '\\', one_of("\"n\\"))(i)
+}
+
+fn array<'a, E: ParseError<&'a str> + ContextError<&'a str>>(
+    i: &'a str,
+    opts: &ParseOptions,
+    path: &JsonPath,
+) -> Result<'a, Vec<JsonValue>, E> {
+    let mut index = 0;
+
+    context(
+        "array",
+        delimited(
+            cut(char('[')),
+            cut(separated_list0(
+                preceded(multispace0, char(',')),
+                move |i| {
+                    let (i, next_char) = peek(anychar)(i)?;
+
+                    if next_char == ']' {
+                        return Err(nom::Err::Error(E::from_error_kind(i, ErrorKind::Char)));
+                    }
+
+                    path.push_index(index);
+                    let (i, value) = cut(|i| json_value(i, opts, path))(i)?;
+                    path.pop();
+                    index += 1;
+
+                    Ok((i, value))
+                },
+            )),
+            preceded(multispace0, char(']')),
+        ),
+    )(i)
+}
+
+fn key_value<'a, E: ParseError<&'a str> + ContextError<&'a str>>(
+    i: &'a str,
+    opts: &ParseOptions,
+    path: &JsonPath,
+) -> Result<'a, (String, JsonValue), E> {
+    let (i, _) = multispace0(i)?;
+
+    let (i, next_char) = peek(anychar)(i)?;
+
+    if next_char == '}' {
+        return Err(nom::Err::Error(E::from_error_kind(i, ErrorKind::Char)));
+    }
+
+    let (i, key) = match opts.lone_surrogates {
+        SurrogateMode::Reject => string(i)?,
+        // Keys are `String`s
+        _ => map(
+            |i| wtf8_string(i, SurrogateMode::Replace),
+            |bytes| String::from_utf8(bytes).expect("surrogates are replaced"),
+        )(i)?,
+    };
+    let (i, _) = cut(preceded(multispace0, char(':')))(i)?;
+
+    path.push_key(key);
+    let (i, value) = cut(|i| json_value(i, opts, path))(i)?;
+
+    Ok((i, (path.pop_key(), value)))
+}
+
+fn hash<'a, E: ParseError<&'a str> + ContextError<&'a str>>(
+    i: &'a str,
+    opts: &ParseOptions,
+    path: &JsonPath,
+) -> Result<'a, Map<String, JsonValue>, E> {
+    context(
+        "map",
+        preceded(
+            cut(tag("{")),
+            cut(terminated(
+                map(
+                    separated_list0(preceded(multispace0, char(',')), |i| {
+                        key_value(i, opts, path)
+                    }),
+                    |tuple_vec| tuple_vec.into_iter().collect(),
+                ),
+                preceded(multispace0, char('}')),
+            )),
+        ),
+    )
+    .parse(i)
+}
+
+fn number_value<'a, E: ParseError<&'a str>>(
+    i: &'a str,
+    opts: &ParseOptions,
+) -> Result<'a, JsonValue, E> {
+    let (rest, n) = number_with(i, &opts.number_backend)?;
+
+    match (n, opts.number_mode) {
+        #[cfg(feature = "bignum")]
+        (Number::Float(_), NumberMode::BigNumber) => {
+            let text = &i[..i.len() - rest.len()];
+
+            match BigNumber::parse(text) {
+                Some(n) => Ok((rest, JsonValue::BigNum(n))),
+                None => Err(nom::Err::Failure(E::from_error_kind(i, ErrorKind::Float))),
+            }
+        }
+        (n, NumberMode::Preserve) => {
+            let value = match n {
+                Number::Int(n) => n as f64,
+                Number::UInt(n) => n as f64,
+                Number::Float(n) => n,
+            };
+
+            Ok((
+                rest,
+                JsonValue::RawNum(value, i[..i.len() - rest.len()].to_owned()),
+            ))
+        }
+        // `to_string` couldn't write it back
+        (Number::Float(n), _) if !n.is_finite() => {
+            Err(nom::Err::Failure(E::from_error_kind(i, ErrorKind::Float)))
+        }
+        (n, _) => Ok((rest, n.into())),
+    }
+}
+
+fn string_value<'a, E: ParseError<&'a str> + ContextError<&'a str>>(
+    i: &'a str,
+    opts: &ParseOptions,
+) -> Result<'a, JsonValue, E> {
+    match opts.lone_surrogates {
+        SurrogateMode::Reject => map(string, JsonValue::Str)(i),
+        mode => map(
+            |i| wtf8_string(i, mode),
+            // Surrogates make it invalid UTF-8
+            |bytes| match String::from_utf8(bytes) {
+                Ok(s) => JsonValue::Str(s),
+                Err(e) => JsonValue::Wtf8(e.into_bytes()),
+            },
+        )(i),
+    }
+}
+
+fn json_value<'a, E: ParseError<&'a str> + ContextError<&'a str>>(
+    i: &'a str,
+    opts: &ParseOptions,
+    path: &JsonPath,
+) -> Result<'a, JsonValue, E> {
+    let (i, _) = many0(multispace1)(i)?;
+
+    let (i, first_char) = peek(anychar)(i)?;
+
+    match first_char {
+        '{' => map(|i| hash(i, opts, path), JsonValue::Object)(i),
+        '[' => map(|i| array(i, opts, path), JsonValue::Array)(i),
+        '"' => string_value(i, opts),
+        '-' | '0'..='9' => number_value(i, opts),
+        'f' => map(parse_false, JsonValue::Boolean)(i),
+        't' => map(parse_true, JsonValue::Boolean)(i),
+        'n' => map(null, |_| JsonValue::Null)(i),
+        c => Err(nom::Err::Failure(E::from_error_kind(i, ErrorKind::Char))),
+    }
+}
+
+/// Start of the document in `i`: after the byte order mark that files
+/// written by Windows tools start with, and after the junk `opts` allows
+fn document_start<'a>(i: &'a str, opts: &ParseOptions) -> &'a str {
+    let i = i.strip_prefix('\u{FEFF}').unwrap_or(i);
+
+    match i.find(['{', '[']) {
+        Some(start) if opts.skip_leading_junk => &i[start..],
+        _ => i,
+    }
+}
+
+pub fn parse(i: &str) -> Result<'_, JsonValue, JsonError> {
+    parse_with_options(i, &ParseOptions::default())
+}
+
+/// For hot paths: the error is `()`, so failing costs nothing, but it only
+/// tells that the input is invalid
+pub fn parse_fast(i: &str) -> Result<'_, JsonValue, ()> {
+    terminated(
+        |i| json_value(i, &ParseOptions::default(), &JsonPath::default()),
+        multispace0,
+    )
+    .parse(document_start(i, &ParseOptions::default()))
+}
+
+/// For debugging: the error keeps every `context(...)` on the way to the
+/// failure, to be displayed with `nom::error::convert_error`
+pub fn parse_verbose(i: &str) -> Result<'_, JsonValue, VerboseError<&str>> {
+    terminated(
+        |i| json_value(i, &ParseOptions::default(), &JsonPath::default()),
+        multispace0,
+    )
+    .parse(document_start(i, &ParseOptions::default()))
+}
+
+/// ```
+/// use step_4::ParseOptions;
+///
+/// let opts = ParseOptions {
+///     skip_leading_junk: true,
+///     ..Default::default()
+/// };
+/// let (_, value) = step_4::parse_with_options(")]}',\n[1]", &opts).unwrap();
+///
+/// assert_eq!(value, step_4::parse_with_path("\u{FEFF}[1]").unwrap());
+/// ```
+pub fn parse_with_options<'a>(i: &'a str, opts: &ParseOptions) -> Result<'a, JsonValue, JsonError> {
+    terminated(|i| json_value(i, opts, &JsonPath::default()), multispace0)
+        .parse(document_start(i, opts))
+}
+
+/// Like [`parse`], but the error tells where in the document it happened,
+/// e.g. `invalid number at $.statuses[17].user.id`
+pub fn parse_with_path(i: &str) -> core::result::Result<JsonValue, JsonError> {
+    let opts = ParseOptions::default();
+    let path = JsonPath::default();
+
+    let result = all_consuming(terminated(
+        |i| json_value::<JsonError>(i, &opts, &path),
+        multispace0,
+    ))(document_start(i, &opts))
+    .finish();
+
+    match result {
+        Ok((_, value)) => Ok(value),
+        Err(e) => Err(JsonError::Path(path.to_string(), Box::new(e))),
+    }
+}
+
+/// Parses a whole document, telling apart an input that is wrong from one
+/// that ended too early, like `{"a": [1, 2`, with [`JsonError::NeedMoreData`].
+///
+/// A number at the very end of the input, like `12`, is considered complete.
+///
+/// ```
+/// use step_4::{parse_complete, JsonError};
+///
+/// assert!(matches!(parse_complete(r#"{"a": [1, 2"#), Err(JsonError::NeedMoreData)));
+/// assert!(matches!(parse_complete("[1, tr"), Err(JsonError::NeedMoreData)));
+/// assert!(matches!(parse_complete("[1e"), Err(JsonError::NeedMoreData)));
+/// assert!(matches!(parse_complete("[-1.5e+"), Err(JsonError::NeedMoreData)));
+/// assert_eq!(JsonError::NeedMoreData.to_string(), "unexpected end of input");
+///
+/// // Whatever follows a whole document is an error
+/// let error = parse_complete("{} tr").unwrap_err();
+/// assert_eq!(error.to_string(), "trailing characters");
+/// ```
+pub fn parse_complete(i: &str) -> core::result::Result<JsonValue, JsonError> {
+    let opts = ParseOptions::default();
+    let path = JsonPath::default();
+
+    let result = all_consuming(terminated(
+        |i| json_value::<JsonError>(i, &opts, &path),
+        multispace0,
+    ))(document_start(i, &opts))
+    .finish();
+
+    match result {
+        Ok((_, value)) => Ok(value),
+        Err(e) if ends_early(i, &e) => Err(JsonError::NeedMoreData),
+        Err(e) => Err(e),
+    }
+}
+
+/// Whether the input could still be valid with more data after it
+fn ends_early(input: &str, error: &JsonError) -> bool {
+    let JsonError::Syntax {
+        remaining, kind, ..
+    } = error
+    else {
+        return false;
+    };
+    // What follows a whole document, which more data can't fix
+    if *kind == ErrorKind::Eof {
+        return false;
+    }
+
+    let offset = input.len() - remaining;
+    let rest = &input[offset..];
+
+    // A sign, or an exponent or fraction without its digits yet
+    let truncated_number = rest.ends_with(['-', '+', '.', 'e', 'E'])
+        && rest
+            .bytes()
+            .all(|b| b.is_ascii_digit() || b"-+.eE".contains(&b));
+    let truncated_token = truncated_number
+        || ["true", "false", "null"]
+            .iter()
+            .any(|literal| literal.starts_with(rest));
+
+    truncated_token || unterminated_string_start(input).is_some_and(|start| start <= offset)
+}
+
+/// Offset of the string the input ends in, if any
+fn unterminated_string_start(input: &str) -> Option<usize> {
+    let mut start = None;
+    let mut escaped = false;
+
+    for (index, byte) in input.bytes().enumerate() {
+        match (start, byte) {
+            (Some(_), _) if escaped => escaped = false,
+            (Some(_), b'\\') => escaped = true,
+            (Some(_), b'"') => start = None,
+            (None, b'"') => start = Some(index),
+            _ => {}
+        }
+    }
+
+    start
+}
+
+/// Parses whitespace-separated concatenated documents (`{}{}{}`).
+///
+/// Parsing stops at the first document that can't be parsed, typically a
+/// truncated one at the end of a chunk, which is returned as the remainder so
+/// it can be completed with more data.
+pub fn parse_all(i: &str) -> (&str, Vec<JsonValue>) {
+    let mut i = i;
+    let mut values = Vec::new();
+
+    while let Ok((rest, value)) =
+        json_value::<Error<&str>>(i, &ParseOptions::default(), &JsonPath::default())
+    {
+        values.push(value);
+        i = rest.trim_start_matches([' ', '\t', '\r', '\n']);
+    }
+
+    (i, values)
+}
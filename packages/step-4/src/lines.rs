@@ -0,0 +1,109 @@
+//! [JSON Lines](https://jsonlines.org/) / NDJSON: one document per line.
+
+use alloc::{boxed::Box, vec::Vec};
+use nom::{
+    character::complete::multispace0, combinator::all_consuming, sequence::terminated, Finish,
+};
+#[cfg(feature = "std")]
+use std::{num::NonZeroUsize, thread};
+
+use crate::{json_value, path::JsonPath, JsonError, JsonValue, ParseOptions};
+
+/// Parses every non-blank line of `input` as a standalone JSON value.
+///
+/// Errors are wrapped in [`JsonError::Line`] so they can be traced back to
+/// the offending line, and don't stop the iteration.
+pub fn parse_lines(input: &str) -> impl Iterator<Item = Result<JsonValue, JsonError>> + '_ {
+    documents(input).map(|(index, line)| parse_line(index, line))
+}
+
+/// Non-blank lines, with their index
+fn documents(input: &str) -> impl Iterator<Item = (usize, &str)> {
+    input
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+}
+
+fn parse_line(index: usize, line: &str) -> Result<JsonValue, JsonError> {
+    all_consuming(terminated(
+        |i| json_value(i, &ParseOptions::default(), &JsonPath::default()),
+        multispace0,
+    ))(line)
+    .finish()
+    .map(|(_, value)| value)
+    .map_err(|error| JsonError::Line(index + 1, Box::new(error)))
+}
+
+/// Lines parsed by each thread at a time
+#[cfg(feature = "std")]
+const LINES_PER_THREAD: usize = 1024;
+
+/// Like [`parse_lines`], but the lines are parsed on every core, a batch at
+/// a time. Values come out in the order of the lines all the same.
+///
+/// Only one batch is kept in memory, so the values can be consumed as they
+/// come, like with [`parse_lines`].
+#[cfg(feature = "std")]
+pub fn par_parse_lines(input: &str) -> ParLines<'_> {
+    let threads = thread::available_parallelism().map_or(1, NonZeroUsize::get);
+
+    ParLines {
+        documents: Box::new(documents(input)),
+        parsed: Vec::new().into_iter(),
+        threads,
+    }
+}
+
+/// Iterator returned by [`par_parse_lines`]
+#[cfg(feature = "std")]
+pub struct ParLines<'a> {
+    documents: Box<dyn Iterator<Item = (usize, &'a str)> + Send + 'a>,
+    /// Values of the current batch, not yielded yet
+    parsed: alloc::vec::IntoIter<Result<JsonValue, JsonError>>,
+    threads: usize,
+}
+
+#[cfg(feature = "std")]
+impl Iterator for ParLines<'_> {
+    type Item = Result<JsonValue, JsonError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(value) = self.parsed.next() {
+            return Some(value);
+        }
+
+        let batch: Vec<_> = self
+            .documents
+            .by_ref()
+            .take(self.threads * LINES_PER_THREAD)
+            .collect();
+
+        if batch.is_empty() {
+            return None;
+        }
+
+        let per_thread = batch.len().div_ceil(self.threads);
+
+        let parsed = thread::scope(|scope| {
+            let handles: Vec<_> = batch
+                .chunks(per_thread)
+                .map(|run| {
+                    scope.spawn(move || {
+                        run.iter()
+                            .map(|&(index, line)| parse_line(index, line))
+                            .collect::<Vec<_>>()
+                    })
+                })
+                .collect();
+
+            handles
+                .into_iter()
+                .flat_map(|handle| handle.join().unwrap())
+                .collect::<Vec<_>>()
+        });
+
+        self.parsed = parsed.into_iter();
+        self.parsed.next()
+    }
+}
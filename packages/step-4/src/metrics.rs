@@ -0,0 +1,393 @@
+//! Parser instrumentation: what a document is made of, and where the time
+//! goes while parsing it.
+//!
+//! This is a parser of its own, built on the same lexer as [`crate::parse`],
+//! which reads the clock around every value: it is slower, and only meant to
+//! explain the performance of the regular one.
+
+use nom::{
+    character::complete::{anychar, char, multispace0},
+    combinator::{cut, peek},
+    error::{ErrorKind, ParseError},
+    Finish,
+};
+use std::{
+    collections::HashMap,
+    fmt::Display,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    lexer::{null, number, parse_false, parse_true, string},
+    JsonError, JsonValue, Map, Result,
+};
+
+/// One `T` per kind of value
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct PerKind<T> {
+    pub null: T,
+    pub boolean: T,
+    pub number: T,
+    /// Keys included
+    pub string: T,
+    pub array: T,
+    pub object: T,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Metrics {
+    /// Values parsed
+    pub counts: PerKind<usize>,
+    /// Object keys, also counted in `counts.string`
+    pub keys: usize,
+    /// Nesting of the deepest value, the root being at depth 0
+    pub max_depth: usize,
+    /// Bytes of the input taken by each kind of scalar. Those of arrays and
+    /// objects are the structure: brackets, commas, colons and whitespace
+    pub bytes: PerKind<usize>,
+    /// Time spent in each kind of value, excluding its children
+    pub time: PerKind<Duration>,
+}
+
+#[derive(Clone, Copy)]
+enum Kind {
+    Null,
+    Boolean,
+    Number,
+    String,
+    Array,
+    Object,
+}
+
+impl<T> PerKind<T> {
+    fn get_mut(&mut self, kind: Kind) -> &mut T {
+        match kind {
+            Kind::Null => &mut self.null,
+            Kind::Boolean => &mut self.boolean,
+            Kind::Number => &mut self.number,
+            Kind::String => &mut self.string,
+            Kind::Array => &mut self.array,
+            Kind::Object => &mut self.object,
+        }
+    }
+}
+
+/// Table of the metrics, for the terminal
+impl Display for Metrics {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let total = self.time.null
+            + self.time.boolean
+            + self.time.number
+            + self.time.string
+            + self.time.array
+            + self.time.object;
+        let rows = [
+            ("null", self.counts.null, self.bytes.null, self.time.null),
+            (
+                "boolean",
+                self.counts.boolean,
+                self.bytes.boolean,
+                self.time.boolean,
+            ),
+            (
+                "number",
+                self.counts.number,
+                self.bytes.number,
+                self.time.number,
+            ),
+            (
+                "string",
+                self.counts.string,
+                self.bytes.string,
+                self.time.string,
+            ),
+            (
+                "array",
+                self.counts.array,
+                self.bytes.array,
+                self.time.array,
+            ),
+            (
+                "object",
+                self.counts.object,
+                self.bytes.object,
+                self.time.object,
+            ),
+        ];
+
+        writeln!(
+            f,
+            "{:<8} {:>10} {:>12} {:>12}",
+            "kind", "count", "bytes", "time"
+        )?;
+        for (kind, count, bytes, time) in rows {
+            let share = time.as_secs_f64() / total.as_secs_f64().max(f64::MIN_POSITIVE);
+            writeln!(
+                f,
+                "{kind:<8} {count:>10} {bytes:>12} {:>12} ({:.0}%)",
+                format!("{time:.2?}"),
+                share * 100.0
+            )?;
+        }
+        write!(f, "{} keys, max depth {}", self.keys, self.max_depth)
+    }
+}
+
+/// Parses a whole document like [`parse_complete`](crate::parse_complete)
+/// would, and tells what it was made of
+pub fn parse_with_metrics(input: &str) -> std::result::Result<(JsonValue, Metrics), JsonError> {
+    let mut recorder = Recorder::default();
+
+    let (rest, (value, _)) = recorder.value(input, 0).finish()?;
+    let (rest, _) = multispace0::<_, JsonError>(rest).finish()?;
+
+    if !rest.is_empty() {
+        return Err(JsonError::from_error_kind(rest, ErrorKind::Eof));
+    }
+
+    let metrics = &mut recorder.metrics;
+    let scalars =
+        metrics.bytes.null + metrics.bytes.boolean + metrics.bytes.number + metrics.bytes.string;
+    // Split between arrays and objects by the number of each
+    let structure = input.len() - scalars;
+    let containers = metrics.counts.array + metrics.counts.object;
+    metrics.bytes.array = structure * metrics.counts.array / containers.max(1);
+    metrics.bytes.object = structure - metrics.bytes.array;
+
+    Ok((value, recorder.metrics))
+}
+
+#[derive(Default)]
+struct Recorder {
+    metrics: Metrics,
+}
+
+impl Recorder {
+    /// Records a value of `kind` spanning from `i` to `rest`, parsed in
+    /// `elapsed` of which `children` were spent in its children
+    fn record(&mut self, kind: Kind, i: &str, rest: &str, elapsed: Duration, children: Duration) {
+        let len = i.len() - rest.len();
+
+        match kind {
+            Kind::Array | Kind::Object => {}
+            Kind::Null => self.metrics.bytes.null += len,
+            Kind::Boolean => self.metrics.bytes.boolean += len,
+            Kind::Number => self.metrics.bytes.number += len,
+            Kind::String => self.metrics.bytes.string += len,
+        }
+
+        *self.metrics.time.get_mut(kind) += elapsed.saturating_sub(children);
+        *self.metrics.counts.get_mut(kind) += 1;
+    }
+
+    /// The value and the time it took to parse it
+    fn value<'a>(
+        &mut self,
+        i: &'a str,
+        depth: usize,
+    ) -> Result<'a, (JsonValue, Duration), JsonError> {
+        let (i, _) = multispace0(i)?;
+        let (_, first_char) = peek(anychar)(i)?;
+
+        self.metrics.max_depth = self.metrics.max_depth.max(depth);
+        let start = Instant::now();
+        let mut children = Duration::ZERO;
+
+        let (rest, value, kind) = match first_char {
+            '{' => {
+                let (rest, map) = self.object(i, depth, &mut children)?;
+                (rest, JsonValue::Object(map), Kind::Object)
+            }
+            '[' => {
+                let (rest, values) = self.array(i, depth, &mut children)?;
+                (rest, JsonValue::Array(values), Kind::Array)
+            }
+            '"' => {
+                let (rest, s) = string(i)?;
+                (rest, JsonValue::Str(s), Kind::String)
+            }
+            '-' | '0'..='9' => {
+                let (rest, n) = number(i)?;
+                (rest, n.into(), Kind::Number)
+            }
+            't' => {
+                let (rest, b) = parse_true(i)?;
+                (rest, JsonValue::Boolean(b), Kind::Boolean)
+            }
+            'f' => {
+                let (rest, b) = parse_false(i)?;
+                (rest, JsonValue::Boolean(b), Kind::Boolean)
+            }
+            'n' => {
+                let (rest, _) = null(i)?;
+                (rest, JsonValue::Null, Kind::Null)
+            }
+            _ => {
+                return Err(nom::Err::Failure(JsonError::from_error_kind(
+                    i,
+                    ErrorKind::Char,
+                )))
+            }
+        };
+
+        let elapsed = start.elapsed();
+        self.record(kind, i, rest, elapsed, children);
+
+        Ok((rest, (value, elapsed)))
+    }
+
+    fn array<'a>(
+        &mut self,
+        i: &'a str,
+        depth: usize,
+        children: &mut Duration,
+    ) -> Result<'a, Vec<JsonValue>, JsonError> {
+        let (i, _) = char('[')(i)?;
+        let mut values = Vec::new();
+
+        let (rest, _) = multispace0(i)?;
+        if let Some(rest) = rest.strip_prefix(']') {
+            return Ok((rest, values));
+        }
+
+        let mut i = i;
+        loop {
+            let (rest, (value, elapsed)) = cut(|i| self.value(i, depth + 1))(i)?;
+            values.push(value);
+            *children += elapsed;
+
+            let (rest, _) = multispace0(rest)?;
+            match rest.chars().next() {
+                Some(',') => i = &rest[1..],
+                Some(']') => return Ok((&rest[1..], values)),
+                _ => return Err(nom::Err::Failure(JsonError::from_char(rest, ']'))),
+            }
+        }
+    }
+
+    fn object<'a>(
+        &mut self,
+        i: &'a str,
+        depth: usize,
+        children: &mut Duration,
+    ) -> Result<'a, Map<String, JsonValue>, JsonError> {
+        let (i, _) = char('{')(i)?;
+        let mut map = Map::default();
+
+        let (rest, _) = multispace0(i)?;
+        if let Some(rest) = rest.strip_prefix('}') {
+            return Ok((rest, map));
+        }
+
+        let mut i = i;
+        loop {
+            let (key_start, _) = multispace0(i)?;
+            let start = Instant::now();
+            let (rest, key) = cut(string)(key_start)?;
+            let elapsed = start.elapsed();
+            self.record(Kind::String, key_start, rest, elapsed, Duration::ZERO);
+            self.metrics.keys += 1;
+            *children += elapsed;
+
+            let (rest, _) = cut(|i| {
+                let (i, _) = multispace0(i)?;
+                char(':')(i)
+            })(rest)?;
+
+            let (rest, (value, elapsed)) = cut(|i| self.value(i, depth + 1))(rest)?;
+            map.insert(key, value);
+            *children += elapsed;
+
+            let (rest, _) = multispace0(rest)?;
+            match rest.chars().next() {
+                Some(',') => i = &rest[1..],
+                Some('}') => return Ok((&rest[1..], map)),
+                _ => return Err(nom::Err::Failure(JsonError::from_char(rest, '}'))),
+            }
+        }
+    }
+}
+
+/// What a value is made of, see [`JsonValue::stats`]
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Stats {
+    /// Values of the tree, the root included. Keys aren't values
+    pub counts: PerKind<usize>,
+    /// Nesting of the deepest value, the root being at depth 0
+    pub max_depth: usize,
+    /// Bytes of the strings once unescaped, keys included
+    pub string_bytes: usize,
+    /// How many times each key appears, in any object
+    pub keys: HashMap<String, usize>,
+}
+
+impl Stats {
+    /// The `n` most frequent keys, ties in key order
+    pub fn top_keys(&self, n: usize) -> Vec<(&str, usize)> {
+        let mut keys: Vec<_> = self
+            .keys
+            .iter()
+            .map(|(key, count)| (key.as_str(), *count))
+            .collect();
+        keys.sort_unstable_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+        keys.truncate(n);
+        keys
+    }
+
+    fn add(&mut self, value: &JsonValue, depth: usize) {
+        self.max_depth = self.max_depth.max(depth);
+
+        let kind = match value {
+            JsonValue::Null => Kind::Null,
+            JsonValue::Boolean(_) => Kind::Boolean,
+            JsonValue::Str(s) => {
+                self.string_bytes += s.len();
+                Kind::String
+            }
+            JsonValue::Wtf8(bytes) => {
+                self.string_bytes += bytes.len();
+                Kind::String
+            }
+            JsonValue::Array(values) => {
+                for value in values {
+                    self.add(value, depth + 1);
+                }
+                Kind::Array
+            }
+            JsonValue::Object(map) => {
+                for (key, value) in map {
+                    self.string_bytes += key.len();
+                    *self.keys.entry(key.clone()).or_default() += 1;
+                    self.add(value, depth + 1);
+                }
+                Kind::Object
+            }
+            // Whatever their representation
+            _ => Kind::Number,
+        };
+
+        *self.counts.get_mut(kind) += 1;
+    }
+}
+
+impl JsonValue {
+    /// Depth, values of each kind and keys of the tree, to get to know a
+    /// document before writing the code that reads it
+    ///
+    /// ```
+    /// let value =
+    ///     step_4::parse_with_path(r#"[{"id": 1, "tags": ["rust"]}, {"id": 2}]"#).unwrap();
+    /// let stats = value.stats();
+    ///
+    /// assert_eq!(stats.max_depth, 3);
+    /// assert_eq!(stats.counts.object, 2);
+    /// assert_eq!(stats.counts.number, 2);
+    /// assert_eq!(stats.string_bytes, 12);
+    /// assert_eq!(stats.top_keys(2), [("id", 2), ("tags", 1)]);
+    /// ```
+    pub fn stats(&self) -> Stats {
+        let mut stats = Stats::default();
+        stats.add(self, 0);
+        stats
+    }
+}
@@ -0,0 +1,127 @@
+//! Parsing of files mapped in memory, rather than copied in a `String`
+//! first with `read_to_string`.
+//!
+//! The mapping is done with the `mmap` of the C library, which std already
+//! links to. On other platforms than Unix, the file is read as usual.
+
+use std::{fs::File, io, path::Path};
+
+use crate::{parse_with_path, JsonError, JsonValue};
+
+#[cfg(unix)]
+mod sys {
+    use std::{
+        ffi::c_void,
+        fs::File,
+        io,
+        ops::Deref,
+        os::{
+            fd::AsRawFd,
+            raw::{c_int, c_long},
+        },
+        ptr, slice,
+    };
+
+    const PROT_READ: c_int = 1;
+    const MAP_PRIVATE: c_int = 2;
+
+    extern "C" {
+        fn mmap(
+            addr: *mut c_void,
+            len: usize,
+            prot: c_int,
+            flags: c_int,
+            fd: c_int,
+            offset: c_long,
+        ) -> *mut c_void;
+        fn munmap(addr: *mut c_void, len: usize) -> c_int;
+    }
+
+    /// Read-only private mapping of a whole file
+    pub(super) struct Mmap {
+        ptr: *mut c_void,
+        len: usize,
+    }
+
+    impl Mmap {
+        pub(super) fn map(file: &File) -> io::Result<Self> {
+            let len = usize::try_from(file.metadata()?.len())
+                .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "file too large"))?;
+
+            // An empty mapping is an error, and not needed to read nothing
+            if len == 0 {
+                return Ok(Self {
+                    ptr: ptr::null_mut(),
+                    len,
+                });
+            }
+
+            // SAFETY: a new mapping is asked for, with a valid descriptor and
+            // the length of the file
+            let ptr = unsafe {
+                mmap(
+                    ptr::null_mut(),
+                    len,
+                    PROT_READ,
+                    MAP_PRIVATE,
+                    file.as_raw_fd(),
+                    0,
+                )
+            };
+
+            // `MAP_FAILED`
+            if ptr as isize == -1 {
+                return Err(io::Error::last_os_error());
+            }
+
+            Ok(Self { ptr, len })
+        }
+    }
+
+    impl Deref for Mmap {
+        type Target = [u8];
+
+        fn deref(&self) -> &[u8] {
+            if self.len == 0 {
+                return &[];
+            }
+
+            // SAFETY: the `len` bytes at `ptr` stay mapped until `drop`
+            unsafe { slice::from_raw_parts(self.ptr as *const u8, self.len) }
+        }
+    }
+
+    impl Drop for Mmap {
+        fn drop(&mut self) {
+            if self.len > 0 {
+                // SAFETY: `ptr` and `len` are those of our own mapping
+                unsafe { munmap(self.ptr, self.len) };
+            }
+        }
+    }
+}
+
+/// Parses the file at `path` straight from its pages in memory, with errors
+/// like [`parse_with_path`]. I/O errors are returned as [`JsonError::Io`].
+///
+/// The file must not be changed by another process while it is parsed,
+/// which the bytes of the mapping would reflect.
+pub fn parse_file(path: impl AsRef<Path>) -> Result<JsonValue, JsonError> {
+    let file = File::open(path).map_err(JsonError::Io)?;
+
+    #[cfg(unix)]
+    let bytes = sys::Mmap::map(&file).map_err(JsonError::Io)?;
+    #[cfg(not(unix))]
+    let bytes = {
+        use std::io::Read;
+
+        let mut bytes = Vec::new();
+        (&file).read_to_end(&mut bytes).map_err(JsonError::Io)?;
+        bytes
+    };
+
+    let text = std::str::from_utf8(&bytes)
+        .map_err(|e| JsonError::Io(io::Error::new(io::ErrorKind::InvalidData, e)))?;
+
+    parse_with_path(text)
+}
@@ -0,0 +1,273 @@
+//! [MessagePack](https://msgpack.org/) encoding of values: smaller than JSON
+//! text and faster to read back, for caches and transport.
+//!
+//! Integers take the smallest encoding that fits them, and other numbers are
+//! 64-bit floats, which is what `Num` holds: `RawNum` loses its text on the
+//! way, and `BigNum` its digits past the precision of an `f64`.
+
+use alloc::{format, string::String, vec::Vec};
+
+use crate::{wtf8, JsonError, JsonValue, Map};
+
+impl JsonValue {
+    /// ```
+    /// let value = step_4::parse_with_path(r#"{"compact": true, "schema": 0}"#).unwrap();
+    /// let bytes = value.to_msgpack();
+    ///
+    /// assert_eq!(bytes.len(), 18);
+    /// assert_eq!(step_4::from_msgpack(&bytes).unwrap(), value);
+    /// ```
+    pub fn to_msgpack(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        encode(&mut out, self);
+        out
+    }
+}
+
+/// Marker of a length, in its fix, 16-bit and 32-bit variants
+fn encode_len(out: &mut Vec<u8>, len: usize, fix: (u8, usize), long: (u8, u8)) {
+    let (fix_marker, fix_max) = fix;
+
+    if len <= fix_max {
+        out.push(fix_marker | len as u8);
+    } else if let Ok(len) = u16::try_from(len) {
+        out.push(long.0);
+        out.extend_from_slice(&len.to_be_bytes());
+    } else {
+        out.push(long.1);
+        out.extend_from_slice(&(len as u32).to_be_bytes());
+    }
+}
+
+fn encode_int(out: &mut Vec<u8>, n: i64) {
+    match n {
+        -32..=0x7f => out.push(n as u8),
+        0x80..=0xff => out.extend_from_slice(&[0xcc, n as u8]),
+        0x100..=0xffff => {
+            out.push(0xcd);
+            out.extend_from_slice(&(n as u16).to_be_bytes());
+        }
+        0x1_0000..=0xffff_ffff => {
+            out.push(0xce);
+            out.extend_from_slice(&(n as u32).to_be_bytes());
+        }
+        0x1_0000_0000.. => {
+            out.push(0xcf);
+            out.extend_from_slice(&(n as u64).to_be_bytes());
+        }
+        -0x80..=-33 => out.extend_from_slice(&[0xd0, n as u8]),
+        -0x8000..=-0x81 => {
+            out.push(0xd1);
+            out.extend_from_slice(&(n as i16).to_be_bytes());
+        }
+        -0x8000_0000..=-0x8001 => {
+            out.push(0xd2);
+            out.extend_from_slice(&(n as i32).to_be_bytes());
+        }
+        _ => {
+            out.push(0xd3);
+            out.extend_from_slice(&n.to_be_bytes());
+        }
+    }
+}
+
+fn encode_str(out: &mut Vec<u8>, s: &str) {
+    encode_len(out, s.len(), (0xa0, 31), (0xda, 0xdb));
+    out.extend_from_slice(s.as_bytes());
+}
+
+fn encode_float(out: &mut Vec<u8>, n: f64) {
+    out.push(0xcb);
+    out.extend_from_slice(&n.to_be_bytes());
+}
+
+fn encode(out: &mut Vec<u8>, value: &JsonValue) {
+    match value {
+        JsonValue::Null => out.push(0xc0),
+        JsonValue::Boolean(false) => out.push(0xc2),
+        JsonValue::Boolean(true) => out.push(0xc3),
+        JsonValue::Int(n) => encode_int(out, *n),
+        JsonValue::UInt(n) => {
+            out.push(0xcf);
+            out.extend_from_slice(&n.to_be_bytes());
+        }
+        JsonValue::Num(n) | JsonValue::RawNum(n, _) => encode_float(out, *n),
+        #[cfg(feature = "bignum")]
+        JsonValue::BigNum(n) => encode_float(out, n.to_f64()),
+        JsonValue::Str(s) => encode_str(out, s),
+        // Text is UTF-8
+        JsonValue::Wtf8(bytes) => encode_str(out, &wtf8::to_string_lossy(bytes)),
+        JsonValue::Array(values) => {
+            encode_len(out, values.len(), (0x90, 15), (0xdc, 0xdd));
+            for value in values {
+                encode(out, value);
+            }
+        }
+        JsonValue::Object(map) => {
+            encode_len(out, map.len(), (0x80, 15), (0xde, 0xdf));
+            for (key, value) in map {
+                encode_str(out, key);
+                encode(out, value);
+            }
+        }
+    }
+}
+
+/// Decodes one value, which must span all of `bytes`.
+///
+/// Binary data, extension types and non-string keys have no JSON
+/// counterpart and are refused. A truncated input is a
+/// [`JsonError::NeedMoreData`].
+pub fn from_msgpack(bytes: &[u8]) -> Result<JsonValue, JsonError> {
+    let mut decoder = Decoder { bytes, pos: 0 };
+    let value = decoder.value()?;
+
+    if decoder.pos < bytes.len() {
+        return Err(decoder.error("trailing bytes after the value"));
+    }
+
+    Ok(value)
+}
+
+struct Decoder<'b> {
+    bytes: &'b [u8],
+    pos: usize,
+}
+
+impl<'b> Decoder<'b> {
+    fn error(&self, message: &str) -> JsonError {
+        JsonError::Custom(format!("{message} at byte {}", self.pos))
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'b [u8], JsonError> {
+        let end = self
+            .pos
+            .checked_add(len)
+            .filter(|&end| end <= self.bytes.len())
+            .ok_or(JsonError::NeedMoreData)?;
+        let taken = &self.bytes[self.pos..end];
+        self.pos = end;
+
+        Ok(taken)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], JsonError> {
+        Ok(self.take(N)?.try_into().unwrap())
+    }
+
+    fn len(&mut self, width: usize) -> Result<usize, JsonError> {
+        Ok(match width {
+            1 => self.array::<1>()?[0] as usize,
+            2 => u16::from_be_bytes(self.array()?) as usize,
+            _ => u32::from_be_bytes(self.array()?) as usize,
+        })
+    }
+
+    fn str(&mut self, len: usize) -> Result<String, JsonError> {
+        let start = self.pos;
+        let bytes = self.take(len)?;
+
+        match core::str::from_utf8(bytes) {
+            Ok(s) => Ok(s.into()),
+            Err(e) => {
+                self.pos = start + e.valid_up_to();
+                Err(self.error("invalid UTF-8"))
+            }
+        }
+    }
+
+    fn values(&mut self, len: usize) -> Result<Vec<JsonValue>, JsonError> {
+        // Every value takes a byte at least: a corrupted length can't make
+        // us allocate more than the input
+        let mut values = Vec::with_capacity(len.min(self.bytes.len() - self.pos));
+
+        for _ in 0..len {
+            values.push(self.value()?);
+        }
+
+        Ok(values)
+    }
+
+    fn map(&mut self, len: usize) -> Result<Map<String, JsonValue>, JsonError> {
+        let mut map = Map::default();
+
+        for _ in 0..len {
+            let key = match self.array::<1>()?[0] {
+                marker @ 0xa0..=0xbf => self.str((marker & 0x1f) as usize)?,
+                0xd9 => {
+                    let len = self.len(1)?;
+                    self.str(len)?
+                }
+                0xda => {
+                    let len = self.len(2)?;
+                    self.str(len)?
+                }
+                0xdb => {
+                    let len = self.len(4)?;
+                    self.str(len)?
+                }
+                _ => {
+                    self.pos -= 1;
+                    return Err(self.error("keys must be strings"));
+                }
+            };
+
+            let value = self.value()?;
+            map.insert(key, value);
+        }
+
+        Ok(map)
+    }
+
+    fn value(&mut self) -> Result<JsonValue, JsonError> {
+        let marker = self.array::<1>()?[0];
+
+        Ok(match marker {
+            0x00..=0x7f => JsonValue::Int(marker as i64),
+            0x80..=0x8f => JsonValue::Object(self.map((marker & 0x0f) as usize)?),
+            0x90..=0x9f => JsonValue::Array(self.values((marker & 0x0f) as usize)?),
+            0xa0..=0xbf => JsonValue::Str(self.str((marker & 0x1f) as usize)?),
+            0xc0 => JsonValue::Null,
+            0xc2 => JsonValue::Boolean(false),
+            0xc3 => JsonValue::Boolean(true),
+            0xca => JsonValue::Num(f32::from_be_bytes(self.array()?) as f64),
+            0xcb => JsonValue::Num(f64::from_be_bytes(self.array()?)),
+            0xcc => JsonValue::Int(self.array::<1>()?[0] as i64),
+            0xcd => JsonValue::Int(u16::from_be_bytes(self.array()?) as i64),
+            0xce => JsonValue::Int(u32::from_be_bytes(self.array()?) as i64),
+            0xcf => {
+                let n = u64::from_be_bytes(self.array()?);
+                i64::try_from(n).map_or(JsonValue::UInt(n), JsonValue::Int)
+            }
+            0xd0 => JsonValue::Int(self.array::<1>()?[0] as i8 as i64),
+            0xd1 => JsonValue::Int(i16::from_be_bytes(self.array()?) as i64),
+            0xd2 => JsonValue::Int(i32::from_be_bytes(self.array()?) as i64),
+            0xd3 => JsonValue::Int(i64::from_be_bytes(self.array()?)),
+            0xd9..=0xdb => {
+                let len = self.len(1 << (marker - 0xd9))?;
+                JsonValue::Str(self.str(len)?)
+            }
+            0xdc | 0xdd => {
+                let len = self.len(2 << (marker - 0xdc))?;
+                JsonValue::Array(self.values(len)?)
+            }
+            0xde | 0xdf => {
+                let len = self.len(2 << (marker - 0xde))?;
+                JsonValue::Object(self.map(len)?)
+            }
+            0xe0..=0xff => JsonValue::Int((marker as i8) as i64),
+            0xc4..=0xc6 => {
+                self.pos -= 1;
+                return Err(self.error("binary data has no JSON counterpart"));
+            }
+            0xc7..=0xc9 | 0xd4..=0xd8 => {
+                self.pos -= 1;
+                return Err(self.error("extension types have no JSON counterpart"));
+            }
+            0xc1 => {
+                self.pos -= 1;
+                return Err(self.error("invalid marker 0xc1"));
+            }
+        })
+    }
+}
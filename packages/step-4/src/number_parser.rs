@@ -0,0 +1,173 @@
+//! How the text of a number becomes an `f64`, picked with
+//! [`ParseOptions::number_backend`](crate::ParseOptions::number_backend).
+//!
+//! Numbers are most of some documents (`canada.json` is about 90% of
+//! coordinates), so their parsing is much of the parse time: see
+//! `benches/numbers.rs`.
+
+use nom::number::complete::double;
+
+/// Parses the number at the start of some input
+pub trait NumberParser {
+    /// Length of the number `i` starts with and its value, `None` if `i`
+    /// doesn't start with a number this parser accepts
+    fn parse_number(&self, i: &str) -> Option<(usize, f64)>;
+}
+
+/// nom's `double`, which is lenient: `01`, `1.` and `.5` are numbers too
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct NomDouble;
+
+impl NumberParser for NomDouble {
+    fn parse_number(&self, i: &str) -> Option<(usize, f64)> {
+        let (rest, n) = double::<_, ()>(i).ok()?;
+
+        Some((i.len() - rest.len(), n))
+    }
+}
+
+/// Parts of a number written as the JSON grammar wants it:
+/// `-?(0|[1-9][0-9]*)(\.[0-9]+)?([eE][+-]?[0-9]+)?`
+struct Parts<'a> {
+    len: usize,
+    integer: &'a [u8],
+    fraction: &'a [u8],
+    exponent: i32,
+}
+
+/// Digits at the start of `bytes`
+fn digits(bytes: &[u8]) -> &[u8] {
+    let len = bytes.iter().take_while(|b| b.is_ascii_digit()).count();
+    &bytes[..len]
+}
+
+fn parts(i: &str) -> Option<Parts<'_>> {
+    let bytes = i.as_bytes();
+    let mut len = usize::from(bytes.first() == Some(&b'-'));
+
+    let integer = digits(&bytes[len..]);
+    // No leading zero but for the `0` of `0.5`
+    if integer.is_empty() || (integer[0] == b'0' && integer.len() > 1) {
+        return None;
+    }
+    len += integer.len();
+
+    let mut fraction: &[u8] = &[];
+    if bytes.get(len) == Some(&b'.') {
+        fraction = digits(&bytes[len + 1..]);
+        if fraction.is_empty() {
+            return None;
+        }
+        len += 1 + fraction.len();
+    }
+
+    let mut exponent = 0;
+    if let Some(b'e' | b'E') = bytes.get(len) {
+        len += 1;
+        let negative = bytes.get(len) == Some(&b'-');
+        if let Some(b'-' | b'+') = bytes.get(len) {
+            len += 1;
+        }
+
+        let exponent_digits = digits(&bytes[len..]);
+        if exponent_digits.is_empty() {
+            return None;
+        }
+        len += exponent_digits.len();
+
+        // Beyond that, the number is 0 or infinite whatever its digits
+        exponent = exponent_digits.iter().fold(0i32, |exponent, digit| {
+            (exponent * 10 + i32::from(digit - b'0')).min(1_000_000)
+        });
+        if negative {
+            exponent = -exponent;
+        }
+    }
+
+    Some(Parts {
+        len,
+        integer,
+        fraction,
+        exponent,
+    })
+}
+
+/// The grammar of the JSON spec, checked by hand, with the value from
+/// `str::parse`
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct StrictNumber;
+
+impl NumberParser for StrictNumber {
+    fn parse_number(&self, i: &str) -> Option<(usize, f64)> {
+        let len = parts(i)?.len;
+
+        Some((len, i[..len].parse().ok()?))
+    }
+}
+
+/// The grammar of [`StrictNumber`], with the value from `minimal-lexical`,
+/// the float algorithm of `lexical-core`, which needs the digits already
+/// split up
+#[cfg(feature = "lexical")]
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Lexical;
+
+#[cfg(feature = "lexical")]
+impl NumberParser for Lexical {
+    fn parse_number(&self, i: &str) -> Option<(usize, f64)> {
+        let Parts {
+            len,
+            integer,
+            fraction,
+            exponent,
+        } = parts(i)?;
+
+        // Which `minimal_lexical::parse_float` can't do on iterators
+        let integer = &integer[integer.iter().take_while(|&&b| b == b'0').count()..];
+        let fraction = &fraction[..fraction
+            .iter()
+            .rposition(|&b| b != b'0')
+            .map_or(0, |i| i + 1)];
+
+        let n: f64 = minimal_lexical::parse_float(integer.iter(), fraction.iter(), exponent);
+
+        Some((len, if i.starts_with('-') { -n } else { n }))
+    }
+}
+
+/// The [`NumberParser`] of a [`ParseOptions`](crate::ParseOptions)
+///
+/// ```
+/// use step_4::{NumberBackend, ParseOptions};
+///
+/// let opts = ParseOptions {
+///     number_backend: NumberBackend::Strict,
+///     ..Default::default()
+/// };
+///
+/// assert!(step_4::parse_with_options("[1.5e3, -0.25]", &opts).is_ok());
+/// assert!(step_4::parse_with_options("[1.]", &opts).is_err());
+/// assert!(step_4::parse_with_options("[1.]", &ParseOptions::default()).is_ok());
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum NumberBackend {
+    /// [`NomDouble`]
+    #[default]
+    Nom,
+    /// [`StrictNumber`]
+    Strict,
+    /// [`Lexical`]
+    #[cfg(feature = "lexical")]
+    Lexical,
+}
+
+impl NumberParser for NumberBackend {
+    fn parse_number(&self, i: &str) -> Option<(usize, f64)> {
+        match self {
+            NumberBackend::Nom => NomDouble.parse_number(i),
+            NumberBackend::Strict => StrictNumber.parse_number(i),
+            #[cfg(feature = "lexical")]
+            NumberBackend::Lexical => Lexical.parse_number(i),
+        }
+    }
+}
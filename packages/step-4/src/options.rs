@@ -0,0 +1,95 @@
+//! Settings for [`parse_with_options`](crate::parse_with_options),
+//! [`to_string_with`](crate::ser::to_string_with) and
+//! [`JsonValue::flatten_with`](crate::JsonValue::flatten_with).
+
+use alloc::string::String;
+
+#[cfg(feature = "bignum")]
+use crate::bignum::BigNumber;
+use crate::NumberBackend;
+
+/// How the numbers that aren't integers fitting in 64 bits are represented
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum NumberMode {
+    /// `JsonValue::Num`, rounded to the nearest `f64`. Numbers beyond its
+    /// range, like `1e400`, are invalid.
+    #[default]
+    Float,
+    /// `JsonValue::BigNum`, an exact [`BigNumber`]
+    #[cfg(feature = "bignum")]
+    BigNumber,
+    /// `JsonValue::RawNum` for every number, integers included, so that it
+    /// is serialized back exactly as written
+    Preserve,
+}
+
+/// What the `\uXXXX` escapes of lone surrogates, which aren't chars,
+/// become. A high surrogate followed by a low one is a char in any case.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum SurrogateMode {
+    /// The string is invalid, as the JSON spec wants
+    #[default]
+    Reject,
+    /// U+FFFD, the replacement character, like `JSON.parse` followed by a
+    /// UTF-8 encoder
+    Replace,
+    /// `JsonValue::Wtf8` for the strings that have some, so that they are
+    /// serialized back as the same escapes. Object keys get U+FFFD.
+    Wtf8,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ParseOptions {
+    pub number_mode: NumberMode,
+    /// What reads the numbers, see [`number_parser`](crate::number_parser)
+    pub number_backend: NumberBackend,
+    pub lone_surrogates: SurrogateMode,
+    /// Skip anything before the first `{` or `[`, like the `)]}',` that
+    /// some APIs prefix their responses with against JSON hijacking
+    pub skip_leading_junk: bool,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SerializeOptions {
+    /// Spaces per level, with one item per line. `None` for compact output
+    pub indent: Option<usize>,
+    /// Highlight keys, strings, numbers and literals with ANSI colors
+    pub color: bool,
+    /// Write object keys in lexicographic order rather than in the
+    /// arbitrary order of the `HashMap`, for output that diffs well
+    pub sort_keys: bool,
+    /// Escape every non-ASCII char as `\uXXXX`, as a surrogate pair beyond
+    /// the Basic Multilingual Plane, for 7-bit clean output
+    pub ensure_ascii: bool,
+    /// Escape `<`, `>`, `&`, U+2028 and U+2029, for output that is safe in
+    /// a `<script>` tag and in JavaScript string literals
+    pub escape_html: bool,
+}
+
+/// How [`JsonValue::flatten_with`](crate::JsonValue::flatten_with) writes the
+/// indexes of arrays
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum IndexStyle {
+    /// `a.b[0].c`
+    #[default]
+    Brackets,
+    /// `a.b.0.c`, like keys. Objects whose keys are all the indexes of an
+    /// array are unflattened to arrays.
+    Separator,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FlattenOptions {
+    /// Between the keys of a path, `.` by default
+    pub separator: String,
+    pub index_style: IndexStyle,
+}
+
+impl Default for FlattenOptions {
+    fn default() -> Self {
+        Self {
+            separator: ".".into(),
+            index_style: IndexStyle::default(),
+        }
+    }
+}
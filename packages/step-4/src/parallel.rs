@@ -0,0 +1,331 @@
+//! Parsing of documents dominated by one large array, like `canada.json`, on
+//! every core.
+//!
+//! A quick scan finds the commas of an array or object, without parsing
+//! anything but the brackets and the strings, and descends into its big
+//! values until it finds an array with enough elements to keep every thread
+//! busy. Those elements are split in runs of about the same size, parsed on
+//! scoped threads, and the runs joined back in order.
+
+use nom::{combinator::all_consuming, Finish};
+use std::{num::NonZeroUsize, ops::Range, thread};
+
+use crate::{
+    document_start, json_value, lexer::string, parse_with_path, path::JsonPath, JsonError,
+    JsonValue, Map, ParseOptions,
+};
+
+const WHITESPACE: [char; 4] = [' ', '\t', '\r', '\n'];
+
+/// How [`parse_parallel_with`] splits the work
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ParallelOptions {
+    /// Threads to parse on, the number of cores by default
+    pub threads: usize,
+    /// Below this size, a value is parsed on the current thread: starting
+    /// threads would cost more than it saves
+    pub min_len: usize,
+}
+
+impl Default for ParallelOptions {
+    fn default() -> Self {
+        Self {
+            threads: thread::available_parallelism().map_or(1, NonZeroUsize::get),
+            min_len: 64 * 1024,
+        }
+    }
+}
+
+/// Like [`parse_with_path`], but the elements of the large arrays are parsed
+/// in parallel. Small documents are parsed on the current thread.
+///
+/// Errors are the same as with [`parse_with_path`], and locate the failure
+/// in the whole input.
+///
+/// ```
+/// use step_4::{parse_parallel, parse_with_path};
+///
+/// let input = "\u{FEFF} [1, 2, 3]";
+/// assert_eq!(parse_parallel(input).unwrap(), parse_with_path(input).unwrap());
+/// ```
+pub fn parse_parallel(input: &str) -> Result<JsonValue, JsonError> {
+    parse_parallel_with(input, &ParallelOptions::default())
+}
+
+/// [`parse_parallel`] with a given number of threads and size threshold
+///
+/// ```
+/// use step_4::{parse_parallel_with, parse_with_path, ParallelOptions};
+///
+/// let input = "[[1, 2], [3, 4], [5, 6]]";
+/// let opts = ParallelOptions {
+///     threads: 2,
+///     min_len: 0,
+/// };
+/// assert_eq!(parse_parallel_with(input, &opts).unwrap(), parse_with_path(input).unwrap());
+/// ```
+pub fn parse_parallel_with(input: &str, opts: &ParallelOptions) -> Result<JsonValue, JsonError> {
+    if opts.threads <= 1 {
+        return parse_with_path(input);
+    }
+
+    let parser = Parallel {
+        input,
+        threads: opts.threads,
+        min_len: opts.min_len,
+    };
+    let start = input.len() - document_start(input, &ParseOptions::default()).len();
+    let (start, end) = trim(input, start, input.len());
+
+    parser.value(start, end, &JsonPath::default())
+}
+
+struct Parallel<'a> {
+    input: &'a str,
+    threads: usize,
+    min_len: usize,
+}
+
+impl Parallel<'_> {
+    /// Parses the value at `start..end`, which has no surrounding whitespace
+    fn value(&self, start: usize, end: usize, path: &JsonPath) -> Result<JsonValue, JsonError> {
+        if end - start < self.min_len {
+            return self.sequential(start, end, path);
+        }
+
+        // Anything the scan doesn't understand goes through the regular
+        // parser, which reports the error
+        match scan(self.input, start, end) {
+            Some(Container::Array(elements)) if elements.len() >= self.threads => {
+                self.parallel(&elements, path)
+            }
+            Some(Container::Array(elements)) => elements
+                .into_iter()
+                .enumerate()
+                .map(|(index, (start, end))| {
+                    path.push_index(index);
+                    let value = self.value(start, end, path)?;
+                    path.pop();
+                    Ok(value)
+                })
+                .collect::<Result<_, _>>()
+                .map(JsonValue::Array),
+            Some(Container::Object(members)) => {
+                let keys: Option<Vec<_>> = members
+                    .iter()
+                    .map(|member| self.key(member.key.clone()))
+                    .collect();
+
+                let Some(keys) = keys else {
+                    return self.sequential(start, end, path);
+                };
+
+                keys.into_iter()
+                    .zip(members)
+                    .map(|(key, member)| {
+                        path.push_key(key);
+                        let value = self.value(member.value.start, member.value.end, path)?;
+                        Ok((path.pop_key(), value))
+                    })
+                    .collect::<Result<Map<_, _>, _>>()
+                    .map(JsonValue::Object)
+            }
+            None => self.sequential(start, end, path),
+        }
+    }
+
+    /// Parses `elements`, in as many runs as there are threads
+    fn parallel(
+        &self,
+        elements: &[(usize, usize)],
+        path: &JsonPath,
+    ) -> Result<JsonValue, JsonError> {
+        // Elements can be of very different sizes, so the runs are cut by
+        // size rather than by count
+        let target = (elements[elements.len() - 1].1 - elements[0].0) / self.threads;
+        let mut runs = Vec::with_capacity(self.threads);
+        let mut first = 0;
+
+        for (index, &(_, end)) in elements.iter().enumerate() {
+            if end - elements[first].0 >= target || index == elements.len() - 1 {
+                runs.push(first..index + 1);
+                first = index + 1;
+            }
+        }
+
+        let runs = thread::scope(|scope| {
+            let handles: Vec<_> = runs
+                .into_iter()
+                .map(|run| {
+                    let path = path.clone();
+                    scope.spawn(move || self.run(elements, run, &path))
+                })
+                .collect();
+
+            handles
+                .into_iter()
+                .map(|handle| handle.join().unwrap())
+                .collect::<Result<Vec<_>, _>>()
+        })?;
+
+        Ok(JsonValue::Array(runs.into_iter().flatten().collect()))
+    }
+
+    /// Parses the elements in `run` on the current thread
+    fn run(
+        &self,
+        elements: &[(usize, usize)],
+        run: Range<usize>,
+        path: &JsonPath,
+    ) -> Result<Vec<JsonValue>, JsonError> {
+        run.map(|index| {
+            let (start, end) = elements[index];
+
+            path.push_index(index);
+            let value = self.sequential(start, end, path)?;
+            path.pop();
+
+            Ok(value)
+        })
+        .collect()
+    }
+
+    fn sequential(
+        &self,
+        start: usize,
+        end: usize,
+        path: &JsonPath,
+    ) -> Result<JsonValue, JsonError> {
+        let opts = ParseOptions::default();
+
+        let result =
+            all_consuming(|i| json_value::<JsonError>(i, &opts, path))(&self.input[start..end])
+                .finish();
+
+        match result {
+            Ok((_, value)) => Ok(value),
+            Err(e) => Err(JsonError::Path(
+                path.to_string(),
+                Box::new(relocate(e, self.input.len() - end)),
+            )),
+        }
+    }
+
+    fn key(&self, range: Range<usize>) -> Option<String> {
+        all_consuming(string::<()>)(&self.input[range])
+            .ok()
+            .map(|(_, key)| key)
+    }
+}
+
+/// Turns the position of an error in a value into its position in the whole
+/// input, `after` being the length of the input after the value
+fn relocate(mut error: JsonError, after: usize) -> JsonError {
+    if let JsonError::Syntax {
+        remaining, context, ..
+    } = &mut error
+    {
+        *remaining += after;
+        for (_, remaining) in context {
+            *remaining += after;
+        }
+    }
+
+    error
+}
+
+/// `start..end` without the whitespace around it
+fn trim(input: &str, start: usize, end: usize) -> (usize, usize) {
+    let text = &input[start..end];
+    let leading = text.len() - text.trim_start_matches(WHITESPACE).len();
+    let trailing = text.len() - text.trim_end_matches(WHITESPACE).len();
+
+    (start + leading, end - trailing)
+}
+
+struct Member {
+    key: Range<usize>,
+    value: Range<usize>,
+}
+
+enum Container {
+    /// Ranges of the elements
+    Array(Vec<(usize, usize)>),
+    Object(Vec<Member>),
+}
+
+/// Splits the array or object at `start..end` at its top-level commas. `None`
+/// if it is another value, or can't be valid
+fn scan(input: &str, start: usize, end: usize) -> Option<Container> {
+    let bytes = input.as_bytes();
+    let close = match bytes[start] {
+        b'[' => b']',
+        b'{' => b'}',
+        _ => return None,
+    };
+
+    // Start, top-level colon, and end of each element or member
+    let mut parts = Vec::new();
+    let mut part = start + 1;
+    let mut colon = None;
+    let mut depth = 0usize;
+    let mut in_string = false;
+    let mut escaped = false;
+
+    for (index, &byte) in bytes.iter().enumerate().take(end).skip(start + 1) {
+        match byte {
+            _ if escaped => escaped = false,
+            b'\\' if in_string => escaped = true,
+            b'"' => in_string = !in_string,
+            _ if in_string => {}
+            b'[' | b'{' => depth += 1,
+            b']' | b'}' if depth > 0 => depth -= 1,
+            b':' if depth == 0 && colon.is_none() => colon = Some(index),
+            b',' if depth == 0 => {
+                parts.push((part, colon.take(), index));
+                part = index + 1;
+            }
+            b']' | b'}' if byte == close && index == end - 1 => {
+                parts.push((part, colon.take(), index))
+            }
+            b']' | b'}' => return None,
+            _ => {}
+        }
+    }
+
+    // Empty, or not closed where expected
+    if parts.last().is_none_or(|&(_, _, last)| last != end - 1) {
+        return None;
+    }
+    if let [(start, _, end)] = parts[..] {
+        if input[start..end].trim_matches(WHITESPACE).is_empty() {
+            return None;
+        }
+    }
+
+    if close == b']' {
+        parts
+            .into_iter()
+            .map(|(start, colon, end)| match colon {
+                Some(_) => None,
+                None => Some(trim(input, start, end)),
+            })
+            .collect::<Option<_>>()
+            .map(Container::Array)
+    } else {
+        parts
+            .into_iter()
+            .map(|(start, colon, end)| {
+                let colon = colon?;
+                let (key_start, key_end) = trim(input, start, colon);
+                let (value_start, value_end) = trim(input, colon + 1, end);
+
+                Some(Member {
+                    key: key_start..key_end,
+                    value: value_start..value_end,
+                })
+            })
+            .collect::<Option<_>>()
+            .map(Container::Object)
+    }
+}
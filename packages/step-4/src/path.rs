@@ -0,0 +1,71 @@
+//! Location of the value being parsed, kept up to date while descending into
+//! arrays and objects so that errors can tell where they happened.
+
+use alloc::{string::String, vec::Vec};
+use core::{cell::RefCell, fmt::Display};
+
+use crate::ser::write_str;
+
+#[derive(Clone)]
+enum Segment {
+    Index(usize),
+    Key(String),
+}
+
+/// Stack of the keys and indices leading to the current value.
+///
+/// Segments are only popped once their value has been parsed, so after a
+/// failure the stack still points at the offending value.
+#[derive(Clone, Default)]
+pub(crate) struct JsonPath {
+    segments: RefCell<Vec<Segment>>,
+}
+
+impl JsonPath {
+    pub(crate) fn push_index(&self, index: usize) {
+        self.segments.borrow_mut().push(Segment::Index(index));
+    }
+
+    /// The key is moved in rather than cloned, and given back by `pop_key`
+    pub(crate) fn push_key(&self, key: String) {
+        self.segments.borrow_mut().push(Segment::Key(key));
+    }
+
+    pub(crate) fn pop(&self) {
+        self.segments.borrow_mut().pop();
+    }
+
+    pub(crate) fn pop_key(&self) -> String {
+        match self.segments.borrow_mut().pop() {
+            Some(Segment::Key(key)) => key,
+            _ => String::new(),
+        }
+    }
+}
+
+/// JSONPath notation: `$.statuses[17].user.id`
+impl Display for JsonPath {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "$")?;
+
+        for segment in self.segments.borrow().iter() {
+            match segment {
+                Segment::Index(index) => write!(f, "[{index}]")?,
+                Segment::Key(key)
+                    if !key.is_empty()
+                        && !key.starts_with(|c: char| c.is_ascii_digit())
+                        && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') =>
+                {
+                    write!(f, ".{key}")?
+                }
+                Segment::Key(key) => {
+                    let mut quoted = String::new();
+                    write_str(&mut quoted, key);
+                    write!(f, "[{quoted}]")?
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,221 @@
+//! Pull parser: walks the document as a sequence of [`Event`]s instead of
+//! building a [`JsonValue`](crate::JsonValue) tree.
+//!
+//! Strings without escapes are borrowed from the input, so skimming through a
+//! document for a few fields barely allocates.
+
+use alloc::{borrow::Cow, vec::Vec};
+use nom::{
+    error::{ErrorKind, ParseError},
+    number::complete::double,
+};
+
+use crate::{
+    lexer::{unescape, Lexer, Token, TokenKind},
+    JsonError,
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Event<'a> {
+    StartObject,
+    EndObject,
+    StartArray,
+    EndArray,
+    Key(Cow<'a, str>),
+    Str(Cow<'a, str>),
+    Number(f64),
+    Boolean(bool),
+    Null,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Container {
+    Array,
+    Object,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Expect {
+    /// A value, or `]` right after `[`
+    FirstValue,
+    Value,
+    /// A key, or `}` right after `{`
+    FirstKey,
+    Key,
+    Colon,
+    /// `,` or the end of the current array/object
+    Separator,
+    /// The root value has been read, only whitespace may follow
+    End,
+}
+
+/// Iterator over the [`Event`]s of a document, each paired with the byte
+/// offset where it starts.
+///
+/// The structure is validated as we go: the iterator yields an error, then
+/// stops, on the first unexpected token.
+///
+/// ```
+/// use step_4::{error_position, JsonReader};
+///
+/// let input = "[1,\n 2 x]";
+/// let error = JsonReader::new(input).find_map(Result::err).unwrap();
+///
+/// assert_eq!(error.to_string(), "unexpected 'x'");
+/// let position = error_position(input, &error).unwrap();
+/// assert_eq!((position.line, position.column), (2, 4));
+/// ```
+pub struct JsonReader<'a> {
+    input: &'a str,
+    lexer: Lexer<'a>,
+    stack: Vec<Container>,
+    expect: Expect,
+    failed: bool,
+}
+
+impl<'a> JsonReader<'a> {
+    pub fn new(input: &'a str) -> Self {
+        Self {
+            input,
+            lexer: Lexer::new(input),
+            stack: Vec::new(),
+            expect: Expect::Value,
+            failed: false,
+        }
+    }
+
+    /// Byte offset of the next unread char
+    pub fn offset(&self) -> usize {
+        self.lexer.offset()
+    }
+
+    fn after_value(&mut self) {
+        self.expect = if self.stack.is_empty() {
+            Expect::End
+        } else {
+            Expect::Separator
+        };
+    }
+
+    fn close(&mut self) -> Event<'a> {
+        let container = self.stack.pop();
+        self.after_value();
+
+        match container {
+            Some(Container::Object) => Event::EndObject,
+            _ => Event::EndArray,
+        }
+    }
+
+    fn value(&mut self, token: Token<'a>) -> core::result::Result<Event<'a>, JsonError> {
+        let event = match token.kind {
+            TokenKind::BeginObject => {
+                self.stack.push(Container::Object);
+                self.expect = Expect::FirstKey;
+                return Ok(Event::StartObject);
+            }
+            TokenKind::BeginArray => {
+                self.stack.push(Container::Array);
+                self.expect = Expect::FirstValue;
+                return Ok(Event::StartArray);
+            }
+            TokenKind::Str => Event::Str(unescape(token.text)?),
+            TokenKind::Number => Event::Number(double::<_, JsonError>(token.text)?.1),
+            TokenKind::True => Event::Boolean(true),
+            TokenKind::False => Event::Boolean(false),
+            TokenKind::Null => Event::Null,
+            _ => return Err(self.unexpected(token.span.start)),
+        };
+
+        self.after_value();
+
+        Ok(event)
+    }
+
+    fn next_token(&mut self) -> core::result::Result<Option<Token<'a>>, JsonError> {
+        for token in self.lexer.by_ref() {
+            let token = token?;
+
+            if token.kind != TokenKind::Whitespace {
+                return Ok(Some(token));
+            }
+        }
+
+        Ok(None)
+    }
+
+    fn next_event(&mut self) -> core::result::Result<Option<(usize, Event<'a>)>, JsonError> {
+        loop {
+            let Some(token) = self.next_token()? else {
+                return match self.expect {
+                    Expect::End => Ok(None),
+                    _ => Err(self.unexpected(self.offset())),
+                };
+            };
+
+            let offset = token.span.start;
+
+            let event = match (self.expect, token.kind) {
+                (Expect::FirstValue, TokenKind::EndArray)
+                | (Expect::FirstKey, TokenKind::EndObject) => self.close(),
+                (Expect::FirstValue | Expect::Value, _) => self.value(token)?,
+                (Expect::FirstKey | Expect::Key, TokenKind::Str) => {
+                    self.expect = Expect::Colon;
+                    Event::Key(unescape(token.text)?)
+                }
+                (Expect::Colon, TokenKind::Colon) => {
+                    self.expect = Expect::Value;
+                    continue;
+                }
+                (Expect::Separator, TokenKind::Comma) => {
+                    self.expect = match self.stack.last() {
+                        Some(Container::Object) => Expect::Key,
+                        _ => Expect::Value,
+                    };
+                    continue;
+                }
+                (Expect::Separator, TokenKind::EndArray)
+                    if self.stack.last() == Some(&Container::Array) =>
+                {
+                    self.close()
+                }
+                (Expect::Separator, TokenKind::EndObject)
+                    if self.stack.last() == Some(&Container::Object) =>
+                {
+                    self.close()
+                }
+                _ => return Err(self.unexpected(offset)),
+            };
+
+            return Ok(Some((offset, event)));
+        }
+    }
+
+    /// Error on the token at `offset`, or on the end of the input
+    fn unexpected(&self, offset: usize) -> JsonError {
+        let kind = match self.expect {
+            Expect::End => ErrorKind::Eof,
+            _ => ErrorKind::Char,
+        };
+
+        JsonError::from_error_kind(&self.input[offset..], kind)
+    }
+}
+
+impl<'a> Iterator for JsonReader<'a> {
+    type Item = core::result::Result<(usize, Event<'a>), JsonError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+
+        match self.next_event() {
+            Ok(event) => event.map(Ok),
+            Err(e) => {
+                self.failed = true;
+                Some(Err(e))
+            }
+        }
+    }
+}
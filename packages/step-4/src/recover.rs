@@ -0,0 +1,270 @@
+//! Parser that keeps going after an error, to report every problem of a
+//! document at once the way a linter does.
+//!
+//! A value that can't be parsed is recorded as an error, replaced by
+//! [`JsonValue::Null`], and skipped up to the next `,`, `]` or `}`.
+//! [`parse_lossy`] also salvages the strings cut by the end of the input, as
+//! in truncated log files.
+
+use crate::{
+    lexer::{null, number, parse_false, parse_true, string},
+    JsonError, JsonValue, Map,
+};
+use nom::{
+    character::complete::multispace0,
+    error::{ErrorKind, ParseError},
+};
+
+struct Recovering {
+    errors: Vec<JsonError>,
+    /// Keep the content of strings cut by the end of the input
+    salvage: bool,
+}
+
+fn ws(i: &str) -> &str {
+    multispace0::<_, JsonError>(i).map_or(i, |(i, _)| i)
+}
+
+/// Up to the next structural char outside of a string
+fn skip_to_structural(i: &str) -> &str {
+    let mut in_string = false;
+    let mut escaped = false;
+
+    for (index, c) in i.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if in_string => escaped = true,
+            '"' => in_string = !in_string,
+            ',' | ']' | '}' if !in_string => return &i[index..],
+            _ => {}
+        }
+    }
+
+    &i[i.len()..]
+}
+
+impl Recovering {
+    /// Records `error`, unless one was already recorded at the same place,
+    /// like at the end of a truncated document
+    fn push(&mut self, error: JsonError) {
+        let remaining = |e: &JsonError| match e {
+            JsonError::Syntax { remaining, .. } => Some(*remaining),
+            _ => None,
+        };
+
+        if let (Some(last), Some(new)) = (self.errors.last().and_then(remaining), remaining(&error))
+        {
+            if last == new {
+                return;
+            }
+        }
+
+        self.errors.push(error);
+    }
+
+    fn error(&mut self, i: &str, kind: ErrorKind) {
+        self.push(JsonError::from_error_kind(i, kind));
+    }
+
+    /// Runs a scalar parser, skipping the value on failure
+    fn scalar<'a>(
+        &mut self,
+        i: &'a str,
+        parser: impl FnOnce(&'a str) -> crate::Result<'a, JsonValue, JsonError>,
+    ) -> (&'a str, JsonValue) {
+        match parser(i) {
+            Ok(parsed) => parsed,
+            Err(e) => {
+                self.push(e.into());
+                (skip_to_structural(i), JsonValue::Null)
+            }
+        }
+    }
+
+    /// Parses a string, closing it if it runs up to the end of the input
+    /// and `salvage` is set. The error is recorded either way
+    fn string<'a>(&mut self, i: &'a str) -> Option<(&'a str, String)> {
+        match string::<JsonError>(i) {
+            Ok(parsed) => Some(parsed),
+            Err(e) => {
+                self.push(e.into());
+
+                let closed = format!("{i}\"");
+                match string::<JsonError>(&closed) {
+                    Ok(("", s)) if self.salvage => Some((&i[i.len()..], s)),
+                    _ => None,
+                }
+            }
+        }
+    }
+
+    fn json_value<'a>(&mut self, i: &'a str) -> (&'a str, JsonValue) {
+        let i = ws(i);
+
+        match i.chars().next() {
+            Some('{') => self.hash(i),
+            Some('[') => self.array(i),
+            Some('"') => match self.string(i) {
+                Some((i, s)) => (i, JsonValue::Str(s)),
+                None => (skip_to_structural(i), JsonValue::Null),
+            },
+            Some('-' | '0'..='9') => self.scalar(i, |i| number(i).map(|(i, n)| (i, n.into()))),
+            Some('t') => self.scalar(i, |i| {
+                parse_true(i).map(|(i, b)| (i, JsonValue::Boolean(b)))
+            }),
+            Some('f') => self.scalar(i, |i| {
+                parse_false(i).map(|(i, b)| (i, JsonValue::Boolean(b)))
+            }),
+            Some('n') => self.scalar(i, |i| null(i).map(|(i, _)| (i, JsonValue::Null))),
+            _ => {
+                self.error(i, ErrorKind::Char);
+                (skip_to_structural(i), JsonValue::Null)
+            }
+        }
+    }
+
+    /// After a value of a container closed by `close`: returns the rest of
+    /// the input and whether another value follows
+    fn separator<'a>(&mut self, i: &'a str, close: char) -> (&'a str, bool) {
+        let mut i = ws(i);
+
+        loop {
+            match i.chars().next() {
+                Some(',') => return (&i[1..], true),
+                Some(c) if c == close => return (&i[1..], false),
+                // Mismatched or missing closer, left to the parent
+                Some(']' | '}') | None => {
+                    self.error(i, ErrorKind::Char);
+                    return (i, false);
+                }
+                Some(_) => {
+                    self.error(i, ErrorKind::Char);
+                    i = skip_to_structural(i);
+                }
+            }
+        }
+    }
+
+    fn array<'a>(&mut self, i: &'a str) -> (&'a str, JsonValue) {
+        let mut i = ws(&i[1..]);
+        let mut values = Vec::new();
+
+        if let Some(rest) = i.strip_prefix(']') {
+            return (rest, JsonValue::Array(values));
+        }
+
+        loop {
+            if i.starts_with('}') || i.is_empty() {
+                self.error(i, ErrorKind::Char);
+                break;
+            }
+
+            // Trailing comma
+            if let Some(rest) = i.strip_prefix(']') {
+                self.error(i, ErrorKind::Char);
+                i = rest;
+                break;
+            }
+
+            let (rest, value) = self.json_value(i);
+            values.push(value);
+
+            let (rest, more) = self.separator(rest, ']');
+            i = ws(rest);
+
+            if !more {
+                break;
+            }
+        }
+
+        (i, JsonValue::Array(values))
+    }
+
+    fn hash<'a>(&mut self, i: &'a str) -> (&'a str, JsonValue) {
+        let mut i = ws(&i[1..]);
+        let mut map = Map::default();
+
+        if let Some(rest) = i.strip_prefix('}') {
+            return (rest, JsonValue::Object(map));
+        }
+
+        loop {
+            if i.starts_with(']') || i.is_empty() {
+                self.error(i, ErrorKind::Char);
+                break;
+            }
+
+            // Trailing comma
+            if let Some(rest) = i.strip_prefix('}') {
+                self.error(i, ErrorKind::Char);
+                i = rest;
+                break;
+            }
+
+            match self.string(i) {
+                Some((rest, key)) => {
+                    let rest = ws(rest);
+
+                    if let Some(rest) = rest.strip_prefix(':') {
+                        let (rest, value) = self.json_value(rest);
+                        map.insert(key, value);
+                        i = rest;
+                    } else {
+                        self.error(rest, ErrorKind::Char);
+                        i = skip_to_structural(rest);
+                    }
+                }
+                None => i = skip_to_structural(i),
+            }
+
+            let (rest, more) = self.separator(i, '}');
+            i = ws(rest);
+
+            if !more {
+                break;
+            }
+        }
+
+        (i, JsonValue::Object(map))
+    }
+}
+
+/// Parses `i` up to its end whatever happens, returning what could be parsed
+/// along with every error met on the way. The value is only complete when
+/// there are no errors
+pub fn parse_recovering(i: &str) -> (JsonValue, Vec<JsonError>) {
+    recover(i, false)
+}
+
+/// Best-effort value of a possibly damaged document, see [`parse_lossy`]
+#[derive(Debug)]
+pub struct Lossy {
+    pub value: JsonValue,
+    /// What had to be dropped or patched to get `value`, empty if the
+    /// document is valid
+    pub diagnostics: Vec<JsonError>,
+}
+
+/// Never fails: invalid values become [`JsonValue::Null`] and strings cut by
+/// the end of the input are kept, each with a diagnostic
+pub fn parse_lossy(i: &str) -> Lossy {
+    let (value, diagnostics) = recover(i, true);
+
+    Lossy { value, diagnostics }
+}
+
+fn recover(i: &str, salvage: bool) -> (JsonValue, Vec<JsonError>) {
+    let mut parser = Recovering {
+        errors: Vec::new(),
+        salvage,
+    };
+
+    let (rest, value) = parser.json_value(i);
+    let rest = ws(rest);
+
+    if !rest.is_empty() {
+        parser.error(rest, ErrorKind::Eof);
+    }
+
+    (value, parser.errors)
+}
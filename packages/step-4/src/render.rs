@@ -0,0 +1,201 @@
+//! Compiler-style rendering of errors: the offending line of the input with
+//! a caret under the failure, and the labels the parser collected.
+
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::fmt::Write;
+
+use crate::{JsonError, Position};
+
+/// Error at the bottom of the `Line`/`Path` wrappers, along with the notes
+/// they add and the part of the input it is relative to
+struct Located<'e> {
+    error: &'e JsonError,
+    /// Byte offset of the failure in the input
+    offset: Option<usize>,
+    notes: Vec<String>,
+    /// Labels of the contexts of the failure, innermost first, with the
+    /// byte offset they start at
+    contexts: Vec<(&'static str, usize)>,
+}
+
+fn locate<'e>(input: &str, window: (usize, usize), error: &'e JsonError) -> Located<'e> {
+    let (start, len) = window;
+
+    match error {
+        JsonError::Syntax {
+            remaining, context, ..
+        } => Located {
+            error,
+            offset: (*remaining <= len).then(|| start + len - remaining),
+            notes: context
+                .iter()
+                .map(|(label, _)| format!("in {label}"))
+                .collect(),
+            contexts: context
+                .iter()
+                .filter(|(_, remaining)| *remaining <= len)
+                .map(|(label, remaining)| (*label, start + len - remaining))
+                .collect(),
+        },
+        JsonError::Line(line, inner) => {
+            // Errors of multi-document inputs are relative to their line
+            let window = input
+                .split_inclusive('\n')
+                .scan(0, |offset, text| {
+                    let line_start = *offset;
+                    *offset += text.len();
+                    Some((line_start, text.trim_end_matches(['\n', '\r']).len()))
+                })
+                .nth(line - 1)
+                .unwrap_or((input.len(), 0));
+
+            locate(input, window, inner)
+        }
+        JsonError::Path(path, inner) => {
+            let mut located = locate(input, window, inner);
+            located.notes.push(format!("at {path}"));
+            located
+        }
+        JsonError::Offset(offset, inner) => {
+            // The inner error is relative to the buffer it was found in
+            let mut located = locate(input, window, inner);
+            located.offset = (*offset <= len).then(|| start + offset);
+            located.contexts.clear();
+            located
+        }
+        _ => Located {
+            error,
+            offset: None,
+            notes: Vec::new(),
+            contexts: Vec::new(),
+        },
+    }
+}
+
+fn position(input: &str, offset: usize) -> Position {
+    let line_start = input[..offset].rfind('\n').map_or(0, |index| index + 1);
+
+    Position {
+        offset,
+        line: input[..line_start].matches('\n').count() + 1,
+        column: input[line_start..offset].chars().count() + 1,
+    }
+}
+
+/// Line of `input` the byte `offset` is on, without its line break
+fn line_at(input: &str, offset: usize) -> &str {
+    let line_start = input[..offset].rfind('\n').map_or(0, |index| index + 1);
+    let line_end = input[offset..]
+        .find('\n')
+        .map_or(input.len(), |index| offset + index);
+
+    input[line_start..line_end].trim_end_matches('\r')
+}
+
+/// Where `error`, which happened while parsing `input`, is in the input.
+/// `None` for errors that aren't tied to a location, like I/O errors
+pub fn error_position(input: &str, error: &JsonError) -> Option<Position> {
+    let located = locate(input, (0, input.len()), error);
+
+    located.offset.map(|offset| position(input, offset))
+}
+
+/// Renders `error`, which happened while parsing `input`, like:
+///
+/// ```text
+/// error: unexpected 'x'
+///  --> 1:14
+///   |
+/// 1 | {"a": [1, 2, x]}
+///   |              ^ in array
+///   = in map
+/// ```
+pub fn render_error(input: &str, error: &JsonError) -> String {
+    let located = locate(input, (0, input.len()), error);
+    let mut out = format!("error: {}\n", located.error);
+
+    let mut notes = located.notes.into_iter();
+
+    if let Some(offset) = located.offset {
+        let text = line_at(input, offset);
+        let Position { line, column, .. } = position(input, offset);
+        let gutter = " ".repeat(line.to_string().len());
+
+        writeln!(out, "{gutter}--> {line}:{column}").unwrap();
+        writeln!(out, "{gutter} |").unwrap();
+        writeln!(out, "{line} | {text}").unwrap();
+        write!(out, "{gutter} | {}^", " ".repeat(column - 1)).unwrap();
+
+        match notes.next() {
+            Some(label) => writeln!(out, " {label}").unwrap(),
+            None => writeln!(out).unwrap(),
+        }
+
+        for note in notes {
+            writeln!(out, "{gutter} = {note}").unwrap();
+        }
+    } else {
+        for note in notes {
+            writeln!(out, "  = {note}").unwrap();
+        }
+    }
+
+    out
+}
+
+impl JsonError {
+    /// Rules the parser was in when it failed, like nom's `convert_error`
+    /// shows them for a `VerboseError`: the failure, then the start of each
+    /// `context(...)` it happened in, innermost first.
+    ///
+    /// ```
+    /// let input = "{\"a\": [1, 2 3]}";
+    /// let error = step_4::parse_with_path(input).unwrap_err();
+    ///
+    /// assert_eq!(error.trace(input), "\
+    /// 0: at line 1, column 13, unexpected '3', expected ']':
+    /// {\"a\": [1, 2 3]}
+    ///             ^
+    ///
+    /// 1: at line 1, column 7, in array:
+    /// {\"a\": [1, 2 3]}
+    ///       ^
+    ///
+    /// 2: at line 1, column 1, in map:
+    /// {\"a\": [1, 2 3]}
+    /// ^
+    ///
+    /// ");
+    /// ```
+    pub fn trace(&self, input: &str) -> String {
+        let located = locate(input, (0, input.len()), self);
+
+        let Some(offset) = located.offset else {
+            return format!("0: {}\n", located.error);
+        };
+
+        let mut out = String::new();
+        let frames = located
+            .contexts
+            .iter()
+            .map(|(label, offset)| (format!("in {label}"), *offset));
+
+        for (index, (message, offset)) in [(located.error.to_string(), offset)]
+            .into_iter()
+            .chain(frames)
+            .enumerate()
+        {
+            let Position { line, column, .. } = position(input, offset);
+
+            writeln!(out, "{index}: at line {line}, column {column}, {message}:").unwrap();
+            writeln!(out, "{}", line_at(input, offset)).unwrap();
+            writeln!(out, "{}^\n", " ".repeat(column - 1)).unwrap();
+        }
+
+        out
+    }
+}
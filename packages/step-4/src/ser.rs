@@ -0,0 +1,256 @@
+//! Serialization of a [`JsonValue`] back to JSON text, compact by default.
+
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::fmt::Display;
+
+use crate::{
+    wtf8::{self, Piece},
+    JsonValue, SerializeOptions,
+};
+
+/// Serializes `value` without any whitespace
+pub fn to_string(value: &JsonValue) -> String {
+    to_string_with(value, &SerializeOptions::default())
+}
+
+/// Serializes `value` as `opts` say
+///
+/// ```
+/// use step_4::SerializeOptions;
+///
+/// let value = step_4::JsonValue::from("</script><b>&</b>");
+/// let opts = SerializeOptions {
+///     escape_html: true,
+///     ..Default::default()
+/// };
+///
+/// assert_eq!(
+///     step_4::to_string_with(&value, &opts),
+///     r#""\u003c/script\u003e\u003cb\u003e\u0026\u003c/b\u003e""#
+/// );
+/// ```
+pub fn to_string_with(value: &JsonValue, opts: &SerializeOptions) -> String {
+    let mut writer = Writer {
+        out: String::new(),
+        opts,
+        depth: 0,
+    };
+    writer.value(value);
+    writer.out
+}
+
+/// Indented and highlighted for a terminal
+pub fn render_colored(value: &JsonValue) -> String {
+    let opts = SerializeOptions {
+        indent: Some(2),
+        color: true,
+        ..Default::default()
+    };
+
+    to_string_with(value, &opts)
+}
+
+/// Compact JSON, same as [`to_string`]
+impl Display for JsonValue {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(&to_string(self))
+    }
+}
+
+const KEY: &str = "\x1b[34;1m";
+const STRING: &str = "\x1b[32m";
+const NUMBER: &str = "\x1b[36m";
+const LITERAL: &str = "\x1b[35m";
+const RESET: &str = "\x1b[0m";
+
+struct Writer<'o> {
+    out: String,
+    opts: &'o SerializeOptions,
+    depth: usize,
+}
+
+impl Writer<'_> {
+    /// Runs `write` between the escape codes of `color`, if colors are on
+    fn paint(&mut self, color: &str, write: impl FnOnce(&mut String)) {
+        if self.opts.color {
+            self.out.push_str(color);
+            write(&mut self.out);
+            self.out.push_str(RESET);
+        } else {
+            write(&mut self.out);
+        }
+    }
+
+    fn newline(&mut self) {
+        if let Some(width) = self.opts.indent {
+            self.out.push('\n');
+            self.out.push_str(&" ".repeat(width * self.depth));
+        }
+    }
+
+    fn value(&mut self, value: &JsonValue) {
+        match value {
+            JsonValue::Null => self.paint(LITERAL, |out| out.push_str("null")),
+            JsonValue::Str(s) => {
+                let opts = self.opts;
+                self.paint(STRING, |out| write_escaped(out, s, opts))
+            }
+            JsonValue::Boolean(b) => self.paint(LITERAL, |out| {
+                out.push_str(if *b { "true" } else { "false" })
+            }),
+            JsonValue::Num(n) => self.paint(NUMBER, |out| write_float(out, *n)),
+            JsonValue::Int(n) => self.paint(NUMBER, |out| out.push_str(&n.to_string())),
+            JsonValue::UInt(n) => self.paint(NUMBER, |out| out.push_str(&n.to_string())),
+            #[cfg(feature = "bignum")]
+            JsonValue::BigNum(n) => self.paint(NUMBER, |out| out.push_str(&n.to_string())),
+            JsonValue::RawNum(_, text) => self.paint(NUMBER, |out| out.push_str(text)),
+            JsonValue::Wtf8(bytes) => {
+                let opts = self.opts;
+                self.paint(STRING, |out| write_wtf8(out, bytes, opts))
+            }
+            JsonValue::Array(values) => {
+                self.out.push('[');
+                self.depth += 1;
+                for (index, value) in values.iter().enumerate() {
+                    if index > 0 {
+                        self.out.push(',');
+                    }
+                    self.newline();
+                    self.value(value);
+                }
+                self.depth -= 1;
+                if !values.is_empty() {
+                    self.newline();
+                }
+                self.out.push(']');
+            }
+            JsonValue::Object(map) => {
+                let mut entries: Vec<_> = map.iter().collect();
+                if self.opts.sort_keys {
+                    entries.sort_unstable_by_key(|(key, _)| *key);
+                }
+
+                self.out.push('{');
+                self.depth += 1;
+                for (index, (key, value)) in entries.into_iter().enumerate() {
+                    if index > 0 {
+                        self.out.push(',');
+                    }
+                    self.newline();
+                    let opts = self.opts;
+                    self.paint(KEY, |out| write_escaped(out, key, opts));
+                    self.out.push(':');
+                    if self.opts.indent.is_some() {
+                        self.out.push(' ');
+                    }
+                    self.value(value);
+                }
+                self.depth -= 1;
+                if !map.is_empty() {
+                    self.newline();
+                }
+                self.out.push('}');
+            }
+        }
+    }
+}
+
+fn write_float(out: &mut String, n: f64) {
+    // JSON has no representation for them
+    if !n.is_finite() {
+        out.push_str("null");
+        return;
+    }
+
+    // `{:e}` gives the shortest digits that read back to `n`, which we lay
+    // out like JavaScript does: plain notation unless the exponent is large
+    let scientific = format!("{n:e}");
+    let (mantissa, exponent) = scientific.split_once('e').unwrap();
+    let exponent: i32 = exponent.parse().unwrap();
+
+    let (sign, mantissa) = match mantissa.strip_prefix('-') {
+        Some(mantissa) => ("-", mantissa),
+        None => ("", mantissa),
+    };
+    let digits = mantissa.replace('.', "");
+    let len = digits.len() as i32;
+    // Position of the decimal point relative to the start of `digits`
+    let point = exponent + 1;
+
+    out.push_str(sign);
+
+    match point {
+        1..=21 if len <= point => {
+            out.push_str(&digits);
+            out.push_str(&"0".repeat((point - len) as usize));
+            out.push_str(".0");
+        }
+        1..=21 => {
+            out.push_str(&digits[..point as usize]);
+            out.push('.');
+            out.push_str(&digits[point as usize..]);
+        }
+        -5..=0 => {
+            out.push_str("0.");
+            out.push_str(&"0".repeat(-point as usize));
+            out.push_str(&digits);
+        }
+        _ => out.push_str(&scientific[sign.len()..]),
+    }
+}
+
+pub(crate) fn write_str(out: &mut String, s: &str) {
+    write_escaped(out, s, &SerializeOptions::default())
+}
+
+/// Writes `s` as a JSON string, with the escapes `opts` asks for
+fn write_escaped(out: &mut String, s: &str, opts: &SerializeOptions) {
+    out.push('"');
+    write_chars(out, s, opts);
+    out.push('"');
+}
+
+/// Like `write_escaped`, surrogates being written as the escapes they were
+/// parsed from
+fn write_wtf8(out: &mut String, bytes: &[u8], opts: &SerializeOptions) {
+    out.push('"');
+
+    for piece in wtf8::pieces(bytes) {
+        match piece {
+            Piece::Run(run) => write_chars(out, &run, opts),
+            Piece::Surrogate(unit) => out.push_str(&format!("\\u{unit:04x}")),
+        }
+    }
+
+    out.push('"');
+}
+
+fn write_chars(out: &mut String, s: &str, opts: &SerializeOptions) {
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            '\x08' => out.push_str("\\b"),
+            '\x0C' => out.push_str("\\f"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            // Would close the `<script>` tag, start an HTML entity, or end
+            // a line in JavaScript before ES2019
+            '<' | '>' | '&' | '\u{2028}' | '\u{2029}' if opts.escape_html => {
+                out.push_str(&format!("\\u{:04x}", c as u32))
+            }
+            c if opts.ensure_ascii && !c.is_ascii() => {
+                for unit in c.encode_utf16(&mut [0; 2]) {
+                    out.push_str(&format!("\\u{unit:04x}"));
+                }
+            }
+            c => out.push(c),
+        }
+    }
+}
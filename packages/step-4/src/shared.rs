@@ -0,0 +1,274 @@
+//! A value tree whose strings, arrays and objects are reference-counted.
+//!
+//! Cloning a [`JsonValue`] copies the whole subtree; cloning a
+//! [`SharedValue`] only bumps a counter, so a cache can hand out the
+//! coordinates array of canada.json to every document built from it without
+//! copying its tens of thousands of points.
+
+use std::{
+    collections::{hash_map::DefaultHasher, HashSet},
+    hash::{Hash, Hasher},
+    mem,
+    sync::Arc,
+};
+
+#[cfg(feature = "bignum")]
+use crate::BigNumber;
+use crate::{JsonValue, Map};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SharedValue {
+    Null,
+    Str(Arc<str>),
+    Boolean(bool),
+    Num(f64),
+    Int(i64),
+    UInt(u64),
+    #[cfg(feature = "bignum")]
+    BigNum(Arc<BigNumber>),
+    RawNum(f64, Arc<str>),
+    Wtf8(Arc<[u8]>),
+    Array(Arc<[SharedValue]>),
+    Object(Arc<Map<String, SharedValue>>),
+}
+
+impl SharedValue {
+    /// Value at `index` of an array, `None` for other values
+    pub fn get_index(&self, index: usize) -> Option<&SharedValue> {
+        match self {
+            SharedValue::Array(values) => values.get(index),
+            _ => None,
+        }
+    }
+
+    /// Value of `key` in an object, `None` for other values
+    pub fn get(&self, key: &str) -> Option<&SharedValue> {
+        match self {
+            SharedValue::Object(map) => map.get(key),
+            _ => None,
+        }
+    }
+
+    /// Whether both values are the same allocation, which is cheaper than
+    /// comparing them and enough to tell that a cached subtree is unchanged.
+    /// Always `false` for values that aren't reference-counted
+    pub fn ptr_eq(&self, other: &SharedValue) -> bool {
+        match (self, other) {
+            (SharedValue::Str(a), SharedValue::Str(b))
+            | (SharedValue::RawNum(_, a), SharedValue::RawNum(_, b)) => Arc::ptr_eq(a, b),
+            #[cfg(feature = "bignum")]
+            (SharedValue::BigNum(a), SharedValue::BigNum(b)) => Arc::ptr_eq(a, b),
+            (SharedValue::Wtf8(a), SharedValue::Wtf8(b)) => Arc::ptr_eq(a, b),
+            (SharedValue::Array(a), SharedValue::Array(b)) => Arc::ptr_eq(a, b),
+            (SharedValue::Object(a), SharedValue::Object(b)) => Arc::ptr_eq(a, b),
+            _ => false,
+        }
+    }
+
+    /// Deep copy into an owned value
+    pub fn to_value(&self) -> JsonValue {
+        match self {
+            SharedValue::Null => JsonValue::Null,
+            SharedValue::Str(s) => JsonValue::Str(s.to_string()),
+            SharedValue::Boolean(b) => JsonValue::Boolean(*b),
+            SharedValue::Num(n) => JsonValue::Num(*n),
+            SharedValue::Int(n) => JsonValue::Int(*n),
+            SharedValue::UInt(n) => JsonValue::UInt(*n),
+            #[cfg(feature = "bignum")]
+            SharedValue::BigNum(n) => JsonValue::BigNum(BigNumber::clone(n)),
+            SharedValue::RawNum(n, text) => JsonValue::RawNum(*n, text.to_string()),
+            SharedValue::Wtf8(bytes) => JsonValue::Wtf8(bytes.to_vec()),
+            SharedValue::Array(values) => {
+                JsonValue::Array(values.iter().map(SharedValue::to_value).collect())
+            }
+            SharedValue::Object(map) => {
+                JsonValue::Object(map.iter().map(|(k, v)| (k.clone(), v.to_value())).collect())
+            }
+        }
+    }
+}
+
+impl From<JsonValue> for SharedValue {
+    fn from(value: JsonValue) -> Self {
+        match value {
+            JsonValue::Null => SharedValue::Null,
+            JsonValue::Str(s) => SharedValue::Str(s.into()),
+            JsonValue::Boolean(b) => SharedValue::Boolean(b),
+            JsonValue::Num(n) => SharedValue::Num(n),
+            JsonValue::Int(n) => SharedValue::Int(n),
+            JsonValue::UInt(n) => SharedValue::UInt(n),
+            #[cfg(feature = "bignum")]
+            JsonValue::BigNum(n) => SharedValue::BigNum(Arc::new(n)),
+            JsonValue::RawNum(n, text) => SharedValue::RawNum(n, text.into()),
+            JsonValue::Wtf8(bytes) => SharedValue::Wtf8(bytes.into()),
+            JsonValue::Array(values) => {
+                SharedValue::Array(values.into_iter().map(SharedValue::from).collect())
+            }
+            JsonValue::Object(map) => SharedValue::Object(Arc::new(
+                map.into_iter()
+                    .map(|(k, v)| (k, SharedValue::from(v)))
+                    .collect(),
+            )),
+        }
+    }
+}
+
+impl JsonValue {
+    /// Converts into a tree that is cheap to clone, see [`SharedValue`]
+    pub fn into_shared(self) -> SharedValue {
+        self.into()
+    }
+
+    /// Like [`into_shared`](JsonValue::into_shared), but equal strings,
+    /// arrays and objects become the same allocation, which shrinks
+    /// documents that repeat the same objects over and over. Object keys
+    /// aren't shared.
+    ///
+    /// ```
+    /// let value = step_4::parse_with_path(r#"[{"seat": "A1"}, {"seat": "A1"}]"#).unwrap();
+    /// let shared = value.dedup_shared();
+    ///
+    /// let (first, second) = (shared.get_index(0).unwrap(), shared.get_index(1).unwrap());
+    /// assert!(first.ptr_eq(second));
+    /// ```
+    pub fn dedup_shared(self) -> SharedValue {
+        Dedup::default().value(self)
+    }
+}
+
+/// Values already seen, see [`JsonValue::dedup_shared`]
+#[derive(Default)]
+struct Dedup {
+    seen: HashSet<Interned>,
+}
+
+impl Dedup {
+    /// Bottom-up, so that the children of a value are deduplicated before
+    /// the value is looked up
+    fn value(&mut self, value: JsonValue) -> SharedValue {
+        let shared = match value {
+            JsonValue::Array(values) => {
+                SharedValue::Array(values.into_iter().map(|value| self.value(value)).collect())
+            }
+            JsonValue::Object(map) => SharedValue::Object(Arc::new(
+                map.into_iter().map(|(k, v)| (k, self.value(v))).collect(),
+            )),
+            value => SharedValue::from(value),
+        };
+
+        if is_scalar(&shared) {
+            return shared;
+        }
+
+        let interned = Interned(shared);
+        if let Some(Interned(seen)) = self.seen.get(&interned) {
+            return seen.clone();
+        }
+        self.seen.insert(Interned(interned.0.clone()));
+
+        interned.0
+    }
+}
+
+/// Stored inline, with nothing to share
+fn is_scalar(value: &SharedValue) -> bool {
+    matches!(
+        value,
+        SharedValue::Null
+            | SharedValue::Boolean(_)
+            | SharedValue::Num(_)
+            | SharedValue::Int(_)
+            | SharedValue::UInt(_)
+    )
+}
+
+/// Children being deduplicated already, the equal ones are the same
+/// allocation: comparing their addresses is enough
+fn child_eq(a: &SharedValue, b: &SharedValue) -> bool {
+    match (a, b) {
+        (SharedValue::Num(a), SharedValue::Num(b)) => a.to_bits() == b.to_bits(),
+        _ if is_scalar(a) => a == b,
+        _ => a.ptr_eq(b),
+    }
+}
+
+fn hash_child<H: Hasher>(value: &SharedValue, state: &mut H) {
+    mem::discriminant(value).hash(state);
+
+    match value {
+        SharedValue::Null => {}
+        SharedValue::Boolean(b) => b.hash(state),
+        SharedValue::Num(n) => n.to_bits().hash(state),
+        SharedValue::Int(n) => n.hash(state),
+        SharedValue::UInt(n) => n.hash(state),
+        SharedValue::Str(s) => Arc::as_ptr(s).cast::<()>().hash(state),
+        #[cfg(feature = "bignum")]
+        SharedValue::BigNum(n) => Arc::as_ptr(n).cast::<()>().hash(state),
+        SharedValue::RawNum(_, text) => Arc::as_ptr(text).cast::<()>().hash(state),
+        SharedValue::Wtf8(bytes) => Arc::as_ptr(bytes).cast::<()>().hash(state),
+        SharedValue::Array(values) => Arc::as_ptr(values).cast::<()>().hash(state),
+        SharedValue::Object(map) => Arc::as_ptr(map).cast::<()>().hash(state),
+    }
+}
+
+/// Value whose children are deduplicated, compared and hashed by content
+/// with [`child_eq`] and [`hash_child`] for its children
+struct Interned(SharedValue);
+
+impl PartialEq for Interned {
+    fn eq(&self, other: &Self) -> bool {
+        match (&self.0, &other.0) {
+            (SharedValue::Str(a), SharedValue::Str(b)) => a == b,
+            #[cfg(feature = "bignum")]
+            (SharedValue::BigNum(a), SharedValue::BigNum(b)) => a == b,
+            (SharedValue::RawNum(a, a_text), SharedValue::RawNum(b, b_text)) => {
+                a.to_bits() == b.to_bits() && a_text == b_text
+            }
+            (SharedValue::Wtf8(a), SharedValue::Wtf8(b)) => a == b,
+            (SharedValue::Array(a), SharedValue::Array(b)) => {
+                a.len() == b.len() && a.iter().zip(b.iter()).all(|(a, b)| child_eq(a, b))
+            }
+            (SharedValue::Object(a), SharedValue::Object(b)) => {
+                a.len() == b.len()
+                    && a.iter()
+                        .all(|(key, a)| b.get(key).is_some_and(|b| child_eq(a, b)))
+            }
+            (a, b) => child_eq(a, b),
+        }
+    }
+}
+
+impl Eq for Interned {}
+
+impl Hash for Interned {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        mem::discriminant(&self.0).hash(state);
+
+        match &self.0 {
+            SharedValue::Str(s) => s.hash(state),
+            #[cfg(feature = "bignum")]
+            SharedValue::BigNum(n) => n.hash(state),
+            SharedValue::RawNum(n, text) => {
+                n.to_bits().hash(state);
+                text.hash(state);
+            }
+            SharedValue::Wtf8(bytes) => bytes.hash(state),
+            SharedValue::Array(values) => {
+                for value in values.iter() {
+                    hash_child(value, state);
+                }
+            }
+            SharedValue::Object(map) => {
+                // Iteration order differs between equal maps, like in `cmp`
+                let sum = map.iter().fold(0u64, |sum, (key, value)| {
+                    let mut hasher = DefaultHasher::new();
+                    key.hash(&mut hasher);
+                    hash_child(value, &mut hasher);
+                    sum.wrapping_add(hasher.finish())
+                });
+                sum.hash(state);
+            }
+            scalar => hash_child(scalar, state),
+        }
+    }
+}
@@ -0,0 +1,223 @@
+//! A value tree where every node knows where it comes from, so that linters
+//! and config validators can point at the exact location of a bad value.
+
+use nom::{
+    character::complete::{anychar, char, multispace0},
+    combinator::{cut, map, peek},
+    error::{context, ContextError, ErrorKind, ParseError},
+    multi::separated_list0,
+    sequence::{delimited, preceded, separated_pair, terminated},
+    Parser,
+};
+
+use crate::{
+    lexer::{null, number, parse_false, parse_true, string, Number},
+    JsonError, JsonValue, Result,
+};
+use alloc::{string::String, vec::Vec};
+
+/// Location of a char in the input
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Position {
+    /// Byte offset
+    pub offset: usize,
+    /// 1-based
+    pub line: usize,
+    /// 1-based, counted in chars
+    pub column: usize,
+}
+
+/// `value`, found between `start` (inclusive) and `end` (exclusive)
+#[derive(Debug, Clone, PartialEq)]
+pub struct Spanned<T> {
+    pub value: T,
+    pub start: Position,
+    pub end: Position,
+}
+
+/// Key and value of an object entry
+pub type SpannedEntry = (Spanned<String>, Spanned<SpannedValue>);
+
+#[derive(Debug, PartialEq)]
+pub enum SpannedValue {
+    Null,
+    Str(String),
+    Boolean(bool),
+    Num(f64),
+    Int(i64),
+    UInt(u64),
+    Array(Vec<Spanned<SpannedValue>>),
+    /// Entries in document order, duplicates included
+    Object(Vec<SpannedEntry>),
+}
+
+impl SpannedValue {
+    /// Drops the spans
+    pub fn into_value(self) -> JsonValue {
+        match self {
+            SpannedValue::Null => JsonValue::Null,
+            SpannedValue::Str(s) => JsonValue::Str(s),
+            SpannedValue::Boolean(b) => JsonValue::Boolean(b),
+            SpannedValue::Num(n) => JsonValue::Num(n),
+            SpannedValue::Int(n) => JsonValue::Int(n),
+            SpannedValue::UInt(n) => JsonValue::UInt(n),
+            SpannedValue::Array(values) => JsonValue::Array(
+                values
+                    .into_iter()
+                    .map(|value| value.value.into_value())
+                    .collect(),
+            ),
+            SpannedValue::Object(entries) => JsonValue::Object(
+                entries
+                    .into_iter()
+                    .map(|(key, value)| (key.value, value.value.into_value()))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+/// The whole input, to turn the remaining input of a parser into a
+/// [`Position`]. Other grammars use it to record spans too
+pub struct Source<'a> {
+    input: &'a str,
+    /// Byte offset of the start of every line
+    line_starts: Vec<usize>,
+}
+
+impl<'a> Source<'a> {
+    pub fn new(input: &'a str) -> Self {
+        let line_starts = core::iter::once(0)
+            .chain(input.match_indices('\n').map(|(index, _)| index + 1))
+            .collect();
+
+        Self { input, line_starts }
+    }
+
+    /// Position of `rest`, the input a parser left
+    pub fn position(&self, rest: &str) -> Position {
+        let offset = self.input.len() - rest.len();
+        let line = self.line_starts.partition_point(|start| *start <= offset);
+        let line_start = self.line_starts[line - 1];
+
+        Position {
+            offset,
+            line,
+            column: self.input[line_start..offset].chars().count() + 1,
+        }
+    }
+
+    /// Runs `parser` after whitespace, recording where its output starts
+    /// and ends
+    pub fn spanned<'s, O, E: ParseError<&'a str>>(
+        &'s self,
+        mut parser: impl Parser<&'a str, O, E> + 's,
+    ) -> impl FnMut(&'a str) -> Result<'a, Spanned<O>, E> + 's {
+        move |i| {
+            let (i, _) = multispace0(i)?;
+            let start = self.position(i);
+            let (i, value) = parser.parse(i)?;
+
+            Ok((
+                i,
+                Spanned {
+                    value,
+                    start,
+                    end: self.position(i),
+                },
+            ))
+        }
+    }
+}
+
+fn array<'a, E: ParseError<&'a str> + ContextError<&'a str>>(
+    i: &'a str,
+    source: &Source<'a>,
+) -> Result<'a, Vec<Spanned<SpannedValue>>, E> {
+    context(
+        "array",
+        delimited(
+            cut(char('[')),
+            cut(separated_list0(preceded(multispace0, char(',')), |i| {
+                let (i, _) = multispace0(i)?;
+                let (i, next_char) = peek(anychar)(i)?;
+
+                if next_char == ']' {
+                    return Err(nom::Err::Error(E::from_error_kind(i, ErrorKind::Char)));
+                }
+
+                json_value(i, source)
+            })),
+            preceded(multispace0, char(']')),
+        ),
+    )(i)
+}
+
+fn key_value<'a, E: ParseError<&'a str> + ContextError<&'a str>>(
+    i: &'a str,
+    source: &Source<'a>,
+) -> Result<'a, SpannedEntry, E> {
+    let (i, _) = multispace0(i)?;
+
+    let (i, next_char) = peek(anychar)(i)?;
+
+    if next_char == '}' {
+        return Err(nom::Err::Error(E::from_error_kind(i, ErrorKind::Char)));
+    }
+
+    separated_pair(
+        source.spanned(string),
+        cut(preceded(multispace0, char(':'))),
+        |i| json_value(i, source),
+    )
+    .parse(i)
+}
+
+fn hash<'a, E: ParseError<&'a str> + ContextError<&'a str>>(
+    i: &'a str,
+    source: &Source<'a>,
+) -> Result<'a, Vec<SpannedEntry>, E> {
+    context(
+        "map",
+        preceded(
+            cut(char('{')),
+            cut(terminated(
+                separated_list0(preceded(multispace0, char(',')), |i| key_value(i, source)),
+                preceded(multispace0, char('}')),
+            )),
+        ),
+    )
+    .parse(i)
+}
+
+fn json_value<'a, E: ParseError<&'a str> + ContextError<&'a str>>(
+    i: &'a str,
+    source: &Source<'a>,
+) -> Result<'a, Spanned<SpannedValue>, E> {
+    source.spanned(|i| {
+        let (i, first_char) = peek(anychar)(i)?;
+
+        match first_char {
+            '{' => map(|i| hash(i, source), SpannedValue::Object)(i),
+            '[' => map(|i| array(i, source), SpannedValue::Array)(i),
+            '"' => map(string, SpannedValue::Str)(i),
+            '-' | '0'..='9' => map(number, |n| match n {
+                Number::Int(n) => SpannedValue::Int(n),
+                Number::UInt(n) => SpannedValue::UInt(n),
+                Number::Float(n) => SpannedValue::Num(n),
+            })(i),
+            'f' => map(parse_false, SpannedValue::Boolean)(i),
+            't' => map(parse_true, SpannedValue::Boolean)(i),
+            'n' => map(null, |_| SpannedValue::Null)(i),
+            c => Err(nom::Err::Failure(E::from_error_kind(i, ErrorKind::Char))),
+        }
+    })(i)
+}
+
+/// Parses `i`, recording the start and end of every value and key
+pub fn parse_spanned(i: &str) -> Result<'_, Spanned<SpannedValue>, JsonError> {
+    let source = Source::new(i);
+    let result = terminated(|i| json_value(i, &source), multispace0).parse(i);
+
+    result
+}
@@ -0,0 +1,506 @@
+//! Parsing from an [`io::Read`] without holding the whole input in memory.
+//!
+//! The input is read in fixed-size chunks and split into tokens with nom's
+//! streaming combinators: when a token runs past the end of the buffer they
+//! return `Incomplete`, and we read another chunk before trying again. The
+//! tree is built with an explicit stack, so nothing recursive has to survive
+//! between two chunks.
+//!
+//! [`JsonFeeder`] does the same with chunks pushed by the caller rather than
+//! read, for non-blocking code that gets data when it arrives.
+
+use nom::{
+    branch::alt,
+    bytes::streaming::{escaped, is_not, tag},
+    character::streaming::{anychar, char, multispace0},
+    combinator::{map, map_opt, peek, recognize},
+    error::{ErrorKind, FromExternalError, ParseError},
+    number,
+    sequence::{delimited, terminated},
+    Finish,
+};
+use std::{
+    borrow::Cow,
+    collections::VecDeque,
+    io::{self, BufRead, BufReader, Read},
+};
+
+use crate::{
+    gzip::{is_gzip, GzDecoder},
+    lexer::{string, Number},
+    Event, JsonError, JsonValue, Map, Result,
+};
+
+const CHUNK_SIZE: usize = 8 * 1024;
+
+#[derive(Debug)]
+enum Token {
+    BeginObject,
+    EndObject,
+    BeginArray,
+    EndArray,
+    Colon,
+    Comma,
+    Value(JsonValue),
+}
+
+/// Recognizes a whole string with the streaming combinators, then lets the
+/// regular `string` parser unescape it
+fn streaming_string(i: &str) -> Result<'_, String, JsonError> {
+    map_opt(
+        recognize(delimited(
+            char('"'),
+            alt((escaped(is_not("\"\\"), '\\', anychar), tag(""))),
+            char('"'),
+        )),
+        |s| string::<JsonError>(s).ok().map(|(_, s)| s),
+    )(i)
+}
+
+/// Next token of the input. A number touching the end of the input is only
+/// complete once we know no more data is coming, hence `eof`
+fn token(i: &str, eof: bool) -> Result<'_, Token, JsonError> {
+    let (i, _) = multispace0(i)?;
+
+    let (i, first_char) = peek(anychar)(i)?;
+
+    match first_char {
+        '{' => map(char('{'), |_| Token::BeginObject)(i),
+        '}' => map(char('}'), |_| Token::EndObject)(i),
+        '[' => map(char('['), |_| Token::BeginArray)(i),
+        ']' => map(char(']'), |_| Token::EndArray)(i),
+        ':' => map(char(':'), |_| Token::Colon)(i),
+        ',' => map(char(','), |_| Token::Comma)(i),
+        '"' => map(streaming_string, |s| Token::Value(JsonValue::Str(s)))(i),
+        '-' | '0'..='9' => {
+            let (rest, n) = if eof {
+                number::complete::double(i)?
+            } else {
+                number::streaming::double(i)?
+            };
+            let number = Number::new(&i[..i.len() - rest.len()], n);
+
+            Ok((rest, Token::Value(number.into())))
+        }
+        't' => map(tag("true"), |_| Token::Value(JsonValue::Boolean(true)))(i),
+        'f' => map(tag("false"), |_| Token::Value(JsonValue::Boolean(false)))(i),
+        'n' => map(tag("null"), |_| Token::Value(JsonValue::Null))(i),
+        c => Err(nom::Err::Failure(JsonError::from_error_kind(
+            i,
+            ErrorKind::Char,
+        ))),
+    }
+}
+
+enum Frame {
+    Array(Vec<JsonValue>),
+    /// The pending key is set between the key and its value
+    Object(Map<String, JsonValue>, Option<String>),
+}
+
+#[derive(Clone, Copy)]
+enum Expect {
+    /// A value, or `]` right after `[`
+    FirstValue,
+    Value,
+    /// A key, or `}` right after `{`
+    FirstKey,
+    Key,
+    Colon,
+    /// `,` or the end of the current array/object
+    Separator,
+}
+
+struct Builder {
+    stack: Vec<Frame>,
+    expect: Expect,
+    root: Option<JsonValue>,
+    /// Events of the tokens applied so far and not pulled yet, when the
+    /// values aren't kept: arrays and objects then stay empty
+    events: Option<VecDeque<Event<'static>>>,
+    /// Bytes of the input parsed and dropped from the buffer so far
+    consumed: usize,
+}
+
+impl Builder {
+    fn new() -> Self {
+        Self {
+            stack: Vec::new(),
+            expect: Expect::Value,
+            root: None,
+            events: None,
+            consumed: 0,
+        }
+    }
+
+    fn event(&mut self, event: Event<'static>) {
+        if let Some(events) = &mut self.events {
+            events.push_back(event);
+        }
+    }
+
+    /// Applies `token`, or gives it back when it can't come next
+    fn apply(&mut self, token: Token) -> std::result::Result<(), Token> {
+        match (self.expect, token) {
+            (Expect::Value | Expect::FirstValue, Token::BeginArray) => {
+                self.event(Event::StartArray);
+                self.stack.push(Frame::Array(Vec::new()));
+                self.expect = Expect::FirstValue;
+            }
+            (Expect::Value | Expect::FirstValue, Token::BeginObject) => {
+                self.event(Event::StartObject);
+                self.stack.push(Frame::Object(Map::default(), None));
+                self.expect = Expect::FirstKey;
+            }
+            (Expect::Value | Expect::FirstValue, Token::Value(value)) => {
+                let value = match &mut self.events {
+                    Some(events) => {
+                        events.push_back(scalar_event(value));
+                        JsonValue::Null
+                    }
+                    None => value,
+                };
+                self.emit(value)
+            }
+            (Expect::FirstKey | Expect::Key, Token::Value(JsonValue::Str(key))) => {
+                match (&mut self.events, self.stack.last_mut()) {
+                    (Some(events), _) => events.push_back(Event::Key(Cow::Owned(key))),
+                    (None, Some(Frame::Object(_, pending))) => *pending = Some(key),
+                    (None, _) => {}
+                }
+                self.expect = Expect::Colon;
+            }
+            (Expect::Colon, Token::Colon) => self.expect = Expect::Value,
+            (Expect::Separator, Token::Comma) => {
+                self.expect = match self.stack.last() {
+                    Some(Frame::Object(..)) => Expect::Key,
+                    _ => Expect::Value,
+                }
+            }
+            (Expect::FirstValue, Token::EndArray) | (Expect::FirstKey, Token::EndObject) => {
+                self.close()
+            }
+            (Expect::Separator, Token::EndArray)
+                if matches!(self.stack.last(), Some(Frame::Array(_))) =>
+            {
+                self.close()
+            }
+            (Expect::Separator, Token::EndObject)
+                if matches!(self.stack.last(), Some(Frame::Object(..))) =>
+            {
+                self.close()
+            }
+            (_, token) => return Err(token),
+        }
+
+        Ok(())
+    }
+
+    fn emit(&mut self, value: JsonValue) {
+        match self.stack.last_mut() {
+            // Only the events are kept
+            Some(_) if self.events.is_some() => {}
+            Some(Frame::Array(values)) => values.push(value),
+            Some(Frame::Object(map, key)) => {
+                map.insert(key.take().unwrap_or_default(), value);
+            }
+            None => self.root = Some(value),
+        }
+
+        self.expect = Expect::Separator;
+    }
+
+    fn close(&mut self) {
+        let (value, event) = match self.stack.pop() {
+            Some(Frame::Array(values)) => (JsonValue::Array(values), Event::EndArray),
+            Some(Frame::Object(map, _)) => (JsonValue::Object(map), Event::EndObject),
+            None => return,
+        };
+
+        self.event(event);
+        self.emit(value);
+    }
+}
+
+/// Event of a `Token::Value` that isn't a key
+fn scalar_event(value: JsonValue) -> Event<'static> {
+    match value {
+        JsonValue::Str(s) => Event::Str(Cow::Owned(s)),
+        JsonValue::Boolean(b) => Event::Boolean(b),
+        JsonValue::Int(n) => Event::Number(n as f64),
+        JsonValue::UInt(n) => Event::Number(n as f64),
+        JsonValue::Num(n) => Event::Number(n),
+        _ => Event::Null,
+    }
+}
+
+/// `error`, found in `text`, at its offset in the whole input, `consumed`
+/// bytes of which came before `text`
+fn at_offset(error: JsonError, text: &str, consumed: usize) -> JsonError {
+    match error {
+        JsonError::Syntax { remaining, .. } => {
+            JsonError::Offset(consumed + text.len() - remaining, Box::new(error))
+        }
+        error => error,
+    }
+}
+
+/// The input ended, after `consumed` bytes, before the document did
+fn unexpected_end(consumed: usize) -> JsonError {
+    JsonError::Offset(
+        consumed,
+        Box::new(JsonError::from_error_kind("", ErrorKind::Eof)),
+    )
+}
+
+/// Parses the tokens that are whole in `buffer` into `builder`, and drops
+/// them from it. With `eof`, no more data is coming.
+fn advance(
+    buffer: &mut Vec<u8>,
+    builder: &mut Builder,
+    eof: bool,
+) -> std::result::Result<(), JsonError> {
+    // A chunk can end in the middle of a multi-byte char, which is kept
+    // in the buffer for the next round
+    let text = match std::str::from_utf8(buffer) {
+        Ok(text) => text,
+        Err(e) if e.error_len().is_none() => {
+            std::str::from_utf8(&buffer[..e.valid_up_to()]).unwrap()
+        }
+        Err(e) => {
+            let error = JsonError::from_external_error("", ErrorKind::Char, "invalid UTF-8");
+            return Err(JsonError::Offset(
+                builder.consumed + e.valid_up_to(),
+                Box::new(error),
+            ));
+        }
+    };
+
+    let mut i = text;
+
+    while builder.root.is_none() {
+        match token(i, eof) {
+            Ok((rest, token)) => {
+                if builder.apply(token).is_err() {
+                    let start = i.trim_start_matches([' ', '\t', '\r', '\n']);
+                    let error = JsonError::from_error_kind(start, ErrorKind::Char);
+                    return Err(at_offset(error, text, builder.consumed));
+                }
+                i = rest;
+            }
+            Err(nom::Err::Incomplete(_)) => break,
+            Err(nom::Err::Error(e) | nom::Err::Failure(e)) => {
+                return Err(at_offset(e, text, builder.consumed))
+            }
+        }
+    }
+
+    if builder.root.is_some() {
+        let (rest, _) = terminated(
+            nom::character::complete::multispace0::<_, JsonError>,
+            nom::combinator::eof,
+        )(i)
+        .finish()
+        .map_err(|e| at_offset(e, text, builder.consumed))?;
+        i = rest;
+    }
+
+    let consumed = text.len() - i.len();
+    buffer.drain(..consumed);
+    builder.consumed += consumed;
+
+    Ok(())
+}
+
+/// Parses a single document from `reader`, `CHUNK_SIZE` bytes at a time.
+/// Gzip-compressed input is recognized and decompressed on the fly.
+///
+/// Only the bytes of the token being parsed are buffered, so memory usage is
+/// bounded by the largest string or number in the document, not its size.
+/// Errors are thus located by their [`JsonError::Offset`] in the input.
+///
+/// ```
+/// use step_4::{error_position, parse_reader};
+///
+/// let input = "[1,\n 2 x]";
+/// let error = parse_reader(input.as_bytes()).unwrap_err();
+///
+/// assert_eq!(error.to_string(), "unexpected 'x' at byte 7");
+/// let position = error_position(input, &error).unwrap();
+/// assert_eq!((position.line, position.column), (2, 4));
+/// ```
+pub fn parse_reader<R: Read>(reader: R) -> std::result::Result<JsonValue, JsonError> {
+    let mut reader = BufReader::new(reader);
+
+    if is_gzip(reader.fill_buf().map_err(JsonError::Io)?) {
+        parse_chunks(GzDecoder::new(reader))
+    } else {
+        parse_chunks(reader)
+    }
+}
+
+/// Like [`parse_reader`], calling `progress(consumed, total)` after each
+/// chunk read from `reader`, for a progress bar. `total` is the size of the
+/// input as known by the caller, e.g. the length of the file, and
+/// `consumed` counts the bytes read so far, compressed ones for gzip input.
+pub fn parse_reader_with_progress<R: Read>(
+    reader: R,
+    total: usize,
+    progress: impl FnMut(usize, usize),
+) -> std::result::Result<JsonValue, JsonError> {
+    parse_reader(Progress {
+        inner: reader,
+        consumed: 0,
+        total,
+        callback: progress,
+    })
+}
+
+struct Progress<R, F> {
+    inner: R,
+    consumed: usize,
+    total: usize,
+    callback: F,
+}
+
+impl<R: Read, F: FnMut(usize, usize)> Read for Progress<R, F> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.inner.read(buf)?;
+
+        if read > 0 {
+            self.consumed += read;
+            (self.callback)(self.consumed, self.total);
+        }
+
+        Ok(read)
+    }
+}
+
+fn parse_chunks<R: Read>(mut reader: R) -> std::result::Result<JsonValue, JsonError> {
+    let mut buffer = Vec::with_capacity(CHUNK_SIZE);
+    let mut chunk = [0; CHUNK_SIZE];
+    let mut builder = Builder::new();
+    let mut eof = false;
+
+    loop {
+        advance(&mut buffer, &mut builder, eof)?;
+
+        if eof {
+            let end = builder.consumed + buffer.len();
+
+            return builder.root.ok_or_else(|| unexpected_end(end));
+        }
+
+        let read = reader.read(&mut chunk).map_err(JsonError::Io)?;
+        eof = read == 0;
+        buffer.extend_from_slice(&chunk[..read]);
+    }
+}
+
+/// Push parser: the caller feeds the bytes of a document as they arrive,
+/// in chunks cut anywhere, and takes the [`Event`]s parsed so far or the
+/// value once it's whole.
+///
+/// ```
+/// use step_4::{stream::JsonFeeder, Event};
+///
+/// let mut feeder = JsonFeeder::new();
+/// for chunk in [&b"{\"greeting\": \"\xE3\x81"[..], b"\x93\", \"n\": 4", b"2}"] {
+///     feeder.feed(chunk).unwrap();
+/// }
+/// feeder.finish().unwrap();
+///
+/// assert_eq!(
+///     feeder.into_value(),
+///     Some(step_4::parse_with_path(r#"{"greeting": "こ", "n": 42}"#).unwrap())
+/// );
+///
+/// let mut feeder = JsonFeeder::with_events();
+/// feeder.feed(b"[tr").unwrap();
+/// assert_eq!(feeder.next_event(), Some(Event::StartArray));
+/// assert_eq!(feeder.next_event(), None);
+///
+/// feeder.feed(b"ue]").unwrap();
+/// assert_eq!(feeder.next_event(), Some(Event::Boolean(true)));
+/// assert_eq!(feeder.next_event(), Some(Event::EndArray));
+/// ```
+pub struct JsonFeeder {
+    buffer: Vec<u8>,
+    builder: Builder,
+    failed: bool,
+}
+
+impl JsonFeeder {
+    /// Feeder that builds the value, see [`JsonFeeder::into_value`]
+    pub fn new() -> Self {
+        Self {
+            buffer: Vec::new(),
+            builder: Builder::new(),
+            failed: false,
+        }
+    }
+
+    /// Feeder that only keeps the events not pulled yet, see
+    /// [`JsonFeeder::next_event`], so that memory usage doesn't grow with
+    /// the document
+    pub fn with_events() -> Self {
+        let mut feeder = Self::new();
+        feeder.builder.events = Some(VecDeque::new());
+        feeder
+    }
+
+    /// Parses what `bytes` completes. Once it fails, it fails for good.
+    pub fn feed(&mut self, bytes: &[u8]) -> std::result::Result<(), JsonError> {
+        self.buffer.extend_from_slice(bytes);
+        self.advance(false)
+    }
+
+    /// Tells that no more bytes are coming, which ends a number at the end of
+    /// the input, and checks that the document is whole
+    pub fn finish(&mut self) -> std::result::Result<(), JsonError> {
+        self.advance(true)?;
+
+        if !self.is_complete() || !self.buffer.is_empty() {
+            self.failed = true;
+            return Err(unexpected_end(self.builder.consumed + self.buffer.len()));
+        }
+
+        Ok(())
+    }
+
+    fn advance(&mut self, eof: bool) -> std::result::Result<(), JsonError> {
+        if self.failed {
+            return Err(JsonError::Custom("The input is already invalid".to_owned()));
+        }
+
+        let result = advance(&mut self.buffer, &mut self.builder, eof);
+        self.failed = result.is_err();
+        result
+    }
+
+    /// Whether the root value has been parsed whole
+    pub fn is_complete(&self) -> bool {
+        self.builder.root.is_some()
+    }
+
+    /// Oldest event not pulled yet, always `None` without
+    /// [`JsonFeeder::with_events`]
+    pub fn next_event(&mut self) -> Option<Event<'static>> {
+        self.builder.events.as_mut()?.pop_front()
+    }
+
+    /// The value, once it's complete. A number alone is only complete after
+    /// [`JsonFeeder::finish`].
+    pub fn into_value(self) -> Option<JsonValue> {
+        match self.builder.events {
+            Some(_) => None,
+            None => self.builder.root,
+        }
+    }
+}
+
+impl Default for JsonFeeder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,201 @@
+//! Random [`JsonValue`] trees for property tests, such as round trips
+//! through the serializer and the parsers.
+//!
+//! Values are reproducible from their seed, and only use the variants the
+//! parser gives back with the default options: `Null`, `Boolean`, `Str`,
+//! `Int`, finite `Num`, `Array` and `Object`.
+
+use alloc::{string::String, vec::Vec};
+use core::ops::RangeInclusive;
+
+use crate::{JsonValue, Map};
+
+/// Chars the strings and keys are made of
+#[derive(Debug, Clone, PartialEq)]
+pub enum Alphabet {
+    /// `a-z`, `A-Z` and `0-9`
+    Alphanumeric,
+    /// Printable ASCII, plus every char that must be escaped: `"`, `\` and
+    /// the control chars
+    Ascii,
+    /// Any char, from all of the above to the astral planes, in about equal
+    /// parts with the Basic Multilingual Plane
+    Unicode,
+    Custom(Vec<char>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RandomOptions {
+    /// Nesting of arrays and objects, past which only scalars are generated
+    pub max_depth: usize,
+    /// Elements of an array, members of an object
+    pub max_len: usize,
+    /// Chars of a string or key
+    pub max_string_len: usize,
+    pub alphabet: Alphabet,
+    pub ints: RangeInclusive<i64>,
+    pub floats: RangeInclusive<f64>,
+}
+
+impl Default for RandomOptions {
+    fn default() -> Self {
+        Self {
+            max_depth: 4,
+            max_len: 8,
+            max_string_len: 16,
+            alphabet: Alphabet::Unicode,
+            ints: i64::MIN..=i64::MAX,
+            floats: -1e9..=1e9,
+        }
+    }
+}
+
+/// Endless iterator of random values
+///
+/// ```
+/// use step_4::testing::{RandomOptions, RandomValues};
+///
+/// for value in RandomValues::new(42, RandomOptions::default()).take(100) {
+///     let json = step_4::to_string(&value);
+///     assert_eq!(step_4::parse_with_path(&json).unwrap(), value);
+/// }
+/// ```
+pub struct RandomValues {
+    rng: Rng,
+    opts: RandomOptions,
+}
+
+/// xorshift64*, the same on every platform
+struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    fn next_u64(&mut self) -> u64 {
+        self.state ^= self.state >> 12;
+        self.state ^= self.state << 25;
+        self.state ^= self.state >> 27;
+        self.state.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    }
+
+    /// In `0..n`, `n` being non-zero
+    fn below(&mut self, n: usize) -> usize {
+        (self.next_u64() % n as u64) as usize
+    }
+
+    /// In `[0, 1]`
+    fn unit(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Printable, or to be escaped half of the time
+    fn ascii(&mut self) -> char {
+        const ESCAPED: &[u8] = b"\"\\\n\r\t\x08\x0c\x00\x1f";
+
+        if self.below(2) == 0 {
+            ESCAPED[self.below(ESCAPED.len())] as char
+        } else {
+            (b' ' + self.below(95) as u8) as char
+        }
+    }
+
+    /// Char in `range`, surrogates being skipped
+    fn scalar(&mut self, range: RangeInclusive<u32>) -> char {
+        loop {
+            let len = range.end() - range.start() + 1;
+            let code = range.start() + (self.next_u64() % len as u64) as u32;
+
+            if let Some(c) = char::from_u32(code) {
+                return c;
+            }
+        }
+    }
+}
+
+impl RandomValues {
+    pub fn new(seed: u64, opts: RandomOptions) -> Self {
+        Self {
+            // xorshift gets stuck on zero
+            rng: Rng {
+                state: seed ^ 0x9e37_79b9_7f4a_7c15,
+            },
+            opts,
+        }
+    }
+
+    fn value(&mut self, depth: usize) -> JsonValue {
+        let kinds = if depth < self.opts.max_depth { 7 } else { 5 };
+
+        match self.rng.below(kinds) {
+            0 => JsonValue::Null,
+            1 => JsonValue::Boolean(self.rng.below(2) == 0),
+            2 => JsonValue::Str(self.string()),
+            3 => JsonValue::Int(self.int()),
+            4 => JsonValue::Num(self.float()),
+            5 => {
+                let len = self.rng.below(self.opts.max_len + 1);
+                JsonValue::Array((0..len).map(|_| self.value(depth + 1)).collect())
+            }
+            _ => {
+                let len = self.rng.below(self.opts.max_len + 1);
+                let mut map = Map::default();
+                for _ in 0..len {
+                    let key = self.string();
+                    map.insert(key, self.value(depth + 1));
+                }
+                JsonValue::Object(map)
+            }
+        }
+    }
+
+    fn int(&mut self) -> i64 {
+        let (start, end) = (*self.opts.ints.start(), *self.opts.ints.end());
+        let span = end.wrapping_sub(start) as u64;
+
+        match span.checked_add(1) {
+            Some(len) => start.wrapping_add((self.rng.next_u64() % len) as i64),
+            // The whole range of `i64`
+            None => self.rng.next_u64() as i64,
+        }
+    }
+
+    fn float(&mut self) -> f64 {
+        let (start, end) = (*self.opts.floats.start(), *self.opts.floats.end());
+        let t = self.rng.unit();
+
+        // Rather than `start + (end - start) * t`, which overflows on wide
+        // ranges
+        start * (1.0 - t) + end * t
+    }
+
+    fn string(&mut self) -> String {
+        let len = self.rng.below(self.opts.max_string_len + 1);
+        (0..len).map(|_| self.char()).collect()
+    }
+
+    fn char(&mut self) -> char {
+        const ALPHANUMERIC: &[u8] =
+            b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789";
+
+        match &self.opts.alphabet {
+            Alphabet::Alphanumeric => ALPHANUMERIC[self.rng.below(ALPHANUMERIC.len())] as char,
+            Alphabet::Ascii => self.rng.ascii(),
+            Alphabet::Unicode => match self.rng.below(4) {
+                0 => self.rng.ascii(),
+                1 => self.rng.scalar(0x80..=0x7ff),
+                2 => self.rng.scalar(0x800..=0xffff),
+                _ => self.rng.scalar(0x1_0000..=0x10_ffff),
+            },
+            Alphabet::Custom(chars) if chars.is_empty() => 'a',
+            Alphabet::Custom(chars) => chars[self.rng.below(chars.len())],
+        }
+    }
+}
+
+impl Iterator for RandomValues {
+    type Item = JsonValue;
+
+    fn next(&mut self) -> Option<JsonValue> {
+        Some(self.value(0))
+    }
+}
@@ -0,0 +1,720 @@
+//! Conversion to TOML, and with the `toml` feature from TOML.
+//!
+//! TOML has no `null`, only 64-bit signed integers, and documents that are
+//! tables: values that don't fit are refused rather than dropped. The other
+//! way, datetimes have no JSON counterpart and are read as strings.
+
+use alloc::{
+    borrow::ToOwned,
+    boxed::Box,
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+
+use crate::{path::JsonPath, ser::write_str, to_string, JsonError, JsonValue, Map};
+
+impl JsonValue {
+    /// TOML document with the same content, keys being in lexicographic
+    /// order.
+    ///
+    /// Objects are written as `[tables]` and arrays of objects as
+    /// `[[arrays.of.tables]]`, unless they are in an array.
+    ///
+    /// ```
+    /// let config = step_4::parse_with_path(r#"{
+    ///     "name": "meetup",
+    ///     "server": {"port": 8080},
+    ///     "talks": [{"title": "nom"}, {"title": "serde"}]
+    /// }"#).unwrap();
+    ///
+    /// assert_eq!(config.to_toml().unwrap(), r#"name = "meetup"
+    ///
+    /// [server]
+    /// port = 8080
+    ///
+    /// [[talks]]
+    /// title = "nom"
+    ///
+    /// [[talks]]
+    /// title = "serde"
+    /// "#);
+    ///
+    /// // There is no `null` in TOML
+    /// let error = step_4::parse_with_path(r#"{"a": [1, null]}"#).unwrap().to_toml();
+    /// assert_eq!(error.unwrap_err().to_string(), "TOML has no null at $.a[1]");
+    /// ```
+    pub fn to_toml(&self) -> Result<String, JsonError> {
+        let JsonValue::Object(map) = self else {
+            return Err(JsonError::Custom(format!(
+                "TOML documents are tables, found {}",
+                self.type_name()
+            )));
+        };
+
+        let mut writer = TomlWriter {
+            out: String::new(),
+            header: Vec::new(),
+            path: JsonPath::default(),
+        };
+        writer.table(map)?;
+
+        Ok(writer.out)
+    }
+}
+
+fn is_array_of_tables(value: &JsonValue) -> bool {
+    match value {
+        JsonValue::Array(values) => {
+            !values.is_empty() && values.iter().all(|v| matches!(v, JsonValue::Object(_)))
+        }
+        _ => false,
+    }
+}
+
+/// Bare if it can be, quoted otherwise
+fn write_key(out: &mut String, key: &str) {
+    let bare = !key.is_empty()
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+
+    if bare {
+        out.push_str(key);
+    } else {
+        write_toml_str(out, key);
+    }
+}
+
+/// Basic string, with the escapes of JSON which TOML shares, plus `DEL`
+fn write_toml_str(out: &mut String, s: &str) {
+    let mut json = String::new();
+    write_str(&mut json, s);
+    out.push_str(&json.replace('\x7f', "\\u007f"));
+}
+
+struct TomlWriter {
+    out: String,
+    /// Keys of the table being written
+    header: Vec<String>,
+    /// Value being written, for errors
+    path: JsonPath,
+}
+
+impl TomlWriter {
+    fn error(&self, message: &str) -> JsonError {
+        JsonError::Path(
+            self.path.to_string(),
+            Box::new(JsonError::Custom(message.to_owned())),
+        )
+    }
+
+    fn write_header(&mut self, array: bool) {
+        if !self.out.is_empty() {
+            self.out.push('\n');
+        }
+        self.out.push_str(if array { "[[" } else { "[" });
+        for (index, key) in self.header.iter().enumerate() {
+            if index > 0 {
+                self.out.push('.');
+            }
+            write_key(&mut self.out, key);
+        }
+        self.out.push_str(if array { "]]\n" } else { "]\n" });
+    }
+
+    /// Key/value pairs first, then sub-tables, as a value can't follow
+    /// them
+    fn table(&mut self, map: &Map<String, JsonValue>) -> Result<(), JsonError> {
+        let mut entries: Vec<_> = map.iter().collect();
+        entries.sort_unstable_by_key(|(key, _)| *key);
+
+        let is_table =
+            |value: &JsonValue| matches!(value, JsonValue::Object(_)) || is_array_of_tables(value);
+
+        for (key, value) in entries.iter().filter(|(_, value)| !is_table(value)) {
+            self.path.push_key((*key).clone());
+            write_key(&mut self.out, key);
+            self.out.push_str(" = ");
+            self.inline(value)?;
+            self.out.push('\n');
+            self.path.pop();
+        }
+
+        for (key, value) in entries.iter().filter(|(_, value)| is_table(value)) {
+            self.path.push_key((*key).clone());
+            self.header.push((*key).clone());
+
+            match value {
+                JsonValue::Object(map) => {
+                    self.write_header(false);
+                    self.table(map)?;
+                }
+                JsonValue::Array(values) => {
+                    for (index, value) in values.iter().enumerate() {
+                        let JsonValue::Object(map) = value else {
+                            unreachable!()
+                        };
+                        self.path.push_index(index);
+                        self.write_header(true);
+                        self.table(map)?;
+                        self.path.pop();
+                    }
+                }
+                _ => unreachable!(),
+            }
+
+            self.header.pop();
+            self.path.pop();
+        }
+
+        Ok(())
+    }
+
+    /// Value on the right of a `=`, or in an array or inline table
+    fn inline(&mut self, value: &JsonValue) -> Result<(), JsonError> {
+        match value {
+            JsonValue::Null => return Err(self.error("TOML has no null")),
+            JsonValue::UInt(_) => return Err(self.error("integer too large for TOML")),
+            JsonValue::Str(s) => write_toml_str(&mut self.out, s),
+            JsonValue::Num(n) if n.is_nan() => self.out.push_str("nan"),
+            JsonValue::Num(n) if n.is_infinite() => {
+                self.out.push_str(if *n > 0.0 { "inf" } else { "-inf" })
+            }
+            JsonValue::Array(values) => {
+                self.out.push('[');
+                for (index, value) in values.iter().enumerate() {
+                    if index > 0 {
+                        self.out.push_str(", ");
+                    }
+                    self.path.push_index(index);
+                    self.inline(value)?;
+                    self.path.pop();
+                }
+                self.out.push(']');
+            }
+            JsonValue::Object(map) => {
+                let mut entries: Vec<_> = map.iter().collect();
+                entries.sort_unstable_by_key(|(key, _)| *key);
+
+                self.out.push('{');
+                for (index, (key, value)) in entries.into_iter().enumerate() {
+                    self.out.push_str(if index > 0 { ", " } else { " " });
+                    self.path.push_key(key.clone());
+                    write_key(&mut self.out, key);
+                    self.out.push_str(" = ");
+                    self.inline(value)?;
+                    self.path.pop();
+                }
+                self.out.push_str(if map.is_empty() { "}" } else { " }" });
+            }
+            // Booleans, integers, and floats which are always written with a
+            // fraction or an exponent, as TOML wants them
+            value => self.out.push_str(&to_string(value)),
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(feature = "toml")]
+pub use self::read::from_toml;
+
+#[cfg(feature = "toml")]
+mod read {
+    use alloc::{borrow::ToOwned, collections::BTreeSet, format, string::String, vec::Vec};
+    use nom::{
+        branch::alt,
+        bytes::complete::{tag, take, take_while, take_while1},
+        character::complete::{anychar, char, satisfy, space0},
+        combinator::{cut, map, map_opt, map_res, opt, recognize, value},
+        error::{context, ErrorKind, FromExternalError, ParseError},
+        multi::{many0, separated_list1},
+        sequence::{delimited, preceded, terminated},
+        Finish,
+    };
+
+    use crate::{JsonError, JsonValue, Map, Result};
+
+    /// Whitespace, comments and line breaks, as found between the values of
+    /// an array
+    fn blank(i: &str) -> Result<'_, (), JsonError> {
+        value(
+            (),
+            many0(alt((
+                take_while1(|c| c == ' ' || c == '\t' || c == '\n'),
+                tag("\r\n"),
+                comment,
+            ))),
+        )(i)
+    }
+
+    fn comment(i: &str) -> Result<'_, &str, JsonError> {
+        recognize(preceded(char('#'), take_while(|c| c != '\n' && c != '\r')))(i)
+    }
+
+    /// End of a line, with an optional comment
+    fn line_end(i: &str) -> Result<'_, (), JsonError> {
+        let (i, _) = opt(comment)(i)?;
+
+        if i.is_empty() {
+            return Ok((i, ()));
+        }
+        value((), alt((tag("\n"), tag("\r\n"))))(i)
+    }
+
+    fn hex_char(len: usize) -> impl Fn(&str) -> Result<'_, char, JsonError> {
+        move |i| {
+            map_opt(
+                map_res(take(len), |hex: &str| {
+                    if hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+                        u32::from_str_radix(hex, 16)
+                    } else {
+                        // Error of `from_str_radix` on an invalid digit
+                        u32::from_str_radix("g", 16)
+                    }
+                }),
+                char::from_u32,
+            )(i)
+        }
+    }
+
+    fn escape(input: &str) -> Result<'_, char, JsonError> {
+        let (i, _) = char('\\')(input)?;
+        let (i, c) = cut(anychar)(i)?;
+
+        let c = match c {
+            'b' => '\x08',
+            't' => '\t',
+            'n' => '\n',
+            'f' => '\x0c',
+            'r' => '\r',
+            '"' => '"',
+            '\\' => '\\',
+            'u' => return cut(hex_char(4))(i),
+            'U' => return cut(hex_char(8))(i),
+            c => return Err(failure(input, format!("invalid escape `\\{c}`"))),
+        };
+
+        Ok((i, c))
+    }
+
+    /// Any char but the ASCII control ones, tabs aside
+    fn is_literal_char(c: char) -> bool {
+        c == '\t' || (c >= ' ' && c != '\x7f')
+    }
+
+    fn basic_string(i: &str) -> Result<'_, String, JsonError> {
+        context(
+            "string",
+            delimited(
+                char('"'),
+                cut(map(
+                    many0(alt((
+                        escape,
+                        satisfy(|c| c != '"' && c != '\\' && is_literal_char(c)),
+                    ))),
+                    |chars| chars.into_iter().collect(),
+                )),
+                cut(char('"')),
+            ),
+        )(i)
+    }
+
+    fn literal_string(i: &str) -> Result<'_, String, JsonError> {
+        context(
+            "string",
+            map(
+                delimited(
+                    char('\''),
+                    take_while(|c| c != '\'' && is_literal_char(c)),
+                    cut(char('\'')),
+                ),
+                str::to_owned,
+            ),
+        )(i)
+    }
+
+    /// `"""..."""`, where a `\` at the end of a line trims the whitespace
+    /// that follows it
+    fn multiline_basic_string(i: &str) -> Result<'_, String, JsonError> {
+        let (mut i, _) = tag("\"\"\"")(i)?;
+        i = i.strip_prefix('\n').or(i.strip_prefix("\r\n")).unwrap_or(i);
+        let mut s = String::new();
+
+        loop {
+            if let Some(rest) = i.strip_prefix("\"\"\"") {
+                // Up to two quotes can end the content
+                let quotes = rest.len() - rest.trim_start_matches('"').len();
+                if quotes > 2 {
+                    return Err(nom::Err::Failure(JsonError::from_error_kind(
+                        i,
+                        ErrorKind::Tag,
+                    )));
+                }
+                s.extend(core::iter::repeat_n('"', quotes));
+                return Ok((&rest[quotes..], s));
+            }
+
+            if let Some(rest) = i.strip_prefix('\\') {
+                let trimmed = rest.trim_start_matches([' ', '\t']);
+                if trimmed.starts_with(['\n', '\r']) {
+                    i = trimmed.trim_start_matches([' ', '\t', '\n', '\r']);
+                    continue;
+                }
+
+                let (rest, c) = escape(i)?;
+                s.push(c);
+                i = rest;
+                continue;
+            }
+
+            let (rest, c) = cut(context(
+                "string",
+                satisfy(|c| c == '\n' || c == '\r' || is_literal_char(c)),
+            ))(i)?;
+            s.push(c);
+            i = rest;
+        }
+    }
+
+    fn multiline_literal_string(i: &str) -> Result<'_, String, JsonError> {
+        let (i, _) = tag("'''")(i)?;
+        let i = i.strip_prefix('\n').or(i.strip_prefix("\r\n")).unwrap_or(i);
+
+        let Some(end) = i.find("'''") else {
+            return Err(nom::Err::Failure(JsonError::from_error_kind(
+                "",
+                ErrorKind::Tag,
+            )));
+        };
+        // Up to two quotes can end the content
+        let quotes = i[end + 3..].len() - i[end + 3..].trim_start_matches('\'').len();
+        let end = end + quotes.min(2);
+
+        Ok((&i[end + 3..], i[..end].to_owned()))
+    }
+
+    fn string(i: &str) -> Result<'_, String, JsonError> {
+        alt((
+            multiline_basic_string,
+            basic_string,
+            multiline_literal_string,
+            literal_string,
+        ))(i)
+    }
+
+    fn key_part(i: &str) -> Result<'_, String, JsonError> {
+        alt((
+            basic_string,
+            literal_string,
+            map(
+                take_while1(|c: char| c.is_ascii_alphanumeric() || c == '_' || c == '-'),
+                str::to_owned,
+            ),
+        ))(i)
+    }
+
+    /// `a.b."c d"`
+    fn key(i: &str) -> Result<'_, Vec<String>, JsonError> {
+        context(
+            "key",
+            separated_list1(delimited(space0, char('.'), space0), key_part),
+        )(i)
+    }
+
+    /// Digits with `_` between them, as `1_000`
+    fn check_underscores(digits: &str) -> bool {
+        !digits.starts_with('_')
+            && !digits.ends_with('_')
+            && !digits.contains("__")
+            && !digits.contains("_.")
+            && !digits.contains("._")
+    }
+
+    /// Integers, floats, booleans and datetimes, told apart by their text
+    fn scalar(text: &str) -> core::result::Result<JsonValue, String> {
+        let invalid = || format!("invalid value `{text}`");
+
+        match text {
+            "true" => return Ok(JsonValue::Boolean(true)),
+            "false" => return Ok(JsonValue::Boolean(false)),
+            "inf" | "+inf" => return Ok(JsonValue::Num(f64::INFINITY)),
+            "-inf" => return Ok(JsonValue::Num(f64::NEG_INFINITY)),
+            "nan" | "+nan" | "-nan" => return Ok(JsonValue::Num(f64::NAN)),
+            _ => {}
+        }
+
+        let bytes = text.as_bytes();
+        let is_date = bytes.len() >= 10 && bytes[4] == b'-' && bytes[7] == b'-';
+        let is_time = bytes.len() >= 8 && bytes[2] == b':' && bytes[5] == b':';
+        if is_date || is_time {
+            let valid = text
+                .bytes()
+                .all(|b| b.is_ascii_digit() || b"-:.+TtZz ".contains(&b));
+            return valid
+                .then(|| JsonValue::Str(text.to_owned()))
+                .ok_or_else(invalid);
+        }
+
+        let radix = [("0x", 16), ("0o", 8), ("0b", 2)]
+            .into_iter()
+            .find_map(|(prefix, radix)| Some((text.strip_prefix(prefix)?, radix)));
+        if let Some((digits, radix)) = radix {
+            if !check_underscores(digits) || digits.starts_with(['+', '-']) {
+                return Err(invalid());
+            }
+            return i64::from_str_radix(&digits.replace('_', ""), radix)
+                .map(JsonValue::Int)
+                .map_err(|_| invalid());
+        }
+
+        let unsigned = text.strip_prefix(['+', '-']).unwrap_or(text);
+        let leading_zero = unsigned.len() > 1
+            && unsigned.starts_with('0')
+            && unsigned.as_bytes()[1].is_ascii_digit();
+        if !check_underscores(unsigned)
+            || leading_zero
+            || !unsigned.starts_with(|c: char| c.is_ascii_digit())
+        {
+            return Err(invalid());
+        }
+
+        let number = text.replace('_', "");
+        if unsigned.bytes().all(|b| b.is_ascii_digit() || b == b'_') {
+            return number.parse().map(JsonValue::Int).map_err(|_| invalid());
+        }
+
+        // A `.` needs digits on both sides
+        let mut parts = unsigned.split(['e', 'E']);
+        let mantissa = parts.next().unwrap_or("");
+        let digits_around_point = mantissa.split_once('.').is_none_or(|(int, frac)| {
+            !int.is_empty() && frac.starts_with(|c: char| c.is_ascii_digit())
+        });
+        if !digits_around_point {
+            return Err(invalid());
+        }
+
+        number.parse().map(JsonValue::Num).map_err(|_| invalid())
+    }
+
+    fn bare_value(i: &str) -> Result<'_, JsonValue, JsonError> {
+        let is_bare = |c: char| c.is_ascii_alphanumeric() || "+-._:".contains(c);
+        let (mut rest, _) = take_while1(is_bare)(i)?;
+
+        // The space between the date and the time of a datetime
+        let date_len = i.len() - rest.len();
+        if date_len == 10 && i.as_bytes()[4] == b'-' {
+            let after = &rest[1.min(rest.len())..];
+            if rest.starts_with(' ') && after.get(2..3) == Some(":") {
+                let (after, _) = take_while1(is_bare)(after)?;
+                rest = after;
+            }
+        }
+
+        map_res(take(i.len() - rest.len()), scalar)(i)
+    }
+
+    fn array(i: &str) -> Result<'_, Vec<JsonValue>, JsonError> {
+        context(
+            "array",
+            preceded(
+                char('['),
+                cut(terminated(
+                    many0(delimited(
+                        blank,
+                        toml_value,
+                        delimited(blank, opt(char(',')), blank),
+                    )),
+                    preceded(blank, char(']')),
+                )),
+            ),
+        )(i)
+    }
+
+    fn inline_table(i: &str) -> Result<'_, Map<String, JsonValue>, JsonError> {
+        let (mut i, _) = terminated(char('{'), space0)(i)?;
+        let mut map = Map::default();
+
+        if let Ok((rest, _)) = char::<_, JsonError>('}')(i) {
+            return Ok((rest, map));
+        }
+
+        loop {
+            let (rest, keys) = cut(key)(i)?;
+            let (rest, _) = cut(delimited(space0, char('='), space0))(rest)?;
+            let (rest, value) = cut(toml_value)(rest)?;
+            insert(&mut map, &keys, value).map_err(|e| failure(i, e))?;
+
+            let (rest, _) = space0(rest)?;
+            let (rest, end) = cut(alt((char(','), char('}'))))(rest)?;
+            let (rest, _) = space0(rest)?;
+            i = rest;
+
+            if end == '}' {
+                return Ok((i, map));
+            }
+        }
+    }
+
+    fn toml_value(i: &str) -> Result<'_, JsonValue, JsonError> {
+        alt((
+            map(string, JsonValue::Str),
+            map(array, JsonValue::Array),
+            map(inline_table, JsonValue::Object),
+            bare_value,
+        ))(i)
+    }
+
+    fn failure(i: &str, message: String) -> nom::Err<JsonError> {
+        nom::Err::Failure(JsonError::from_external_error(
+            i,
+            ErrorKind::Verify,
+            message,
+        ))
+    }
+
+    /// Inserts `value` at the dotted `keys` of `map`
+    fn insert(
+        map: &mut Map<String, JsonValue>,
+        keys: &[String],
+        value: JsonValue,
+    ) -> core::result::Result<(), String> {
+        let (last, parents) = keys.split_last().expect("keys aren't empty");
+        let table = descend(map, parents)?;
+
+        if table.contains_key(last) {
+            return Err(format!("duplicate key `{}`", keys.join(".")));
+        }
+        table.insert(last.clone(), value);
+
+        Ok(())
+    }
+
+    /// Table at `keys`, created if needed. Arrays of tables stand for their
+    /// last table.
+    fn descend<'m>(
+        mut map: &'m mut Map<String, JsonValue>,
+        keys: &[String],
+    ) -> core::result::Result<&'m mut Map<String, JsonValue>, String> {
+        for (depth, key) in keys.iter().enumerate() {
+            let entry = map
+                .entry(key.clone())
+                .or_insert_with(|| JsonValue::Object(Map::default()));
+
+            map = match entry {
+                JsonValue::Object(map) => map,
+                JsonValue::Array(values) => match values.last_mut() {
+                    Some(JsonValue::Object(map)) => map,
+                    _ => return Err(not_a_table(&keys[..=depth])),
+                },
+                _ => return Err(not_a_table(&keys[..=depth])),
+            };
+        }
+
+        Ok(map)
+    }
+
+    fn not_a_table(keys: &[String]) -> String {
+        format!("`{}` is already a value, not a table", keys.join("."))
+    }
+
+    /// Parses a TOML document into an object. Errors locate the failure
+    /// like those of [`parse_with_path`](crate::parse_with_path), see
+    /// [`render_error`](crate::render_error).
+    ///
+    /// ```
+    /// let config = step_4::from_toml(r#"
+    /// name = "meetup"  # where it all began
+    ///
+    /// [server]
+    /// port = 8_080
+    /// started = 2024-01-15T19:00:00+09:00
+    ///
+    /// [[talks]]
+    /// title = "nom"
+    /// "#).unwrap();
+    ///
+    /// assert_eq!(
+    ///     config,
+    ///     step_4::parse_with_path(r#"{
+    ///         "name": "meetup",
+    ///         "server": {"port": 8080, "started": "2024-01-15T19:00:00+09:00"},
+    ///         "talks": [{"title": "nom"}]
+    ///     }"#).unwrap()
+    /// );
+    /// ```
+    pub fn from_toml(input: &str) -> core::result::Result<JsonValue, JsonError> {
+        document(input)
+            .finish()
+            .map(|(_, root)| JsonValue::Object(root))
+    }
+
+    fn document(input: &str) -> Result<'_, Map<String, JsonValue>, JsonError> {
+        let mut root = Map::default();
+        // Keys of the current table
+        let mut current: Vec<String> = Vec::new();
+        // Tables defined by a header, which can't be defined twice
+        let mut defined = BTreeSet::new();
+        let mut i = input;
+
+        loop {
+            let (rest, _) = blank(i)?;
+            i = rest;
+
+            if i.is_empty() {
+                return Ok((i, root));
+            }
+
+            if let Some(rest) = i.strip_prefix("[[") {
+                let (rest, keys) =
+                    cut(terminated(delimited(space0, key, space0), tag("]]")))(rest)?;
+                let (parents, last) = keys.split_at(keys.len() - 1);
+
+                let table = descend(&mut root, parents).map_err(|e| failure(i, e))?;
+                let entry = table
+                    .entry(last[0].clone())
+                    .or_insert_with(|| JsonValue::Array(Vec::new()));
+                match entry {
+                    JsonValue::Array(tables)
+                        if tables.iter().all(|t| matches!(t, JsonValue::Object(_))) =>
+                    {
+                        tables.push(JsonValue::Object(Map::default()))
+                    }
+                    _ => return Err(failure(i, not_a_table(&keys))),
+                }
+
+                // The tables of the previous element can be defined again
+                defined.retain(|table: &Vec<String>| !table.starts_with(&keys));
+                current = keys;
+                i = rest;
+            } else if let Some(rest) = i.strip_prefix('[') {
+                let (rest, keys) =
+                    cut(terminated(delimited(space0, key, space0), char(']')))(rest)?;
+
+                if !defined.insert(keys.clone()) {
+                    return Err(failure(
+                        i,
+                        format!("table `{}` is defined twice", keys.join(".")),
+                    ));
+                }
+                descend(&mut root, &keys).map_err(|e| failure(i, e))?;
+                current = keys;
+                i = rest;
+            } else {
+                let (rest, keys) = key(i)?;
+                let (rest, _) = cut(delimited(space0, char('='), space0))(rest)?;
+                let (rest, value) = cut(toml_value)(rest)?;
+
+                let table = descend(&mut root, &current).map_err(|e| failure(i, e))?;
+                insert(table, &keys, value).map_err(|e| failure(i, e))?;
+                i = rest;
+            }
+
+            let (rest, _) = space0(i)?;
+            let (rest, _) = line_end(rest)
+                .map_err(|_| failure(rest, "expected a line break after the value".into()))?;
+            i = rest;
+        }
+    }
+}
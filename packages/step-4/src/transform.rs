@@ -0,0 +1,126 @@
+//! Transformations of a whole tree: the recursion that data cleaning
+//! (renaming keys, dropping nulls, sorting arrays) always needs, written
+//! once.
+
+use alloc::string::String;
+use core::cmp::Ordering;
+
+use crate::JsonValue;
+
+impl JsonValue {
+    /// Replaces every value with what `f` returns for it, the children of
+    /// arrays and objects being replaced before `f` sees them
+    ///
+    /// ```
+    /// use step_4::JsonValue;
+    ///
+    /// let value = step_4::parse_with_path(r#"{"price": "12", "tags": ["7", "x"]}"#).unwrap();
+    ///
+    /// let value = value.map_values(|value| match value {
+    ///     JsonValue::Str(s) => s.parse().map_or(JsonValue::Str(s), JsonValue::Int),
+    ///     value => value,
+    /// });
+    /// assert_eq!(value, step_4::parse_with_path(r#"{"price": 12, "tags": [7, "x"]}"#).unwrap());
+    /// ```
+    pub fn map_values(self, mut f: impl FnMut(JsonValue) -> JsonValue) -> JsonValue {
+        map_values(self, &mut f)
+    }
+
+    /// Renames the keys of every object with `f`. When two keys get the
+    /// same name, one of the values is lost
+    ///
+    /// ```
+    /// let mut value = step_4::parse_with_path(r#"{"user-id": 1, "tags": [{"tag-name": "a"}]}"#).unwrap();
+    ///
+    /// value.map_keys(|key| key.replace('-', "_"));
+    /// assert_eq!(value, step_4::parse_with_path(r#"{"user_id": 1, "tags": [{"tag_name": "a"}]}"#).unwrap());
+    /// ```
+    pub fn map_keys(&mut self, mut f: impl FnMut(&str) -> String) {
+        self.walk_mut(&mut |value| {
+            if let JsonValue::Object(map) = value {
+                *map = core::mem::take(map)
+                    .into_iter()
+                    .map(|(key, value)| (f(&key), value))
+                    .collect();
+            }
+        });
+    }
+
+    /// Keeps the elements of arrays and the members of objects for which
+    /// `f` is true, everywhere in the tree. Values are cleaned before `f`
+    /// sees them, so that it can drop those left empty
+    ///
+    /// ```
+    /// use step_4::JsonValue;
+    ///
+    /// let mut value = step_4::parse_with_path(r#"{"a": null, "b": {"c": null}, "d": [1, null]}"#).unwrap();
+    ///
+    /// value.retain(|value| match value {
+    ///     JsonValue::Null => false,
+    ///     JsonValue::Object(map) => !map.is_empty(),
+    ///     _ => true,
+    /// });
+    /// assert_eq!(value, step_4::parse_with_path(r#"{"d": [1]}"#).unwrap());
+    /// ```
+    pub fn retain(&mut self, mut f: impl FnMut(&JsonValue) -> bool) {
+        self.walk_mut(&mut |value| match value {
+            JsonValue::Array(values) => values.retain(&mut f),
+            JsonValue::Object(map) => map.retain(|_, value| f(value)),
+            _ => {}
+        });
+    }
+
+    /// Sorts every array with `compare`, stably
+    ///
+    /// ```
+    /// let mut value = step_4::parse_with_path(r#"{"users": [{"id": 2}, {"id": 1}]}"#).unwrap();
+    ///
+    /// value.sort_arrays_by(|a, b| a.get_path("id").cmp(&b.get_path("id")));
+    /// assert_eq!(value, step_4::parse_with_path(r#"{"users": [{"id": 1}, {"id": 2}]}"#).unwrap());
+    /// ```
+    pub fn sort_arrays_by(&mut self, mut compare: impl FnMut(&JsonValue, &JsonValue) -> Ordering) {
+        self.walk_mut(&mut |value| {
+            if let JsonValue::Array(values) = value {
+                values.sort_by(&mut compare);
+            }
+        });
+    }
+
+    /// Calls `f` on every value, children first
+    fn walk_mut(&mut self, f: &mut impl FnMut(&mut JsonValue)) {
+        match self {
+            JsonValue::Array(values) => {
+                for value in values.iter_mut() {
+                    value.walk_mut(f);
+                }
+            }
+            JsonValue::Object(map) => {
+                for value in map.values_mut() {
+                    value.walk_mut(f);
+                }
+            }
+            _ => {}
+        }
+
+        f(self);
+    }
+}
+
+fn map_values(value: JsonValue, f: &mut impl FnMut(JsonValue) -> JsonValue) -> JsonValue {
+    let value = match value {
+        JsonValue::Array(values) => JsonValue::Array(
+            values
+                .into_iter()
+                .map(|value| map_values(value, f))
+                .collect(),
+        ),
+        JsonValue::Object(map) => JsonValue::Object(
+            map.into_iter()
+                .map(|(key, value)| (key, map_values(value, f)))
+                .collect(),
+        ),
+        value => value,
+    };
+
+    f(value)
+}
@@ -0,0 +1,60 @@
+//! Callback-based consumption of a document, for building custom data
+//! structures (counters, indexes, typed structs, ...) during the parse instead
+//! of post-processing a [`JsonValue`](crate::JsonValue).
+
+use alloc::borrow::Cow;
+
+use crate::{Event, JsonError, JsonReader};
+
+/// Receives the values of a document in order. Every method does nothing by
+/// default, so only the relevant ones have to be implemented.
+///
+/// Strings without escapes are borrowed from the input, hence the lifetime.
+#[allow(unused_variables)]
+pub trait JsonVisitor<'a> {
+    fn visit_null(&mut self) {}
+
+    fn visit_bool(&mut self, value: bool) {}
+
+    fn visit_number(&mut self, value: f64) {}
+
+    fn visit_str(&mut self, value: Cow<'a, str>) {}
+
+    fn begin_object(&mut self) {}
+
+    /// Called before the value of each entry
+    fn visit_key(&mut self, key: Cow<'a, str>) {}
+
+    fn end_object(&mut self) {}
+
+    fn begin_array(&mut self) {}
+
+    fn end_array(&mut self) {}
+}
+
+/// Walks `input` with a [`JsonReader`], forwarding every event to `visitor`.
+///
+/// The visitor may have been called for the beginning of the document when an
+/// error is returned.
+pub fn parse_with_visitor<'a, V: JsonVisitor<'a>>(
+    input: &'a str,
+    visitor: &mut V,
+) -> Result<(), JsonError> {
+    for event in JsonReader::new(input) {
+        let (_, event) = event?;
+
+        match event {
+            Event::StartObject => visitor.begin_object(),
+            Event::EndObject => visitor.end_object(),
+            Event::StartArray => visitor.begin_array(),
+            Event::EndArray => visitor.end_array(),
+            Event::Key(key) => visitor.visit_key(key),
+            Event::Str(value) => visitor.visit_str(value),
+            Event::Number(value) => visitor.visit_number(value),
+            Event::Boolean(value) => visitor.visit_bool(value),
+            Event::Null => visitor.visit_null(),
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,67 @@
+//! WTF-8, the superset of UTF-8 that encodes lone surrogates too, like
+//! UTF-8 would if they were chars: the strings of
+//! [`SurrogateMode::Wtf8`](crate::SurrogateMode::Wtf8).
+
+use alloc::{borrow::Cow, string::String, vec::Vec};
+
+pub(crate) enum Piece<'a> {
+    /// UTF-8, invalid bytes being replaced with U+FFFD
+    Run(Cow<'a, str>),
+    Surrogate(u16),
+}
+
+pub(crate) fn push_surrogate(bytes: &mut Vec<u8>, unit: u16) {
+    bytes.extend([
+        0xE0 | (unit >> 12) as u8,
+        0x80 | ((unit >> 6) & 0x3F) as u8,
+        0x80 | (unit & 0x3F) as u8,
+    ]);
+}
+
+/// Surrogate `bytes` start with
+fn surrogate(bytes: &[u8]) -> Option<u16> {
+    match *bytes {
+        [0xED, high @ 0xA0..=0xBF, low @ 0x80..=0xBF, ..] => {
+            Some(0xD000 | ((high as u16 & 0x3F) << 6) | (low as u16 & 0x3F))
+        }
+        _ => None,
+    }
+}
+
+/// Runs of UTF-8 of `bytes`, and the surrogates between them
+pub(crate) fn pieces(bytes: &[u8]) -> impl Iterator<Item = Piece<'_>> {
+    let mut rest = bytes;
+
+    core::iter::from_fn(move || {
+        if rest.is_empty() {
+            return None;
+        }
+
+        if let Some(unit) = surrogate(rest) {
+            rest = &rest[3..];
+            return Some(Piece::Surrogate(unit));
+        }
+
+        let end = (1..rest.len())
+            .find(|&index| surrogate(&rest[index..]).is_some())
+            .unwrap_or(rest.len());
+        let (run, tail) = rest.split_at(end);
+        rest = tail;
+
+        Some(Piece::Run(String::from_utf8_lossy(run)))
+    })
+}
+
+/// `bytes` with U+FFFD for each surrogate
+pub(crate) fn to_string_lossy(bytes: &[u8]) -> String {
+    let mut string = String::with_capacity(bytes.len());
+
+    for piece in pieces(bytes) {
+        match piece {
+            Piece::Run(run) => string.push_str(&run),
+            Piece::Surrogate(_) => string.push('\u{FFFD}'),
+        }
+    }
+
+    string
+}
@@ -0,0 +1,624 @@
+//! Conversion to YAML, and with the `yaml` feature from YAML.
+//!
+//! Only the part of YAML that maps to JSON is read: block and flow
+//! collections, plain, quoted and block scalars, and comments. Anchors,
+//! aliases, tags and complex keys are refused, as are documents after the
+//! first one.
+
+use alloc::{
+    borrow::ToOwned,
+    string::{String, ToString},
+    vec::Vec,
+};
+
+#[cfg(feature = "yaml")]
+use crate::{lexer::string, JsonError, Map};
+use crate::{ser::write_str, to_string, JsonValue};
+
+impl JsonValue {
+    /// Block-style YAML, with keys in lexicographic order and a trailing
+    /// newline. Strings are quoted when they would read back as something
+    /// else than a string.
+    ///
+    /// ```
+    /// let value = step_4::parse_with_path(r#"{"name": "nom", "tags": ["parser", "true"]}"#)
+    ///     .unwrap();
+    ///
+    /// assert_eq!(value.to_yaml(), "name: nom\ntags:\n  - parser\n  - \"true\"\n");
+    /// ```
+    pub fn to_yaml(&self) -> String {
+        let mut out = String::new();
+        write_block(&mut out, self, 0);
+        out.push('\n');
+        out
+    }
+}
+
+fn is_collection(value: &JsonValue) -> bool {
+    match value {
+        JsonValue::Array(values) => !values.is_empty(),
+        JsonValue::Object(map) => !map.is_empty(),
+        _ => false,
+    }
+}
+
+fn newline(out: &mut String, indent: usize) {
+    out.push('\n');
+    out.extend(core::iter::repeat_n(' ', indent));
+}
+
+/// Writes `value` from the current column, which is `indent`
+fn write_block(out: &mut String, value: &JsonValue, indent: usize) {
+    match value {
+        JsonValue::Array(values) if !values.is_empty() => {
+            for (index, value) in values.iter().enumerate() {
+                if index > 0 {
+                    newline(out, indent);
+                }
+                out.push_str("- ");
+                write_block(out, value, indent + 2);
+            }
+        }
+        JsonValue::Object(map) if !map.is_empty() => {
+            let mut entries: Vec<_> = map.iter().collect();
+            entries.sort_unstable_by_key(|(key, _)| *key);
+
+            for (index, (key, value)) in entries.into_iter().enumerate() {
+                if index > 0 {
+                    newline(out, indent);
+                }
+                write_scalar_str(out, key);
+                out.push(':');
+
+                if is_collection(value) {
+                    newline(out, indent + 2);
+                    write_block(out, value, indent + 2);
+                } else {
+                    out.push(' ');
+                    write_block(out, value, indent + 2);
+                }
+            }
+        }
+        JsonValue::Str(s) => write_scalar_str(out, s),
+        // Empty collections, `null`, booleans and numbers read the same in
+        // JSON and in YAML
+        value => out.push_str(&to_string(value)),
+    }
+}
+
+/// Plain if it can be, double-quoted with the escapes of JSON otherwise
+fn write_scalar_str(out: &mut String, s: &str) {
+    if is_plain_safe(s) {
+        out.push_str(s);
+    } else {
+        write_str(out, s);
+    }
+}
+
+/// Whether `s` reads back as the same string without quotes
+fn is_plain_safe(s: &str) -> bool {
+    const INDICATORS: &[char] = &[
+        '-', '?', ':', ',', '[', ']', '{', '}', '#', '&', '*', '!', '|', '>', '\'', '"', '%', '@',
+        '`',
+    ];
+
+    !s.is_empty()
+        && !s.starts_with(INDICATORS)
+        && !s.starts_with(char::is_whitespace)
+        && !s.ends_with(char::is_whitespace)
+        && !s.ends_with(':')
+        && !s.contains(": ")
+        && !s.contains(" #")
+        && !s.starts_with("...")
+        && !s.contains([',', '[', ']', '{', '}', '\u{2028}', '\u{2029}', '\u{feff}'])
+        && !s.chars().any(char::is_control)
+        && matches!(resolve(s), JsonValue::Str(_))
+}
+
+/// Value of a plain scalar, following the core schema of YAML 1.2
+fn resolve(s: &str) -> JsonValue {
+    match s {
+        "" | "~" | "null" | "Null" | "NULL" => return JsonValue::Null,
+        "true" | "True" | "TRUE" => return JsonValue::Boolean(true),
+        "false" | "False" | "FALSE" => return JsonValue::Boolean(false),
+        ".inf" | ".Inf" | ".INF" | "+.inf" | "+.Inf" | "+.INF" => {
+            return JsonValue::Num(f64::INFINITY)
+        }
+        "-.inf" | "-.Inf" | "-.INF" => return JsonValue::Num(f64::NEG_INFINITY),
+        ".nan" | ".NaN" | ".NAN" => return JsonValue::Num(f64::NAN),
+        _ => {}
+    }
+
+    let radix = [("0x", 16), ("0o", 8)]
+        .into_iter()
+        .find_map(|(prefix, radix)| Some((s.strip_prefix(prefix)?, radix)));
+    if let Some((digits, radix)) = radix {
+        return match i64::from_str_radix(digits, radix) {
+            Ok(n) if !digits.starts_with(['+', '-']) => JsonValue::Int(n),
+            _ => JsonValue::Str(s.to_owned()),
+        };
+    }
+
+    let unsigned = s.strip_prefix(['+', '-']).unwrap_or(s);
+    if unsigned.is_empty() || !unsigned.bytes().all(|b| b.is_ascii_digit()) {
+        let float = unsigned.contains(|c: char| c.is_ascii_digit())
+            && unsigned.bytes().all(|b| b"0123456789.eE+-".contains(&b))
+            && !unsigned.starts_with(['e', 'E']);
+
+        return match s.parse() {
+            Ok(n) if float => JsonValue::Num(n),
+            _ => JsonValue::Str(s.to_owned()),
+        };
+    }
+
+    if let Ok(n) = s.parse() {
+        JsonValue::Int(n)
+    } else if let Ok(n) = s.parse() {
+        JsonValue::UInt(n)
+    } else {
+        JsonValue::Num(s.parse().unwrap_or(f64::NAN))
+    }
+}
+
+/// Parses the first document of `input`. Errors are [`JsonError::Line`]s,
+/// giving the line of the failure.
+///
+/// ```
+/// let value = step_4::from_yaml("
+/// name: nom  # the parser
+/// tags: [parser, combinators]
+/// versions:
+///   - major: 7
+///     date: 2021-08-21
+/// ").unwrap();
+///
+/// assert_eq!(
+///     value,
+///     step_4::parse_with_path(r#"{
+///         "name": "nom",
+///         "tags": ["parser", "combinators"],
+///         "versions": [{"major": 7, "date": "2021-08-21"}]
+///     }"#).unwrap()
+/// );
+/// ```
+#[cfg(feature = "yaml")]
+pub fn from_yaml(input: &str) -> Result<JsonValue, JsonError> {
+    let mut lines = Vec::new();
+
+    for (index, raw) in input.lines().enumerate() {
+        let trimmed = raw.trim_end();
+
+        if trimmed.starts_with('%') && lines.is_empty() {
+            continue;
+        }
+        if trimmed == "---" || trimmed.starts_with("--- ") {
+            if lines.iter().any(|line: &Line| !line.is_blank()) {
+                break;
+            }
+            // `--- value` puts the document on the same line
+            let rest = &raw[3..];
+            lines.clear();
+            lines.push(Line::new(index + 1, rest));
+            continue;
+        }
+        if trimmed == "..." {
+            break;
+        }
+
+        lines.push(Line::new(index + 1, raw));
+    }
+
+    let mut parser = Parser { lines, pos: 0 };
+    parser.skip_blank();
+
+    let Some(first) = parser.lines.get(parser.pos) else {
+        return Ok(JsonValue::Null);
+    };
+
+    let value = parser.node(first.indent)?;
+    parser.skip_blank();
+
+    match parser.lines.get(parser.pos) {
+        Some(line) => Err(line.error("unexpected indentation")),
+        None => Ok(value),
+    }
+}
+
+#[cfg(feature = "yaml")]
+struct Line<'a> {
+    /// 1-based
+    number: usize,
+    raw: &'a str,
+    /// Spaces before the content
+    indent: usize,
+    /// Content, without the comment
+    text: &'a str,
+}
+
+#[cfg(feature = "yaml")]
+impl<'a> Line<'a> {
+    fn new(number: usize, raw: &'a str) -> Self {
+        let indent = raw.len() - raw.trim_start_matches(' ').len();
+
+        Line {
+            number,
+            raw,
+            indent,
+            text: strip_comment(&raw[indent..]).trim_end(),
+        }
+    }
+
+    fn is_blank(&self) -> bool {
+        self.text.is_empty()
+    }
+
+    fn error(&self, message: &str) -> JsonError {
+        JsonError::Line(
+            self.number,
+            alloc::boxed::Box::new(JsonError::Custom(message.into())),
+        )
+    }
+}
+
+/// `s` up to a `#` that starts a comment: at the start, or after a space,
+/// and outside of quotes
+#[cfg(feature = "yaml")]
+fn strip_comment(s: &str) -> &str {
+    let mut quote = None;
+    let mut escaped = false;
+    let mut previous = ' ';
+
+    for (index, c) in s.char_indices() {
+        match (quote, c) {
+            (Some('"'), '\\') if !escaped => {
+                escaped = true;
+                previous = c;
+                continue;
+            }
+            (Some(q), c) if c == q && !escaped => quote = None,
+            (None, '"' | '\'') if previous == ' ' || "[{,:".contains(previous) => quote = Some(c),
+            (None, '#') if previous.is_whitespace() => return &s[..index],
+            _ => {}
+        }
+        escaped = false;
+        previous = c;
+    }
+
+    s
+}
+
+#[cfg(feature = "yaml")]
+fn is_sequence_item(text: &str) -> bool {
+    text == "-" || text.starts_with("- ")
+}
+
+/// Key and rest of a `key: value` line
+#[cfg(feature = "yaml")]
+fn split_key(text: &str) -> Option<(String, &str)> {
+    let (key, rest) = if text.starts_with(['"', '\'']) {
+        let (key, rest) = quoted(text).ok()?;
+        (key, rest.trim_start().strip_prefix(':')?)
+    } else if text.starts_with(['[', '{']) || is_sequence_item(text) {
+        return None;
+    } else {
+        let end = text
+            .match_indices(':')
+            .map(|(index, _)| index)
+            .find(|&index| text[index + 1..].is_empty() || text[index + 1..].starts_with(' '))?;
+        (text[..end].trim_end().to_owned(), &text[end + 1..])
+    };
+
+    (rest.is_empty() || rest.starts_with(' ')).then(|| (key, rest.trim_start()))
+}
+
+/// Double-quoted with the escapes of JSON, or single-quoted
+#[cfg(feature = "yaml")]
+fn quoted(s: &str) -> Result<(String, &str), String> {
+    if s.starts_with('"') {
+        return match string::<JsonError>(s) {
+            Ok((rest, value)) => Ok((value, rest)),
+            Err(_) => Err("invalid double-quoted string".into()),
+        };
+    }
+
+    let mut value = String::new();
+    let mut rest = &s[1..];
+
+    loop {
+        let end = rest.find('\'').ok_or("unterminated single-quoted string")?;
+        value.push_str(&rest[..end]);
+        rest = &rest[end + 1..];
+
+        // `''` is an escaped quote
+        match rest.strip_prefix('\'') {
+            Some(after) => {
+                value.push('\'');
+                rest = after;
+            }
+            None => return Ok((value, rest)),
+        }
+    }
+}
+
+/// Value of a flow collection or scalar, and what follows it
+#[cfg(feature = "yaml")]
+fn flow(s: &str, in_collection: bool) -> Result<(JsonValue, &str), String> {
+    let s = s.trim_start();
+
+    if let Some(mut rest) = s.strip_prefix('[') {
+        let mut values = Vec::new();
+
+        loop {
+            rest = rest.trim_start();
+            if let Some(after) = rest.strip_prefix(']') {
+                return Ok((JsonValue::Array(values), after));
+            }
+
+            let (value, after) = flow(rest, true)?;
+            values.push(value);
+            rest = after.trim_start();
+
+            match rest.strip_prefix(',') {
+                Some(after) => rest = after,
+                None if rest.starts_with(']') => {}
+                None => return Err("expected `,` or `]` in flow sequence".into()),
+            }
+        }
+    }
+
+    if let Some(mut rest) = s.strip_prefix('{') {
+        let mut map = Map::default();
+
+        loop {
+            rest = rest.trim_start();
+            if let Some(after) = rest.strip_prefix('}') {
+                return Ok((JsonValue::Object(map), after));
+            }
+
+            let (key, after) = if rest.starts_with(['"', '\'']) {
+                quoted(rest)?
+            } else {
+                let end = rest.find([':', ',', '}']).unwrap_or(rest.len());
+                (rest[..end].trim_end().to_owned(), &rest[end..])
+            };
+
+            let after = after.trim_start();
+            let (value, after) = match after.strip_prefix(':') {
+                Some(after) => flow(after, true)?,
+                // `{a, b}` is a set, which is a mapping to nulls
+                None => (JsonValue::Null, after),
+            };
+            map.insert(key, value);
+            rest = after.trim_start();
+
+            match rest.strip_prefix(',') {
+                Some(after) => rest = after,
+                None if rest.starts_with('}') => {}
+                None => return Err("expected `,` or `}` in flow mapping".into()),
+            }
+        }
+    }
+
+    if s.starts_with(['"', '\'']) {
+        let (value, rest) = quoted(s)?;
+        return Ok((JsonValue::Str(value), rest));
+    }
+
+    if s.starts_with(['&', '*', '!']) {
+        return Err("anchors, aliases and tags are not supported".into());
+    }
+
+    let end = if in_collection {
+        s.find([',', ']', '}']).unwrap_or(s.len())
+    } else {
+        s.len()
+    };
+
+    Ok((resolve(s[..end].trim_end()), &s[end..]))
+}
+
+#[cfg(feature = "yaml")]
+struct Parser<'a> {
+    lines: Vec<Line<'a>>,
+    pos: usize,
+}
+
+#[cfg(feature = "yaml")]
+impl<'a> Parser<'a> {
+    fn skip_blank(&mut self) {
+        while self.lines.get(self.pos).is_some_and(Line::is_blank) {
+            self.pos += 1;
+        }
+    }
+
+    /// Next non-blank line, if it is indented by more than `indent`
+    fn child_indent(&mut self, indent: usize) -> Option<usize> {
+        self.skip_blank();
+        self.lines
+            .get(self.pos)
+            .map(|line| line.indent)
+            .filter(|&child| child > indent)
+    }
+
+    /// Value starting at the current line, which is indented by `indent`
+    fn node(&mut self, indent: usize) -> Result<JsonValue, JsonError> {
+        let line = &self.lines[self.pos];
+
+        if is_sequence_item(line.text) {
+            self.sequence(indent)
+        } else if split_key(line.text).is_some() {
+            self.mapping(indent)
+        } else {
+            let text = line.text;
+            self.pos += 1;
+            self.inline(text, indent)
+        }
+    }
+
+    fn sequence(&mut self, indent: usize) -> Result<JsonValue, JsonError> {
+        let mut values = Vec::new();
+
+        loop {
+            self.skip_blank();
+            let Some(line) = self.lines.get_mut(self.pos) else {
+                break;
+            };
+            if line.indent != indent || !is_sequence_item(line.text) {
+                break;
+            }
+
+            let text = line.text;
+            let rest = text[1..].trim_start();
+            let value = if rest.is_empty() {
+                self.pos += 1;
+                match self.child_indent(indent) {
+                    Some(child) => self.node(child)?,
+                    None => JsonValue::Null,
+                }
+            } else if is_sequence_item(rest) || split_key(rest).is_some() {
+                // `- key: value`: the rest of the line is the first one of a
+                // collection, indented by where it starts
+                let column = indent + text.len() - rest.len();
+                line.indent = column;
+                line.text = rest;
+                self.node(column)?
+            } else {
+                self.pos += 1;
+                self.inline(rest, indent)?
+            };
+
+            values.push(value);
+        }
+
+        Ok(JsonValue::Array(values))
+    }
+
+    fn mapping(&mut self, indent: usize) -> Result<JsonValue, JsonError> {
+        let mut map = Map::default();
+
+        loop {
+            self.skip_blank();
+            let Some(line) = self.lines.get(self.pos) else {
+                break;
+            };
+            if line.indent != indent || is_sequence_item(line.text) {
+                break;
+            }
+
+            let text = line.text;
+            let Some((key, rest)) = split_key(text) else {
+                return Err(line.error("expected a `key: value` pair"));
+            };
+            self.pos += 1;
+
+            let value = if rest.is_empty() {
+                self.skip_blank();
+                match self.lines.get(self.pos) {
+                    Some(next) if next.indent > indent => self.node(next.indent)?,
+                    // Sequences can be as indented as their key
+                    Some(next) if next.indent == indent && is_sequence_item(next.text) => {
+                        self.sequence(indent)?
+                    }
+                    _ => JsonValue::Null,
+                }
+            } else {
+                self.inline(rest, indent)?
+            };
+
+            map.insert(key, value);
+        }
+
+        Ok(JsonValue::Object(map))
+    }
+
+    /// Value written on the line of its key or `-`, the next lines indented
+    /// by more than `indent` continuing it
+    fn inline(&mut self, text: &str, indent: usize) -> Result<JsonValue, JsonError> {
+        let number = self.lines[self.pos - 1].number;
+        let error = |message: String| {
+            JsonError::Line(number, alloc::boxed::Box::new(JsonError::Custom(message)))
+        };
+
+        if let Some(header) = text.strip_prefix(['|', '>']) {
+            return Ok(JsonValue::Str(self.block_scalar(
+                text.starts_with('>'),
+                header,
+                indent,
+            )));
+        }
+
+        if text.starts_with(['[', '{', '"', '\'']) {
+            let (value, rest) = flow(text, false).map_err(error)?;
+
+            return match rest.trim() {
+                "" => Ok(value),
+                _ => Err(error("unexpected text after the value".to_string())),
+            };
+        }
+
+        // Plain scalars can be folded over several lines
+        let mut text = String::from(text);
+        while self.child_indent(indent).is_some() {
+            let next = self.lines[self.pos].text;
+            // Left for the caller to refuse
+            if is_sequence_item(next) || split_key(next).is_some() {
+                break;
+            }
+            text.push(' ');
+            text.push_str(next);
+            self.pos += 1;
+        }
+
+        flow(&text, false).map(|(value, _)| value).map_err(error)
+    }
+
+    /// Text of a `|` (literal) or `>` (folded) block scalar
+    fn block_scalar(&mut self, folded: bool, header: &str, indent: usize) -> String {
+        let start = self.pos;
+
+        while let Some(line) = self.lines.get(self.pos) {
+            if !line.raw.trim().is_empty() && line.indent <= indent {
+                break;
+            }
+            self.pos += 1;
+        }
+
+        let raw = &self.lines[start..self.pos];
+        let content_indent = raw
+            .iter()
+            .find(|line| !line.raw.trim().is_empty())
+            .map_or(0, |line| line.indent);
+        let lines: Vec<&str> = raw
+            .iter()
+            .map(|line| line.raw.get(content_indent..).unwrap_or("").trim_end())
+            .collect();
+
+        let mut text = String::new();
+        for (index, line) in lines.iter().enumerate() {
+            if index > 0 {
+                // Folding joins consecutive lines with a space, blank lines
+                // being line breaks
+                match (folded, lines[index - 1].is_empty(), line.is_empty()) {
+                    (false, _, _) | (true, _, true) => text.push('\n'),
+                    (true, false, false) => text.push(' '),
+                    (true, true, false) => {}
+                }
+            }
+            text.push_str(line);
+        }
+
+        // Chomping: `-` strips the final line breaks, `+` keeps them all, and
+        // by default a single one is kept
+        let content = text.trim_end_matches('\n').len();
+        if header.contains('+') {
+            text.push('\n');
+        } else {
+            text.truncate(content);
+            if !header.contains('-') && content > 0 {
+                text.push('\n');
+            }
+        }
+
+        text
+    }
+}
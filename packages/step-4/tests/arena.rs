@@ -0,0 +1,74 @@
+//! The arena document against the `JsonValue` tree, and reading its nodes.
+
+use step_4::{arena::ValueRef, parse_arena, parse_with_path, testing::RandomValues, to_string};
+
+#[test]
+fn same_value_as_parse() {
+    let input = r#" {"a": [1, -2.5, 18446744073709551615, "s\n", true, null],
+        "b": {"c": {}, "d": []}, "é": "😀"} "#;
+    let (rest, document) = parse_arena(input).unwrap();
+
+    assert_eq!(rest, "");
+    assert_eq!(document.root().to_value(), parse_with_path(input).unwrap());
+}
+
+#[test]
+fn random_values() {
+    for value in RandomValues::new(11, Default::default()).take(200) {
+        let input = to_string(&value);
+        let (_, document) = parse_arena(&input).unwrap();
+
+        assert_eq!(document.root().to_value(), value, "{input}");
+    }
+}
+
+#[test]
+fn indexing() {
+    let (_, document) =
+        parse_arena(r#"{"a": [10, [20, 21], {"b": "x"}], "c": null, "c": 3}"#).unwrap();
+
+    let ValueRef::Object(root) = document.root() else {
+        panic!("root isn't an object");
+    };
+    assert_eq!(root.len(), 3);
+    // The last of repeated keys
+    assert!(matches!(root.get("c"), Some(ValueRef::Int(3))));
+    assert!(root.get("z").is_none());
+
+    let Some(ValueRef::Array(a)) = root.get("a") else {
+        panic!("`a` isn't an array");
+    };
+    assert_eq!(a.len(), 3);
+    assert!(matches!(a.get(0), Some(ValueRef::Int(10))));
+    assert!(a.get(3).is_none());
+
+    let Some(ValueRef::Array(inner)) = a.get(1) else {
+        panic!("`a[1]` isn't an array");
+    };
+    let items: Vec<_> = inner.iter().map(|item| item.to_value()).collect();
+    assert_eq!(items, [20.into(), 21.into()]);
+
+    let Some(ValueRef::Object(b)) = a.get(2) else {
+        panic!("`a[2]` isn't an object");
+    };
+    assert!(matches!(b.get("b"), Some(ValueRef::Str("x"))));
+
+    let keys: Vec<_> = root.iter().map(|(key, _)| key).collect();
+    assert_eq!(keys, ["a", "c", "c"]);
+}
+
+#[test]
+fn node_count() {
+    let (_, document) = parse_arena(r#"[1, {"a": [true]}, "s"]"#).unwrap();
+
+    // `[`, `1`, `{`, `[`, `true` and `"s"`
+    assert_eq!(document.len(), 6);
+    assert!(!document.is_empty());
+}
+
+#[test]
+fn errors() {
+    for input in ["[1, 2", r#"{"a" 1}"#, "[1 2]", "[nul]", r#"{"a": 1,}"#] {
+        assert!(parse_arena(input).is_err(), "{input}");
+    }
+}
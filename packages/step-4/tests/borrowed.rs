@@ -0,0 +1,87 @@
+//! The borrowed parser: which strings are borrowed from the input, and what
+//! the raw values of `parse_lazy` are made of.
+
+use std::borrow::Cow;
+
+use step_4::{parse_borrowed, parse_lazy, parse_with_path, BorrowedValue};
+
+/// Value of `key` in the object `value`
+fn get<'v, 'a>(value: &'v BorrowedValue<'a>, key: &str) -> &'v BorrowedValue<'a> {
+    let BorrowedValue::Object(map) = value else {
+        panic!("{value:?}");
+    };
+
+    &map[key]
+}
+
+#[test]
+fn strings_without_escapes_are_borrowed() {
+    let input = r#"{"plain": "tokyo 🦀", "escaped": "a\nb", "unicode": "\u00e9", "k\"ey": "", "list": ["x", "\/"]}"#;
+    let (_, value) = parse_borrowed(input).unwrap();
+
+    assert!(matches!(
+        get(&value, "plain"),
+        BorrowedValue::Str(Cow::Borrowed("tokyo 🦀"))
+    ));
+    assert!(matches!(get(&value, "escaped"), BorrowedValue::Str(Cow::Owned(s)) if s == "a\nb"));
+    assert!(matches!(get(&value, "unicode"), BorrowedValue::Str(Cow::Owned(s)) if s == "é"));
+    assert!(matches!(
+        get(&value, "k\"ey"),
+        BorrowedValue::Str(Cow::Borrowed(""))
+    ));
+
+    let BorrowedValue::Array(list) = get(&value, "list") else {
+        panic!("{value:?}");
+    };
+    assert!(matches!(&list[0], BorrowedValue::Str(Cow::Borrowed("x"))));
+    assert!(matches!(&list[1], BorrowedValue::Str(Cow::Owned(s)) if s == "/"));
+
+    // Keys too
+    let BorrowedValue::Object(map) = &value else {
+        panic!("{value:?}");
+    };
+    for key in map.keys() {
+        match key.as_ref() {
+            "k\"ey" => assert!(matches!(key, Cow::Owned(_))),
+            _ => assert!(matches!(key, Cow::Borrowed(_)), "{key}"),
+        }
+    }
+}
+
+#[test]
+fn raw_slices_are_exact() {
+    let input = r#"{
+        "geo": {"coords": [[1, 2.5],  [3, {"x": "]"}]], "kind": "line"},
+        "number": -12.5e3,
+        "text": "a}\"]",
+        "empty": {}
+    }"#;
+    let raw_keys = ["coords", "number", "text", "empty"];
+    let (rest, value) = parse_lazy(input, &raw_keys).unwrap();
+
+    assert_eq!(rest, "");
+    assert_eq!(
+        get(get(&value, "geo"), "coords"),
+        &BorrowedValue::Raw(r#"[[1, 2.5],  [3, {"x": "]"}]]"#)
+    );
+    assert_eq!(get(&value, "number"), &BorrowedValue::Raw("-12.5e3"));
+    assert_eq!(get(&value, "text"), &BorrowedValue::Raw(r#""a}\"]""#));
+    assert_eq!(get(&value, "empty"), &BorrowedValue::Raw("{}"));
+
+    // Parsed on the way to an owned value
+    assert_eq!(value.into_value().unwrap(), parse_with_path(input).unwrap());
+}
+
+#[test]
+fn raw_slices_are_checked() {
+    for input in [
+        r#"{"raw": [1, 2}}"#,
+        r#"{"raw": {"a": [1}]}"#,
+        r#"{"raw": ["a]}"#,
+        r#"{"raw": "a"#,
+        r#"{"raw": [1, [2]"#,
+        r#"{"raw": }"#,
+    ] {
+        assert!(parse_lazy(input, &["raw"]).is_err(), "{input}");
+    }
+}
@@ -0,0 +1,206 @@
+//! `from_bson(v.to_bson()) == v` over the benchmark corpus, as fetched by
+//! `cargo xtask fetch`, and over random values, plus the Extended JSON
+//! conventions.
+
+mod common;
+
+use step_4::{
+    from_bson, parse_with_path,
+    testing::{Alphabet, RandomOptions, RandomValues},
+    JsonError, JsonValue,
+};
+
+fn to_bson(value: &JsonValue) -> Vec<u8> {
+    value.to_bson().unwrap()
+}
+
+#[test]
+fn corpus_round_trip() {
+    common::corpus_round_trip(to_bson, from_bson);
+}
+
+#[test]
+fn random_round_trip() {
+    // Keys can't contain NUL
+    let opts = RandomOptions {
+        alphabet: Alphabet::Alphanumeric,
+        ..Default::default()
+    };
+    let documents = RandomValues::new(5, opts)
+        .take(2_000)
+        .map(|value| JsonValue::Object([("value".to_owned(), value)].into_iter().collect()));
+
+    common::round_trip(documents, to_bson, from_bson);
+}
+
+#[test]
+fn extended_json_round_trip() {
+    let json = r#"{
+        "_id": {"$oid": "5f43a1b2c3d4e5f60718293a"},
+        "created": {"$date": "2023-06-01T12:34:56.789Z"},
+        "epoch": {"$date": "1970-01-01T00:00:00Z"},
+        "ancient": {"$date": {"$numberLong": "-62198755200000"}},
+        "blob": {"$binary": {"base64": "aGVsbG8=", "subType": "80"}},
+        "empty": {"$binary": {"base64": "", "subType": "00"}},
+        "ts": {"$timestamp": {"t": 1700000000, "i": 7}},
+        "re": {"$regularExpression": {"pattern": "^a.*", "options": "i"}},
+        "inf": {"$numberDouble": "-Infinity"},
+        "min": {"$minKey": 1},
+        "max": {"$maxKey": 1},
+        "undefined": {"$undefined": true},
+        "code": {"$code": "function() {}"},
+        "symbol": {"$symbol": "sym"},
+        "nested": [{"$oid": "000000000000000000000000"}, {"a": null}]
+    }"#;
+    let document = parse_with_path(json).unwrap();
+
+    assert_eq!(from_bson(&document.to_bson().unwrap()).unwrap(), document);
+}
+
+#[test]
+fn canonical_forms_are_relaxed() {
+    let document = parse_with_path(
+        r#"{
+            "int": {"$numberInt": "42"},
+            "long": {"$numberLong": "-9000000000"},
+            "double": {"$numberDouble": "1.5"},
+            "date": {"$date": {"$numberLong": "1685622896789"}},
+            "offset": {"$date": "2023-06-01T14:34:56.789+02:00"}
+        }"#,
+    )
+    .unwrap();
+    let relaxed = parse_with_path(
+        r#"{
+            "int": 42,
+            "long": -9000000000,
+            "double": 1.5,
+            "date": {"$date": "2023-06-01T12:34:56.789Z"},
+            "offset": {"$date": "2023-06-01T12:34:56.789Z"}
+        }"#,
+    )
+    .unwrap();
+
+    assert_eq!(from_bson(&document.to_bson().unwrap()).unwrap(), relaxed);
+}
+
+#[test]
+fn layout() {
+    let document = parse_with_path(r#"{"hello": "world"}"#).unwrap();
+
+    // The example of bsonspec.org
+    assert_eq!(
+        document.to_bson().unwrap(),
+        b"\x16\x00\x00\x00\x02hello\x00\x06\x00\x00\x00world\x00\x00"
+    );
+}
+
+#[test]
+fn invalid_input() {
+    let error = |json: &str| {
+        parse_with_path(json)
+            .unwrap()
+            .to_bson()
+            .unwrap_err()
+            .to_string()
+    };
+    assert_eq!(error("[]"), "BSON documents are objects, found an array");
+    assert_eq!(error(r#"{"a": {"$oid": "xyz"}}"#), "invalid `$oid` at $.a");
+    assert_eq!(
+        error(r#"{"a": [{"$date": "yesterday"}]}"#),
+        "invalid `$date` at $.a[0]"
+    );
+    assert_eq!(
+        error(r#"{"a\u0000b": 1}"#),
+        r#"BSON keys and regexes can't contain NUL at $["a\u0000b"]"#
+    );
+
+    assert!(matches!(
+        from_bson(b"\x16\x00\x00\x00\x02hello"),
+        Err(JsonError::NeedMoreData)
+    ));
+
+    let message = |bytes: &[u8]| from_bson(bytes).unwrap_err().to_string();
+    assert_eq!(
+        message(b"\x0c\x00\x00\x00\x08a\x00\x02\x00\x00\x00\x00"),
+        "invalid boolean at byte 7"
+    );
+    assert_eq!(
+        message(b"\x08\x00\x00\x00\x0ca\x00\x00"),
+        "unsupported type 0x0c at byte 7"
+    );
+    assert_eq!(
+        message(b"\x05\x00\x00\x00\x00\x00"),
+        "trailing bytes after the document at byte 5"
+    );
+}
+
+#[test]
+fn top_level_must_be_a_document() {
+    for (json, found) in [
+        ("null", "null"),
+        ("true", "a boolean"),
+        ("1", "an integer"),
+        ("1.5", "a number"),
+        (r#""s""#, "a string"),
+        ("[{}]", "an array"),
+    ] {
+        let error = parse_with_path(json).unwrap().to_bson().unwrap_err();
+
+        assert_eq!(
+            error.to_string(),
+            format!("BSON documents are objects, found {found}")
+        );
+    }
+
+    // Nested values can be anything
+    let document = parse_with_path(r#"{"a": null, "b": [1, "s"]}"#).unwrap();
+    assert_eq!(from_bson(&to_bson(&document)).unwrap(), document);
+}
+
+/// Type of the single element of `{"n": n}`, and the bytes of its value
+fn element(n: JsonValue) -> (u8, Vec<u8>) {
+    let document = JsonValue::Object([("n".to_owned(), n)].into_iter().collect());
+    let bytes = to_bson(&document);
+
+    assert_eq!(from_bson(&bytes).unwrap(), document);
+    // Length, type, `n\0`, value, then the final `\0`
+    (bytes[4], bytes[7..bytes.len() - 1].to_vec())
+}
+
+#[test]
+fn smallest_integer_type() {
+    const INT32: u8 = 0x10;
+    const INT64: u8 = 0x12;
+
+    for n in [0, 1, -1, i32::MAX as i64, i32::MIN as i64] {
+        assert_eq!(
+            element(JsonValue::Int(n)),
+            (INT32, (n as i32).to_le_bytes().to_vec()),
+            "{n}"
+        );
+    }
+
+    for n in [i32::MAX as i64 + 1, i32::MIN as i64 - 1, i64::MAX, i64::MIN] {
+        assert_eq!(
+            element(JsonValue::Int(n)),
+            (INT64, n.to_le_bytes().to_vec()),
+            "{n}"
+        );
+    }
+
+    // Unsigned integers that fit too, read back as signed ones
+    for (n, kind) in [(7, INT32), (i64::MAX as u64, INT64)] {
+        let document =
+            JsonValue::Object([("n".to_owned(), JsonValue::UInt(n))].into_iter().collect());
+        let bytes = to_bson(&document);
+
+        assert_eq!(bytes[4], kind, "{n}");
+        assert_eq!(
+            from_bson(&bytes).unwrap(),
+            parse_with_path(&format!(r#"{{"n": {n}}}"#)).unwrap()
+        );
+    }
+
+    // Floats stay doubles, integral or not
+    assert_eq!(element(JsonValue::Num(1.0)).0, 0x01);
+}
@@ -0,0 +1,81 @@
+//! The parser over `&[u8]`: same values as over a `str`, UTF-8 checked inside
+//! strings, and errors at the same offsets.
+
+use step_4::{error_position, parse_bytes, parse_with_path, JsonError, JsonValue};
+
+fn json(input: &str) -> JsonValue {
+    parse_with_path(input).unwrap()
+}
+
+/// Message and byte offset of the error of `input`
+fn error(input: &[u8]) -> (String, usize) {
+    let error = JsonError::from(parse_bytes(input).unwrap_err());
+    let JsonError::Syntax { remaining, .. } = error else {
+        panic!("{error:?}");
+    };
+
+    (error.to_string(), input.len() - remaining)
+}
+
+#[test]
+fn same_values_as_over_a_str() {
+    for input in [
+        r#"{"a": [1, -2.5, 1e3, true, false, null], "b": {"c": "d"}}"#,
+        r#"["é", "é", "😀", "😀", "a\"b\\c\n"]"#,
+        "  [1]  ",
+        "18446744073709551615",
+    ] {
+        let (rest, value) = parse_bytes(input.as_bytes()).unwrap();
+
+        assert_eq!(rest, b"", "{input}");
+        assert_eq!(value, json(input), "{input}");
+    }
+}
+
+#[test]
+fn from_a_vec() {
+    let input: Vec<u8> = br#"{"bytes": [104, 105]}"#.to_vec();
+
+    let (_, value) = parse_bytes(&input).unwrap();
+
+    assert_eq!(value, json(r#"{"bytes": [104, 105]}"#));
+}
+
+#[test]
+fn invalid_utf8_in_strings() {
+    // A lone continuation byte, a truncated sequence, and an overlong one
+    for (input, offset) in [
+        (&b"[\"a\x80b\"]"[..], 3),
+        (b"[\"ok\", \"\xe2\x82\"]", 8),
+        (b"{\"\xc0\xaf\": 1}", 2),
+    ] {
+        let (message, at) = error(input);
+
+        assert_eq!(at, offset, "{input:?}");
+        assert_eq!(message, "unexpected '\u{fffd}'", "{input:?}");
+    }
+
+    // Right after valid multi-byte chars
+    assert_eq!(error(b"[\"\xc3\xa9\xff\"]").1, 4);
+}
+
+#[test]
+fn errors_at_the_same_offsets_as_over_a_str() {
+    for input in [
+        r#"{"a": 1,}"#,
+        r#"[1, 2"#,
+        r#"{"a" 1}"#,
+        r#"["é", x]"#,
+        r#"{"a": "\q"}"#,
+        r#"[nul]"#,
+        r#"{"a": nul}"#,
+    ] {
+        let expected = parse_with_path(input).unwrap_err();
+        let position = error_position(input, &expected).unwrap();
+        let (message, at) = error(input.as_bytes());
+
+        assert_eq!(at, position.offset, "{input}");
+        // Followed by the path over a `str`
+        assert!(expected.to_string().starts_with(&message), "{input}");
+    }
+}
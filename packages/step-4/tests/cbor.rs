@@ -0,0 +1,235 @@
+//! `from_cbor(v.to_cbor()) == v` over the benchmark corpus, as fetched by
+//! `cargo xtask fetch`, and over random values, plus examples of RFC 8949.
+
+mod common;
+
+use step_4::{
+    from_cbor, parse_with_path,
+    testing::{RandomOptions, RandomValues},
+    JsonError, JsonValue,
+};
+
+#[test]
+fn corpus_round_trip() {
+    for (name, json_len, len) in common::corpus_round_trip(JsonValue::to_cbor, from_cbor) {
+        assert!(len < json_len, "{name}");
+    }
+}
+
+#[test]
+fn random_round_trip() {
+    common::round_trip(
+        RandomValues::new(4, RandomOptions::default()).take(2_000),
+        JsonValue::to_cbor,
+        from_cbor,
+    );
+}
+
+fn json(input: &str) -> JsonValue {
+    parse_with_path(input).unwrap()
+}
+
+fn message(bytes: &[u8]) -> String {
+    from_cbor(bytes).unwrap_err().to_string()
+}
+
+/// From appendix A of the RFC
+#[test]
+fn rfc_examples() {
+    let cases: [(&[u8], &str); 14] = [
+        (&[0x17], "23"),
+        (&[0x18, 0x64], "100"),
+        (&[0x39, 0x03, 0xe7], "-1000"),
+        (
+            &[0x1b, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff],
+            "18446744073709551615",
+        ),
+        (&[0xf9, 0x3c, 0x00], "1.0"),
+        (&[0xf9, 0xc4, 0x00], "-4.0"),
+        (&[0xf9, 0x00, 0x01], "5.960464477539063e-8"),
+        (&[0xfa, 0x47, 0xc3, 0x50, 0x00], "100000.0"),
+        (&[0x64, 0x49, 0x45, 0x54, 0x46], r#""IETF""#),
+        (
+            &[0xa2, 0x61, 0x61, 0x01, 0x61, 0x62, 0x82, 0x02, 0x03],
+            r#"{"a": 1, "b": [2, 3]}"#,
+        ),
+        (&[0xc1, 0x1a, 0x51, 0x4b, 0x67, 0xb0], "1363896240"),
+        (
+            &[0x9f, 0x01, 0x82, 0x02, 0x03, 0x9f, 0x04, 0x05, 0xff, 0xff],
+            "[1, [2, 3], [4, 5]]",
+        ),
+        (
+            &[
+                0x7f, 0x65, 0x73, 0x74, 0x72, 0x65, 0x61, 0x64, 0x6d, 0x69, 0x6e, 0x67, 0xff,
+            ],
+            r#""streaming""#,
+        ),
+        (
+            &[
+                0xbf, 0x63, 0x46, 0x75, 0x6e, 0xf5, 0x63, 0x41, 0x6d, 0x74, 0x21, 0xff,
+            ],
+            r#"{"Fun": true, "Amt": -2}"#,
+        ),
+    ];
+
+    for (bytes, json) in cases {
+        assert_eq!(
+            from_cbor(bytes).unwrap(),
+            parse_with_path(json).unwrap(),
+            "{json}"
+        );
+    }
+
+    assert_eq!(JsonValue::Int(-1000).to_cbor(), [0x39, 0x03, 0xe7]);
+    assert_eq!(JsonValue::UInt(u64::MAX).to_cbor()[0], 0x1b);
+}
+
+#[test]
+fn invalid_input() {
+    assert!(matches!(
+        from_cbor(&[0x82, 0x01]),
+        Err(JsonError::NeedMoreData)
+    ));
+
+    assert_eq!(
+        message(&[0x01, 0x02]),
+        "trailing bytes after the value at byte 1"
+    );
+    assert_eq!(
+        message(&[0x81, 0x42, 0x01, 0x02]),
+        "byte strings have no JSON counterpart at byte 1"
+    );
+    assert_eq!(
+        message(&[0xa1, 0x01, 0xf6]),
+        "keys must be strings at byte 1"
+    );
+    assert_eq!(
+        message(&[0xf7]),
+        "undefined has no JSON counterpart at byte 0"
+    );
+    assert_eq!(message(&[0x1f]), "invalid additional information at byte 0");
+}
+
+#[test]
+fn indefinite_lengths() {
+    let cases: [(&[u8], &str); 6] = [
+        (&[0x9f, 0xff], "[]"),
+        (&[0xbf, 0xff], "{}"),
+        (&[0x7f, 0xff], r#""""#),
+        // Chunks of text, one of them empty
+        (
+            &[0x7f, 0x61, 0x61, 0x60, 0x62, 0x62, 0x63, 0xff],
+            r#""abc""#,
+        ),
+        // Nested, with definite items inside
+        (
+            &[0x9f, 0x9f, 0xff, 0x82, 0x01, 0x9f, 0x02, 0xff, 0xff],
+            "[[], [1, [2]]]",
+        ),
+        // Indefinite keys and values
+        (
+            &[0xbf, 0x7f, 0x61, 0x6b, 0xff, 0x9f, 0xf6, 0xff, 0xff],
+            r#"{"k": [null]}"#,
+        ),
+    ];
+
+    for (bytes, expected) in cases {
+        assert_eq!(from_cbor(bytes).unwrap(), json(expected), "{expected}");
+    }
+
+    // No break
+    for bytes in [
+        &[0x9f, 0x01][..],
+        &[0xbf, 0x61, 0x61, 0x01],
+        &[0x7f, 0x61, 0x61],
+    ] {
+        assert!(
+            matches!(from_cbor(bytes), Err(JsonError::NeedMoreData)),
+            "{bytes:02x?}"
+        );
+    }
+
+    assert_eq!(
+        message(&[0x7f, 0x01, 0xff]),
+        "expected a chunk of text at byte 1"
+    );
+    assert_eq!(
+        message(&[0x7f, 0x7f, 0xff, 0xff]),
+        "expected a chunk of text at byte 1"
+    );
+    assert_eq!(message(&[0x81, 0xff]), "unexpected break at byte 1");
+    // Only strings, arrays and maps have an indefinite length
+    assert_eq!(message(&[0x1f]), "invalid additional information at byte 0");
+    assert_eq!(
+        message(&[0x5f, 0x41, 0x00, 0xff]),
+        "byte strings have no JSON counterpart at byte 0"
+    );
+}
+
+#[test]
+fn tags_are_skipped() {
+    let cases: [(&[u8], &str); 4] = [
+        // Standard date/time string
+        (
+            &[
+                0xc0, 0x74, 0x32, 0x30, 0x31, 0x33, 0x2d, 0x30, 0x33, 0x2d, 0x32, 0x31, 0x54, 0x32,
+                0x30, 0x3a, 0x30, 0x34, 0x3a, 0x30, 0x30, 0x5a,
+            ],
+            r#""2013-03-21T20:04:00Z""#,
+        ),
+        // Self-described CBOR, a tag with a two-byte number
+        (&[0xd9, 0xd9, 0xf7, 0xa1, 0x61, 0x61, 0x01], r#"{"a": 1}"#),
+        // Tags of tags, and inside an array
+        (&[0x82, 0xd8, 0x20, 0xc1, 0x01, 0xc1, 0xf5], "[1, true]"),
+        // Decimal fraction, kept as its exponent and mantissa
+        (&[0xc4, 0x82, 0x21, 0x19, 0x6a, 0xb3], "[-2, 27315]"),
+    ];
+
+    for (bytes, expected) in cases {
+        assert_eq!(from_cbor(bytes).unwrap(), json(expected), "{expected}");
+    }
+
+    // Without the tagged value
+    assert!(matches!(from_cbor(&[0xc1]), Err(JsonError::NeedMoreData)));
+    // Bignums are byte strings
+    assert_eq!(
+        message(&[0xc2, 0x49, 0x01, 0, 0, 0, 0, 0, 0, 0, 0]),
+        "byte strings have no JSON counterpart at byte 1"
+    );
+}
+
+#[test]
+fn half_floats() {
+    let cases: [([u8; 2], f64); 10] = [
+        ([0x00, 0x00], 0.0),
+        ([0x80, 0x00], -0.0),
+        ([0x3c, 0x00], 1.0),
+        ([0x3e, 0x00], 1.5),
+        ([0x7b, 0xff], 65504.0),
+        // Smallest normal, and largest subnormal
+        ([0x04, 0x00], 0.00006103515625),
+        ([0x03, 0xff], 0.00006097555160522461),
+        ([0x83, 0xff], -0.00006097555160522461),
+        ([0x7c, 0x00], f64::INFINITY),
+        ([0xfc, 0x00], f64::NEG_INFINITY),
+    ];
+
+    for (half, expected) in cases {
+        let JsonValue::Num(n) = from_cbor(&[0xf9, half[0], half[1]]).unwrap() else {
+            panic!("{half:02x?}");
+        };
+
+        // Bitwise, for the sign of zero
+        assert_eq!(n.to_bits(), expected.to_bits(), "{half:02x?}");
+    }
+
+    let JsonValue::Num(nan) = from_cbor(&[0xf9, 0x7e, 0x00]).unwrap() else {
+        panic!("NaN");
+    };
+    assert!(nan.is_nan());
+
+    assert!(matches!(
+        from_cbor(&[0xf9, 0x3c]),
+        Err(JsonError::NeedMoreData)
+    ));
+}
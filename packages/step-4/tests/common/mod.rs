@@ -0,0 +1,60 @@
+//! Round trips shared by the tests of the binary formats.
+
+use std::{
+    env, fs,
+    io::{self, Write},
+    path::Path,
+};
+
+use step_4::{parse_with_path, JsonError, JsonValue};
+
+/// The benchmark corpus, as fetched by `cargo xtask fetch`
+const CORPUS: [&str; 3] = ["canada.json", "twitter.json", "citm_catalog.json"];
+
+/// Checks that `decode(encode(v)) == v` for every document of the corpus.
+/// Returns the name, JSON size and encoded size of each of them.
+///
+/// A missing document is reported on stderr, past the output capture of the
+/// test harness so that it isn't mistaken for a pass, and fails the test if
+/// `REQUIRE_CORPUS` is set.
+pub fn corpus_round_trip(
+    encode: impl Fn(&JsonValue) -> Vec<u8>,
+    decode: impl Fn(&[u8]) -> Result<JsonValue, JsonError>,
+) -> Vec<(&'static str, usize, usize)> {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("../../test-files");
+    let mut sizes = Vec::new();
+
+    for name in CORPUS {
+        let Ok(json) = fs::read_to_string(dir.join(name)) else {
+            assert!(
+                env::var_os("REQUIRE_CORPUS").is_none(),
+                "test-files/{name} is missing, run `cargo xtask fetch`"
+            );
+            let _ = writeln!(
+                io::stderr(),
+                "skipped test-files/{name}: missing, run `cargo xtask fetch`"
+            );
+            continue;
+        };
+        let value = parse_with_path(&json).unwrap();
+        let bytes = encode(&value);
+
+        assert_eq!(decode(&bytes).unwrap(), value, "{name}");
+        sizes.push((name, json.len(), bytes.len()));
+    }
+
+    sizes
+}
+
+/// Checks that `decode(encode(v)) == v` for every value of `values`
+pub fn round_trip(
+    values: impl IntoIterator<Item = JsonValue>,
+    encode: impl Fn(&JsonValue) -> Vec<u8>,
+    decode: impl Fn(&[u8]) -> Result<JsonValue, JsonError>,
+) {
+    for value in values {
+        let bytes = encode(&value);
+
+        assert_eq!(decode(&bytes).unwrap(), value, "{bytes:02x?}");
+    }
+}
@@ -0,0 +1,77 @@
+//! Concatenated documents: where `parse_all` stops, and what it leaves for
+//! the next chunk.
+
+use step_4::{parse_all, parse_with_path, JsonValue};
+
+fn json(input: &str) -> JsonValue {
+    parse_with_path(input).unwrap()
+}
+
+#[test]
+fn back_to_back() {
+    let (rest, values) = parse_all(r#"{"a":1}[2]"s"{}"#);
+
+    assert_eq!(rest, "");
+    assert_eq!(
+        values,
+        [json(r#"{"a":1}"#), json("[2]"), json(r#""s""#), json("{}")]
+    );
+}
+
+#[test]
+fn whitespace_between_documents() {
+    let (rest, values) = parse_all(" {\"a\": 1}\n\n[2]\r\n\t3 true  null \n");
+
+    assert_eq!(rest, "");
+    assert_eq!(
+        values,
+        [
+            json(r#"{"a":1}"#),
+            json("[2]"),
+            json("3"),
+            json("true"),
+            json("null")
+        ]
+    );
+}
+
+#[test]
+fn empty_input() {
+    assert_eq!(parse_all(""), ("", Vec::new()));
+    // Left for the next chunk, like the start of a document
+    assert_eq!(parse_all(" \n "), (" \n ", Vec::new()));
+}
+
+#[test]
+fn trailing_partial_document() {
+    for (input, partial, count) in [
+        (r#"{"a":1} {"b": [1, 2"#, r#"{"b": [1, 2"#, 1),
+        (r#"[1] "unterminated"#, r#""unterminated"#, 1),
+        ("[1]\n[2]\ntr", "tr", 2),
+    ] {
+        let (rest, values) = parse_all(input);
+
+        assert_eq!(rest, partial, "{input}");
+        assert_eq!(values.len(), count, "{input}");
+    }
+}
+
+#[test]
+fn completed_with_the_next_chunk() {
+    let (rest, first) = parse_all(r#"{"a": 1} {"b": [1,"#);
+    let next = format!("{rest} 2]}} [3]");
+    let (rest, second) = parse_all(&next);
+
+    assert_eq!(rest, "");
+    assert_eq!(first, [json(r#"{"a":1}"#)]);
+    assert_eq!(second, [json(r#"{"b":[1,2]}"#), json("[3]")]);
+}
+
+#[test]
+fn stops_at_an_invalid_document() {
+    let (rest, values) = parse_all("[1] {x} [2]");
+
+    // What follows isn't parsed either, more data wouldn't fix it
+    assert_eq!(rest, "{x} [2]");
+    assert_eq!(values, [json("[1]")]);
+}
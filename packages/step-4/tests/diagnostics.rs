@@ -0,0 +1,68 @@
+//! Snapshots of the rendered errors of malformed documents, so that a change
+//! of the parser that makes them worse shows up in review.
+//!
+//! Snapshots are in `tests/snapshots/`. Run with `UPDATE_SNAPSHOTS=1` to
+//! write them after a deliberate change, then review the diff.
+
+use std::{env, fs, path::Path};
+
+use step_4::{parse_with_path, render_error};
+
+const CASES: &[(&str, &str)] = &[
+    ("empty", ""),
+    ("whitespace_only", "  \n "),
+    ("unterminated_string", r#"{"name": "nom"#),
+    ("unterminated_array", "[1, 2"),
+    ("trailing_comma_array", "[1, 2,]"),
+    ("trailing_comma_object", r#"{"a": 1,}"#),
+    ("missing_colon", r#"{"a" 1}"#),
+    ("missing_comma", r#"{"a": 1 "b": 2}"#),
+    ("unquoted_key", "{a: 1}"),
+    ("single_quotes", "['a']"),
+    ("invalid_literal", "[tru]"),
+    ("invalid_number", "[-]"),
+    ("invalid_escape", r#"["\x41"]"#),
+    ("invalid_unicode_escape", r#"["\uZZZZ"]"#),
+    ("lone_surrogate", r#"["\ud800"]"#),
+    ("trailing_characters", "{} {}"),
+    ("nested", r#"{"a": [{"b": [1, 2, {"c": nul}]}]}"#),
+    (
+        "multiline",
+        "{\n  \"a\": 1,\n  \"b\": [true, false,, null]\n}",
+    ),
+    ("non_ascii", r#"{"名前": "ジュール", "年": x}"#),
+    ("fail_json", include_str!("fail.json")),
+];
+
+#[test]
+fn rendered_errors() {
+    let update = env::var_os("UPDATE_SNAPSHOTS").is_some();
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/snapshots");
+    let mut mismatches = Vec::new();
+
+    for (name, input) in CASES {
+        let error = parse_with_path(input).expect_err(name);
+        let rendered = render_error(input, &error);
+        let path = dir.join(format!("{name}.txt"));
+
+        if update {
+            fs::create_dir_all(&dir).unwrap();
+            fs::write(&path, &rendered).unwrap();
+            continue;
+        }
+
+        match fs::read_to_string(&path) {
+            Ok(snapshot) if snapshot == rendered => {}
+            Ok(snapshot) => {
+                mismatches.push(format!("{name}: expected\n{snapshot}\nfound\n{rendered}"))
+            }
+            Err(_) => mismatches.push(format!("{name}: no snapshot, found\n{rendered}")),
+        }
+    }
+
+    assert!(
+        mismatches.is_empty(),
+        "{}\nrun with UPDATE_SNAPSHOTS=1 if the changes are expected",
+        mismatches.join("\n")
+    );
+}
@@ -0,0 +1,111 @@
+//! Format-preserving edits: only what was edited changes, on documents laid
+//! out on one line or on several.
+
+use step_4::{edit, JsonValue};
+
+const SINGLE_LINE: &str = r#"{"a": 1, "b": [1, 2], "c": "x"}"#;
+
+const MULTI_LINE: &str = r#"{
+  "a": 1,
+  "b": [
+    1,
+    2
+  ],
+  "c": "x"
+}
+"#;
+
+#[test]
+fn set_single_line() {
+    let replaced = edit(SINGLE_LINE, |doc| doc.set("a", JsonValue::Boolean(true))).unwrap();
+    assert_eq!(replaced, r#"{"a": true, "b": [1, 2], "c": "x"}"#);
+
+    let added = edit(SINGLE_LINE, |doc| doc.set("d", JsonValue::Null)).unwrap();
+    assert_eq!(added, r#"{"a": 1, "b": [1, 2], "c": "x", "d": null}"#);
+
+    let first = edit("{}", |doc| doc.set("d", JsonValue::Null)).unwrap();
+    assert_eq!(first, r#"{"d": null}"#);
+}
+
+#[test]
+fn set_multi_line() {
+    let added = edit(MULTI_LINE, |doc| doc.set("d", JsonValue::Null)).unwrap();
+
+    assert_eq!(
+        added,
+        r#"{
+  "a": 1,
+  "b": [
+    1,
+    2
+  ],
+  "c": "x",
+  "d": null
+}
+"#
+    );
+}
+
+#[test]
+fn remove_single_line() {
+    let remove = |key| edit(SINGLE_LINE, |doc| assert!(doc.remove(key).is_some())).unwrap();
+
+    assert_eq!(remove("a"), r#"{"b": [1, 2], "c": "x"}"#);
+    assert_eq!(remove("b"), r#"{"a": 1, "c": "x"}"#);
+    assert_eq!(remove("c"), r#"{"a": 1, "b": [1, 2]}"#);
+
+    let padded = edit(r#"{ "a": 1, "b": 2 }"#, |doc| {
+        doc.remove("a");
+    });
+    assert_eq!(padded.unwrap(), r#"{ "b": 2 }"#);
+}
+
+#[test]
+fn remove_multi_line() {
+    let remove = |key| edit(MULTI_LINE, |doc| assert!(doc.remove(key).is_some())).unwrap();
+
+    assert_eq!(
+        remove("a"),
+        "{\n  \"b\": [\n    1,\n    2\n  ],\n  \"c\": \"x\"\n}\n"
+    );
+    assert_eq!(remove("b"), "{\n  \"a\": 1,\n  \"c\": \"x\"\n}\n");
+    assert_eq!(
+        remove("c"),
+        "{\n  \"a\": 1,\n  \"b\": [\n    1,\n    2\n  ]\n}\n"
+    );
+}
+
+#[test]
+fn remove_returns_the_value() {
+    edit(SINGLE_LINE, |doc| {
+        assert_eq!(
+            doc.remove("b"),
+            Some(JsonValue::Array(vec![JsonValue::Int(1), JsonValue::Int(2)]))
+        );
+        assert_eq!(doc.remove("b"), None);
+    })
+    .unwrap();
+}
+
+#[test]
+fn push_single_line() {
+    let pushed = edit(SINGLE_LINE, |doc| {
+        doc.get_mut("b").unwrap().push(JsonValue::Int(3));
+    });
+    assert_eq!(pushed.unwrap(), r#"{"a": 1, "b": [1, 2, 3], "c": "x"}"#);
+
+    let first = edit("[]", |doc| doc.push(JsonValue::Int(3))).unwrap();
+    assert_eq!(first, "[3]");
+}
+
+#[test]
+fn push_multi_line() {
+    let pushed = edit(MULTI_LINE, |doc| {
+        doc.get_mut("b").unwrap().push(JsonValue::Int(3));
+    });
+
+    assert_eq!(
+        pushed.unwrap(),
+        "{\n  \"a\": 1,\n  \"b\": [\n    1,\n    2,\n    3\n  ],\n  \"c\": \"x\"\n}\n"
+    );
+}
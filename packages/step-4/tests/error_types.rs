@@ -0,0 +1,54 @@
+//! `parse_fast` and `parse_verbose` only differ from `parse` by their error.
+
+use nom::error::{convert_error, VerboseErrorKind};
+use step_4::{parse, parse_fast, parse_verbose, testing::RandomValues, to_string};
+
+#[test]
+fn same_values_as_parse() {
+    let mut inputs: Vec<String> = RandomValues::new(3, Default::default())
+        .take(200)
+        .map(|value| to_string(&value))
+        .collect();
+    inputs.push(" {\"a\": [1, -2.5e3, \"\\u00e9\"], \"b\": null} ".to_owned());
+
+    for input in &inputs {
+        let expected = parse(input).unwrap();
+
+        assert_eq!(parse_fast(input).unwrap(), expected, "{input}");
+        assert_eq!(parse_verbose(input).unwrap(), expected, "{input}");
+    }
+}
+
+#[test]
+fn same_failures_as_parse() {
+    for input in ["", "[1, 2", r#"{"a" 1}"#, "[tru]", r#"["\x"]"#, "{a: 1}"] {
+        assert!(parse(input).is_err(), "{input}");
+        assert!(parse_fast(input).is_err(), "{input}");
+        assert!(parse_verbose(input).is_err(), "{input}");
+    }
+}
+
+#[test]
+fn verbose_errors_carry_the_context() {
+    let input = r#"{"a": [1, {"b": x}]}"#;
+
+    let (nom::Err::Error(error) | nom::Err::Failure(error)) = parse_verbose(input).unwrap_err()
+    else {
+        panic!("incomplete");
+    };
+    let contexts: Vec<_> = error
+        .errors
+        .iter()
+        .filter_map(|(_, kind)| match kind {
+            VerboseErrorKind::Context(context) => Some(*context),
+            _ => None,
+        })
+        .collect();
+
+    // Innermost first
+    assert_eq!(contexts, ["map", "array", "map"]);
+
+    let rendered = convert_error(input, error);
+    assert!(rendered.contains("in map"), "{rendered}");
+    assert!(rendered.contains("in array"), "{rendered}");
+}
@@ -0,0 +1,121 @@
+//! The gzip decoder against streams written by zlib, one per kind of
+//! DEFLATE block, and against broken ones.
+
+use std::io::{self, Read};
+
+use step_4::{gzip::GzDecoder, parse_reader, parse_with_path};
+
+/// Level 0, with the original file name in the header
+const STORED: &[u8] = include_bytes!("gzip/stored.json.gz");
+/// `Z_FIXED` strategy
+const FIXED: &[u8] = include_bytes!("gzip/fixed.json.gz");
+const DYNAMIC: &[u8] = include_bytes!("gzip/dynamic.json.gz");
+/// About 100 KiB once decompressed, more than the 32 KiB window
+const LARGE: &[u8] = include_bytes!("gzip/large.json.gz");
+
+fn gunzip(bytes: &[u8]) -> io::Result<String> {
+    let mut text = String::new();
+    GzDecoder::new(bytes).read_to_string(&mut text)?;
+    Ok(text)
+}
+
+fn dynamic_text() -> String {
+    let items: Vec<_> = (0..12)
+        .map(|i| {
+            format!(
+                r#"{{"id": {i}, "name": "item {}", "tags": ["x", "y"]}}"#,
+                i * 7 % 13
+            )
+        })
+        .collect();
+
+    format!("[{}]", items.join(", "))
+}
+
+/// The message of the error `bytes` fail to decompress with
+fn error(bytes: &[u8]) -> String {
+    let error = gunzip(bytes).unwrap_err();
+    assert_eq!(error.kind(), io::ErrorKind::InvalidData, "{error}");
+    error.to_string()
+}
+
+#[test]
+fn stored_block() {
+    assert_eq!(gunzip(STORED).unwrap(), r#"{"a": [1, 2, 3]}"#);
+}
+
+#[test]
+fn fixed_block() {
+    assert_eq!(
+        gunzip(FIXED).unwrap(),
+        r#"{"a": [1, 2, 3], "b": "abcabcabcabc"}"#
+    );
+}
+
+#[test]
+fn dynamic_block() {
+    assert_eq!(gunzip(DYNAMIC).unwrap(), dynamic_text());
+}
+
+#[test]
+fn beyond_the_window() {
+    let items: Vec<_> = (0..4000)
+        .map(|i| {
+            format!(
+                r#"{{"id": {}, "square": {}}}"#,
+                i % 100,
+                (i % 100) * (i % 100)
+            )
+        })
+        .collect();
+
+    assert_eq!(gunzip(LARGE).unwrap(), format!("[{}]", items.join(", ")));
+}
+
+#[test]
+fn multiple_members() {
+    let bytes = [STORED, FIXED].concat();
+
+    assert_eq!(
+        gunzip(&bytes).unwrap(),
+        r#"{"a": [1, 2, 3]}{"a": [1, 2, 3], "b": "abcabcabcabc"}"#
+    );
+}
+
+#[test]
+fn crc_mismatch() {
+    let mut bytes = DYNAMIC.to_vec();
+    let crc = bytes.len() - 8;
+    bytes[crc] ^= 1;
+
+    assert_eq!(error(&bytes), "gzip: CRC mismatch");
+}
+
+#[test]
+fn size_mismatch() {
+    let mut bytes = DYNAMIC.to_vec();
+    let size = bytes.len() - 4;
+    bytes[size] ^= 1;
+
+    assert_eq!(error(&bytes), "gzip: size mismatch");
+}
+
+#[test]
+fn truncated() {
+    for len in 1..DYNAMIC.len() {
+        assert!(gunzip(&DYNAMIC[..len]).is_err(), "{len} bytes");
+    }
+}
+
+#[test]
+fn not_gzip() {
+    assert_eq!(error(b"\x1f\x8c\x08"), "gzip: not a gzip stream");
+}
+
+#[test]
+fn parse_reader_decompresses() {
+    assert_eq!(
+        parse_reader(DYNAMIC).unwrap(),
+        parse_with_path(&dynamic_text()).unwrap()
+    );
+}
@@ -0,0 +1,56 @@
+//! Heap usage of owned vs interned keys, measured with a counting allocator.
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use step_4::{parse, parse_interned, Interner};
+
+struct Counting;
+
+static LIVE: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        LIVE.fetch_add(layout.size(), Ordering::SeqCst);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        LIVE.fetch_sub(layout.size(), Ordering::SeqCst);
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: Counting = Counting;
+
+/// Bytes still allocated once `f` returns, while its result is alive
+fn retained<T>(f: impl FnOnce() -> T) -> (usize, T) {
+    let before = LIVE.load(Ordering::SeqCst);
+    let value = f();
+
+    (LIVE.load(Ordering::SeqCst) - before, value)
+}
+
+#[test]
+fn interned_keys_use_less_memory() {
+    let tweet = r#"{"created_at": "Sun Aug 31 00:29:15 +0000 2014", "favorite_count": 0,
+        "in_reply_to_screen_name": null, "retweet_count": 0, "possibly_sensitive": false}"#;
+    let json = format!("[{}]", vec![tweet; 500].join(","));
+
+    let (owned, value) = retained(|| parse(&json).unwrap().1);
+    let (interned, interned_value) = retained(|| {
+        let interner = Interner::new();
+        let value = parse_interned(&json, &interner).unwrap().1;
+        assert_eq!(interner.len(), 5);
+        value
+    });
+
+    assert_eq!(interned_value.into_value(), value);
+    assert!(
+        interned < owned,
+        "interned: {interned} bytes, owned: {owned} bytes"
+    );
+}
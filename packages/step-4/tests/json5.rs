@@ -0,0 +1,92 @@
+//! The extensions of JSON5 over JSON, one by one.
+
+use step_4::{error_position, json5::parse, parse_with_path, JsonError, JsonValue};
+
+/// `input` parsed as JSON5, which must be the whole input
+fn json5(input: &str) -> JsonValue {
+    let (rest, value) = parse(input).unwrap();
+    assert_eq!(rest, "", "{input}");
+    value
+}
+
+fn json(input: &str) -> JsonValue {
+    parse_with_path(input).unwrap()
+}
+
+#[test]
+fn comments() {
+    let input = "// a line comment
+    {
+        /* a block
+           comment */
+        \"a\": 1, // after a member
+    }";
+
+    assert_eq!(json5(input), json(r#"{"a": 1}"#));
+}
+
+#[test]
+fn unquoted_keys() {
+    assert_eq!(json5("{a: 1, $b_2: 2}"), json(r#"{"a": 1, "$b_2": 2}"#));
+}
+
+#[test]
+fn single_quotes() {
+    assert_eq!(json5(r#"'say "hi"'"#), JsonValue::from(r#"say "hi""#));
+    assert_eq!(json5(r"'it\'s'"), JsonValue::from("it's"));
+}
+
+#[test]
+fn line_continuations() {
+    assert_eq!(json5("'a\\\nb'"), JsonValue::from("ab"));
+    assert_eq!(json5("'a\\\r\nb'"), JsonValue::from("ab"));
+    assert!(parse("'a\nb'").is_err());
+}
+
+#[test]
+fn hex_numbers() {
+    assert_eq!(json5("0xFF"), JsonValue::Int(255));
+    assert_eq!(json5("-0x10"), JsonValue::Int(-16));
+    assert_eq!(json5("0xffffffffffffffff"), JsonValue::UInt(u64::MAX));
+}
+
+#[test]
+fn decimal_points_and_signs() {
+    assert_eq!(json5(".5"), JsonValue::Num(0.5));
+    assert_eq!(json5("5."), JsonValue::Num(5.0));
+    assert_eq!(json5("+1"), JsonValue::Int(1));
+    assert_eq!(json5("-0"), JsonValue::Num(-0.0));
+    assert_eq!(json5("-0x0"), JsonValue::Num(-0.0));
+}
+
+#[test]
+fn infinity_and_nan() {
+    assert_eq!(json5("Infinity"), JsonValue::Num(f64::INFINITY));
+    assert_eq!(json5("-Infinity"), JsonValue::Num(f64::NEG_INFINITY));
+    assert!(matches!(json5("NaN"), JsonValue::Num(n) if n.is_nan()));
+}
+
+#[test]
+fn trailing_commas() {
+    assert_eq!(json5("[1, 2,]"), json("[1, 2]"));
+    assert_eq!(json5("{a: 1,}"), json(r#"{"a": 1}"#));
+    assert_eq!(json5("[ ]"), json("[]"));
+    assert_eq!(json5("{ }"), json("{}"));
+}
+
+#[test]
+fn lone_commas() {
+    for input in ["[,]", "{,}", "[1,,]", "{a: 1,,}"] {
+        assert!(parse(input).is_err(), "{input}");
+    }
+}
+
+#[test]
+fn errors_are_located() {
+    let input = "{\n  a: 1,\n  b: [2, @],\n}";
+    let error = JsonError::from(parse(input).unwrap_err());
+    let position = error_position(input, &error).unwrap();
+
+    assert_eq!(error.to_string(), "unexpected '@'");
+    assert_eq!((position.line, position.column), (3, 10));
+}
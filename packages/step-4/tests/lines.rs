@@ -0,0 +1,102 @@
+//! JSON Lines: which lines are documents, and which line an error is on.
+
+use step_4::{
+    parse_lines, parse_with_path, to_string_with, JsonError, JsonValue, SerializeOptions,
+};
+
+fn json(input: &str) -> JsonValue {
+    parse_with_path(input).unwrap()
+}
+
+/// Values of the valid lines, with sorted keys, and `line: message` for the
+/// others
+fn outcomes(results: impl Iterator<Item = Result<JsonValue, JsonError>>) -> Vec<String> {
+    let opts = SerializeOptions {
+        sort_keys: true,
+        ..Default::default()
+    };
+
+    results
+        .map(|result| match result {
+            Ok(value) => to_string_with(&value, &opts),
+            Err(error) => error.to_string(),
+        })
+        .collect()
+}
+
+#[test]
+fn one_value_per_line() {
+    let input = "{\"a\": 1}\n[1, 2]\n\"s\"\n3\n";
+
+    let values: Vec<_> = parse_lines(input).map(Result::unwrap).collect();
+
+    assert_eq!(
+        values,
+        [json(r#"{"a":1}"#), json("[1,2]"), json(r#""s""#), json("3")]
+    );
+}
+
+#[test]
+fn blank_lines_are_skipped() {
+    let input = "\n1\n\n   \n\t\n2\n\n";
+
+    assert_eq!(outcomes(parse_lines(input)), ["1", "2"]);
+}
+
+#[test]
+fn crlf() {
+    let input = "{\"a\": 1}\r\n\r\n[true]\r\n";
+
+    assert_eq!(outcomes(parse_lines(input)), [r#"{"a":1}"#, "[true]"]);
+}
+
+#[test]
+fn error_on_a_middle_line() {
+    let input = "1\n\n[2\n{\"b\": x}\n5\n";
+
+    // Line numbers count the blank lines, and the lines after an error are
+    // still parsed
+    assert_eq!(
+        outcomes(parse_lines(input)),
+        [
+            "1",
+            "line 3: unexpected end of input, expected ']'",
+            "line 4: unexpected 'x'",
+            "5"
+        ]
+    );
+
+    let Some(Err(JsonError::Line(line, _))) = parse_lines(input).nth(1) else {
+        panic!("no error on the second document");
+    };
+    assert_eq!(line, 3);
+}
+
+#[test]
+fn document_spanning_lines_is_an_error() {
+    let input = "{\n\"a\": 1\n}";
+
+    assert!(parse_lines(input).all(|result| result.is_err()));
+}
+
+/// Enough lines for several batches on any machine, with blank lines and
+/// errors here and there
+#[cfg(feature = "std")]
+#[test]
+fn parallel_matches_sequential() {
+    use step_4::par_parse_lines;
+
+    let input: String = (0..20_000)
+        .map(|n| match n % 97 {
+            0 => "\n".to_owned(),
+            13 => format!("[{n},\n"),
+            _ => format!("{{\"n\": {n}, \"s\": \"{n:x}\"}}\r\n"),
+        })
+        .collect();
+
+    let sequential = outcomes(parse_lines(&input));
+    let parallel = outcomes(par_parse_lines(&input));
+
+    assert_eq!(sequential.len(), 20_000 - 20_000 / 97 - 1);
+    assert_eq!(parallel, sequential);
+}
@@ -0,0 +1,73 @@
+//! `parse_file` on files written for the occasion.
+
+use std::{fs, io, path::PathBuf};
+
+use step_4::{parse_file, parse_with_path, JsonError};
+
+/// `contents` in a file of its own, named after the test
+fn file(name: &str, contents: &[u8]) -> PathBuf {
+    let path = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(format!("mmap-{name}.json"));
+    fs::write(&path, contents).unwrap();
+    path
+}
+
+#[test]
+fn valid_file() {
+    let json = r#"{"a": [1, 2.5, "é"], "b": null}"#;
+
+    assert_eq!(
+        parse_file(file("valid", json.as_bytes())).unwrap(),
+        parse_with_path(json).unwrap()
+    );
+}
+
+#[test]
+fn several_pages() {
+    let items: Vec<_> = (0..10_000).map(|i| format!(r#"{{"id": {i}}}"#)).collect();
+    let json = format!("[{}]", items.join(", "));
+
+    assert_eq!(
+        parse_file(file("pages", json.as_bytes())).unwrap(),
+        parse_with_path(&json).unwrap()
+    );
+}
+
+#[test]
+fn empty_file() {
+    // Not mapped at all, and an empty document like any other
+    let error = parse_file(file("empty", b"")).unwrap_err();
+
+    assert_eq!(
+        error.to_string(),
+        parse_with_path("").unwrap_err().to_string()
+    );
+}
+
+#[test]
+fn missing_file() {
+    let path = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("mmap-missing.json");
+
+    assert!(matches!(
+        parse_file(path),
+        Err(JsonError::Io(e)) if e.kind() == io::ErrorKind::NotFound
+    ));
+}
+
+#[test]
+fn invalid_utf8() {
+    assert!(matches!(
+        parse_file(file("utf8", b"[\"\xff\"]")),
+        Err(JsonError::Io(e)) if e.kind() == io::ErrorKind::InvalidData
+    ));
+}
+
+#[test]
+fn syntax_error() {
+    let json = "[1,\n 2 x]";
+    let error = parse_file(file("syntax", json.as_bytes())).unwrap_err();
+
+    assert_eq!(
+        error.to_string(),
+        parse_with_path(json).unwrap_err().to_string()
+    );
+}
@@ -0,0 +1,71 @@
+//! `from_msgpack(v.to_msgpack()) == v` over the benchmark corpus, as fetched
+//! by `cargo xtask fetch`, and over random values.
+
+mod common;
+
+use step_4::{
+    from_msgpack,
+    testing::{RandomOptions, RandomValues},
+    JsonError, JsonValue,
+};
+
+#[test]
+fn corpus_round_trip() {
+    for (name, json_len, len) in common::corpus_round_trip(JsonValue::to_msgpack, from_msgpack) {
+        assert!(len < json_len, "{name}");
+    }
+}
+
+#[test]
+fn random_round_trip() {
+    common::round_trip(
+        RandomValues::new(3, RandomOptions::default()).take(2_000),
+        JsonValue::to_msgpack,
+        from_msgpack,
+    );
+}
+
+#[test]
+fn integers_take_the_smallest_encoding() {
+    let cases: [(i64, &[u8]); 8] = [
+        (0, &[0x00]),
+        (127, &[0x7f]),
+        (-32, &[0xe0]),
+        (128, &[0xcc, 0x80]),
+        (-33, &[0xd0, 0xdf]),
+        (65_536, &[0xce, 0x00, 0x01, 0x00, 0x00]),
+        (-32_769, &[0xd2, 0xff, 0xff, 0x7f, 0xff]),
+        (i64::MIN, &[0xd3, 0x80, 0, 0, 0, 0, 0, 0, 0]),
+    ];
+
+    for (n, bytes) in cases {
+        assert_eq!(JsonValue::Int(n).to_msgpack(), bytes, "{n}");
+        assert_eq!(from_msgpack(bytes).unwrap(), JsonValue::Int(n));
+    }
+
+    let max = JsonValue::UInt(u64::MAX);
+    assert_eq!(from_msgpack(&max.to_msgpack()).unwrap(), max);
+}
+
+#[test]
+fn invalid_input() {
+    assert!(matches!(
+        from_msgpack(&[0x92, 0x01]),
+        Err(JsonError::NeedMoreData)
+    ));
+
+    let message = |bytes: &[u8]| from_msgpack(bytes).unwrap_err().to_string();
+    assert_eq!(
+        message(&[0x01, 0x02]),
+        "trailing bytes after the value at byte 1"
+    );
+    assert_eq!(
+        message(&[0x91, 0xc4, 0x01, 0xff]),
+        "binary data has no JSON counterpart at byte 1"
+    );
+    assert_eq!(
+        message(&[0x81, 0x01, 0xc0]),
+        "keys must be strings at byte 1"
+    );
+    assert_eq!(message(&[0xa2, b'a', 0xff]), "invalid UTF-8 at byte 2");
+}
@@ -0,0 +1,89 @@
+//! `parse_parallel_with` on documents big enough to be split, against the
+//! sequential parser.
+
+use step_4::{
+    error_position, parse_parallel_with, parse_with_path, testing::RandomValues, to_string,
+    ParallelOptions,
+};
+
+const OPTS: ParallelOptions = ParallelOptions {
+    threads: 4,
+    min_len: 256,
+};
+
+/// An object with a large array of objects, and another of arrays
+fn document() -> String {
+    let records: Vec<_> = (0..500)
+        .map(|i| {
+            format!(r#"{{"id": {i}, "name": "r\"{i}", "tags": ["a", "b,c", {{"d": [{i}]}}]}}"#)
+        })
+        .collect();
+    let pairs: Vec<_> = (0..300).map(|i| format!("[{i}, {}.5]", -i)).collect();
+
+    format!(
+        "\u{FEFF} {{\"records\": [{}],\n \"pairs\": [{}], \"empty\": [], \"n\": 1}} ",
+        records.join(",\n  "),
+        pairs.join(", ")
+    )
+}
+
+#[test]
+fn same_value() {
+    let input = document();
+
+    assert_eq!(
+        parse_parallel_with(&input, &OPTS).unwrap(),
+        parse_with_path(&input).unwrap()
+    );
+}
+
+#[test]
+fn random_values() {
+    for value in RandomValues::new(5, Default::default()).take(200) {
+        let input = to_string(&value);
+        let opts = ParallelOptions {
+            threads: 3,
+            min_len: 16,
+        };
+
+        assert_eq!(
+            parse_parallel_with(&input, &opts).unwrap(),
+            value,
+            "{input}"
+        );
+    }
+}
+
+/// Same message, path included, and same position as the sequential parser
+fn assert_same_error(input: &str) {
+    let parallel = parse_parallel_with(input, &OPTS).unwrap_err();
+    let sequential = parse_with_path(input).unwrap_err();
+
+    assert_eq!(parallel.to_string(), sequential.to_string());
+    assert_eq!(
+        error_position(input, &parallel),
+        error_position(input, &sequential)
+    );
+}
+
+#[test]
+fn error_in_an_element() {
+    let input = document().replace(r#"{"d": [321]}"#, r#"{"d": [32x]}"#);
+
+    assert_same_error(&input);
+}
+
+#[test]
+fn error_in_a_member() {
+    let input = document().replace(r#"[150, -150.5]"#, r#"[150, -150.5.]"#);
+
+    assert_same_error(&input);
+}
+
+#[test]
+fn error_in_the_structure() {
+    // Which the scan gives up on, leaving it to the sequential parser
+    let input = document().replace(r#""empty": []"#, r#""empty": ]"#);
+
+    assert_same_error(&input);
+}
@@ -0,0 +1,74 @@
+//! Round trips of random values through the serializer and the parsers.
+
+use step_4::{
+    parse_cst, parse_with_path,
+    testing::{Alphabet, RandomOptions, RandomValues},
+    to_string, to_string_with, JsonValue, SerializeOptions,
+};
+
+const VALUES: usize = 2_000;
+
+#[test]
+fn compact_round_trip() {
+    for value in RandomValues::new(1, RandomOptions::default()).take(VALUES) {
+        let json = to_string(&value);
+
+        assert_eq!(parse_with_path(&json).unwrap(), value, "{json}");
+    }
+}
+
+#[test]
+fn indented_ascii_round_trip() {
+    let opts = SerializeOptions {
+        indent: Some(2),
+        ensure_ascii: true,
+        ..Default::default()
+    };
+
+    for value in RandomValues::new(2, RandomOptions::default()).take(VALUES) {
+        let json = to_string_with(&value, &opts);
+
+        assert!(json.is_ascii(), "{json}");
+        assert_eq!(parse_with_path(&json).unwrap(), value, "{json}");
+    }
+}
+
+#[test]
+fn cst_round_trip() {
+    let opts = RandomOptions {
+        alphabet: Alphabet::Ascii,
+        ints: -1000..=1000,
+        floats: -1e300..=1e300,
+        ..Default::default()
+    };
+    let indent = SerializeOptions {
+        indent: Some(4),
+        ..Default::default()
+    };
+
+    for value in RandomValues::new(3, opts).take(VALUES) {
+        let json = to_string_with(&value, &indent);
+        let (rest, cst) = parse_cst(&json).unwrap();
+
+        assert_eq!(rest, "");
+        assert_eq!(cst.to_string(), json);
+        assert_eq!(cst.to_value(), value, "{json}");
+    }
+}
+
+#[test]
+fn flatten_round_trip() {
+    // Keys without separators or brackets, which would read as paths
+    let opts = RandomOptions {
+        alphabet: Alphabet::Alphanumeric,
+        ..Default::default()
+    };
+
+    for value in RandomValues::new(4, opts).take(VALUES) {
+        // Under a key: an object with an empty key at the root flattens like a
+        // scalar does
+        let value = JsonValue::Object([("value".to_owned(), value)].into_iter().collect());
+
+        assert_eq!(value.flatten().unflatten().unwrap(), value);
+    }
+}
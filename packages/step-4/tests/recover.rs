@@ -0,0 +1,111 @@
+//! What the error-tolerant parsers make of damaged documents: the value they
+//! salvage, and the errors they report on the way, with their offsets.
+
+use step_4::{
+    error_position, parse_lossy, parse_recovering, parse_with_path, JsonError, JsonValue, Lossy,
+};
+
+fn json(input: &str) -> JsonValue {
+    parse_with_path(input).unwrap()
+}
+
+/// Message and byte offset of each error of `input`
+fn errors(input: &str, errors: &[JsonError]) -> Vec<(String, usize)> {
+    errors
+        .iter()
+        .map(|error| {
+            let position = error_position(input, error).unwrap();
+            (error.to_string(), position.offset)
+        })
+        .collect()
+}
+
+#[test]
+fn recovering_invalid_values() {
+    let input = r#"{"a": tru, "b": [1, x, 3]}"#;
+    let (value, found) = parse_recovering(input);
+
+    assert_eq!(value, json(r#"{"a": null, "b": [1, null, 3]}"#));
+    assert_eq!(
+        errors(input, &found),
+        [
+            ("invalid literal".to_owned(), 6),
+            ("unexpected 'x'".to_owned(), 20)
+        ]
+    );
+}
+
+#[test]
+fn recovering_trailing_commas() {
+    let input = "[1, 2,]";
+    let (value, found) = parse_recovering(input);
+
+    assert_eq!(value, json("[1, 2]"));
+    assert_eq!(errors(input, &found), [("unexpected ']'".to_owned(), 6)]);
+
+    let input = r#"{"a": 1,}"#;
+    let (value, found) = parse_recovering(input);
+
+    assert_eq!(value, json(r#"{"a": 1}"#));
+    assert_eq!(errors(input, &found), [("unexpected '}'".to_owned(), 8)]);
+}
+
+#[test]
+fn recovering_valid_document() {
+    let input = r#"{"a": [1, "x", true]}"#;
+
+    let (value, found) = parse_recovering(input);
+
+    assert_eq!(value, json(input));
+    assert!(found.is_empty());
+}
+
+#[test]
+fn lossy_truncated_string() {
+    let input = r#"{"a": [1, "x"#;
+    let Lossy { value, diagnostics } = parse_lossy(input);
+
+    // Where `parse_recovering` drops the string
+    assert_eq!(parse_recovering(input).0, json(r#"{"a": [1, null]}"#));
+    assert_eq!(value, json(r#"{"a": [1, "x"]}"#));
+    assert_eq!(
+        errors(input, &diagnostics),
+        [("unexpected end of input, expected '\"'".to_owned(), 12)]
+    );
+}
+
+#[test]
+fn lossy_unclosed_containers() {
+    let input = r#"[1, {"b": [2"#;
+    let Lossy { value, diagnostics } = parse_lossy(input);
+
+    assert_eq!(value, json(r#"[1, {"b": [2]}]"#));
+    assert_eq!(
+        errors(input, &diagnostics),
+        [("unexpected end of input".to_owned(), 12)]
+    );
+}
+
+#[test]
+fn lossy_invalid_values() {
+    let input = r#"{"a": tru, "b": "ok"#;
+    let Lossy { value, diagnostics } = parse_lossy(input);
+
+    assert_eq!(value, json(r#"{"a": null, "b": "ok"}"#));
+    assert_eq!(
+        errors(input, &diagnostics),
+        [
+            ("invalid literal".to_owned(), 6),
+            ("unexpected end of input, expected '\"'".to_owned(), 19)
+        ]
+    );
+}
+
+#[test]
+fn lossy_valid_document() {
+    let input = r#"{"a": [1, "x", true]}"#;
+    let Lossy { value, diagnostics } = parse_lossy(input);
+
+    assert_eq!(value, json(input));
+    assert!(diagnostics.is_empty());
+}
@@ -0,0 +1,119 @@
+//! `parse(to_string(v)) == v` for floats all over the `f64` range, and for
+//! strings with lone surrogates.
+
+use step_4::{parse, parse_with_options, to_string, JsonValue, ParseOptions, SurrogateMode};
+
+#[test]
+fn floats_round_trip() {
+    // xorshift, to cover arbitrary bit patterns deterministically
+    let mut state = 0x2545_f491_4f6c_dd1du64;
+    let mut next = move || {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        state
+    };
+
+    let mut values = vec![
+        0.0,
+        -0.0,
+        0.1,
+        1.0,
+        -1.5,
+        1e21,
+        1e-7,
+        5e-324,
+        f64::MAX,
+        f64::MIN_POSITIVE,
+    ];
+    values.extend(
+        (0..10_000)
+            .map(|_| f64::from_bits(next()))
+            .filter(|n| n.is_finite()),
+    );
+
+    for n in values {
+        let json = to_string(&JsonValue::Num(n));
+        let (rest, parsed) = parse(&json).unwrap();
+
+        assert_eq!(rest, "");
+        assert_eq!(parsed, JsonValue::Num(n), "{json}");
+    }
+}
+
+#[test]
+fn lone_surrogates() {
+    let json = r#"["\ud83d\ude00", "a\ud800b", "\udfff\ud800"]"#;
+    let with = |mode| ParseOptions {
+        lone_surrogates: mode,
+        ..Default::default()
+    };
+
+    assert!(parse(json).is_err());
+
+    let (_, replaced) = parse_with_options(json, &with(SurrogateMode::Replace)).unwrap();
+    assert_eq!(to_string(&replaced), r#"["😀","a�b","��"]"#);
+
+    let (_, passed) = parse_with_options(json, &with(SurrogateMode::Wtf8)).unwrap();
+    let JsonValue::Array(values) = &passed else {
+        panic!("{passed:?}");
+    };
+    assert_eq!(values[0], JsonValue::Str("😀".to_owned()));
+    assert_eq!(values[1], JsonValue::Wtf8(b"a\xed\xa0\x80b".to_vec()));
+    assert_eq!(to_string(&passed), r#"["😀","a\ud800b","\udfff\ud800"]"#);
+
+    // Keys are strings
+    let (_, object) = parse_with_options(r#"{"\ud800": 1}"#, &with(SurrogateMode::Wtf8)).unwrap();
+    assert_eq!(to_string(&object), r#"{"�":1}"#);
+}
+
+#[test]
+fn negative_zero() {
+    for json in ["-0", "-0.0", "-0e0"] {
+        let (_, parsed) = parse(json).unwrap();
+        assert_eq!(parsed, JsonValue::Num(-0.0), "{json}");
+
+        let (_, reparsed) = parse(&to_string(&parsed)).unwrap();
+        assert_eq!(reparsed, parsed, "{json}");
+    }
+
+    assert_eq!(parse("0").unwrap().1, JsonValue::Int(0));
+}
+
+#[cfg(feature = "bignum")]
+#[test]
+fn big_numbers() {
+    use step_4::NumberMode;
+
+    let opts = ParseOptions {
+        number_mode: NumberMode::BigNumber,
+        ..Default::default()
+    };
+    let json = "[123456789012345678901234567890,-0,1.5,-0.000225,225e-42]";
+    let (_, value) = parse_with_options(json, &opts).unwrap();
+
+    assert_eq!(to_string(&value), json);
+}
+
+#[test]
+fn out_of_range() {
+    use step_4::{parse_with_path, NumberMode};
+
+    for json in ["1e400", "[-1e400]", r#"{"a": 2e308}"#] {
+        let err = parse_with_path(json).unwrap_err();
+        assert!(
+            err.to_string().starts_with("invalid number"),
+            "{json}: {err}"
+        );
+    }
+
+    // Underflow rounds to zero, which reads back the same
+    assert_eq!(parse("1e-400").unwrap().1, JsonValue::Num(0.0));
+
+    let opts = ParseOptions {
+        number_mode: NumberMode::Preserve,
+        ..Default::default()
+    };
+    let (_, value) = parse_with_options("[1e400]", &opts).unwrap();
+    assert_eq!(to_string(&value), "[1e400]");
+}
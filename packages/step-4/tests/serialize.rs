@@ -0,0 +1,72 @@
+//! `to_string_with` and its options.
+
+use step_4::{parse_with_path, to_string_with, SerializeOptions};
+
+fn sorted(indent: Option<usize>) -> SerializeOptions {
+    SerializeOptions {
+        indent,
+        sort_keys: true,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn sort_keys_at_every_depth() {
+    let value = parse_with_path(
+        r#"{"b": {"z": 1, "a": {"y": [], "x": {}}}, "a": [{"d": 1, "c": 2}], "c": null}"#,
+    )
+    .unwrap();
+
+    assert_eq!(
+        to_string_with(&value, &sorted(None)),
+        r#"{"a":[{"c":2,"d":1}],"b":{"a":{"x":{},"y":[]},"z":1},"c":null}"#
+    );
+}
+
+#[test]
+fn sort_keys_pretty() {
+    let value =
+        parse_with_path(r#"{"b": {"z": [1, {"y": 2, "x": 3}], "a": {}}, "a": "s"}"#).unwrap();
+
+    assert_eq!(
+        to_string_with(&value, &sorted(Some(2))),
+        r#"{
+  "a": "s",
+  "b": {
+    "a": {},
+    "z": [
+      1,
+      {
+        "x": 3,
+        "y": 2
+      }
+    ]
+  }
+}"#
+    );
+}
+
+/// Code point order, uppercase before lowercase and ASCII before the rest
+#[test]
+fn sort_keys_order() {
+    let value = parse_with_path(r#"{"é": 1, "b": 2, "B": 3, "a1": 4, "a": 5, "": 6}"#).unwrap();
+
+    assert_eq!(
+        to_string_with(&value, &sorted(None)),
+        r#"{"":6,"B":3,"a":5,"a1":4,"b":2,"é":1}"#
+    );
+}
+
+/// Whatever order the entries were inserted in
+#[test]
+fn sort_keys_is_deterministic() {
+    let keys: Vec<_> = (0..50).map(|n| format!("\"k{n}\": {n}")).collect();
+    let forward = parse_with_path(&format!("{{{}}}", keys.join(","))).unwrap();
+    let backward = keys.iter().rev().cloned().collect::<Vec<_>>().join(",");
+    let backward = parse_with_path(&format!("{{{backward}}}")).unwrap();
+
+    assert_eq!(
+        to_string_with(&forward, &sorted(Some(4))),
+        to_string_with(&backward, &sorted(Some(4)))
+    );
+}
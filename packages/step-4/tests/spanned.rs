@@ -0,0 +1,89 @@
+//! Where `parse_spanned` says the keys and values of a document are.
+
+use step_4::{parse_spanned, parse_with_path, Position, Spanned, SpannedValue};
+
+const INPUT: &str = "{
+  \"name\": \"é\",
+  \"list\": [1, true],
+  \"nested\": {\"k\": null}
+}";
+
+/// Offset, line and column
+fn at(position: Position) -> (usize, usize, usize) {
+    (position.offset, position.line, position.column)
+}
+
+fn span<T>(spanned: &Spanned<T>) -> [(usize, usize, usize); 2] {
+    [at(spanned.start), at(spanned.end)]
+}
+
+fn entries(value: &SpannedValue) -> &[(Spanned<String>, Spanned<SpannedValue>)] {
+    match value {
+        SpannedValue::Object(entries) => entries,
+        value => panic!("{value:?} isn't an object"),
+    }
+}
+
+#[test]
+fn keys_and_values() {
+    let (rest, root) = parse_spanned(INPUT).unwrap();
+    assert_eq!(rest, "");
+    assert_eq!(span(&root), [(0, 1, 1), (64, 5, 2)]);
+
+    let [(name, e), (list, array), (nested, object)] = entries(&root.value) else {
+        panic!("not three entries");
+    };
+
+    assert_eq!(name.value, "name");
+    assert_eq!(span(name), [(4, 2, 3), (10, 2, 9)]);
+    // `é` is two bytes, but one column
+    assert_eq!(span(e), [(12, 2, 11), (16, 2, 14)]);
+
+    assert_eq!(span(list), [(20, 3, 3), (26, 3, 9)]);
+    assert_eq!(span(array), [(28, 3, 11), (37, 3, 20)]);
+    let SpannedValue::Array(items) = &array.value else {
+        panic!("{array:?}");
+    };
+    assert_eq!(span(&items[0]), [(29, 3, 12), (30, 3, 13)]);
+    assert_eq!(span(&items[1]), [(32, 3, 15), (36, 3, 19)]);
+
+    assert_eq!(span(nested), [(41, 4, 3), (49, 4, 11)]);
+    assert_eq!(span(object), [(51, 4, 13), (62, 4, 24)]);
+    let [(k, null)] = entries(&object.value) else {
+        panic!("{object:?}");
+    };
+    assert_eq!(span(k), [(52, 4, 14), (55, 4, 17)]);
+    assert_eq!(span(null), [(57, 4, 19), (61, 4, 23)]);
+}
+
+#[test]
+fn spans_are_the_source_text() {
+    let (_, root) = parse_spanned(INPUT).unwrap();
+    let text = |spanned: &Spanned<SpannedValue>| &INPUT[spanned.start.offset..spanned.end.offset];
+
+    let values: Vec<_> = entries(&root.value)
+        .iter()
+        .map(|(_, value)| text(value))
+        .collect();
+    assert_eq!(values, ["\"é\"", "[1, true]", "{\"k\": null}"]);
+    assert_eq!(text(&root), INPUT);
+}
+
+#[test]
+fn crlf_and_surrounding_whitespace() {
+    let input = "\r\n [\r\n  1,\r\n  2 ] ";
+    let (_, root) = parse_spanned(input).unwrap();
+
+    assert_eq!(span(&root), [(3, 2, 2), (17, 4, 6)]);
+    let SpannedValue::Array(items) = &root.value else {
+        panic!("{root:?}");
+    };
+    assert_eq!(span(&items[1]), [(14, 4, 3), (15, 4, 4)]);
+}
+
+#[test]
+fn same_value_as_parse() {
+    let (_, root) = parse_spanned(INPUT).unwrap();
+
+    assert_eq!(root.value.into_value(), parse_with_path(INPUT).unwrap());
+}
@@ -0,0 +1,150 @@
+//! The callbacks `parse_with_visitor` makes, in order, and where it stops on
+//! errors.
+
+use std::borrow::Cow;
+
+use step_4::{error_position, parse_with_visitor, JsonVisitor};
+
+/// Writes down every callback
+#[derive(Default)]
+struct Recorder {
+    calls: Vec<String>,
+    borrowed: usize,
+}
+
+impl Recorder {
+    fn string(&mut self, kind: &str, s: Cow<'_, str>) {
+        if let Cow::Borrowed(_) = s {
+            self.borrowed += 1;
+        }
+        self.calls.push(format!("{kind} {s}"));
+    }
+}
+
+impl<'a> JsonVisitor<'a> for Recorder {
+    fn visit_null(&mut self) {
+        self.calls.push("null".into());
+    }
+
+    fn visit_bool(&mut self, value: bool) {
+        self.calls.push(format!("bool {value}"));
+    }
+
+    fn visit_number(&mut self, value: f64) {
+        self.calls.push(format!("number {value}"));
+    }
+
+    fn visit_str(&mut self, value: Cow<'a, str>) {
+        self.string("str", value);
+    }
+
+    fn begin_object(&mut self) {
+        self.calls.push("{".into());
+    }
+
+    fn visit_key(&mut self, key: Cow<'a, str>) {
+        self.string("key", key);
+    }
+
+    fn end_object(&mut self) {
+        self.calls.push("}".into());
+    }
+
+    fn begin_array(&mut self) {
+        self.calls.push("[".into());
+    }
+
+    fn end_array(&mut self) {
+        self.calls.push("]".into());
+    }
+}
+
+#[test]
+fn nested_document() {
+    let input = r#"{"a": [1, {"b": null}, [], "x\ny"], "c": {"d": true, "e": -2.5}, "f": {}}"#;
+    let mut recorder = Recorder::default();
+
+    parse_with_visitor(input, &mut recorder).unwrap();
+
+    assert_eq!(
+        recorder.calls,
+        [
+            "{",
+            "key a",
+            "[",
+            "number 1",
+            "{",
+            "key b",
+            "null",
+            "}",
+            "[",
+            "]",
+            "str x\ny",
+            "]",
+            "key c",
+            "{",
+            "key d",
+            "bool true",
+            "key e",
+            "number -2.5",
+            "}",
+            "key f",
+            "{",
+            "}",
+            "}",
+        ]
+    );
+    // Every key, the only string having an escape
+    assert_eq!(recorder.borrowed, 6);
+}
+
+#[test]
+fn scalar_document() {
+    let mut recorder = Recorder::default();
+
+    parse_with_visitor(" \"s\" ", &mut recorder).unwrap();
+
+    assert_eq!(recorder.calls, ["str s"]);
+}
+
+#[test]
+fn callbacks_stop_at_the_error() {
+    let input = r#"{"a": [1, 2}, "b": 3}"#;
+    let mut recorder = Recorder::default();
+
+    let error = parse_with_visitor(input, &mut recorder).unwrap_err();
+
+    assert_eq!(recorder.calls, ["{", "key a", "[", "number 1", "number 2"]);
+    assert_eq!(error_position(input, &error).unwrap().offset, 11);
+}
+
+#[test]
+fn error_before_any_value() {
+    for input in ["", "  ", "x", "]"] {
+        let mut recorder = Recorder::default();
+
+        assert!(
+            parse_with_visitor(input, &mut recorder).is_err(),
+            "{input:?}"
+        );
+        assert!(recorder.calls.is_empty(), "{input:?}");
+    }
+}
+
+/// Only the callbacks of interest have to be implemented
+#[test]
+fn default_methods() {
+    #[derive(Default)]
+    struct Keys(Vec<String>);
+
+    impl<'a> JsonVisitor<'a> for Keys {
+        fn visit_key(&mut self, key: Cow<'a, str>) {
+            self.0.push(key.into_owned());
+        }
+    }
+
+    let mut keys = Keys::default();
+    parse_with_visitor(r#"{"a": {"b": [1, {"c": null}]}, "d": "e"}"#, &mut keys).unwrap();
+
+    assert_eq!(keys.0, ["a", "b", "c", "d"]);
+}
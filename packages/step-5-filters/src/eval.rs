@@ -0,0 +1,228 @@
+use std::cmp::Ordering;
+
+use step_4::JsonValue;
+
+use crate::{Comparison, Filter, FilterError};
+
+fn type_name(value: &JsonValue) -> &'static str {
+    match value {
+        JsonValue::Null => "null",
+        JsonValue::Str(_) => "a string",
+        JsonValue::Boolean(_) => "a boolean",
+        JsonValue::Array(_) => "an array",
+        JsonValue::Object(_) => "an object",
+        _ => "a number",
+    }
+}
+
+fn number(value: &JsonValue) -> Option<f64> {
+    match value {
+        JsonValue::Null
+        | JsonValue::Str(_)
+        | JsonValue::Boolean(_)
+        | JsonValue::Array(_)
+        | JsonValue::Object(_) => None,
+        value => f64::try_from(value.clone()).ok(),
+    }
+}
+
+/// `false` and `null` are false, everything else is true
+fn truthy(value: &JsonValue) -> bool {
+    !matches!(value, JsonValue::Null | JsonValue::Boolean(false))
+}
+
+/// Order of jq: `null`, `false`, `true`, numbers, strings, arrays, objects
+fn rank(value: &JsonValue) -> u8 {
+    match value {
+        JsonValue::Null => 0,
+        JsonValue::Boolean(false) => 1,
+        JsonValue::Boolean(true) => 2,
+        JsonValue::Str(_) => 4,
+        JsonValue::Array(_) => 5,
+        JsonValue::Object(_) => 6,
+        _ => 3,
+    }
+}
+
+/// Numbers by value, whatever their representation, unlike the `Ord` of
+/// `JsonValue`
+fn compare(a: &JsonValue, b: &JsonValue) -> Ordering {
+    match (a, b) {
+        (JsonValue::Int(a), JsonValue::Int(b)) => a.cmp(b),
+        (JsonValue::Str(a), JsonValue::Str(b)) => a.cmp(b),
+        (JsonValue::Array(a), JsonValue::Array(b)) => a
+            .iter()
+            .zip(b)
+            .map(|(a, b)| compare(a, b))
+            .find(|ordering| ordering.is_ne())
+            .unwrap_or(a.len().cmp(&b.len())),
+        (JsonValue::Object(a), JsonValue::Object(b)) => {
+            let mut a: Vec<_> = a.iter().collect();
+            let mut b: Vec<_> = b.iter().collect();
+            a.sort_unstable_by_key(|(key, _)| *key);
+            b.sort_unstable_by_key(|(key, _)| *key);
+
+            // Keys first, then values
+            a.iter()
+                .map(|(key, _)| key)
+                .cmp(b.iter().map(|(key, _)| key))
+                .then_with(|| {
+                    a.iter()
+                        .zip(&b)
+                        .map(|((_, a), (_, b))| compare(a, b))
+                        .find(|ordering| ordering.is_ne())
+                        .unwrap_or(Ordering::Equal)
+                })
+        }
+        (a, b) => match (number(a), number(b)) {
+            (Some(a), Some(b)) => a.total_cmp(&b),
+            _ => rank(a).cmp(&rank(b)),
+        },
+    }
+}
+
+fn recurse(value: &JsonValue, outputs: &mut Vec<JsonValue>) {
+    outputs.push(value.clone());
+
+    match value {
+        JsonValue::Array(values) => values.iter().for_each(|value| recurse(value, outputs)),
+        JsonValue::Object(map) => map.values().for_each(|value| recurse(value, outputs)),
+        _ => {}
+    }
+}
+
+/// Pushes the outputs of `filter` for `input` to `outputs`
+pub(crate) fn apply(
+    filter: &Filter,
+    input: &JsonValue,
+    outputs: &mut Vec<JsonValue>,
+) -> Result<(), FilterError> {
+    let error = |message: String| Err(FilterError::Apply(message));
+
+    match (filter, input) {
+        (Filter::Identity, input) => outputs.push(input.clone()),
+        (Filter::Recurse, input) => recurse(input, outputs),
+        (Filter::Key(key), JsonValue::Object(map)) => {
+            outputs.push(map.get(key).cloned().unwrap_or(JsonValue::Null))
+        }
+        (Filter::Index(index), JsonValue::Array(items)) => {
+            let index = match usize::try_from(*index) {
+                Ok(index) => Some(index),
+                Err(_) => items.len().checked_sub(index.unsigned_abs() as usize),
+            };
+            outputs.push(
+                index
+                    .and_then(|index| items.get(index))
+                    .cloned()
+                    .unwrap_or(JsonValue::Null),
+            );
+        }
+        (Filter::Key(_) | Filter::Index(_), JsonValue::Null) => outputs.push(JsonValue::Null),
+        (Filter::Key(key), input) => {
+            return error(format!("cannot get key \"{key}\" of {}", type_name(input)))
+        }
+        (Filter::Index(index), input) => {
+            return error(format!("cannot get index {index} of {}", type_name(input)))
+        }
+        (Filter::Each, JsonValue::Array(items)) => outputs.extend(items.iter().cloned()),
+        (Filter::Each, JsonValue::Object(map)) => outputs.extend(map.values().cloned()),
+        (Filter::Each, input) => return error(format!("cannot iterate over {}", type_name(input))),
+        (Filter::Literal(value), _) => outputs.push(value.clone()),
+        (Filter::Collect(None), _) => outputs.push(JsonValue::Array(Vec::new())),
+        (Filter::Collect(Some(f)), input) => {
+            let mut items = Vec::new();
+            apply(f, input, &mut items)?;
+            outputs.push(JsonValue::Array(items));
+        }
+        (Filter::Pipe(f, g), input) => {
+            let mut values = Vec::new();
+            apply(f, input, &mut values)?;
+            for value in &values {
+                apply(g, value, outputs)?;
+            }
+        }
+        (Filter::Comma(f, g), input) => {
+            apply(f, input, outputs)?;
+            apply(g, input, outputs)?;
+        }
+        (Filter::Compare(f, comparison, g), input) => {
+            let (mut left, mut right) = (Vec::new(), Vec::new());
+            apply(f, input, &mut left)?;
+            apply(g, input, &mut right)?;
+
+            // Like jq, the right side varies the slowest
+            for b in &right {
+                for a in &left {
+                    let ordering = compare(a, b);
+                    let result = match comparison {
+                        Comparison::Eq => ordering.is_eq(),
+                        Comparison::Ne => ordering.is_ne(),
+                        Comparison::Lt => ordering.is_lt(),
+                        Comparison::Le => ordering.is_le(),
+                        Comparison::Gt => ordering.is_gt(),
+                        Comparison::Ge => ordering.is_ge(),
+                    };
+                    outputs.push(JsonValue::Boolean(result));
+                }
+            }
+        }
+        (Filter::And(f, g) | Filter::Or(f, g), input) => {
+            let is_and = matches!(filter, Filter::And(..));
+            let mut left = Vec::new();
+            apply(f, input, &mut left)?;
+
+            for a in &left {
+                // Short-circuits: `false and g` is false without `g`
+                if truthy(a) != is_and {
+                    outputs.push(JsonValue::Boolean(!is_and));
+                    continue;
+                }
+
+                let mut right = Vec::new();
+                apply(g, input, &mut right)?;
+                outputs.extend(right.iter().map(|b| JsonValue::Boolean(truthy(b))));
+            }
+        }
+        (Filter::Select(f), input) => {
+            let mut conditions = Vec::new();
+            apply(f, input, &mut conditions)?;
+            for condition in &conditions {
+                if truthy(condition) {
+                    outputs.push(input.clone());
+                }
+            }
+        }
+        (Filter::Map(f), input) => {
+            let each = Filter::Pipe(Box::new(Filter::Each), f.clone());
+            apply(&Filter::Collect(Some(Box::new(each))), input, outputs)?;
+        }
+        (Filter::Not, input) => outputs.push(JsonValue::Boolean(!truthy(input))),
+        (Filter::Length, input) => {
+            let length = match input {
+                JsonValue::Null => JsonValue::Int(0),
+                JsonValue::Str(s) => JsonValue::Int(s.chars().count() as i64),
+                JsonValue::Array(items) => JsonValue::Int(items.len() as i64),
+                JsonValue::Object(map) => JsonValue::Int(map.len() as i64),
+                JsonValue::Int(n) => JsonValue::Int(n.saturating_abs()),
+                input => match number(input) {
+                    Some(n) => JsonValue::Num(n.abs()),
+                    None => return error(format!("{} has no length", type_name(input))),
+                },
+            };
+            outputs.push(length);
+        }
+        (Filter::Keys, JsonValue::Object(map)) => {
+            let mut keys: Vec<_> = map.keys().cloned().collect();
+            keys.sort_unstable();
+            outputs.push(JsonValue::Array(
+                keys.into_iter().map(JsonValue::Str).collect(),
+            ));
+        }
+        (Filter::Keys, JsonValue::Array(items)) => outputs.push(JsonValue::Array(
+            (0..items.len() as i64).map(JsonValue::Int).collect(),
+        )),
+        (Filter::Keys, input) => return error(format!("{} has no keys", type_name(input))),
+    }
+
+    Ok(())
+}
@@ -0,0 +1,119 @@
+//! Step 5: a small [jq](https://jqlang.github.io/jq/manual/) over the values
+//! of step 4, with a parser written with nom, like the JSON one.
+//!
+//! ```text
+//! .                 the input itself
+//! ..                the input and every value nested in it
+//! .key, ."a key"    value of a key, `null` when it's missing
+//! .[0], .[-1]       item of an array, from the end when negative
+//! .[]               every item of an array, or value of an object
+//! f | g             outputs of `g` for every output of `f`
+//! f, g              outputs of `f`, then those of `g`
+//! [f]               array of the outputs of `f`
+//! f == g            also !=, <, <=, > and >=
+//! f and g, f or g   `false` and `null` are false, everything else is true
+//! select(f)         the input, when `f` is true
+//! map(f)            [.[] | f]
+//! not, length, keys
+//! "text", 1, true   literals, `null` too
+//! ```
+//!
+//! Indexes and keys are chained like in jq: `.statuses[0].user.name` is
+//! `.statuses | .[0] | .user | .name`.
+//!
+//! ```
+//! use step_5_filters::Filter;
+//!
+//! let tweets: step_4::JsonValue = r#"{"statuses": [
+//!     {"user": {"name": "Jules"}, "retweet_count": 12},
+//!     {"user": {"name": "Ferris"}, "retweet_count": 3}
+//! ]}"#.parse().unwrap();
+//!
+//! let filter = Filter::parse(".statuses[] | select(.retweet_count > 10) | .user.name").unwrap();
+//! assert_eq!(filter.apply(&tweets).unwrap(), vec!["Jules".into()]);
+//! ```
+
+use std::fmt::Display;
+
+use step_4::JsonValue;
+
+mod eval;
+mod parse;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Comparison {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Filter {
+    /// `.`
+    Identity,
+    /// `..`
+    Recurse,
+    /// `.key`
+    Key(String),
+    /// `.[0]`
+    Index(i64),
+    /// `.[]`
+    Each,
+    Literal(JsonValue),
+    /// `[f]`, `[]` being `None`
+    Collect(Option<Box<Filter>>),
+    Pipe(Box<Filter>, Box<Filter>),
+    Comma(Box<Filter>, Box<Filter>),
+    Compare(Box<Filter>, Comparison, Box<Filter>),
+    And(Box<Filter>, Box<Filter>),
+    Or(Box<Filter>, Box<Filter>),
+    Select(Box<Filter>),
+    Map(Box<Filter>),
+    Not,
+    Length,
+    Keys,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum FilterError {
+    /// The filter doesn't parse past this column (1-based)
+    Syntax { column: usize },
+    /// A filter doesn't apply to a value, like `.key` to a number
+    Apply(String),
+}
+
+impl Display for FilterError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FilterError::Syntax { column } => write!(f, "unexpected input at column {column}"),
+            FilterError::Apply(message) => write!(f, "{message}"),
+        }
+    }
+}
+
+impl std::error::Error for FilterError {}
+
+impl Filter {
+    pub fn parse(filter: &str) -> Result<Filter, FilterError> {
+        parse::filter(filter)
+    }
+
+    /// Outputs of the filter for `input`, in order
+    pub fn apply(&self, input: &JsonValue) -> Result<Vec<JsonValue>, FilterError> {
+        let mut outputs = Vec::new();
+        eval::apply(self, input, &mut outputs)?;
+
+        Ok(outputs)
+    }
+}
+
+impl std::str::FromStr for Filter {
+    type Err = FilterError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Filter::parse(s)
+    }
+}
@@ -0,0 +1,199 @@
+use nom::{
+    branch::alt,
+    bytes::complete::{tag, take_while1},
+    character::complete::{char, i64, multispace0, one_of, satisfy},
+    combinator::{all_consuming, map, not, opt, peek, value},
+    error::{Error, ErrorKind},
+    multi::many0,
+    sequence::{delimited, pair, preceded, terminated},
+    Finish, IResult,
+};
+
+use step_4::JsonValue;
+
+use crate::{Comparison, Filter, FilterError};
+
+type Result<'a, O> = IResult<&'a str, O>;
+
+fn ws<'a, O>(inner: impl FnMut(&'a str) -> Result<'a, O>) -> impl FnMut(&'a str) -> Result<'a, O> {
+    delimited(multispace0, inner, multispace0)
+}
+
+fn is_identifier(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+/// `word`, but not the start of a longer identifier
+fn keyword<'a>(word: &'static str) -> impl FnMut(&'a str) -> Result<'a, &'a str> {
+    terminated(tag(word), not(satisfy(is_identifier)))
+}
+
+/// Value parsed by step 4
+fn json(i: &str) -> Result<'_, JsonValue> {
+    step_4::parse(i).map_err(|_| nom::Err::Error(Error::new(i, ErrorKind::Verify)))
+}
+
+fn literal(i: &str) -> Result<'_, JsonValue> {
+    alt((
+        value(JsonValue::Null, keyword("null")),
+        value(JsonValue::Boolean(true), keyword("true")),
+        value(JsonValue::Boolean(false), keyword("false")),
+        // Only strings and numbers: brackets start a `[f]`
+        preceded(peek(one_of("\"-0123456789")), json),
+    ))(i)
+}
+
+/// Identifier, or string with the escapes of JSON
+fn key(i: &str) -> Result<'_, String> {
+    let (rest, key) = alt((
+        map(take_while1(is_identifier), |key: &str| {
+            JsonValue::Str(key.to_owned())
+        }),
+        preceded(peek(char('"')), json),
+    ))(i)?;
+
+    match key {
+        JsonValue::Str(key) => Ok((rest, key)),
+        _ => Err(nom::Err::Error(Error::new(i, ErrorKind::Verify))),
+    }
+}
+
+/// `[0]` or `[]`
+fn brackets(i: &str) -> Result<'_, Filter> {
+    map(delimited(char('['), ws(opt(i64)), char(']')), |index| {
+        index.map_or(Filter::Each, Filter::Index)
+    })(i)
+}
+
+/// `.key`, `[0]` or `[]` after a term
+fn suffix(i: &str) -> Result<'_, Filter> {
+    alt((
+        preceded(char('.'), alt((map(key, Filter::Key), brackets))),
+        brackets,
+    ))(i)
+}
+
+fn parenthesized(i: &str) -> Result<'_, Filter> {
+    delimited(char('('), ws(pipe), char(')'))(i)
+}
+
+fn builtin(i: &str) -> Result<'_, Filter> {
+    alt((
+        map(
+            preceded(pair(keyword("select"), multispace0), parenthesized),
+            |f| Filter::Select(Box::new(f)),
+        ),
+        map(
+            preceded(pair(keyword("map"), multispace0), parenthesized),
+            |f| Filter::Map(Box::new(f)),
+        ),
+        value(Filter::Not, keyword("not")),
+        value(Filter::Length, keyword("length")),
+        value(Filter::Keys, keyword("keys")),
+    ))(i)
+}
+
+fn term(i: &str) -> Result<'_, Filter> {
+    alt((
+        value(Filter::Recurse, tag("..")),
+        preceded(
+            char('.'),
+            map(opt(alt((map(key, Filter::Key), brackets))), |f| {
+                f.unwrap_or(Filter::Identity)
+            }),
+        ),
+        map(literal, Filter::Literal),
+        parenthesized,
+        map(delimited(char('['), ws(opt(pipe)), char(']')), |f| {
+            Filter::Collect(f.map(Box::new))
+        }),
+        builtin,
+    ))(i)
+}
+
+/// Term and its suffixes: `.a[0].b` is `.a | .[0] | .b`
+fn postfix(i: &str) -> Result<'_, Filter> {
+    let (i, first) = ws(term)(i)?;
+    let (i, suffixes) = terminated(many0(suffix), multispace0)(i)?;
+
+    let filter = suffixes.into_iter().fold(first, |f, suffix| {
+        Filter::Pipe(Box::new(f), Box::new(suffix))
+    });
+
+    Ok((i, filter))
+}
+
+fn comparison(i: &str) -> Result<'_, Filter> {
+    let operator = alt((
+        value(Comparison::Eq, tag("==")),
+        value(Comparison::Ne, tag("!=")),
+        value(Comparison::Le, tag("<=")),
+        value(Comparison::Ge, tag(">=")),
+        value(Comparison::Lt, tag("<")),
+        value(Comparison::Gt, tag(">")),
+    ));
+
+    let (i, left) = postfix(i)?;
+    let (i, right) = opt(pair(operator, postfix))(i)?;
+
+    let filter = match right {
+        Some((comparison, right)) => Filter::Compare(Box::new(left), comparison, Box::new(right)),
+        None => left,
+    };
+
+    Ok((i, filter))
+}
+
+/// `f op g op h`, associating to the left
+fn left_associative<'a>(
+    i: &'a str,
+    operand: fn(&'a str) -> Result<'a, Filter>,
+    operator: impl FnMut(&'a str) -> Result<'a, &'a str>,
+    node: fn(Box<Filter>, Box<Filter>) -> Filter,
+) -> Result<'a, Filter> {
+    let (i, first) = operand(i)?;
+    let (i, rest) = many0(preceded(operator, operand))(i)?;
+
+    let filter = rest
+        .into_iter()
+        .fold(first, |f, g| node(Box::new(f), Box::new(g)));
+
+    Ok((i, filter))
+}
+
+fn and(i: &str) -> Result<'_, Filter> {
+    left_associative(i, comparison, keyword("and"), Filter::And)
+}
+
+fn or(i: &str) -> Result<'_, Filter> {
+    left_associative(i, and, keyword("or"), Filter::Or)
+}
+
+fn comma(i: &str) -> Result<'_, Filter> {
+    left_associative(i, or, tag(","), Filter::Comma)
+}
+
+/// Lowest precedence: `f | g, h` is `f | (g, h)`
+fn pipe(i: &str) -> Result<'_, Filter> {
+    let (i, first) = comma(i)?;
+    let (i, rest) = opt(preceded(char('|'), pipe))(i)?;
+
+    let filter = match rest {
+        Some(rest) => Filter::Pipe(Box::new(first), Box::new(rest)),
+        None => first,
+    };
+
+    Ok((i, filter))
+}
+
+pub(crate) fn filter(filter: &str) -> std::result::Result<Filter, FilterError> {
+    all_consuming(ws(pipe))(filter)
+        .finish()
+        .map(|(_, filter)| filter)
+        .map_err(|e| {
+            let parsed = &filter[..filter.len() - e.input.len()];
+            FilterError::Syntax {
+                column: parsed.chars().count() + 1,
+            }
+        })
+}
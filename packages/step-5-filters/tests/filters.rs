@@ -0,0 +1,81 @@
+use step_4::JsonValue;
+use step_5_filters::{Filter, FilterError};
+
+fn json(text: &str) -> JsonValue {
+    text.parse().unwrap()
+}
+
+/// Outputs of `filter` for `input`, as a JSON array
+fn run(filter: &str, input: &str) -> JsonValue {
+    let filter = Filter::parse(filter).unwrap();
+    JsonValue::Array(filter.apply(&json(input)).unwrap())
+}
+
+#[test]
+fn paths() {
+    let input = r#"{"a": {"b": [1, 2, {"c": "d"}]}, "key with spaces": true}"#;
+
+    assert_eq!(run(".", "3"), json("[3]"));
+    assert_eq!(run(".a.b[2].c", input), json(r#"["d"]"#));
+    assert_eq!(run(".a.b[-1].c", input), json(r#"["d"]"#));
+    assert_eq!(run(r#"."key with spaces""#, input), json("[true]"));
+    assert_eq!(run(".a.b[]", input), json(r#"[1, 2, {"c": "d"}]"#));
+    assert_eq!(run(".[0]", "[5]"), json("[5]"));
+    assert_eq!(run(".missing.deeper[3]", input), json("[null]"));
+    assert_eq!(run("..", "[[1]]"), json("[[[1]], [1], 1]"));
+}
+
+#[test]
+fn pipes_and_constructions() {
+    let input = r#"[{"n": 1, "tags": ["a"]}, {"n": 5, "tags": []}, {"n": 3}]"#;
+
+    assert_eq!(run(".[] | .n", input), json("[1, 5, 3]"));
+    assert_eq!(run("[.[].n]", input), json("[[1, 5, 3]]"));
+    assert_eq!(run(".[0] | .n, .tags", input), json(r#"[1, ["a"]]"#));
+    assert_eq!(run("map(.n)", input), json("[[1, 5, 3]]"));
+    assert_eq!(run("[.[] | select(.n >= 3) | .n]", input), json("[[5, 3]]"));
+    assert_eq!(
+        run(".[] | select(.tags and (.tags | length) == 0) | .n", input),
+        json("[5]")
+    );
+    assert_eq!(run("[]", "null"), json("[[]]"));
+}
+
+#[test]
+fn comparisons_and_builtins() {
+    assert_eq!(run(".a == 1.0", r#"{"a": 1}"#), json("[true]"));
+    assert_eq!(run(r#". < "a""#, "99"), json("[true]"));
+    assert_eq!(run("null < false, [1] > [0, 5]", "0"), json("[true, true]"));
+    assert_eq!(
+        run("(1, 2) == (1, 2)", "0"),
+        json("[true, false, false, true]")
+    );
+    assert_eq!(run("false or . | not", "null"), json("[true]"));
+    assert_eq!(run("keys", r#"{"b": 1, "a": 2}"#), json(r#"[["a", "b"]]"#));
+    assert_eq!(run("length", r#""héllo""#), json("[5]"));
+    assert_eq!(run("length", "-3"), json("[3]"));
+}
+
+#[test]
+fn errors() {
+    assert_eq!(
+        Filter::parse(".a |").unwrap_err(),
+        FilterError::Syntax { column: 4 }
+    );
+    assert_eq!(
+        Filter::parse("select(.a").unwrap_err(),
+        FilterError::Syntax { column: 1 }
+    );
+
+    let error = |filter: &str, input: &str| {
+        Filter::parse(filter)
+            .unwrap()
+            .apply(&json(input))
+            .unwrap_err()
+            .to_string()
+    };
+    assert_eq!(error(".a", "1"), r#"cannot get key "a" of a number"#);
+    assert_eq!(error(".[0]", "{}"), "cannot get index 0 of an object");
+    assert_eq!(error(".[]", r#""s""#), "cannot iterate over a string");
+    assert_eq!(error("keys", "true"), "a boolean has no keys");
+}
@@ -0,0 +1,255 @@
+//! Step 5: the parser of step 4 over `&[u8]` rather than `&str`.
+//!
+//! Over `&str`, nom hands out chars: every byte of the input goes through
+//! UTF-8 decoding, even though JSON is ASCII outside of strings. Here the
+//! structure is matched byte by byte, and strings are scanned by hand up to
+//! their next `"` or `\`, each run being checked as UTF-8 at once. `main`
+//! times both parsers on the same files.
+//!
+//! ```
+//! use step_4::JsonValue;
+//!
+//! let (rest, value) = step_5::parse(br#"{"hello": ["world", 42]}"#).unwrap();
+//!
+//! assert!(rest.is_empty());
+//! assert_eq!(value, step_4::parse_with_path(r#"{"hello": ["world", 42]}"#).unwrap());
+//! ```
+
+use nom::{
+    bytes::complete::tag,
+    combinator::{cut, map, value},
+    error::{Error, ErrorKind, ParseError},
+    multi::separated_list0,
+    sequence::{delimited, preceded, separated_pair},
+    IResult,
+};
+
+use step_4::{JsonValue, Map};
+
+type Result<'a, O, E> = IResult<&'a [u8], O, E>;
+
+fn fail<'a, O, E: ParseError<&'a [u8]>>(i: &'a [u8], kind: ErrorKind) -> Result<'a, O, E> {
+    Err(nom::Err::Failure(E::from_error_kind(i, kind)))
+}
+
+/// Whitespace of JSON, without going through chars
+fn ws<'a, E: ParseError<&'a [u8]>>(i: &'a [u8]) -> Result<'a, (), E> {
+    let len = i
+        .iter()
+        .take_while(|b| matches!(b, b' ' | b'\t' | b'\n' | b'\r'))
+        .count();
+
+    Ok((&i[len..], ()))
+}
+
+fn byte<'a, E: ParseError<&'a [u8]>>(expected: u8) -> impl Fn(&'a [u8]) -> Result<'a, u8, E> {
+    move |i| match i.first() {
+        Some(&b) if b == expected => Ok((&i[1..], b)),
+        _ => Err(nom::Err::Error(E::from_error_kind(i, ErrorKind::Char))),
+    }
+}
+
+fn hex4<'a, E: ParseError<&'a [u8]>>(i: &'a [u8]) -> Result<'a, u16, E> {
+    let Some(digits) = i.get(..4) else {
+        return fail(i, ErrorKind::Eof);
+    };
+
+    let mut n = 0;
+    for &digit in digits {
+        let Some(digit) = (digit as char).to_digit(16) else {
+            return fail(i, ErrorKind::HexDigit);
+        };
+        n = n * 16 + digit as u16;
+    }
+
+    Ok((&i[4..], n))
+}
+
+/// What follows `\u`, a surrogate pair being two escapes
+fn unicode_escape<'a, E: ParseError<&'a [u8]>>(i: &'a [u8]) -> Result<'a, char, E> {
+    let (rest, high) = hex4(i)?;
+
+    let (rest, code_point) = match high {
+        0xD800..=0xDBFF => {
+            let (rest, low) = preceded(tag("\\u"), hex4)(rest)?;
+            if !(0xDC00..=0xDFFF).contains(&low) {
+                return fail(i, ErrorKind::Verify);
+            }
+            let code_point = ((high as u32 - 0xD800) << 10) + (low as u32 - 0xDC00) + 0x10000;
+            (rest, code_point)
+        }
+        0xDC00..=0xDFFF => return fail(i, ErrorKind::Verify),
+        high => (rest, high as u32),
+    };
+
+    match char::from_u32(code_point) {
+        Some(c) => Ok((rest, c)),
+        None => fail(i, ErrorKind::Verify),
+    }
+}
+
+/// What follows a `\` inside a string
+fn escape<'a, E: ParseError<&'a [u8]>>(i: &'a [u8]) -> Result<'a, char, E> {
+    let c = match i.first() {
+        Some(b'"') => '"',
+        Some(b'\\') => '\\',
+        Some(b'/') => '/',
+        Some(b'b') => '\x08',
+        Some(b'f') => '\x0C',
+        Some(b'n') => '\n',
+        Some(b'r') => '\r',
+        Some(b't') => '\t',
+        Some(b'u') => return unicode_escape(&i[1..]),
+        _ => return fail(i, ErrorKind::Escaped),
+    };
+
+    Ok((&i[1..], c))
+}
+
+fn string<'a, E: ParseError<&'a [u8]>>(i: &'a [u8]) -> Result<'a, String, E> {
+    let (mut i, _) = byte(b'"')(i)?;
+    let mut string = String::new();
+
+    loop {
+        // Run of bytes that need no unescaping: the whole string, usually
+        let end = i
+            .iter()
+            .position(|&b| b == b'"' || b == b'\\' || b < 0x20)
+            .unwrap_or(i.len());
+
+        match std::str::from_utf8(&i[..end]) {
+            Ok(run) => string.push_str(run),
+            Err(e) => return fail(&i[e.valid_up_to()..], ErrorKind::Char),
+        }
+
+        match i.get(end) {
+            Some(b'"') => return Ok((&i[end + 1..], string)),
+            Some(b'\\') => {
+                let (rest, c) = escape(&i[end + 1..])?;
+                string.push(c);
+                i = rest;
+            }
+            // Unescaped control char
+            Some(_) => return fail(&i[end..], ErrorKind::Char),
+            None => return fail(&i[end..], ErrorKind::Eof),
+        }
+    }
+}
+
+/// `-?(0|[1-9][0-9]*)(\.[0-9]+)?([eE][+-]?[0-9]+)?`, as an `Int` when it's an
+/// integer that fits
+fn number<'a, E: ParseError<&'a [u8]>>(i: &'a [u8]) -> Result<'a, JsonValue, E> {
+    let digits = |from: usize| i[from..].iter().take_while(|b| b.is_ascii_digit()).count();
+
+    let mut len = (i.first() == Some(&b'-')) as usize;
+    let integer = digits(len);
+    if integer == 0 || (integer > 1 && i[len] == b'0') {
+        return fail(i, ErrorKind::Digit);
+    }
+    len += integer;
+
+    let mut is_integer = true;
+    if i.get(len) == Some(&b'.') {
+        let fraction = digits(len + 1);
+        if fraction == 0 {
+            return fail(&i[len + 1..], ErrorKind::Digit);
+        }
+        len += 1 + fraction;
+        is_integer = false;
+    }
+    if matches!(i.get(len), Some(b'e' | b'E')) {
+        len += 1;
+        if matches!(i.get(len), Some(b'+' | b'-')) {
+            len += 1;
+        }
+        let exponent = digits(len);
+        if exponent == 0 {
+            return fail(&i[len..], ErrorKind::Digit);
+        }
+        len += exponent;
+        is_integer = false;
+    }
+
+    // Only ASCII digits and signs
+    let text = std::str::from_utf8(&i[..len]).unwrap();
+    let rest = &i[len..];
+
+    // `-0` stays a float, which keeps its sign
+    if is_integer && text != "-0" {
+        if let Ok(n) = text.parse() {
+            return Ok((rest, JsonValue::Int(n)));
+        }
+        if let Ok(n) = text.parse() {
+            return Ok((rest, JsonValue::UInt(n)));
+        }
+    }
+
+    match text.parse() {
+        Ok(n) => Ok((rest, JsonValue::Num(n))),
+        Err(_) => fail(i, ErrorKind::Float),
+    }
+}
+
+fn array<'a, E: ParseError<&'a [u8]>>(i: &'a [u8]) -> Result<'a, Vec<JsonValue>, E> {
+    let (i, _) = byte(b'[')(i)?;
+    let (i, _) = ws(i)?;
+
+    if let Ok((i, _)) = byte::<E>(b']')(i) {
+        return Ok((i, Vec::new()));
+    }
+
+    cut(terminated_by(
+        separated_list0(delimited(ws, byte(b','), ws), json_value),
+        b']',
+    ))(i)
+}
+
+fn member<'a, E: ParseError<&'a [u8]>>(i: &'a [u8]) -> Result<'a, (String, JsonValue), E> {
+    separated_pair(string, delimited(ws, byte(b':'), ws), json_value)(i)
+}
+
+fn object<'a, E: ParseError<&'a [u8]>>(i: &'a [u8]) -> Result<'a, Map<String, JsonValue>, E> {
+    let (i, _) = byte(b'{')(i)?;
+    let (i, _) = ws(i)?;
+
+    if let Ok((i, _)) = byte::<E>(b'}')(i) {
+        return Ok((i, Map::default()));
+    }
+
+    cut(map(
+        terminated_by(separated_list0(delimited(ws, byte(b','), ws), member), b'}'),
+        |members| members.into_iter().collect(),
+    ))(i)
+}
+
+/// `inner`, then whitespace and `close`
+fn terminated_by<'a, O, E: ParseError<&'a [u8]>>(
+    mut inner: impl FnMut(&'a [u8]) -> Result<'a, O, E>,
+    close: u8,
+) -> impl FnMut(&'a [u8]) -> Result<'a, O, E> {
+    move |i| {
+        let (i, output) = inner(i)?;
+        let (i, _) = ws(i)?;
+        let (i, _) = byte(close)(i)?;
+
+        Ok((i, output))
+    }
+}
+
+fn json_value<'a, E: ParseError<&'a [u8]>>(i: &'a [u8]) -> Result<'a, JsonValue, E> {
+    match i.first() {
+        Some(b'{') => map(object, JsonValue::Object)(i),
+        Some(b'[') => map(array, JsonValue::Array)(i),
+        Some(b'"') => map(string, JsonValue::Str)(i),
+        Some(b'-' | b'0'..=b'9') => number(i),
+        Some(b't') => value(JsonValue::Boolean(true), tag("true"))(i),
+        Some(b'f') => value(JsonValue::Boolean(false), tag("false"))(i),
+        Some(b'n') => value(JsonValue::Null, tag("null"))(i),
+        _ => Err(nom::Err::Error(E::from_error_kind(i, ErrorKind::Char))),
+    }
+}
+
+/// Value surrounded by whitespace, the input left after it being returned
+pub fn parse(i: &[u8]) -> Result<'_, JsonValue, Error<&[u8]>> {
+    delimited(ws, json_value, ws)(i)
+}
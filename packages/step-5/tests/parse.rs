@@ -0,0 +1,75 @@
+//! Step 5 parses like step 4: same documents, same failures.
+
+use step_4::{
+    testing::{Alphabet, RandomOptions, RandomValues},
+    to_string,
+};
+
+#[test]
+fn random_values() {
+    let opts = RandomOptions {
+        alphabet: Alphabet::Ascii,
+        ..Default::default()
+    };
+
+    for value in RandomValues::new(6, opts).take(2_000) {
+        let json = to_string(&value);
+        let (rest, parsed) = step_5::parse(json.as_bytes()).unwrap();
+
+        assert!(rest.is_empty());
+        assert_eq!(parsed, value, "{json}");
+    }
+}
+
+#[test]
+fn escapes() {
+    let json = r#""tab\there é 🦀 \"q\" \/ \ud83e\udd80""#;
+    let (_, parsed) = step_5::parse(json.as_bytes()).unwrap();
+
+    assert_eq!(parsed, "tab\there é 🦀 \"q\" / 🦀".into());
+}
+
+#[test]
+fn numbers() {
+    for json in [
+        "0",
+        "-0",
+        "12",
+        "-7",
+        "1.5",
+        "2e10",
+        "-3.25E-2",
+        "18446744073709551615",
+    ] {
+        let (_, step_4) = step_4::parse(json).unwrap();
+        let (_, step_5) = step_5::parse(json.as_bytes()).unwrap();
+
+        assert_eq!(step_4, step_5, "{json}");
+    }
+}
+
+#[test]
+fn invalid() {
+    let invalid: [&[u8]; 10] = [
+        b"[1, 2",
+        b"[1,]",
+        br#"{"a" 1}"#,
+        br#"{"a": 1,}"#,
+        b"01",
+        b"1.",
+        b"-",
+        br#""\x""#,
+        br#""\ud800""#,
+        b"\"\xff\"",
+    ];
+
+    for json in invalid {
+        let result = step_5::parse(json);
+
+        assert!(
+            !matches!(result, Ok((rest, _)) if rest.is_empty()),
+            "{}",
+            String::from_utf8_lossy(json)
+        );
+    }
+}
@@ -0,0 +1,464 @@
+//! Step 6: nom's streaming combinators, over an input read in fixed-size
+//! chunks.
+//!
+//! The `complete` combinators of the previous steps take the end of the
+//! input for the end of the document. Their `streaming` counterparts, used
+//! here, return `Err::Incomplete` when the input could go on instead: `tru`
+//! may be the start of `true`, `12` that of `123`, and a string isn't over
+//! before its closing quote.
+//!
+//! [`Entries`] then reads more and parses again, yielding the entries of the
+//! top-level array or object one by one. Only the entry being parsed is
+//! parsed again, not the whole document, and the input is read until it has
+//! doubled before trying again: a large entry isn't parsed once per chunk.
+//!
+//! ```
+//! use step_6::{Entries, Entry};
+//!
+//! let input = r#"{"name": "meetup", "talks": [1, 2]}"#;
+//! // 4 bytes at a time
+//! let mut entries = Entries::new(input.as_bytes(), 4);
+//!
+//! assert_eq!(entries.next().unwrap().unwrap(), Entry::Member("name".into(), "meetup".into()));
+//! assert!(matches!(entries.next(), Some(Ok(Entry::Member(key, _))) if key == "talks"));
+//! assert!(entries.next().is_none());
+//! assert!(entries.stats.incomplete > 0);
+//! ```
+
+use std::{fmt::Display, io::Read};
+
+use nom::{
+    branch::alt,
+    bytes::streaming::{is_not, tag, take},
+    character::streaming::{anychar, char, multispace0},
+    combinator::{cut, map, map_opt, peek, value, verify},
+    error::{Error, ErrorKind, ParseError},
+    multi::{fold_many0, separated_list0},
+    number::streaming::double,
+    sequence::{delimited, preceded, separated_pair, terminated},
+    IResult,
+};
+
+use step_4::{JsonValue, Map};
+
+type Result<'a, O, E> = IResult<&'a str, O, E>;
+
+fn u16_hex<'a, E: ParseError<&'a str>>(i: &'a str) -> Result<'a, u16, E> {
+    map_opt(take(4usize), |s: &'a str| u16::from_str_radix(s, 16).ok())(i)
+}
+
+fn unicode_escape<'a, E: ParseError<&'a str>>(i: &'a str) -> Result<'a, char, E> {
+    map_opt(
+        alt((
+            // Not a surrogate
+            map(verify(u16_hex, |cp| !(0xD800..0xE000).contains(cp)), |cp| {
+                cp as u32
+            }),
+            map(
+                verify(
+                    separated_pair(u16_hex, tag("\\u"), u16_hex),
+                    |(high, low)| (0xD800..0xDC00).contains(high) && (0xDC00..0xE000).contains(low),
+                ),
+                |(high, low)| {
+                    let high_ten = (high as u32) - 0xD800;
+                    let low_ten = (low as u32) - 0xDC00;
+                    (high_ten << 10) + low_ten + 0x10000
+                },
+            ),
+        )),
+        std::char::from_u32,
+    )(i)
+}
+
+/// What follows a `\` inside a string
+fn escape<'a, E: ParseError<&'a str>>(input: &'a str) -> Result<'a, char, E> {
+    let (i, escaped_char) = anychar(input)?;
+    let final_char = match escaped_char {
+        '"' | '\\' | '/' => escaped_char,
+        'b' => '\x08',
+        'f' => '\x0C',
+        'n' => '\n',
+        'r' => '\r',
+        't' => '\t',
+        'u' => return unicode_escape(i),
+        _ => {
+            return Err(nom::Err::Failure(E::from_error_kind(
+                input,
+                ErrorKind::Escaped,
+            )))
+        }
+    };
+
+    Ok((i, final_char))
+}
+
+enum Piece<'a> {
+    Run(&'a str),
+    Escaped(char),
+}
+
+fn string<'a, E: ParseError<&'a str>>(i: &'a str) -> Result<'a, String, E> {
+    preceded(
+        char('"'),
+        cut(terminated(
+            fold_many0(
+                alt((
+                    // Incomplete when the input ends before a `"` or a `\`
+                    map(is_not("\"\\"), Piece::Run),
+                    map(preceded(char('\\'), escape), Piece::Escaped),
+                )),
+                String::new,
+                |mut string, piece| {
+                    match piece {
+                        Piece::Run(run) => string.push_str(run),
+                        Piece::Escaped(c) => string.push(c),
+                    }
+                    string
+                },
+            ),
+            char('"'),
+        )),
+    )(i)
+}
+
+/// Incomplete at the end of the input, where more digits could follow
+fn number<'a, E: ParseError<&'a str>>(i: &'a str) -> Result<'a, JsonValue, E> {
+    let (rest, float) = double(i)?;
+    let text = &i[..i.len() - rest.len()];
+
+    // `-0` stays a float, which keeps its sign
+    let negative_zero = float == 0.0 && text.starts_with('-');
+
+    if !negative_zero && text.bytes().all(|b| b == b'-' || b.is_ascii_digit()) {
+        if let Ok(n) = text.parse() {
+            return Ok((rest, JsonValue::Int(n)));
+        }
+        if let Ok(n) = text.parse() {
+            return Ok((rest, JsonValue::UInt(n)));
+        }
+    }
+
+    Ok((rest, JsonValue::Num(float)))
+}
+
+fn array<'a, E: ParseError<&'a str>>(i: &'a str) -> Result<'a, Vec<JsonValue>, E> {
+    delimited(
+        char('['),
+        cut(separated_list0(
+            preceded(multispace0, char(',')),
+            json_value,
+        )),
+        cut(preceded(multispace0, char(']'))),
+    )(i)
+}
+
+fn member<'a, E: ParseError<&'a str>>(i: &'a str) -> Result<'a, (String, JsonValue), E> {
+    separated_pair(
+        preceded(multispace0, string),
+        cut(preceded(multispace0, char(':'))),
+        json_value,
+    )(i)
+}
+
+fn object<'a, E: ParseError<&'a str>>(i: &'a str) -> Result<'a, Map<String, JsonValue>, E> {
+    delimited(
+        char('{'),
+        cut(map(
+            separated_list0(preceded(multispace0, char(',')), member),
+            |members| members.into_iter().collect(),
+        )),
+        cut(preceded(multispace0, char('}'))),
+    )(i)
+}
+
+fn json_value<'a, E: ParseError<&'a str>>(i: &'a str) -> Result<'a, JsonValue, E> {
+    let (i, _) = multispace0(i)?;
+    let (i, first_char) = peek(anychar)(i)?;
+
+    match first_char {
+        '{' => map(object, JsonValue::Object)(i),
+        '[' => map(array, JsonValue::Array)(i),
+        '"' => map(string, JsonValue::Str)(i),
+        '-' | '0'..='9' => number(i),
+        't' => value(JsonValue::Boolean(true), tag("true"))(i),
+        'f' => value(JsonValue::Boolean(false), tag("false"))(i),
+        'n' => value(JsonValue::Null, tag("null"))(i),
+        _ => Err(nom::Err::Error(E::from_error_kind(i, ErrorKind::Char))),
+    }
+}
+
+/// Item of the top-level value
+#[derive(Debug, PartialEq)]
+pub enum Entry {
+    /// Item of an array, or the whole document when it isn't a container
+    Element(JsonValue),
+    /// Member of an object
+    Member(String, JsonValue),
+}
+
+#[derive(Debug)]
+pub enum StreamError {
+    Io(std::io::Error),
+    /// Invalid document, from this byte of the input
+    Syntax {
+        offset: usize,
+    },
+    InvalidUtf8 {
+        offset: usize,
+    },
+    /// The input ends before the document does
+    Truncated,
+}
+
+impl Display for StreamError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StreamError::Io(e) => write!(f, "{e}"),
+            StreamError::Syntax { offset } => write!(f, "invalid JSON at byte {offset}"),
+            StreamError::InvalidUtf8 { offset } => write!(f, "invalid UTF-8 at byte {offset}"),
+            StreamError::Truncated => write!(f, "unexpected end of input"),
+        }
+    }
+}
+
+impl std::error::Error for StreamError {}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum State {
+    /// Before the top-level value
+    Start,
+    /// After the `[` or `{` of the top-level value, and maybe some entries
+    Entries {
+        close: char,
+        first: bool,
+    },
+    /// After the top-level value: only whitespace may follow
+    End,
+    Done,
+}
+
+/// What parsing the next entry gave
+enum Step {
+    /// Bytes of input consumed, entry parsed if any, and what follows
+    Parsed(usize, Option<Entry>, State),
+    Incomplete,
+    Invalid(usize),
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Stats {
+    /// Chunks read
+    pub chunks: usize,
+    /// Times a parser returned `Err::Incomplete`, and more input was read
+    pub incomplete: usize,
+}
+
+/// Iterator over the entries of the top-level array or object of a document
+/// read from `reader`, `chunk_size` bytes at a time
+pub struct Entries<R> {
+    reader: R,
+    chunk_size: usize,
+    /// Input read and not parsed yet, from `start`
+    buffer: String,
+    start: usize,
+    /// Bytes of the input before `buffer[start..]`, for error offsets
+    offset: usize,
+    /// Start of a char cut in two by the end of the last chunk
+    partial: Vec<u8>,
+    eof: bool,
+    state: State,
+    pub stats: Stats,
+}
+
+impl<R: Read> Entries<R> {
+    pub fn new(reader: R, chunk_size: usize) -> Self {
+        Self {
+            reader,
+            chunk_size: chunk_size.max(1),
+            buffer: String::new(),
+            start: 0,
+            offset: 0,
+            partial: Vec::new(),
+            eof: false,
+            state: State::Start,
+            stats: Stats::default(),
+        }
+    }
+
+    /// Reads a chunk into `buffer`, keeping a cut char for the next one
+    fn read_chunk(&mut self) -> std::result::Result<(), StreamError> {
+        let mut chunk = vec![0; self.chunk_size];
+        let read = self.reader.read(&mut chunk).map_err(StreamError::Io)?;
+        self.stats.chunks += 1;
+
+        if read == 0 {
+            self.eof = true;
+            return match self.partial.is_empty() {
+                true => Ok(()),
+                false => Err(StreamError::InvalidUtf8 {
+                    offset: self.offset + self.buffer.len() - self.start,
+                }),
+            };
+        }
+
+        self.partial.extend_from_slice(&chunk[..read]);
+        let valid = match std::str::from_utf8(&self.partial) {
+            Ok(_) => self.partial.len(),
+            // Cut by the end of the chunk: the rest of the char comes next
+            Err(e) if e.error_len().is_none() => e.valid_up_to(),
+            Err(e) => {
+                return Err(StreamError::InvalidUtf8 {
+                    offset: self.offset + self.buffer.len() - self.start + e.valid_up_to(),
+                })
+            }
+        };
+
+        self.buffer
+            .push_str(std::str::from_utf8(&self.partial[..valid]).unwrap());
+        self.partial.drain(..valid);
+
+        Ok(())
+    }
+
+    /// Reads until there's twice the input that wasn't enough, or the end
+    fn read_more(&mut self) -> std::result::Result<(), StreamError> {
+        // What was parsed is dropped first
+        self.buffer.drain(..self.start);
+        self.start = 0;
+
+        let wanted = self.buffer.len() * 2;
+        loop {
+            self.read_chunk()?;
+            if self.eof || self.buffer.len() >= wanted {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Parses the next entry of `input`, with nom errors turned into a
+    /// [`Step`]
+    fn step(state: State, input: &str) -> Step {
+        let result = match state {
+            State::Start => Self::start(input),
+            State::Entries { close, first } => Self::entry(input, close, first),
+            State::End | State::Done => unreachable!(),
+        };
+
+        match result {
+            Ok((rest, (entry, state))) => Step::Parsed(input.len() - rest.len(), entry, state),
+            Err(nom::Err::Incomplete(_)) => Step::Incomplete,
+            Err(nom::Err::Error(e) | nom::Err::Failure(e)) => {
+                Step::Invalid(input.len() - e.input.len())
+            }
+        }
+    }
+
+    fn start(i: &str) -> Result<'_, (Option<Entry>, State), Error<&str>> {
+        let (i, _) = multispace0(i)?;
+
+        match peek(anychar)(i)? {
+            (i, '[') => Ok((
+                &i[1..],
+                (
+                    None,
+                    State::Entries {
+                        close: ']',
+                        first: true,
+                    },
+                ),
+            )),
+            (i, '{') => Ok((
+                &i[1..],
+                (
+                    None,
+                    State::Entries {
+                        close: '}',
+                        first: true,
+                    },
+                ),
+            )),
+            (i, _) => map(json_value, |value| {
+                (Some(Entry::Element(value)), State::End)
+            })(i),
+        }
+    }
+
+    fn entry(i: &str, close: char, first: bool) -> Result<'_, (Option<Entry>, State), Error<&str>> {
+        let (i, _) = multispace0(i)?;
+
+        if let (i, Some(_)) = nom::combinator::opt(char(close))(i)? {
+            return Ok((i, (None, State::End)));
+        }
+        let i = match first {
+            true => i,
+            false => cut(preceded(char(','), multispace0))(i)?.0,
+        };
+
+        let state = State::Entries {
+            close,
+            first: false,
+        };
+        let (i, entry) = match close {
+            ']' => map(json_value, Entry::Element)(i)?,
+            _ => map(member, |(key, value)| Entry::Member(key, value))(i)?,
+        };
+
+        Ok((i, (Some(entry), state)))
+    }
+}
+
+impl<R: Read> Iterator for Entries<R> {
+    type Item = std::result::Result<Entry, StreamError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let input = &self.buffer[self.start..];
+
+            let step = match self.state {
+                State::Done => return None,
+                // Only whitespace may follow the document
+                State::End => match input.trim_start() {
+                    "" if self.eof => {
+                        self.state = State::Done;
+                        return None;
+                    }
+                    "" => Step::Incomplete,
+                    rest => Step::Invalid(input.len() - rest.len()),
+                },
+                state => Self::step(state, input),
+            };
+
+            match step {
+                Step::Parsed(consumed, entry, state) => {
+                    self.start += consumed;
+                    self.offset += consumed;
+                    self.state = state;
+
+                    if let Some(entry) = entry {
+                        return Some(Ok(entry));
+                    }
+                }
+                // Nothing comes after a number ending the input: a space
+                // tells the parser so
+                Step::Incomplete if self.eof && !input.ends_with(' ') => self.buffer.push(' '),
+                Step::Incomplete if self.eof => {
+                    self.state = State::Done;
+                    return Some(Err(StreamError::Truncated));
+                }
+                Step::Incomplete => {
+                    self.stats.incomplete += 1;
+                    if let Err(e) = self.read_more() {
+                        self.state = State::Done;
+                        return Some(Err(e));
+                    }
+                }
+                Step::Invalid(at) => {
+                    self.state = State::Done;
+                    return Some(Err(StreamError::Syntax {
+                        offset: self.offset + at,
+                    }));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,61 @@
+use std::fs::File;
+
+use step_4::JsonValue;
+use step_6::{Entries, Entry};
+
+/// Description of `value`, short enough for a line
+fn describe(value: &JsonValue) -> String {
+    match value {
+        JsonValue::Array(items) => format!("array of {} items", items.len()),
+        JsonValue::Object(members) => format!("object of {} members", members.len()),
+        value => {
+            let mut json = step_4::to_string(value);
+            if json.len() > 40 {
+                let end = (0..=40).rev().find(|&i| json.is_char_boundary(i)).unwrap();
+                json.truncate(end);
+                json.push('…');
+            }
+            json
+        }
+    }
+}
+
+fn main() {
+    let mut args = std::env::args().skip(1);
+    let path = args
+        .next()
+        .unwrap_or_else(|| "./test-files/twitter.json".into());
+    let chunk_size = match args.next().map(|size| size.parse()) {
+        None => 4096,
+        Some(Ok(size)) => size,
+        Some(Err(e)) => {
+            eprintln!("invalid chunk size: {e}");
+            std::process::exit(1);
+        }
+    };
+
+    let file = match File::open(&path) {
+        Ok(file) => file,
+        Err(e) => {
+            eprintln!("{path}: {e}, see `cargo xtask fetch`");
+            std::process::exit(1);
+        }
+    };
+
+    let mut entries = Entries::new(file, chunk_size);
+    for entry in &mut entries {
+        match entry {
+            Ok(Entry::Element(value)) => println!("- {}", describe(&value)),
+            Ok(Entry::Member(key, value)) => println!("{key}: {}", describe(&value)),
+            Err(e) => {
+                eprintln!("{path}: {e}");
+                std::process::exit(1);
+            }
+        }
+    }
+
+    println!(
+        "\n{} chunks of {chunk_size} bytes, parsing resumed {} times",
+        entries.stats.chunks, entries.stats.incomplete
+    );
+}
@@ -0,0 +1,92 @@
+//! Whatever the chunk size, step 6 parses like step 4.
+
+use step_4::{
+    testing::{RandomOptions, RandomValues},
+    to_string, JsonValue,
+};
+use step_6::{Entries, Entry, StreamError};
+
+/// Entries of `json` read `chunk_size` bytes at a time, back into one value
+fn parse(json: &str, chunk_size: usize) -> Result<JsonValue, StreamError> {
+    let entries = Entries::new(json.as_bytes(), chunk_size).collect::<Result<Vec<_>, _>>()?;
+    let is_object = json.trim_start().starts_with('{');
+
+    Ok(match entries.as_slice() {
+        [Entry::Element(value)] if !json.trim_start().starts_with('[') => value.clone(),
+        _ if is_object => JsonValue::Object(
+            entries
+                .into_iter()
+                .map(|entry| match entry {
+                    Entry::Member(key, value) => (key, value),
+                    Entry::Element(_) => unreachable!(),
+                })
+                .collect(),
+        ),
+        _ => JsonValue::Array(
+            entries
+                .into_iter()
+                .map(|entry| match entry {
+                    Entry::Element(value) => value,
+                    Entry::Member(..) => unreachable!(),
+                })
+                .collect(),
+        ),
+    })
+}
+
+#[test]
+fn chunk_sizes() {
+    let json = r#" {"name": "tokyo 🦀", "escaped": "\né🦀",
+        "numbers": [0, -12, 1.5e3, 18446744073709551615], "nested": {"a": [[], {}]},
+        "flags": [true, false, null]} "#;
+    let (_, expected) = step_4::parse(json).unwrap();
+
+    for chunk_size in 1..=17 {
+        assert_eq!(parse(json, chunk_size).unwrap(), expected, "{chunk_size}");
+    }
+}
+
+#[test]
+fn top_level_scalars() {
+    for json in ["42", " -1.5 ", "-0", r#""s""#, "true", "null", "[]", "{}"] {
+        let (_, expected) = step_4::parse(json).unwrap();
+
+        for chunk_size in 1..=3 {
+            assert_eq!(parse(json, chunk_size).unwrap(), expected, "{json}");
+        }
+    }
+}
+
+#[test]
+fn random_values() {
+    for value in RandomValues::new(7, RandomOptions::default()).take(2_000) {
+        let json = to_string(&value);
+
+        assert_eq!(parse(&json, 7).unwrap(), value, "{json}");
+    }
+}
+
+#[test]
+fn errors() {
+    for json in [
+        "[1, 2",
+        r#"{"a": "b"#,
+        "tru",
+        "[1] 2",
+        "[1,]",
+        r#"{"a" 1}"#,
+        "",
+    ] {
+        for chunk_size in [1, 64] {
+            assert!(parse(json, chunk_size).is_err(), "{json}");
+        }
+    }
+
+    let entries = Entries::new(&b"[1, 2, x]"[..], 4);
+    let error = entries.last().unwrap().unwrap_err();
+    assert_eq!(error.to_string(), "invalid JSON at byte 7");
+
+    let entries = Entries::new(&b"[\"\xff\"]"[..], 1);
+    let error = entries.last().unwrap().unwrap_err();
+    assert_eq!(error.to_string(), "invalid UTF-8 at byte 2");
+}
@@ -0,0 +1,205 @@
+use std::fmt::Display;
+
+use nom::{
+    branch::alt,
+    bytes::complete::take_while_m_n,
+    character::complete::{char, digit1, one_of},
+    combinator::{map, map_res, opt, verify},
+    error::{FromExternalError, ParseError},
+    sequence::{pair, preceded, separated_pair, tuple},
+    IResult,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Date {
+    pub year: u16,
+    pub month: u8,
+    pub day: u8,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Time {
+    pub hour: u8,
+    pub minute: u8,
+    pub second: u8,
+    pub nanosecond: u32,
+}
+
+/// Offset from UTC
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Offset {
+    /// `Z`
+    Utc,
+    /// `+09:00` is 540
+    Minutes(i16),
+}
+
+/// One of the four kinds of datetimes of TOML, depending on the parts that
+/// are there: offset datetime, local datetime, local date and local time.
+/// There's an offset only with a date and a time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Datetime {
+    pub date: Option<Date>,
+    pub time: Option<Time>,
+    pub offset: Option<Offset>,
+}
+
+/// RFC 3339, as written in TOML
+impl Display for Datetime {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(Date { year, month, day }) = self.date {
+            write!(f, "{year:04}-{month:02}-{day:02}")?;
+        }
+        if self.date.is_some() && self.time.is_some() {
+            write!(f, "T")?;
+        }
+
+        if let Some(time) = self.time {
+            write!(f, "{:02}:{:02}:{:02}", time.hour, time.minute, time.second)?;
+            if time.nanosecond > 0 {
+                let fraction = format!("{:09}", time.nanosecond);
+                write!(f, ".{}", fraction.trim_end_matches('0'))?;
+            }
+        }
+
+        match self.offset {
+            None => Ok(()),
+            Some(Offset::Utc) => write!(f, "Z"),
+            Some(Offset::Minutes(minutes)) => {
+                let sign = if minutes < 0 { '-' } else { '+' };
+                let minutes = minutes.unsigned_abs();
+                write!(f, "{sign}{:02}:{:02}", minutes / 60, minutes % 60)
+            }
+        }
+    }
+}
+
+type Result<'a, O, E> = IResult<&'a str, O, E>;
+
+/// Exactly `len` digits
+fn digits<'a, E>(len: usize) -> impl FnMut(&'a str) -> Result<'a, u16, E>
+where
+    E: ParseError<&'a str> + FromExternalError<&'a str, std::num::ParseIntError>,
+{
+    map_res(
+        take_while_m_n(len, len, |c: char| c.is_ascii_digit()),
+        str::parse,
+    )
+}
+
+fn days_in_month(year: u16, month: u8) -> u8 {
+    let leap = year.is_multiple_of(4) && (!year.is_multiple_of(100) || year.is_multiple_of(400));
+
+    match month {
+        2 if leap => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// `1979-05-27`
+fn date<'a, E>(i: &'a str) -> Result<'a, Date, E>
+where
+    E: ParseError<&'a str> + FromExternalError<&'a str, std::num::ParseIntError>,
+{
+    verify(
+        map(
+            tuple((digits(4), char('-'), digits(2), char('-'), digits(2))),
+            |(year, _, month, _, day)| Date {
+                year,
+                month: month as u8,
+                day: day as u8,
+            },
+        ),
+        |date| {
+            (1..=12).contains(&date.month)
+                && (1..=days_in_month(date.year, date.month)).contains(&date.day)
+        },
+    )(i)
+}
+
+/// Nanoseconds of the digits after the `.` of a time, those past the
+/// nanosecond being dropped
+fn nanoseconds(fraction: &str) -> u32 {
+    fraction
+        .bytes()
+        .chain(std::iter::repeat(b'0'))
+        .take(9)
+        .fold(0, |n, digit| n * 10 + (digit - b'0') as u32)
+}
+
+/// `07:32:00`, `00:32:00.999999`
+fn time<'a, E>(i: &'a str) -> Result<'a, Time, E>
+where
+    E: ParseError<&'a str> + FromExternalError<&'a str, std::num::ParseIntError>,
+{
+    verify(
+        map(
+            tuple((
+                digits(2),
+                char(':'),
+                digits(2),
+                char(':'),
+                digits(2),
+                opt(preceded(char('.'), digit1)),
+            )),
+            |(hour, _, minute, _, second, fraction)| Time {
+                hour: hour as u8,
+                minute: minute as u8,
+                second: second as u8,
+                nanosecond: fraction.map_or(0, nanoseconds),
+            },
+        ),
+        // A leap second can be 60
+        |time| time.hour < 24 && time.minute < 60 && time.second <= 60,
+    )(i)
+}
+
+/// `Z`, `+09:00`
+fn offset<'a, E>(i: &'a str) -> Result<'a, Offset, E>
+where
+    E: ParseError<&'a str> + FromExternalError<&'a str, std::num::ParseIntError>,
+{
+    alt((
+        map(one_of("Zz"), |_| Offset::Utc),
+        map(
+            verify(
+                pair(
+                    one_of("+-"),
+                    separated_pair(digits(2), char(':'), digits(2)),
+                ),
+                |(_, (hours, minutes))| *hours < 24 && *minutes < 60,
+            ),
+            |(sign, (hours, minutes))| {
+                let minutes = (hours * 60 + minutes) as i16;
+                Offset::Minutes(if sign == '-' { -minutes } else { minutes })
+            },
+        ),
+    ))(i)
+}
+
+pub(crate) fn datetime<'a, E>(i: &'a str) -> Result<'a, Datetime, E>
+where
+    E: ParseError<&'a str> + FromExternalError<&'a str, std::num::ParseIntError>,
+{
+    alt((
+        map(
+            pair(
+                date,
+                // A space can separate the date from the time too
+                opt(pair(preceded(one_of("Tt "), time), opt(offset))),
+            ),
+            |(date, time)| Datetime {
+                date: Some(date),
+                time: time.map(|(time, _)| time),
+                offset: time.and_then(|(_, offset)| offset),
+            },
+        ),
+        map(time, |time| Datetime {
+            date: None,
+            time: Some(time),
+            offset: None,
+        }),
+    ))(i)
+}
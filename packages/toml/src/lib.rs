@@ -0,0 +1,101 @@
+//! [TOML](https://toml.io/en/v1.0.0), with a parser written with nom, next
+//! to the JSON one.
+//!
+//! The values are JSON's, datetimes aside, but the grammar differs:
+//!
+//! - JSON is a tree read in one go, a TOML document is a list of lines,
+//!   headers and `key = value`, that each add to the tables read so far.
+//!   Errors like a key defined twice only show then, once lines are parsed.
+//! - Strings come in four kinds, multi-line or not, with escapes or not.
+//! - Numbers, booleans and datetimes are told apart by their first chars,
+//!   `1979-05-27` being a date and not a subtraction.
+//!
+//! The errors are those of the JSON parser, so [`step_4::render_error`]
+//! shows where the input went wrong.
+//!
+//! ```
+//! use toml::{Datetime, Offset, TomlValue};
+//!
+//! let config = toml::parse(r#"
+//! name = "meetup"  # where it all began
+//!
+//! [server]
+//! port = 8_080
+//! started = 2024-01-15T19:00:00+09:00
+//!
+//! [[talks]]
+//! title = "nom"
+//! "#).unwrap();
+//!
+//! let TomlValue::Table(server) = &config["server"] else { panic!() };
+//! let TomlValue::Datetime(started) = server["started"] else { panic!() };
+//! assert_eq!(started.offset, Some(Offset::Minutes(9 * 60)));
+//!
+//! // As JSON, datetimes are strings
+//! assert_eq!(
+//!     TomlValue::Table(config).into_json(),
+//!     step_4::parse_with_path(r#"{
+//!         "name": "meetup",
+//!         "server": {"port": 8080, "started": "2024-01-15T19:00:00+09:00"},
+//!         "talks": [{"title": "nom"}]
+//!     }"#).unwrap()
+//! );
+//! ```
+
+use step_4::{JsonError, JsonValue, Map};
+
+mod datetime;
+mod parse;
+
+pub use datetime::{Date, Datetime, Offset, Time};
+
+pub type Table = Map<String, TomlValue>;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TomlValue {
+    String(String),
+    Integer(i64),
+    Float(f64),
+    Boolean(bool),
+    Datetime(Datetime),
+    Array(Vec<TomlValue>),
+    Table(Table),
+}
+
+impl TomlValue {
+    /// Same value in JSON, datetimes being written as strings
+    pub fn into_json(self) -> JsonValue {
+        match self {
+            TomlValue::String(s) => JsonValue::Str(s),
+            TomlValue::Integer(n) => JsonValue::Int(n),
+            TomlValue::Float(n) => JsonValue::Num(n),
+            TomlValue::Boolean(b) => JsonValue::Boolean(b),
+            TomlValue::Datetime(datetime) => JsonValue::Str(datetime.to_string()),
+            TomlValue::Array(values) => {
+                JsonValue::Array(values.into_iter().map(TomlValue::into_json).collect())
+            }
+            TomlValue::Table(table) => JsonValue::Object(
+                table
+                    .into_iter()
+                    .map(|(key, value)| (key, value.into_json()))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+impl From<TomlValue> for JsonValue {
+    fn from(value: TomlValue) -> Self {
+        value.into_json()
+    }
+}
+
+/// Root table of a TOML document
+pub fn parse(input: &str) -> Result<Table, JsonError> {
+    parse::document(input)
+}
+
+/// The document as a JSON object
+pub fn to_json(input: &str) -> Result<JsonValue, JsonError> {
+    parse(input).map(|table| TomlValue::Table(table).into_json())
+}
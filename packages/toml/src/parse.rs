@@ -0,0 +1,517 @@
+use std::collections::HashSet;
+
+use nom::{
+    branch::alt,
+    bytes::complete::{tag, take_while, take_while1, take_while_m_n},
+    character::complete::{char, digit1, hex_digit1, line_ending, oct_digit1, one_of, space0},
+    combinator::{cut, map, map_opt, map_res, not, opt, peek, recognize, value, verify},
+    error::{context, ErrorKind, FromExternalError, ParseError},
+    multi::{fold_many0, many0, separated_list0, separated_list1},
+    sequence::{delimited, pair, preceded, separated_pair, terminated, tuple},
+    Finish, IResult,
+};
+
+use step_4::JsonError;
+
+use crate::{datetime::datetime, Table, TomlValue};
+
+type Result<'a, O> = IResult<&'a str, O, JsonError>;
+
+fn failure(i: &str, message: String) -> nom::Err<JsonError> {
+    nom::Err::Failure(JsonError::from_external_error(
+        i,
+        ErrorKind::Verify,
+        message,
+    ))
+}
+
+fn comment(i: &str) -> Result<'_, &str> {
+    recognize(pair(char('#'), take_while(|c| c != '\n' && c != '\r')))(i)
+}
+
+/// Whitespace, line breaks and comments, as between the values of an array
+fn blank(i: &str) -> Result<'_, ()> {
+    value(
+        (),
+        many0(alt((
+            take_while1(|c| c == ' ' || c == '\t'),
+            line_ending,
+            comment,
+        ))),
+    )(i)
+}
+
+/// Rest of a line: whitespace, maybe a comment, then a line break or the
+/// end of the input
+fn line_end(i: &str) -> Result<'_, ()> {
+    let (i, _) = pair(space0, opt(comment))(i)?;
+
+    match i.is_empty() {
+        true => Ok((i, ())),
+        false => value((), line_ending)(i).map_err(|_: nom::Err<JsonError>| {
+            failure(i, "expected the end of the line".to_owned())
+        }),
+    }
+}
+
+/// Any char but the control ones, tabs aside
+fn is_literal(c: char) -> bool {
+    c == '\t' || !c.is_control()
+}
+
+/// `\u` or `\U` escape, of `len` hex digits
+fn unicode(len: usize) -> impl FnMut(&str) -> Result<'_, char> {
+    move |i| {
+        map_opt(
+            take_while_m_n(len, len, |c: char| c.is_ascii_hexdigit()),
+            |hex| u32::from_str_radix(hex, 16).ok().and_then(char::from_u32),
+        )(i)
+    }
+}
+
+fn escape(i: &str) -> Result<'_, char> {
+    preceded(
+        char('\\'),
+        cut(alt((
+            value('\x08', char('b')),
+            value('\t', char('t')),
+            value('\n', char('n')),
+            value('\x0C', char('f')),
+            value('\r', char('r')),
+            value('"', char('"')),
+            value('\\', char('\\')),
+            preceded(char('u'), unicode(4)),
+            preceded(char('U'), unicode(8)),
+        ))),
+    )(i)
+}
+
+#[derive(Clone)]
+enum Piece<'a> {
+    Run(&'a str),
+    Escaped(char),
+}
+
+fn push(mut string: String, piece: Piece<'_>) -> String {
+    match piece {
+        Piece::Run(run) => string.push_str(run),
+        Piece::Escaped(c) => string.push(c),
+    }
+    string
+}
+
+/// `"..."`
+fn basic_string(i: &str) -> Result<'_, String> {
+    delimited(
+        char('"'),
+        fold_many0(
+            alt((
+                map(
+                    take_while1(|c| c != '"' && c != '\\' && is_literal(c)),
+                    Piece::Run,
+                ),
+                map(escape, Piece::Escaped),
+            )),
+            String::new,
+            push,
+        ),
+        cut(char('"')),
+    )(i)
+}
+
+/// `'...'`, without escapes
+fn literal_string(i: &str) -> Result<'_, String> {
+    map(
+        delimited(
+            char('\''),
+            take_while(|c| c != '\'' && is_literal(c)),
+            cut(char('\'')),
+        ),
+        str::to_owned,
+    )(i)
+}
+
+/// Quotes inside a multi-line string: there are fewer than three of them
+fn inner_quotes(quote: char) -> impl FnMut(&str) -> Result<'_, &str> {
+    move |i| {
+        let len = i.len() - i.trim_start_matches(quote).len();
+        match len {
+            1 | 2 => Ok((&i[len..], &i[..len])),
+            _ => Err(nom::Err::Error(JsonError::from_error_kind(
+                i,
+                ErrorKind::Char,
+            ))),
+        }
+    }
+}
+
+/// End of a multi-line string: three quotes, after up to two that are part
+/// of the string, as in `"""say "hi"""""`
+fn closing_quotes(quote: char) -> impl FnMut(&str) -> Result<'_, &str> {
+    move |i| {
+        let len = i.len() - i.trim_start_matches(quote).len();
+        match len {
+            3..=5 => Ok((&i[len..], &i[..len - 3])),
+            _ => Err(nom::Err::Error(JsonError::from_error_kind(
+                i,
+                ErrorKind::Char,
+            ))),
+        }
+    }
+}
+
+/// `"""..."""`, where a `\` at the end of a line trims the whitespace that
+/// follows it
+fn multiline_basic_string(i: &str) -> Result<'_, String> {
+    let is_content = |c| c != '"' && c != '\\' && (is_literal(c) || c == '\n' || c == '\r');
+    let line_ending_backslash = tuple((
+        char('\\'),
+        space0,
+        line_ending,
+        take_while(|c: char| c.is_ascii_whitespace()),
+    ));
+
+    map(
+        preceded(
+            pair(tag("\"\"\""), opt(line_ending)),
+            cut(pair(
+                fold_many0(
+                    alt((
+                        map(take_while1(is_content), Piece::Run),
+                        value(Piece::Run(""), line_ending_backslash),
+                        map(escape, Piece::Escaped),
+                        map(inner_quotes('"'), Piece::Run),
+                    )),
+                    String::new,
+                    push,
+                ),
+                closing_quotes('"'),
+            )),
+        ),
+        |(string, quotes)| string + quotes,
+    )(i)
+}
+
+/// `'''...'''`, without escapes
+fn multiline_literal_string(i: &str) -> Result<'_, String> {
+    let is_content = |c| c != '\'' && (is_literal(c) || c == '\n' || c == '\r');
+
+    map(
+        preceded(
+            pair(tag("'''"), opt(line_ending)),
+            cut(pair(
+                fold_many0(
+                    alt((take_while1(is_content), inner_quotes('\''))),
+                    String::new,
+                    |string, run| string + run,
+                ),
+                closing_quotes('\''),
+            )),
+        ),
+        |(string, quotes)| string + quotes,
+    )(i)
+}
+
+fn string(i: &str) -> Result<'_, String> {
+    context(
+        "string",
+        alt((
+            multiline_basic_string,
+            basic_string,
+            multiline_literal_string,
+            literal_string,
+        )),
+    )(i)
+}
+
+fn key_part(i: &str) -> Result<'_, String> {
+    alt((
+        map(
+            take_while1(|c: char| c.is_ascii_alphanumeric() || c == '_' || c == '-'),
+            str::to_owned,
+        ),
+        basic_string,
+        literal_string,
+    ))(i)
+}
+
+/// `a.b."c d"`
+fn key(i: &str) -> Result<'_, Vec<String>> {
+    context(
+        "key",
+        separated_list1(delimited(space0, char('.'), space0), key_part),
+    )(i)
+}
+
+/// Digits with single `_` between them, as in `1_000`
+fn separated_digits<'a>(
+    digits: impl FnMut(&'a str) -> Result<'a, &'a str>,
+) -> impl FnMut(&'a str) -> Result<'a, &'a str> {
+    recognize(separated_list1(char('_'), digits))
+}
+
+/// Decimal integer, without leading zeros
+fn decimal(i: &str) -> Result<'_, &str> {
+    recognize(pair(
+        opt(one_of("+-")),
+        verify(separated_digits(digit1), |digits: &str| {
+            digits == "0" || !digits.starts_with('0')
+        }),
+    ))(i)
+}
+
+fn radix<'a>(
+    prefix: &'static str,
+    digits: impl FnMut(&'a str) -> Result<'a, &'a str>,
+    radix: u32,
+) -> impl FnMut(&'a str) -> Result<'a, i64> {
+    map_res(
+        preceded(tag(prefix), separated_digits(digits)),
+        move |digits: &str| i64::from_str_radix(&digits.replace('_', ""), radix),
+    )
+}
+
+fn integer(i: &str) -> Result<'_, i64> {
+    alt((
+        radix("0x", hex_digit1, 16),
+        radix("0o", oct_digit1, 8),
+        radix("0b", take_while1(|c| c == '0' || c == '1'), 2),
+        map_res(decimal, |digits: &str| digits.replace('_', "").parse()),
+    ))(i)
+}
+
+fn exponent(i: &str) -> Result<'_, &str> {
+    recognize(tuple((
+        one_of("eE"),
+        opt(one_of("+-")),
+        separated_digits(digit1),
+    )))(i)
+}
+
+/// `3.14`, `6e-2`, `-inf`
+fn float(i: &str) -> Result<'_, f64> {
+    let special = alt((
+        value(f64::INFINITY, tag("inf")),
+        value(f64::NAN, tag("nan")),
+    ));
+
+    alt((
+        map_res(
+            recognize(pair(
+                decimal,
+                alt((
+                    recognize(pair(
+                        preceded(char('.'), separated_digits(digit1)),
+                        opt(exponent),
+                    )),
+                    exponent,
+                )),
+            )),
+            |number: &str| number.replace('_', "").parse(),
+        ),
+        map(pair(opt(one_of("+-")), special), |(sign, n): (_, f64)| {
+            if sign == Some('-') {
+                -n
+            } else {
+                n
+            }
+        }),
+    ))(i)
+}
+
+fn boolean(i: &str) -> Result<'_, bool> {
+    alt((value(true, tag("true")), value(false, tag("false"))))(i)
+}
+
+/// `[1, 2, 3]`, across lines and with a trailing comma maybe
+fn array(i: &str) -> Result<'_, Vec<TomlValue>> {
+    context(
+        "array",
+        preceded(
+            char('['),
+            cut(terminated(
+                separated_list0(char(','), delimited(blank, toml_value, blank)),
+                tuple((blank, opt(pair(char(','), blank)), char(']'))),
+            )),
+        ),
+    )(i)
+}
+
+fn key_value(i: &str) -> Result<'_, (Vec<String>, TomlValue)> {
+    separated_pair(
+        key,
+        cut(delimited(space0, char('='), space0)),
+        cut(toml_value),
+    )(i)
+}
+
+/// `{ a = 1, b.c = 2 }`, on a single line
+fn inline_table(i: &str) -> Result<'_, Table> {
+    let (rest, pairs) = context(
+        "inline table",
+        delimited(
+            pair(char('{'), space0),
+            separated_list0(delimited(space0, char(','), space0), key_value),
+            cut(pair(space0, char('}'))),
+        ),
+    )(i)?;
+
+    let mut table = Table::default();
+    for (keys, value) in pairs {
+        insert(&mut table, &keys, value).map_err(|e| failure(i, e))?;
+    }
+
+    Ok((rest, table))
+}
+
+fn toml_value(i: &str) -> Result<'_, TomlValue> {
+    // After a number or a datetime comes anything but what they are made of
+    let end = || not(peek(one_of("0123456789_.:+-eE")));
+
+    alt((
+        map(string, TomlValue::String),
+        map(terminated(datetime, end()), TomlValue::Datetime),
+        map(terminated(float, end()), TomlValue::Float),
+        map(terminated(integer, end()), TomlValue::Integer),
+        map(boolean, TomlValue::Boolean),
+        map(array, TomlValue::Array),
+        map(inline_table, TomlValue::Table),
+    ))(i)
+}
+
+/// Inserts `value` at the dotted `keys` of `table`
+fn insert(table: &mut Table, keys: &[String], value: TomlValue) -> std::result::Result<(), String> {
+    let (last, parents) = keys.split_last().expect("keys aren't empty");
+    let table = descend(table, parents)?;
+
+    if table.contains_key(last) {
+        return Err(format!("duplicate key `{}`", keys.join(".")));
+    }
+    table.insert(last.clone(), value);
+
+    Ok(())
+}
+
+/// Table at `keys`, created if needed. Arrays of tables stand for their last
+/// table
+fn descend<'t>(
+    mut table: &'t mut Table,
+    keys: &[String],
+) -> std::result::Result<&'t mut Table, String> {
+    for (depth, key) in keys.iter().enumerate() {
+        let entry = table
+            .entry(key.clone())
+            .or_insert_with(|| TomlValue::Table(Table::default()));
+
+        table = match entry {
+            TomlValue::Table(table) => table,
+            TomlValue::Array(values) => match values.last_mut() {
+                Some(TomlValue::Table(table)) => table,
+                _ => return Err(not_a_table(&keys[..=depth])),
+            },
+            _ => return Err(not_a_table(&keys[..=depth])),
+        };
+    }
+
+    Ok(table)
+}
+
+fn not_a_table(keys: &[String]) -> String {
+    format!("`{}` is already a value, not a table", keys.join("."))
+}
+
+enum Line {
+    /// `[table]`
+    Table(Vec<String>),
+    /// `[[array.of.tables]]`
+    ArrayOfTables(Vec<String>),
+    KeyValue(Vec<String>, TomlValue),
+}
+
+fn line(i: &str) -> Result<'_, Line> {
+    let header = |open, close| {
+        delimited(
+            pair(tag(open), space0),
+            cut(key),
+            cut(pair(space0, tag(close))),
+        )
+    };
+
+    terminated(
+        alt((
+            map(header("[[", "]]"), Line::ArrayOfTables),
+            map(header("[", "]"), Line::Table),
+            map(key_value, |(keys, value)| Line::KeyValue(keys, value)),
+        )),
+        // Anything else after it is an error
+        line_end,
+    )(i)
+}
+
+/// Tables of a document as its lines are read
+#[derive(Default)]
+struct Document {
+    root: Table,
+    /// Keys of the table of the last header
+    current: Vec<String>,
+    /// Tables defined by a header, which can't be defined twice
+    defined: HashSet<Vec<String>>,
+}
+
+impl Document {
+    fn add(&mut self, line: Line) -> std::result::Result<(), String> {
+        match line {
+            Line::Table(keys) => {
+                if !self.defined.insert(keys.clone()) {
+                    return Err(format!("table `{}` is defined twice", keys.join(".")));
+                }
+                descend(&mut self.root, &keys)?;
+                self.current = keys;
+            }
+            Line::ArrayOfTables(keys) => {
+                let (last, parents) = keys.split_last().expect("keys aren't empty");
+                let table = descend(&mut self.root, parents)?;
+
+                match table
+                    .entry(last.clone())
+                    .or_insert_with(|| TomlValue::Array(Vec::new()))
+                {
+                    TomlValue::Array(tables)
+                        if tables.iter().all(|t| matches!(t, TomlValue::Table(_))) =>
+                    {
+                        tables.push(TomlValue::Table(Table::default()))
+                    }
+                    _ => return Err(not_a_table(&keys)),
+                }
+
+                // The tables of the previous element can be defined again
+                self.defined.retain(|table| !table.starts_with(&keys));
+                self.current = keys;
+            }
+            Line::KeyValue(keys, value) => {
+                let table = descend(&mut self.root, &self.current)?;
+                insert(table, &keys, value)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+pub(crate) fn document(input: &str) -> std::result::Result<Table, JsonError> {
+    let mut document = Document::default();
+    let mut i = input;
+
+    loop {
+        let (rest, _) = blank(i).finish()?;
+        if rest.is_empty() {
+            return Ok(document.root);
+        }
+
+        let (next, line) = line(rest).finish()?;
+        document
+            .add(line)
+            .map_err(|message| JsonError::from_external_error(rest, ErrorKind::Verify, message))?;
+        i = next;
+    }
+}
@@ -0,0 +1,185 @@
+use step_4::{render_error, JsonValue};
+use toml::{Date, Datetime, Offset, Time, TomlValue};
+
+fn value(toml: &str) -> TomlValue {
+    let mut table = toml::parse(&format!("v = {toml}")).unwrap();
+    table.remove("v").unwrap()
+}
+
+#[test]
+fn strings() {
+    assert_eq!(
+        value(r#""tab\t\"é\" \u00e9 \U0001F980""#),
+        TomlValue::String("tab\t\"é\" é 🦀".into())
+    );
+    assert_eq!(
+        value(r"'C:\Users\nom'"),
+        TomlValue::String(r"C:\Users\nom".into())
+    );
+    assert_eq!(
+        value("\"\"\"\nline one\nline \\\n    two \"quoted\"\"\"\"\""),
+        TomlValue::String("line one\nline two \"quoted\"\"".into())
+    );
+    assert_eq!(
+        value("'''\nno \\escapes\n'here'''"),
+        TomlValue::String("no \\escapes\n'here".into())
+    );
+    assert_eq!(value(r#""""#), TomlValue::String(String::new()));
+}
+
+#[test]
+fn numbers_and_booleans() {
+    assert_eq!(value("+99"), TomlValue::Integer(99));
+    assert_eq!(value("-17"), TomlValue::Integer(-17));
+    assert_eq!(value("1_000_000"), TomlValue::Integer(1_000_000));
+    assert_eq!(value("0xdead_BEEF"), TomlValue::Integer(0xdead_beef));
+    assert_eq!(value("0o755"), TomlValue::Integer(0o755));
+    assert_eq!(value("0b1101"), TomlValue::Integer(13));
+    assert_eq!(value("2.5"), TomlValue::Float(2.5));
+    assert_eq!(value("-6e-2"), TomlValue::Float(-0.06));
+    assert_eq!(value("5e+22"), TomlValue::Float(5e22));
+    assert_eq!(value("-inf"), TomlValue::Float(f64::NEG_INFINITY));
+    assert_eq!(value("true"), TomlValue::Boolean(true));
+
+    for invalid in [
+        "01",
+        "1__0",
+        "_1",
+        "1.",
+        ".5",
+        "0x",
+        "9223372036854775808",
+        "1.5.2",
+    ] {
+        assert!(toml::parse(&format!("v = {invalid}")).is_err(), "{invalid}");
+    }
+}
+
+#[test]
+fn datetimes() {
+    let date = Some(Date {
+        year: 1979,
+        month: 5,
+        day: 27,
+    });
+    let time = Some(Time {
+        hour: 7,
+        minute: 32,
+        second: 0,
+        nanosecond: 999_000_000,
+    });
+
+    assert_eq!(
+        value("1979-05-27T07:32:00.999-07:00"),
+        TomlValue::Datetime(Datetime {
+            date,
+            time,
+            offset: Some(Offset::Minutes(-7 * 60))
+        })
+    );
+    assert_eq!(
+        value("1979-05-27 07:32:00.999Z"),
+        TomlValue::Datetime(Datetime {
+            date,
+            time,
+            offset: Some(Offset::Utc)
+        })
+    );
+    assert_eq!(
+        value("1979-05-27"),
+        TomlValue::Datetime(Datetime {
+            date,
+            time: None,
+            offset: None
+        })
+    );
+    assert_eq!(
+        value("07:32:00.999"),
+        TomlValue::Datetime(Datetime {
+            date: None,
+            time,
+            offset: None
+        })
+    );
+
+    for datetime in [
+        "1979-05-27T07:32:00.999-07:00",
+        "1979-05-27T07:32:00",
+        "07:32:00",
+        "2024-02-29",
+    ] {
+        let TomlValue::Datetime(parsed) = value(datetime) else {
+            panic!("{datetime}")
+        };
+        assert_eq!(parsed.to_string(), datetime);
+    }
+
+    for invalid in ["1979-13-01", "2023-02-29", "24:00:00", "1979-05-27T07:32"] {
+        assert!(toml::parse(&format!("v = {invalid}")).is_err(), "{invalid}");
+    }
+}
+
+#[test]
+fn tables() {
+    let document = r#"
+# Top-level keys come first
+title = "TOML"
+owner.name = "Tom"
+
+[database]
+ports = [ 8000, 8001,
+  8002, # trailing commas and comments
+]
+inline = { a = 1, b.c = "d" }
+
+[servers.alpha]
+ip = '10.0.0.1'
+
+[[products]]
+name = "Hammer"
+
+[[products]]
+
+[[products]]
+name = "Nail"
+"#;
+    let expected: JsonValue = r#"{
+        "title": "TOML",
+        "owner": {"name": "Tom"},
+        "database": {"ports": [8000, 8001, 8002], "inline": {"a": 1, "b": {"c": "d"}}},
+        "servers": {"alpha": {"ip": "10.0.0.1"}},
+        "products": [{"name": "Hammer"}, {}, {"name": "Nail"}]
+    }"#
+    .parse()
+    .unwrap();
+
+    assert_eq!(toml::to_json(document).unwrap(), expected);
+    // The parser of step 4 reads the same document
+    assert_eq!(step_4::from_toml(document).unwrap(), expected);
+}
+
+#[test]
+fn errors() {
+    let error = |input: &str| render_error(input, &toml::parse(input).unwrap_err());
+
+    assert_eq!(
+        error("a = 1\na = 2\n"),
+        "error: duplicate key `a`\n --> 2:1\n  |\n2 | a = 2\n  | ^\n"
+    );
+    assert_eq!(
+        error("[a]\n[a]\n"),
+        "error: table `a` is defined twice\n --> 2:1\n  |\n2 | [a]\n  | ^\n"
+    );
+    assert_eq!(
+        error("a = 1\n[a.b]\n"),
+        "error: `a` is already a value, not a table\n --> 2:1\n  |\n2 | [a.b]\n  | ^\n"
+    );
+    assert_eq!(
+        error("name = \"nom\" parsers\n"),
+        "error: expected the end of the line\n --> 1:14\n  |\n1 | name = \"nom\" parsers\n  |              ^\n"
+    );
+    assert_eq!(
+        error("s = \"open\n"),
+        "error: unexpected '\\n', expected '\"'\n --> 1:10\n  |\n1 | s = \"open\n  |          ^ in string\n"
+    );
+}
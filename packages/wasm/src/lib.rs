@@ -0,0 +1,54 @@
+//! Browser bindings of the step-4 parser.
+//!
+//! `parse_to_js` builds plain JS values, like `JSON.parse` does, so that the
+//! two can be compared on the same documents in `www/index.html`.
+
+use js_sys::{Array, Object, Reflect};
+use wasm_bindgen::prelude::*;
+
+use step_4::{edit, parse_recovering, parse_with_path, render_error, JsonValue};
+
+fn to_js(value: &JsonValue) -> JsValue {
+    match value {
+        JsonValue::Null => JsValue::NULL,
+        JsonValue::Str(s) => JsValue::from_str(s),
+        JsonValue::Wtf8(bytes) => JsValue::from_str(&String::from_utf8_lossy(bytes)),
+        JsonValue::Boolean(b) => JsValue::from_bool(*b),
+        JsonValue::Num(n) | JsonValue::RawNum(n, _) => JsValue::from_f64(*n),
+        // Numbers are doubles in JS, like in `JSON.parse`
+        JsonValue::Int(n) => JsValue::from_f64(*n as f64),
+        JsonValue::UInt(n) => JsValue::from_f64(*n as f64),
+        JsonValue::Array(values) => values.iter().map(to_js).collect::<Array>().into(),
+        JsonValue::Object(map) => {
+            let object = Object::new();
+            for (key, value) in map {
+                Reflect::set(&object, &JsValue::from_str(key), &to_js(value)).unwrap_throw();
+            }
+            object.into()
+        }
+    }
+}
+
+/// Parses `input` into JS values, throwing the rendered error if it is
+/// invalid
+#[wasm_bindgen]
+pub fn parse_to_js(input: &str) -> Result<JsValue, JsError> {
+    parse_with_path(input)
+        .map(|value| to_js(&value))
+        .map_err(|e| JsError::new(&render_error(input, &e)))
+}
+
+/// Every error of `input`, rendered. Empty when `input` is valid
+#[wasm_bindgen]
+pub fn validate(input: &str) -> Vec<String> {
+    let (_, errors) = parse_recovering(input);
+
+    errors.iter().map(|e| render_error(input, e)).collect()
+}
+
+/// Pretty-prints `input` indented by `indent` spaces, or minifies it without
+/// `indent`, keeping its key order
+#[wasm_bindgen]
+pub fn format(input: &str, indent: Option<usize>) -> Result<String, JsError> {
+    edit(input, |doc| doc.reformat(indent)).map_err(|e| JsError::new(&render_error(input, &e)))
+}
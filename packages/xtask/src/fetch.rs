@@ -0,0 +1,64 @@
+//! `cargo xtask fetch`: downloads the usual corpus of JSON benchmarks, with
+//! `curl` so that the workspace needs no HTTP client.
+
+use std::{fs, process::Command};
+
+use crate::test_files;
+
+const USAGE: &str = "usage: cargo xtask fetch [--force]
+
+    --force   download the files that are already there again";
+
+const BASE_URL: &str = "https://raw.githubusercontent.com/miloyip/nativejson-benchmark/master/data";
+
+const FILES: [&str; 3] = ["canada.json", "twitter.json", "citm_catalog.json"];
+
+pub fn run(args: impl Iterator<Item = String>) -> Result<(), String> {
+    let mut force = false;
+
+    for arg in args {
+        match arg.as_str() {
+            "--force" => force = true,
+            "-h" | "--help" => return Err(USAGE.to_owned()),
+            arg => return Err(format!("unknown option `{arg}`\n\n{USAGE}")),
+        }
+    }
+
+    let dir = test_files();
+    fs::create_dir_all(&dir).map_err(|e| format!("{}: {e}", dir.display()))?;
+
+    for file in FILES {
+        let path = dir.join(file);
+
+        if path.exists() && !force {
+            println!("{file}: already there");
+            continue;
+        }
+
+        // Downloaded next to it first, so that an interrupted download
+        // doesn't leave a truncated file behind
+        let partial = dir.join(format!("{file}.part"));
+        let status = Command::new("curl")
+            .args([
+                "--fail",
+                "--silent",
+                "--show-error",
+                "--location",
+                "--output",
+            ])
+            .arg(&partial)
+            .arg(format!("{BASE_URL}/{file}"))
+            .status()
+            .map_err(|e| format!("couldn't run curl: {e}"))?;
+
+        if !status.success() {
+            let _ = fs::remove_file(&partial);
+            return Err(format!("{file}: download failed"));
+        }
+
+        fs::rename(&partial, &path).map_err(|e| format!("{}: {e}", path.display()))?;
+        println!("{file}: downloaded");
+    }
+
+    Ok(())
+}
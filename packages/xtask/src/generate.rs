@@ -0,0 +1,223 @@
+//! `cargo xtask generate`: synthetic documents stressing one part of a
+//! parser each, of any size and reproducible from their seed.
+
+use std::{fs, io::Write};
+
+const USAGE: &str = "usage: cargo xtask generate deep|wide|strings [options]
+
+shapes:
+    deep      array of values nested DEPTH levels deep
+    wide      one object with a lot of keys
+    strings   array of long strings full of escapes and non-ASCII chars
+
+options:
+    --size SIZE        approximate size of the output, like 500K or 10M, 1M by default
+    --depth N          nesting of `deep`, 64 by default
+    --string-len N     chars per string of `strings`, 1024 by default
+    --seed N           seed of the random values, 0 by default
+    -o, --output FILE  write to FILE rather than stdout";
+
+#[derive(Clone, Copy)]
+enum Shape {
+    Deep,
+    Wide,
+    Strings,
+}
+
+struct Options {
+    shape: Shape,
+    size: usize,
+    depth: usize,
+    string_len: usize,
+    seed: u64,
+    output: Option<String>,
+}
+
+/// `500`, `500K`, `10M` or `1G`
+fn parse_size(size: &str) -> Option<usize> {
+    let (digits, unit) = match size.char_indices().find(|(_, c)| !c.is_ascii_digit()) {
+        Some((index, _)) => size.split_at(index),
+        None => (size, ""),
+    };
+    let factor = match unit.to_ascii_uppercase().as_str() {
+        "" | "B" => 1,
+        "K" | "KB" => 1 << 10,
+        "M" | "MB" => 1 << 20,
+        "G" | "GB" => 1 << 30,
+        _ => return None,
+    };
+
+    digits.parse::<usize>().ok()?.checked_mul(factor)
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Options, String> {
+    let shape = match args.next().as_deref() {
+        Some("deep") => Shape::Deep,
+        Some("wide") => Shape::Wide,
+        Some("strings") => Shape::Strings,
+        Some("-h" | "--help") | None => return Err(USAGE.to_owned()),
+        Some(shape) => return Err(format!("unknown shape `{shape}`\n\n{USAGE}")),
+    };
+    let mut options = Options {
+        shape,
+        size: 1 << 20,
+        depth: 64,
+        string_len: 1024,
+        seed: 0,
+        output: None,
+    };
+
+    while let Some(arg) = args.next() {
+        let mut value = |what: &str| args.next().ok_or(format!("{arg} needs {what}\n\n{USAGE}"));
+
+        match arg.as_str() {
+            "--size" => {
+                options.size =
+                    parse_size(&value("a size")?).ok_or(format!("invalid size\n\n{USAGE}"))?
+            }
+            "--depth" => {
+                options.depth = value("a number")?
+                    .parse()
+                    .map_err(|_| format!("--depth needs a number\n\n{USAGE}"))?
+            }
+            "--string-len" => {
+                options.string_len = value("a number")?
+                    .parse()
+                    .map_err(|_| format!("--string-len needs a number\n\n{USAGE}"))?
+            }
+            "--seed" => {
+                options.seed = value("a number")?
+                    .parse()
+                    .map_err(|_| format!("--seed needs a number\n\n{USAGE}"))?
+            }
+            "-o" | "--output" => options.output = Some(value("a file")?),
+            "-h" | "--help" => return Err(USAGE.to_owned()),
+            arg => return Err(format!("unknown option `{arg}`\n\n{USAGE}")),
+        }
+    }
+
+    Ok(options)
+}
+
+/// xorshift64*, plenty for test data and the same on every platform
+struct Rng(u64);
+
+impl Rng {
+    fn new(seed: u64) -> Self {
+        // The state must not be zero
+        Self(seed ^ 0x9e37_79b9_7f4a_7c15)
+    }
+
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+
+    fn number(&mut self, out: &mut String) {
+        match self.below(3) {
+            0 => out.push_str(&(self.next() as i64 >> 16).to_string()),
+            1 => out.push_str(&format!(
+                "{:.6}",
+                self.next() as f64 / u64::MAX as f64 * 360.0 - 180.0
+            )),
+            _ => out.push_str(&format!("{:e}", self.next() as f64)),
+        }
+    }
+}
+
+/// Pieces of the strings: plain text, every kind of escape, and chars of
+/// one to four bytes in UTF-8, as is and escaped
+const PIECES: [&str; 14] = [
+    "lorem",
+    " ",
+    "ipsum",
+    "\\n",
+    "\\t",
+    "\\\"",
+    "\\\\",
+    "\\/",
+    "é",
+    "あ",
+    "😀",
+    "\\u00e9",
+    "\\u3042",
+    "\\ud83d\\ude00",
+];
+
+fn string(rng: &mut Rng, len: usize, out: &mut String) {
+    out.push('"');
+    for _ in 0..len {
+        out.push_str(PIECES[rng.below(PIECES.len())]);
+    }
+    out.push('"');
+}
+
+fn nested(rng: &mut Rng, depth: usize, out: &mut String) {
+    if depth == 0 {
+        return rng.number(out);
+    }
+
+    if depth.is_multiple_of(2) {
+        out.push_str("{\"a\":");
+        nested(rng, depth - 1, out);
+        out.push('}');
+    } else {
+        out.push('[');
+        nested(rng, depth - 1, out);
+        out.push(']');
+    }
+}
+
+fn generate(options: &Options) -> String {
+    let mut rng = Rng::new(options.seed);
+    let mut out = String::with_capacity(options.size + 1024);
+    let (open, close) = match options.shape {
+        Shape::Wide => ('{', '}'),
+        Shape::Deep | Shape::Strings => ('[', ']'),
+    };
+
+    out.push(open);
+
+    let mut count = 0;
+    while out.len() < options.size || count == 0 {
+        if count > 0 {
+            out.push(',');
+        }
+
+        match options.shape {
+            Shape::Deep => nested(&mut rng, options.depth, &mut out),
+            Shape::Wide => {
+                out.push_str(&format!("\"key_{count}\":"));
+                match rng.below(3) {
+                    0 => rng.number(&mut out),
+                    1 => string(&mut rng, 4, &mut out),
+                    _ => out.push_str(if rng.below(2) == 0 { "true" } else { "null" }),
+                }
+            }
+            Shape::Strings => string(&mut rng, options.string_len, &mut out),
+        }
+
+        count += 1;
+    }
+
+    out.push(close);
+    out
+}
+
+pub fn run(args: impl Iterator<Item = String>) -> Result<(), String> {
+    let options = parse_args(args)?;
+    let json = generate(&options);
+
+    match &options.output {
+        Some(path) => fs::write(path, json).map_err(|e| format!("{path}: {e}")),
+        None => std::io::stdout()
+            .write_all(json.as_bytes())
+            .map_err(|e| e.to_string()),
+    }
+}
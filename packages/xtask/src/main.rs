@@ -0,0 +1,46 @@
+//! Maintenance tasks, run with `cargo xtask <task>` from anywhere in the
+//! workspace.
+//!
+//! ```text
+//! cargo xtask fetch [--force]
+//! cargo xtask generate deep|wide|strings [--size SIZE] [--depth N] [--string-len N] [--seed N] [-o FILE]
+//! ```
+
+use std::{path::PathBuf, process::ExitCode};
+
+mod fetch;
+mod generate;
+
+const USAGE: &str = "usage: cargo xtask <task> [options]
+
+tasks:
+    fetch       download the benchmark corpus into test-files/
+    generate    write synthetic JSON of a given size and shape";
+
+/// Where the benchmarks and the steps look for their inputs
+fn test_files() -> PathBuf {
+    PathBuf::from(concat!(env!("CARGO_MANIFEST_DIR"), "/../../test-files"))
+}
+
+fn main() -> ExitCode {
+    let mut args = std::env::args().skip(1);
+
+    let result = match args.next().as_deref() {
+        Some("fetch") => fetch::run(args),
+        Some("generate") => generate::run(args),
+        Some("-h" | "--help") => {
+            println!("{USAGE}");
+            return ExitCode::SUCCESS;
+        }
+        Some(task) => Err(format!("unknown task `{task}`\n\n{USAGE}")),
+        None => Err(USAGE.to_owned()),
+    };
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(message) => {
+            eprintln!("{message}");
+            ExitCode::FAILURE
+        }
+    }
+}